@@ -0,0 +1,54 @@
+//! Computer players. An `Engine` hands each of its computer-played seats a view of the game when
+//! that seat has a move to make, and plays whichever move the seat's player picks.
+
+pub mod ismcts;
+
+use crate::{game::GameState, rng::Rng};
+
+/// What a seat is asked to pick a move from.
+pub struct GameView<'a, G: GameState> {
+    pub seat: usize,
+    /// The game as it stands. Players should only go by what `seat` could see at the table.
+    pub state: &'a G,
+    /// The moves `seat` can make, of which there's always at least one.
+    pub moves: Vec<G::Move>,
+}
+
+impl<'a, G: GameState> GameView<'a, G> {
+    /// The view from `seat`, or `None` if it has no moves to make.
+    pub fn new(state: &'a G, seat: usize) -> Option<Self> {
+        let moves = state.moves_for(seat);
+        (!moves.is_empty()).then_some(Self { seat, state, moves })
+    }
+}
+
+/// A way of playing a game.
+pub trait AiPlayer<G: GameState> {
+    /// Picks one of `view.moves`.
+    fn choose_move(&mut self, view: &GameView<G>) -> G::Move;
+}
+
+/// Plays any legal move, each as likely as the next. A baseline for other players to beat.
+#[derive(Debug, Clone)]
+pub struct RandomPlayer {
+    rng: Rng,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl<G: GameState> AiPlayer<G> for RandomPlayer {
+    fn choose_move(&mut self, view: &GameView<G>) -> G::Move {
+        view.moves[self.rng.below(view.moves.len())].clone()
+    }
+}
+
+/// Plays by a few simple rules of thumb, which each game that supports it spells out alongside its
+/// own rules.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuleBasedPlayer;
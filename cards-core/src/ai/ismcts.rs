@@ -0,0 +1,179 @@
+//! Information set Monte Carlo tree search, for games where some of the cards are hidden.
+//!
+//! Each iteration deals the cards the searching seat can't see out again at random, as a guess at
+//! what the others might be holding, then walks a single tree of moves shared by every guess,
+//! playing out to the end of the game at random from the first move it hasn't tried yet. Moves
+//! that work out well across many guesses get played more often, and the one tried most is
+//! picked.
+
+use crate::{
+    ai::{AiPlayer, GameView},
+    game::GameState,
+    rng::Rng,
+};
+
+/// How strongly the search favours trying moves it hasn't tried much over ones that have done
+/// well so far.
+const EXPLORATION: f64 = 0.7;
+
+/// A game with hidden cards that can be searched.
+pub trait HiddenInformation: GameState {
+    /// A copy of the game with everything `seat` can't see dealt out again at random, in a way
+    /// that fits with everything it has seen so far.
+    fn determinize(&self, seat: usize, rng: &mut Rng) -> Self;
+
+    /// How well a finished game went for `seat`, from 0 for as badly as possible to 1 for as well.
+    fn reward(&self, seat: usize) -> f64;
+}
+
+/// Searches a set number of iterations before each move. More iterations play more strongly, but
+/// take longer.
+#[derive(Debug, Clone)]
+pub struct IsmctsPlayer {
+    iterations: usize,
+    rng: Rng,
+}
+
+impl IsmctsPlayer {
+    pub const DEFAULT_ITERATIONS: usize = 500;
+
+    pub fn new(iterations: usize, seed: u64) -> Self {
+        Self {
+            iterations: iterations.max(1),
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+struct Node<M> {
+    /// The move that led here, and the seat that made it.
+    mv: Option<(usize, M)>,
+    children: Vec<usize>,
+    visits: u32,
+    /// How many times this node's move could have been picked when its parent was visited.
+    available: u32,
+    /// The sum of the rewards, for the seat that made the move, of every game played through here.
+    reward: f64,
+}
+
+impl<M> Node<M> {
+    fn new(mv: Option<(usize, M)>) -> Self {
+        Self {
+            mv,
+            children: Vec::new(),
+            visits: 0,
+            available: 0,
+            reward: 0.0,
+        }
+    }
+
+    fn score(&self) -> f64 {
+        self.reward / self.visits as f64
+            + EXPLORATION * ((self.available as f64).ln() / self.visits as f64).sqrt()
+    }
+}
+
+/// Plays whatever the game does by itself, leaving the next seat to move, if any.
+fn settle<G: GameState>(state: &mut G) -> Option<usize> {
+    while let Some(mv) = state.automatic_move() {
+        state.apply_move(&mv);
+    }
+    if state.is_over() {
+        return None;
+    }
+    state.seats_to_move().first().copied()
+}
+
+impl<G: HiddenInformation> AiPlayer<G> for IsmctsPlayer {
+    fn choose_move(&mut self, view: &GameView<G>) -> G::Move {
+        if view.moves.len() == 1 {
+            return view.moves[0].clone();
+        }
+
+        let mut nodes = vec![Node::new(None)];
+
+        for _ in 0..self.iterations {
+            let mut state = view.state.determinize(view.seat, &mut self.rng);
+            let mut path = vec![0];
+            let mut node = 0;
+            let mut seat = Some(view.seat);
+
+            // Follow the tree down while every move that can be made here has been tried.
+            while let Some(mover) = seat {
+                let moves = if node == 0 {
+                    view.moves.clone()
+                } else {
+                    state.moves_for(mover)
+                };
+                if moves.is_empty() {
+                    break;
+                }
+
+                let tried = |mv: &G::Move| {
+                    nodes[node].children.iter().copied().find(|&child| {
+                        matches!(&nodes[child].mv, Some((by, tried)) if *by == mover && tried == mv)
+                    })
+                };
+                let untried = moves
+                    .iter()
+                    .filter(|mv| tried(mv).is_none())
+                    .collect::<Vec<_>>();
+
+                if !untried.is_empty() {
+                    let mv = untried[self.rng.below(untried.len())].clone();
+                    state.apply_move(&mv);
+                    let child = nodes.len();
+                    nodes.push(Node::new(Some((mover, mv))));
+                    nodes[node].children.push(child);
+                    path.push(child);
+                    seat = settle(&mut state);
+                    break;
+                }
+
+                let available = moves.iter().filter_map(tried).collect::<Vec<_>>();
+                for &child in &available {
+                    nodes[child].available += 1;
+                }
+                let Some(&best) = available
+                    .iter()
+                    .max_by(|&&a, &&b| nodes[a].score().total_cmp(&nodes[b].score()))
+                else {
+                    break;
+                };
+
+                if let Some((_, mv)) = &nodes[best].mv {
+                    state.apply_move(mv);
+                }
+                node = best;
+                path.push(node);
+                seat = settle(&mut state);
+            }
+
+            // Play the rest of the game out at random.
+            while let Some(mover) = seat {
+                let moves = state.moves_for(mover);
+                if moves.is_empty() {
+                    break;
+                }
+                state.apply_move(&moves[self.rng.below(moves.len())]);
+                seat = settle(&mut state);
+            }
+
+            for &index in &path {
+                let node = &mut nodes[index];
+                node.visits += 1;
+                if let Some((by, _)) = node.mv {
+                    node.reward += state.reward(by);
+                }
+            }
+        }
+
+        nodes[0]
+            .children
+            .iter()
+            .max_by_key(|&&child| nodes[child].visits)
+            .and_then(|&child| nodes[child].mv.as_ref())
+            .map(|(_, mv)| mv.clone())
+            .unwrap_or_else(|| view.moves[0].clone())
+    }
+}
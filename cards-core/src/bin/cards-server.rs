@@ -0,0 +1,251 @@
+//! Hosts multiplayer games for players connecting over WebSockets, speaking
+//! `cards_core::protocol`.
+//!
+//! ```sh
+//! cargo run --release -p cards-core --features server --bin cards-server -- --address 0.0.0.0:9000
+//! ```
+//!
+//! The rooms are run by `cards_core::host`; this is the WebSocket server around it, with a thread
+//! for each connection passing messages to and from the thread running the rooms, up to
+//! `MAX_CONNECTIONS` at once.
+
+use std::{
+    io::ErrorKind,
+    net::{TcpListener, TcpStream},
+    process::ExitCode,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use cards_core::{
+    host::{self, ClientId, Event, Host},
+    protocol::{self, ClientMessage, ServerMessage},
+};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use tungstenite::{Message, WebSocket};
+
+const USAGE: &str = "usage: cards-server [--address <host:port>]";
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:9000";
+
+/// How often the rooms are brought up to date, which is also the longest a connection waits to
+/// hear from its player before passing on what the server has to say.
+const TICK: Duration = Duration::from_millis(50);
+
+/// How long a new connection has to open its WebSocket, and then to say `Hello`.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The most connections served at once, each with a thread of its own. Any more are closed
+/// straight away.
+const MAX_CONNECTIONS: usize = 256;
+
+/// Runs every room, taking the connections' events as they come, until there can be no more.
+fn run_rooms(events: Receiver<Event>) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let mut host = Host::new(now.as_nanos() as u64, now.as_secs());
+    let mut last_update = Instant::now();
+
+    loop {
+        match events.recv_timeout(TICK) {
+            Ok(event) => host.handle(event),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let now = Instant::now();
+        if now - last_update >= TICK {
+            host.update(now - last_update);
+            last_update = now;
+        }
+    }
+}
+
+type Socket = WebSocket<TcpStream>;
+
+/// The connection has closed, or broken down.
+struct Closed;
+
+fn send(socket: &mut Socket, message: &ServerMessage) -> Result<(), Closed> {
+    socket
+        .send(Message::Binary(protocol::encode(message)))
+        .map_err(|_| Closed)
+}
+
+/// The next message from the player, or `None` if nothing came before the read timed out. Binary
+/// frames that aren't messages are answered with `Rejected`, and other frames ignored.
+fn receive(socket: &mut Socket) -> Result<Option<ClientMessage>, Closed> {
+    match socket.read() {
+        Ok(Message::Binary(bytes)) => {
+            let message = protocol::decode(&bytes);
+            if message.is_none() {
+                send(
+                    socket,
+                    &ServerMessage::Rejected("couldn't read that".to_owned()),
+                )?;
+            }
+            Ok(message)
+        }
+        Ok(Message::Close(_)) => Err(Closed),
+        Ok(_) => Ok(None),
+        Err(tungstenite::Error::Io(e))
+            if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+        {
+            Ok(None)
+        }
+        Err(_) => Err(Closed),
+    }
+}
+
+/// Waits for the player to say `Hello`, and answers it, returning whether they speak the same
+/// version of the protocol.
+fn greet(socket: &mut Socket) -> Result<bool, Closed> {
+    let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+    while Instant::now() < deadline {
+        let Some(message) = receive(socket)? else {
+            continue;
+        };
+
+        return match host::greet(&message) {
+            Ok(welcome) => send(socket, &welcome).map(|_| true),
+            Err(reason) => send(socket, &ServerMessage::Rejected(reason)).map(|_| false),
+        };
+    }
+
+    Ok(false)
+}
+
+/// Speaks to one player until they go, passing what they say on to `events` and sending them
+/// what the server has to say.
+fn serve(stream: TcpStream, id: ClientId, events: Sender<Event>) {
+    // A connection that never finishes opening its WebSocket only holds its thread so long.
+    if let Err(e) = stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)) {
+        eprintln!("player {id} couldn't be served: {e}");
+        return;
+    }
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(e) => {
+            eprintln!("player {id} couldn't connect: {e}");
+            return;
+        }
+    };
+
+    // Reads give up after a tick, so messages for the player don't wait on the player.
+    if let Err(e) = socket.get_ref().set_read_timeout(Some(TICK)) {
+        eprintln!("player {id} couldn't be served: {e}");
+        return;
+    }
+
+    match greet(&mut socket) {
+        Ok(true) => {}
+        Ok(false) => {
+            println!("player {id} didn't say hello in this protocol's version");
+            let _ = socket.close(None);
+            let _ = socket.flush();
+            return;
+        }
+        Err(_) => return,
+    }
+
+    let (outbox, messages) = mpsc::channel();
+    if events.send(Event::Connected(id, outbox)).is_err() {
+        return;
+    }
+    println!("player {id} connected");
+
+    'connection: loop {
+        match receive(&mut socket) {
+            Ok(Some(message)) => {
+                if events.send(Event::Message(id, message)).is_err() {
+                    break;
+                }
+            }
+            Ok(None) => {}
+            Err(_) => break,
+        }
+
+        for message in messages.try_iter() {
+            if send(&mut socket, &message).is_err() {
+                break 'connection;
+            }
+        }
+    }
+
+    println!("player {id} disconnected");
+    let _ = events.send(Event::Disconnected(id));
+}
+
+/// Prints what the rooms log, which is how the server reports what its players are up to.
+struct PrintLogger;
+
+impl Log for PrintLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Info
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            println!("{}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn main() -> ExitCode {
+    if log::set_logger(&PrintLogger).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+
+    let mut address = DEFAULT_ADDRESS.to_owned();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--address", Some(value)) => address = value,
+            _ => {
+                eprintln!("unexpected argument {arg:?}\n{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("couldn't listen on {address}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("listening on {address}");
+
+    let (events, received) = mpsc::channel();
+    thread::spawn(move || run_rooms(received));
+
+    let connections = Arc::new(AtomicUsize::new(0));
+    for (id, stream) in (0..).zip(listener.incoming()) {
+        match stream {
+            Ok(_) if connections.load(Ordering::SeqCst) >= MAX_CONNECTIONS => {
+                eprintln!("turned player {id} away, with {MAX_CONNECTIONS} connections open");
+            }
+            Ok(stream) => {
+                let events = events.clone();
+                let connections = Arc::clone(&connections);
+                connections.fetch_add(1, Ordering::SeqCst);
+                thread::spawn(move || {
+                    serve(stream, id, events);
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+            Err(e) => eprintln!("couldn't accept a connection: {e}"),
+        }
+    }
+
+    ExitCode::SUCCESS
+}
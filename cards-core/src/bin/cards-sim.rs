@@ -0,0 +1,361 @@
+//! Plays batches of games between computer players with nothing on screen, and prints how each
+//! seat did.
+//!
+//! ```sh
+//! cargo run --release -p cards-core --features sim --bin cards-sim -- \
+//!     --game hearts --games 1000 --seed 7 --players ismcts:200,rules,rules,rules
+//! ```
+//!
+//! `--game poker` instead deals hold'em hands straight to a showdown, with no betting, and prints
+//! how often each seat takes the pot and how often each kind of hand comes up.
+//!
+//! `--game puzzles` plays the solution of every bundled puzzle against the rule-based computer
+//! players, and fails unless each one meets its puzzle's goals.
+
+use std::{cmp::Ordering, process::ExitCode, time::Instant};
+
+use cards_core::{
+    ai::{
+        ismcts::{HiddenInformation, IsmctsPlayer},
+        AiPlayer, GameView, RandomPlayer, RuleBasedPlayer,
+    },
+    deck::Deck,
+    doppelkopf::{self, Doppelkopf},
+    game::{
+        poker::{self, Category},
+        Engine, Game, GameState, Orientation,
+    },
+    hearts::{self, Hearts},
+    klondike::Klondike,
+    puzzle::Puzzle,
+    trick::SEAT_NAMES,
+};
+
+const USAGE: &str =
+    "usage: cards-sim [--game hearts|doppelkopf|poker|puzzles] [--games <n>] [--seed <n>] \
+                     [--players <player>,...]
+players are rules, random or ismcts[:<iterations>], one for every seat or one per seat";
+
+struct Options {
+    game: String,
+    games: u64,
+    seed: u64,
+    players: Vec<String>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            game: "hearts".to_owned(),
+            games: 1000,
+            seed: 0,
+            players: vec!["rules".to_owned()],
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--game" => options.game = value()?,
+                "--games" => options.games = parse_number(&arg, value()?)?,
+                "--seed" => options.seed = parse_number(&arg, value()?)?,
+                "--players" => {
+                    options.players = value()?.split(',').map(str::to_owned).collect();
+                }
+                _ => return Err(format!("unknown argument {arg:?}")),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_number(arg: &str, value: String) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{arg} expects a number, not {value:?}"))
+}
+
+fn make_player<G: HiddenInformation>(name: &str, seed: u64) -> Option<Box<dyn AiPlayer<G>>>
+where
+    RuleBasedPlayer: AiPlayer<G>,
+{
+    Some(match name {
+        "rules" => Box::new(RuleBasedPlayer),
+        "random" => Box::new(RandomPlayer::new(seed)),
+        "ismcts" => Box::new(IsmctsPlayer::new(IsmctsPlayer::DEFAULT_ITERATIONS, seed)),
+        _ => {
+            let iterations = name.strip_prefix("ismcts:")?.parse().ok()?;
+            Box::new(IsmctsPlayer::new(iterations, seed))
+        }
+    })
+}
+
+/// What happened to one seat over every game.
+#[derive(Default)]
+struct Tally {
+    score: f64,
+    squared: f64,
+    wins: u64,
+}
+
+/// Plays one game through, with the seats' moves and the game's own.
+fn play<G: GameState>(seed: u64, players: &mut [Box<dyn AiPlayer<G>>]) -> G {
+    let mut state = G::setup(seed);
+
+    loop {
+        if let Some(mv) = state.automatic_move() {
+            state.apply_move(&mv);
+            continue;
+        }
+
+        let view = state
+            .seats_to_move()
+            .into_iter()
+            .find_map(|seat| GameView::new(&state, seat));
+        let Some(view) = view else {
+            return state;
+        };
+        let mv = players[view.seat].choose_move(&view);
+        state.apply_move(&mv);
+    }
+}
+
+fn simulate<G: HiddenInformation>(options: &Options) -> Result<(), String>
+where
+    RuleBasedPlayer: AiPlayer<G>,
+{
+    let seats = G::setup(options.seed).seats();
+    let names = match options.players.len() {
+        1 => vec![options.players[0].clone(); seats],
+        count if count == seats => options.players.clone(),
+        count => return Err(format!("{count} players given for {seats} seats")),
+    };
+
+    let mut players = names
+        .iter()
+        .enumerate()
+        .map(|(seat, name)| {
+            make_player::<G>(name, options.seed.wrapping_add(seat as u64))
+                .ok_or(format!("unknown player {name:?}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut tallies = (0..seats).map(|_| Tally::default()).collect::<Vec<_>>();
+    let start = Instant::now();
+
+    for game in 0..options.games {
+        let state = play(options.seed.wrapping_add(game), &mut players);
+
+        // Whoever did best wins, and everyone level with them.
+        let rewards = (0..seats)
+            .map(|seat| state.reward(seat))
+            .collect::<Vec<_>>();
+        let best = rewards.iter().copied().fold(f64::MIN, f64::max);
+        let status = state.status();
+
+        for (seat, tally) in tallies.iter_mut().enumerate() {
+            let score = status
+                .as_ref()
+                .and_then(|status| status.players.get(seat))
+                .map_or(0, |player| player.score) as f64;
+            tally.score += score;
+            tally.squared += score * score;
+            if rewards[seat] >= best {
+                tally.wins += 1;
+            }
+        }
+    }
+
+    let games = options.games.max(1) as f64;
+    println!(
+        "{} games of {} from seed {} in {:.1?}",
+        options.games,
+        options.game,
+        options.seed,
+        start.elapsed()
+    );
+    println!(
+        "{:<6} {:<14} {:>10} {:>10} {:>7}",
+        "seat", "player", "mean score", "std dev", "wins"
+    );
+    for (seat, tally) in tallies.iter().enumerate() {
+        let mean = tally.score / games;
+        let deviation = (tally.squared / games - mean * mean).max(0.0).sqrt();
+        println!(
+            "{:<6} {:<14} {:>10.2} {:>10.2} {:>6.1}%",
+            SEAT_NAMES.get(seat).copied().unwrap_or("?"),
+            names[seat],
+            mean,
+            deviation,
+            100.0 * tally.wins as f64 / games
+        );
+    }
+
+    Ok(())
+}
+
+/// The cards each seat holds in a showdown, and the cards shared between them.
+const HOLE_CARDS: usize = 2;
+const BOARD_CARDS: usize = 5;
+
+/// Every kind of poker hand, weakest first.
+const CATEGORIES: [Category; 9] = [
+    Category::HighCard,
+    Category::Pair,
+    Category::TwoPair,
+    Category::ThreeOfAKind,
+    Category::Straight,
+    Category::Flush,
+    Category::FullHouse,
+    Category::FourOfAKind,
+    Category::StraightFlush,
+];
+
+/// Deals hold'em hands to every seat and the board, and shares each pot between the best hands
+/// at the showdown. The best five cards picked out of each hand are checked against the rank of
+/// the whole hand along the way.
+fn showdowns(options: &Options) -> Result<(), String> {
+    let seats = SEAT_NAMES.len();
+    let mut pots = vec![0.0; seats];
+    let mut categories = [0u64; CATEGORIES.len()];
+    let start = Instant::now();
+
+    for game in 0..options.games {
+        let mut deck = Deck::standard();
+        deck.shuffle_with_seed(options.seed.wrapping_add(game));
+        let holes = (0..seats)
+            .map(|_| deck.deal(HOLE_CARDS))
+            .collect::<Vec<_>>();
+        let board = deck.deal(BOARD_CARDS);
+
+        let mut hands = Vec::new();
+        for hole in &holes {
+            let cards = [hole.as_slice(), &board].concat();
+            let rank = poker::evaluate(&cards);
+            let best = poker::best_five(&cards).map(|(five, _)| five);
+            if best.is_none_or(|five| poker::evaluate(&five) != rank) {
+                return Err(format!("best five of {cards:?} don't rank as {rank:?}"));
+            }
+            categories[rank.category as usize] += 1;
+            hands.push(cards);
+        }
+
+        let mut winners = vec![0];
+        for seat in 1..seats {
+            match poker::compare(&hands[seat], &hands[winners[0]]) {
+                Ordering::Greater => winners = vec![seat],
+                Ordering::Equal => winners.push(seat),
+                Ordering::Less => {}
+            }
+        }
+        for &seat in &winners {
+            pots[seat] += 1.0 / winners.len() as f64;
+        }
+    }
+
+    let games = options.games.max(1) as f64;
+    println!(
+        "{} showdowns from seed {} in {:.1?}",
+        options.games,
+        options.seed,
+        start.elapsed()
+    );
+    println!("{:<6} {:>7}", "seat", "pots");
+    for (seat, pot) in pots.iter().enumerate() {
+        println!("{:<6} {:>6.1}%", SEAT_NAMES[seat], 100.0 * pot / games);
+    }
+    println!("{:<16} {:>7}", "hand", "share");
+    let hands = games * seats as f64;
+    for (category, count) in CATEGORIES.iter().zip(categories) {
+        println!(
+            "{:<16} {:>6.2}%",
+            category.name(),
+            100.0 * count as f64 / hands
+        );
+    }
+
+    Ok(())
+}
+
+fn deal<G: GameState>(seed: u64) -> Engine<G> {
+    Engine::new(G::setup(seed), Orientation::Landscape)
+}
+
+/// Plays `puzzle`'s solution in `engine`, which has dealt the puzzle, returning how many moves the
+/// game took if the solution solves it.
+fn play_solution<G: GameState>(puzzle: &Puzzle, mut engine: Engine<G>) -> Result<usize, String> {
+    let mut solution = puzzle.solution.iter();
+
+    while !engine.state().is_over() {
+        let made = engine.moves().len();
+        engine.update(engine.state().automatic_move_delay());
+        if engine.moves().len() > made {
+            continue;
+        }
+
+        let index = *solution
+            .next()
+            .ok_or(format!("the solution runs out after {made} moves"))?;
+        let mv = engine
+            .state()
+            .legal_moves()
+            .get(index)
+            .cloned()
+            .ok_or(format!(
+                "move {index} after {made} moves isn't a legal move"
+            ))?;
+        engine.play(&mv);
+    }
+
+    if solution.next().is_some() {
+        return Err("the game is over before the solution is".to_owned());
+    }
+    let outcome = engine.state().outcome(engine.seat());
+    if !puzzle.is_solved(outcome, &engine) {
+        return Err(format!("the solution misses its goals {:?}", puzzle.goals));
+    }
+    Ok(engine.moves().len())
+}
+
+/// Checks that every bundled puzzle's solution solves it.
+fn check_puzzles() -> Result<(), String> {
+    for puzzle in Puzzle::bundled() {
+        let moves = match puzzle.game.as_str() {
+            "klondike" => play_solution(&puzzle, deal::<Klondike>(puzzle.seed)),
+            "hearts" => play_solution(
+                &puzzle,
+                deal::<Hearts>(puzzle.seed)
+                    .with_computer_players(hearts::PLAYER, |_| Box::new(RuleBasedPlayer)),
+            ),
+            "doppelkopf" => play_solution(
+                &puzzle,
+                deal::<Doppelkopf>(puzzle.seed)
+                    .with_computer_players(doppelkopf::PLAYER, |_| Box::new(RuleBasedPlayer)),
+            ),
+            other => Err(format!("there's no checking puzzles of {other}")),
+        }
+        .map_err(|message| format!("puzzle {}: {message}", puzzle.id))?;
+        println!("{:<24} solved in {moves} moves", puzzle.id);
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let result =
+        Options::parse(std::env::args().skip(1)).and_then(|options| match options.game.as_str() {
+            "hearts" => simulate::<Hearts>(&options),
+            "doppelkopf" => simulate::<Doppelkopf>(&options),
+            "poker" => showdowns(&options),
+            "puzzles" => check_puzzles(),
+            other => Err(format!("unknown game {other:?}")),
+        });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,532 @@
+//! Blackjack against the dealer, dealt from a shoe of several decks.
+//!
+//! A blackjack pays 3 to 2 and any other win pays even money. The player may double down on their
+//! first two cards, and split a pair into as many as four hands, with split aces getting one card
+//! each. The dealer draws to 17, and on a soft 17 too if the rules say so.
+
+use crate::{
+    card::{notation, Rank, ACE, TEN},
+    deck::Deck,
+    game::{CardView, Fan, GameState, Location, Orientation, Pile},
+    rng::Rng,
+    status::{GameStatus, Outcome, PlayerStatus},
+    trick::Card,
+};
+
+pub const SHOE: usize = 0;
+pub const DEALER: usize = 1;
+/// The first of the player's hands, of which there are more after splitting.
+pub const HANDS: usize = 2;
+const MAX_HANDS: usize = 4;
+
+const STARTING_CHIPS: i32 = 1000;
+
+/// What the player bets on each round.
+const BET: i32 = 10;
+
+/// The shoe is shuffled again before a round once less than this fraction of it is left.
+const RESHUFFLE_FRACTION: f32 = 0.25;
+
+/// How far apart the cards in a hand are, as a fraction of a card.
+const HAND_SPREAD: [f32; 2] = [0.3, -0.15];
+
+/// The distance between the middles of the player's hands, in card widths.
+const HAND_SPACING: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlackjackRules {
+    /// How many decks are shuffled together into the shoe.
+    pub decks: usize,
+    /// Whether the dealer draws on a soft 17, one counting an ace as 11.
+    pub hit_soft_17: bool,
+}
+
+impl Default for BlackjackRules {
+    fn default() -> Self {
+        Self {
+            decks: 6,
+            hit_soft_17: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlackjackMove {
+    /// Places a bet and deals a new round.
+    Deal,
+    /// Takes another card on the hand being played.
+    Hit,
+    /// Finishes the hand being played.
+    Stand,
+    /// Doubles the bet on the hand being played and takes exactly one more card.
+    Double,
+    /// Splits a pair into two hands, with a bet on each.
+    Split,
+    /// The dealer turns over the hole card.
+    Reveal,
+    /// The dealer takes another card.
+    DealerHit,
+    /// Pays out the winning hands and collects the losing ones.
+    Settle,
+}
+
+/// The total of a hand, counting an ace as 11 where that doesn't take it over 21, and whether an
+/// ace was counted that way, making the hand soft.
+pub fn hand_value(cards: &[Card]) -> (u32, bool) {
+    let value = |rank: Rank| match rank {
+        ACE => 1,
+        rank if rank >= TEN => 10,
+        rank => rank as u32 + 1,
+    };
+
+    let hard = cards.iter().map(|&(_, rank)| value(rank)).sum::<u32>();
+    let has_ace = cards.iter().any(|&(_, rank)| rank == ACE);
+
+    if has_ace && hard + 10 <= 21 {
+        (hard + 10, true)
+    } else {
+        (hard, false)
+    }
+}
+
+fn is_blackjack(cards: &[Card]) -> bool {
+    cards.len() == 2 && hand_value(cards).0 == 21
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Hand {
+    cards: Vec<Card>,
+    bet: i32,
+    /// Whether the player has finished with the hand, by standing, doubling or going over 21.
+    done: bool,
+}
+
+impl Hand {
+    fn value(&self) -> u32 {
+        hand_value(&self.cards).0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Phase {
+    /// Waiting for the player to deal a round.
+    Betting,
+    /// The player is playing their hands.
+    Player,
+    /// The dealer is playing out their hand.
+    Dealer,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Blackjack {
+    rules: BlackjackRules,
+    rng: Rng,
+    shoe: Deck,
+    dealer: Vec<Card>,
+    hole_hidden: bool,
+    hands: Vec<Hand>,
+    /// The index into `hands` of the hand being played.
+    active: usize,
+    phase: Phase,
+    chips: i32,
+    /// What the player won or lost on the last round, once it's settled.
+    last_round: Option<i32>,
+}
+
+impl Blackjack {
+    pub fn new(rules: BlackjackRules, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let shoe = Self::new_shoe(&rules, &mut rng);
+
+        Self {
+            rules,
+            rng,
+            shoe,
+            dealer: Vec::new(),
+            hole_hidden: false,
+            hands: Vec::new(),
+            active: 0,
+            phase: Phase::Betting,
+            chips: STARTING_CHIPS,
+            last_round: None,
+        }
+    }
+
+    pub fn rules(&self) -> BlackjackRules {
+        self.rules
+    }
+
+    pub fn chips(&self) -> i32 {
+        self.chips
+    }
+
+    fn new_shoe(rules: &BlackjackRules, rng: &mut Rng) -> Deck {
+        let mut shoe = Deck::shoe(rules.decks.max(1));
+        shoe.shuffle(rng);
+        shoe
+    }
+
+    fn draw(&mut self) -> Card {
+        if self.shoe.is_empty() {
+            self.shoe = Self::new_shoe(&self.rules, &mut self.rng);
+        }
+        self.shoe.deal(1)[0]
+    }
+
+    fn can_double(&self) -> bool {
+        let hand = &self.hands[self.active];
+        hand.cards.len() == 2 && self.chips >= hand.bet
+    }
+
+    fn can_split(&self) -> bool {
+        let hand = &self.hands[self.active];
+        match hand.cards[..] {
+            [first, second] => {
+                hand_value(&[first]) == hand_value(&[second])
+                    && self.hands.len() < MAX_HANDS
+                    && self.chips >= hand.bet
+            }
+            _ => false,
+        }
+    }
+
+    /// Finishes any hand that's reached 21 or gone over, and moves on to the next hand still to
+    /// play, or to the dealer once there are none.
+    fn advance(&mut self) {
+        for hand in &mut self.hands {
+            if hand.value() >= 21 {
+                hand.done = true;
+            }
+        }
+
+        match self.hands.iter().position(|hand| !hand.done) {
+            Some(next) => self.active = next,
+            None => self.phase = Phase::Dealer,
+        }
+    }
+
+    /// Whether any hand is still waiting to see how the dealer does, so the dealer has to draw.
+    fn dealer_must_play(&self) -> bool {
+        let single_blackjack = self.hands.len() == 1 && is_blackjack(&self.hands[0].cards);
+        !single_blackjack && self.hands.iter().any(|hand| hand.value() <= 21)
+    }
+
+    fn dealer_draws(&self) -> bool {
+        let (value, soft) = hand_value(&self.dealer);
+        value < 17 || (value == 17 && soft && self.rules.hit_soft_17)
+    }
+
+    /// What a hand returns to the player, including its bet, against the dealer's hand.
+    fn payout(&self, hand: &Hand) -> i32 {
+        let dealer = hand_value(&self.dealer).0;
+        let dealer_blackjack = is_blackjack(&self.dealer);
+        // Hands made by splitting don't count as blackjacks.
+        let blackjack = self.hands.len() == 1 && is_blackjack(&hand.cards);
+
+        match (blackjack, dealer_blackjack) {
+            (true, true) => hand.bet,
+            (true, false) => hand.bet + hand.bet * 3 / 2,
+            (false, true) => 0,
+            _ if hand.value() > 21 => 0,
+            _ if dealer > 21 || hand.value() > dealer => 2 * hand.bet,
+            _ if hand.value() == dealer => hand.bet,
+            _ => 0,
+        }
+    }
+
+    fn dealer_move(&self) -> BlackjackMove {
+        if self.hole_hidden {
+            BlackjackMove::Reveal
+        } else if self.dealer_must_play() && self.dealer_draws() {
+            BlackjackMove::DealerHit
+        } else {
+            BlackjackMove::Settle
+        }
+    }
+}
+
+impl GameState for Blackjack {
+    type Move = BlackjackMove;
+
+    fn setup(seed: u64) -> Self {
+        Self::new(BlackjackRules::default(), seed)
+    }
+
+    fn legal_moves(&self) -> Vec<BlackjackMove> {
+        match self.phase {
+            Phase::Dealer => vec![self.dealer_move()],
+            _ => self.actions().into_iter().map(|(_, mv)| mv).collect(),
+        }
+    }
+
+    fn apply_move(&mut self, mv: &BlackjackMove) {
+        match *mv {
+            BlackjackMove::Deal => {
+                let cut = (self.rules.decks * 52) as f32 * RESHUFFLE_FRACTION;
+                if (self.shoe.len() as f32) < cut {
+                    self.shoe = Self::new_shoe(&self.rules, &mut self.rng);
+                }
+
+                self.chips -= BET;
+                self.last_round = None;
+                self.dealer.clear();
+                self.hands = vec![Hand {
+                    cards: Vec::new(),
+                    bet: BET,
+                    done: false,
+                }];
+                self.active = 0;
+
+                for _ in 0..2 {
+                    let card = self.draw();
+                    self.hands[0].cards.push(card);
+                    let card = self.draw();
+                    self.dealer.push(card);
+                }
+                self.hole_hidden = true;
+                self.phase = Phase::Player;
+
+                // The dealer checks for a blackjack straight away, which ends the round.
+                if is_blackjack(&self.dealer) {
+                    self.hands[0].done = true;
+                }
+                self.advance();
+            }
+            BlackjackMove::Hit => {
+                let card = self.draw();
+                self.hands[self.active].cards.push(card);
+                self.advance();
+            }
+            BlackjackMove::Stand => {
+                self.hands[self.active].done = true;
+                self.advance();
+            }
+            BlackjackMove::Double => {
+                let card = self.draw();
+                let hand = &mut self.hands[self.active];
+                self.chips -= hand.bet;
+                hand.bet *= 2;
+                hand.cards.push(card);
+                hand.done = true;
+                self.advance();
+            }
+            BlackjackMove::Split => {
+                let hand = &mut self.hands[self.active];
+                let Some(moved) = hand.cards.pop() else {
+                    return;
+                };
+                let bet = hand.bet;
+                let aces = moved.1 == ACE;
+                self.chips -= bet;
+                self.hands.insert(
+                    self.active + 1,
+                    Hand {
+                        cards: vec![moved],
+                        bet,
+                        done: false,
+                    },
+                );
+
+                for index in [self.active, self.active + 1] {
+                    let card = self.draw();
+                    let hand = &mut self.hands[index];
+                    hand.cards.push(card);
+                    // Split aces only get the one card.
+                    hand.done = aces;
+                }
+                self.advance();
+            }
+            BlackjackMove::Reveal => self.hole_hidden = false,
+            BlackjackMove::DealerHit => {
+                let card = self.draw();
+                self.dealer.push(card);
+            }
+            BlackjackMove::Settle => {
+                let returned = self.hands.iter().map(|hand| self.payout(hand)).sum::<i32>();
+                let staked = self.hands.iter().map(|hand| hand.bet).sum::<i32>();
+
+                self.chips += returned;
+                self.last_round = Some(returned - staked);
+                self.phase = Phase::Betting;
+            }
+        }
+    }
+
+    /// Cards drawn are named, which means looking ahead at the shoe.
+    fn describe_move(&self, mv: &BlackjackMove) -> String {
+        let mut after = self.clone();
+        after.apply_move(mv);
+        let drawn = |cards: &[Card]| {
+            cards
+                .last()
+                .map_or(String::new(), |&(suit, rank)| notation(suit, rank))
+        };
+
+        match mv {
+            BlackjackMove::Deal => format!("Deal, betting {BET}"),
+            BlackjackMove::Hit => {
+                format!("Hit, drawing {}", drawn(&after.hands[self.active].cards))
+            }
+            BlackjackMove::Stand => "Stand".to_owned(),
+            BlackjackMove::Double => {
+                format!("Double, drawing {}", drawn(&after.hands[self.active].cards))
+            }
+            BlackjackMove::Split => "Split".to_owned(),
+            BlackjackMove::Reveal => {
+                format!("Dealer turns over {}", drawn(&self.dealer))
+            }
+            BlackjackMove::DealerHit => format!("Dealer draws {}", drawn(&after.dealer)),
+            BlackjackMove::Settle => match after.last_round {
+                Some(won) if won > 0 => format!("Won {won}"),
+                Some(won) if won < 0 => format!("Lost {}", -won),
+                _ => "Push".to_owned(),
+            },
+        }
+    }
+
+    /// Once the player can't cover another bet.
+    fn is_over(&self) -> bool {
+        self.phase == Phase::Betting && self.chips < BET
+    }
+
+    /// The game only ends once the player can't afford another bet.
+    fn outcome(&self, _seat: usize) -> Option<Outcome> {
+        self.is_over().then_some(Outcome::Lost)
+    }
+
+    /// Pile `SHOE` is the shoe, `DEALER` the dealer's hand and `HANDS..HANDS + 4` the player's
+    /// hands, with the one being played selected.
+    fn table(&self, _seat: usize, _orientation: Orientation) -> Vec<Pile> {
+        let shoe = Pile::new(
+            [3.5, 1.3],
+            Fan::Stacked,
+            self.shoe
+                .cards()
+                .iter()
+                .map(|&(suit, rank)| CardView::new(suit, rank, true))
+                .collect(),
+        );
+
+        let dealer = Pile::new(
+            [-0.5, 1.3],
+            Fan::Spread([HAND_SPREAD[0], 0.0]),
+            self.dealer
+                .iter()
+                .enumerate()
+                .map(|(index, &(suit, rank))| {
+                    CardView::new(suit, rank, index == 1 && self.hole_hidden)
+                })
+                .collect(),
+        );
+
+        let middle = (self.hands.len().max(1) - 1) as f32 / 2.0;
+        let hands = (0..MAX_HANDS).map(|index| {
+            let cards = self
+                .hands
+                .get(index)
+                .map(|hand| {
+                    let active = self.phase == Phase::Player && index == self.active;
+                    hand.cards
+                        .iter()
+                        .map(|&(suit, rank)| CardView {
+                            selected: active,
+                            ..CardView::new(suit, rank, false)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            Pile::new(
+                [(index as f32 - middle) * HAND_SPACING, -1.0],
+                Fan::Spread(HAND_SPREAD),
+                cards,
+            )
+        });
+
+        [shoe, dealer].into_iter().chain(hands).collect()
+    }
+
+    /// Clicking the shoe deals a round, or takes a card.
+    fn move_at(&self, _seat: usize, location: Location) -> Option<BlackjackMove> {
+        if location.pile != SHOE {
+            return None;
+        }
+
+        match self.phase {
+            Phase::Betting => Some(BlackjackMove::Deal),
+            Phase::Player => Some(BlackjackMove::Hit),
+            Phase::Dealer => None,
+        }
+    }
+
+    fn move_between(&self, _seat: usize, _from: Location, _to: Location) -> Option<BlackjackMove> {
+        None
+    }
+
+    fn automatic_move(&self) -> Option<BlackjackMove> {
+        (self.phase == Phase::Dealer).then(|| self.dealer_move())
+    }
+
+    fn status(&self) -> Option<GameStatus> {
+        let row = |name: &str, score: i32| PlayerStatus {
+            name: name.to_owned(),
+            score,
+        };
+
+        let bet = match self.phase {
+            Phase::Betting => 0,
+            _ => self.hands.iter().map(|hand| hand.bet).sum(),
+        };
+        let visible_dealer = if self.hole_hidden {
+            &self.dealer[..1]
+        } else {
+            &self.dealer[..]
+        };
+
+        let mut players = vec![
+            row("Chips", self.chips),
+            row("Bet", bet),
+            row("Dealer", hand_value(visible_dealer).0 as i32),
+        ];
+        if let Some(hand) = self.hands.get(self.active) {
+            players.push(row("Hand", hand.value() as i32));
+        }
+        if let Some(won) = self.last_round {
+            players.push(row("Won", won));
+        }
+
+        Some(GameStatus {
+            players,
+            tricks: None,
+            turn: None,
+        })
+    }
+
+    fn actions(&self) -> Vec<(String, BlackjackMove)> {
+        let mut actions = Vec::new();
+
+        match self.phase {
+            Phase::Betting if self.chips >= BET => actions.push(("Deal", BlackjackMove::Deal)),
+            Phase::Player => {
+                actions.push(("Hit", BlackjackMove::Hit));
+                actions.push(("Stand", BlackjackMove::Stand));
+                if self.can_double() {
+                    actions.push(("Double", BlackjackMove::Double));
+                }
+                if self.can_split() {
+                    actions.push(("Split", BlackjackMove::Split));
+                }
+            }
+            _ => {}
+        }
+
+        actions
+            .into_iter()
+            .map(|(label, mv)| (label.to_owned(), mv))
+            .collect()
+    }
+}
@@ -0,0 +1,60 @@
+use strum::EnumIter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Suit {
+    Clubs,
+    Spades,
+    Hearts,
+    Diamonds,
+}
+
+impl Suit {
+    pub fn is_red(&self) -> bool {
+        matches!(self, Suit::Hearts | Suit::Diamonds)
+    }
+
+    /// The suit's initial, as used in move notation.
+    pub fn letter(&self) -> char {
+        match self {
+            Suit::Clubs => 'C',
+            Suit::Spades => 'S',
+            Suit::Hearts => 'H',
+            Suit::Diamonds => 'D',
+        }
+    }
+
+    pub fn doppelkopf_suit_strength(&self) -> u8 {
+        match self {
+            Suit::Clubs => 4,
+            Suit::Spades => 3,
+            Suit::Hearts => 2,
+            Suit::Diamonds => 1,
+        }
+    }
+}
+
+/// A card's rank, from 0 for the ace up to 12 for the king.
+pub type Rank = u8;
+
+pub const ACE: Rank = 0;
+pub const TWO: Rank = 1;
+pub const NINE: Rank = 8;
+pub const TEN: Rank = 9;
+pub const JACK: Rank = 10;
+pub const QUEEN: Rank = 11;
+pub const KING: Rank = 12;
+
+/// A card in move notation: its rank then its suit, such as `QS` for the queen of spades or `TH`
+/// for the ten of hearts.
+pub fn notation(suit: Suit, rank: Rank) -> String {
+    let rank = match rank {
+        ACE => 'A',
+        TEN => 'T',
+        JACK => 'J',
+        QUEEN => 'Q',
+        KING => 'K',
+        rank => char::from(b'1' + rank),
+    };
+    format!("{rank}{}", suit.letter())
+}
@@ -0,0 +1,73 @@
+//! Decks of cards to deal games from.
+
+use strum::IntoEnumIterator;
+
+use crate::{
+    card::{Rank, Suit, ACE, NINE},
+    rng::Rng,
+};
+
+/// Cards waiting to be dealt, dealt from the end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Deck {
+    cards: Vec<(Suit, Rank)>,
+}
+
+impl Deck {
+    /// The 52 cards of a French-suited deck, in suit and rank order.
+    pub fn standard() -> Self {
+        Self {
+            cards: Suit::iter()
+                .flat_map(|suit| (0..13).map(move |rank| (suit, rank)))
+                .collect(),
+        }
+    }
+
+    /// The 48 cards of a Doppelkopf deck: two of each nine, ten, jack, queen, king and ace.
+    pub fn doppelkopf() -> Self {
+        Self {
+            cards: Suit::iter()
+                .flat_map(|suit| (NINE..13).chain([ACE]).map(move |rank| (suit, rank)))
+                .flat_map(|card| [card, card])
+                .collect(),
+        }
+    }
+
+    /// `decks` standard decks together, as dealt from a casino shoe.
+    pub fn shoe(decks: usize) -> Self {
+        let deck = Self::standard();
+        Self {
+            cards: deck.cards.repeat(decks),
+        }
+    }
+
+    pub fn shuffle(&mut self, rng: &mut Rng) {
+        rng.shuffle(&mut self.cards);
+    }
+
+    /// Shuffles from a fresh `Rng` seeded with `seed`, so the same seed always gives the same
+    /// order, on every platform.
+    pub fn shuffle_with_seed(&mut self, seed: u64) {
+        self.shuffle(&mut Rng::new(seed));
+    }
+
+    /// Takes `count` cards off the deck, or as many as are left.
+    pub fn deal(&mut self, count: usize) -> Vec<(Suit, Rank)> {
+        let start = self.cards.len().saturating_sub(count);
+        self.cards.split_off(start)
+    }
+
+    /// The cards left, from the bottom of the deck up.
+    pub fn cards(&self) -> &[(Suit, Rank)] {
+        &self.cards
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+}
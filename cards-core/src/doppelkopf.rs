@@ -0,0 +1,349 @@
+//! Doppelkopf for four players, played as a normal game without announcements or extra points.
+//!
+//! Whoever holds a queen of clubs plays Re, and the others Kontra. A player holding both queens
+//! of clubs plays Re alone, as a silent solo. Re needs 121 of the 240 card points to win.
+
+use crate::{
+    ai::{ismcts::HiddenInformation, AiPlayer, GameView, RuleBasedPlayer},
+    card::{notation, Suit, QUEEN},
+    deck::Deck,
+    game::{Framing, GameState, Hint, Label, Location, Orientation, Pile, Sweep},
+    rng::Rng,
+    status::{GameStatus, Outcome, PlayerStatus},
+    trick::{dim_unplayable, Card, TrickRules, Tricks, SEAT_NAMES},
+    trumps::TrumpMode,
+};
+
+pub const SEATS: usize = 4;
+
+/// The seat played from this device, unless the game is played over the network. The computer
+/// plays the others.
+pub const PLAYER: usize = 0;
+
+/// Card points Re needs to win.
+const RE_TARGET: i32 = 121;
+
+const QUEEN_OF_CLUBS: Card = (Suit::Clubs, QUEEN);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DoppelkopfMove {
+    /// The player whose turn it is plays a card into the trick.
+    Play { seat: usize, card: Card },
+    /// Whoever won the finished trick takes it.
+    Collect,
+}
+
+/// How a finished game went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameResult {
+    pub re_points: i32,
+    pub re_won: bool,
+    /// What the game was worth to each player, positive for the winners.
+    pub scores: [i32; SEATS],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Doppelkopf {
+    tricks: Tricks<TrumpMode>,
+    re: [bool; SEATS],
+}
+
+impl Doppelkopf {
+    const TRUMPS: TrumpMode = TrumpMode::Doppelkopf;
+
+    /// Whose turn it is to play, or `None` while a finished trick waits to be collected.
+    pub fn turn(&self) -> Option<usize> {
+        self.tricks.turn()
+    }
+
+    /// The cards `seat` is allowed to play: one of the suit led if it has any, otherwise anything.
+    pub fn playable(&self, seat: usize) -> Vec<Card> {
+        self.tricks.playable(seat)
+    }
+
+    pub fn is_re(&self, seat: usize) -> bool {
+        self.re[seat]
+    }
+
+    /// The outcome, once every trick has been played.
+    pub fn result(&self) -> Option<GameResult> {
+        if !self.is_over() {
+            return None;
+        }
+
+        let points: [i32; SEATS] = std::array::from_fn(|seat| self.tricks.points(seat));
+        let tricks_won: [i32; SEATS] =
+            std::array::from_fn(|seat| self.tricks.tricks_won(seat) as i32);
+
+        let party_total = |re: bool, values: &[i32; SEATS]| -> i32 {
+            (0..SEATS)
+                .filter(|&seat| self.re[seat] == re)
+                .map(|seat| values[seat])
+                .sum()
+        };
+        let re_points = party_total(true, &points);
+        let re_won = re_points >= RE_TARGET;
+        let loser_points = party_total(!re_won, &points);
+
+        let mut game_points = 1;
+        for threshold in [90, 60, 30] {
+            if loser_points < threshold {
+                game_points += 1;
+            }
+        }
+        if party_total(!re_won, &tricks_won) == 0 {
+            game_points += 1;
+        }
+        if !re_won {
+            // Beating the queens of clubs is worth a point of its own.
+            game_points += 1;
+        }
+
+        let re_players = self.re.iter().filter(|&&re| re).count() as i32;
+        let scores = std::array::from_fn(|seat| {
+            let sign = if self.re[seat] == re_won { 1 } else { -1 };
+            // A player on their own wins or loses against all three of the others.
+            let multiplier = if self.re[seat] && re_players == 1 {
+                3
+            } else {
+                1
+            };
+            sign * multiplier * game_points
+        });
+
+        Some(GameResult {
+            re_points,
+            re_won,
+            scores,
+        })
+    }
+
+    /// A simple way to play: take the trick with the strongest card that can, or failing that
+    /// throw the card worth fewest points.
+    fn choose_card(&self, seat: usize) -> Option<Card> {
+        let mut playable = self.playable(seat);
+        Self::TRUMPS.sort_hand(&mut playable);
+
+        playable
+            .iter()
+            .copied()
+            .find(|&card| self.tricks.would_win(card))
+            .or_else(|| {
+                playable
+                    .iter()
+                    .copied()
+                    .min_by_key(|&card| Self::TRUMPS.card_points(card))
+            })
+    }
+}
+
+impl GameState for Doppelkopf {
+    type Move = DoppelkopfMove;
+
+    fn setup(seed: u64) -> Self {
+        let mut deck = Deck::doppelkopf();
+        deck.shuffle_with_seed(seed);
+
+        let hands = (0..SEATS)
+            .map(|_| {
+                let mut hand = deck.deal(48 / SEATS);
+                Self::TRUMPS.sort_hand(&mut hand);
+                hand
+            })
+            .collect::<Vec<_>>();
+        let re = std::array::from_fn(|seat: usize| hands[seat].contains(&QUEEN_OF_CLUBS));
+
+        Self {
+            tricks: Tricks::new(Self::TRUMPS, hands, 0),
+            re,
+        }
+    }
+
+    fn legal_moves(&self) -> Vec<DoppelkopfMove> {
+        match self.turn() {
+            Some(seat) => self
+                .playable(seat)
+                .into_iter()
+                .map(|card| DoppelkopfMove::Play { seat, card })
+                .collect(),
+            None => vec![DoppelkopfMove::Collect],
+        }
+    }
+
+    fn apply_move(&mut self, mv: &DoppelkopfMove) {
+        match *mv {
+            DoppelkopfMove::Play { seat, card } => {
+                self.tricks.play(seat, card);
+            }
+            DoppelkopfMove::Collect => {
+                self.tricks.collect();
+            }
+        }
+    }
+
+    /// `North: QC` for a card played, and `Trick to North` once it's taken.
+    fn describe_move(&self, mv: &DoppelkopfMove) -> String {
+        match *mv {
+            DoppelkopfMove::Play { seat, card } => {
+                format!("{}: {}", SEAT_NAMES[seat], notation(card.0, card.1))
+            }
+            DoppelkopfMove::Collect => match self.tricks.trick_winner() {
+                Some(seat) => format!("Trick to {}", SEAT_NAMES[seat]),
+                None => "Trick collected".to_owned(),
+            },
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.tricks.is_over()
+    }
+
+    /// Whoever's party won has a positive score.
+    fn outcome(&self, seat: usize) -> Option<Outcome> {
+        let score = self.result()?.scores[seat];
+        Some(match score.signum() {
+            1 => Outcome::Won,
+            -1 => Outcome::Lost,
+            _ => Outcome::Drawn,
+        })
+    }
+
+    /// Piles `0..SEATS` are the hands, with only `seat`'s own face up and, on its turn, the cards
+    /// it can't play greyed out, `SEATS..2 * SEATS` the trick and `2 * SEATS..3 * SEATS` the
+    /// cards each seat has taken.
+    fn table(&self, seat: usize, _orientation: Orientation) -> Vec<Pile> {
+        let mut table = self.tricks.table(seat);
+        if self.turn() == Some(seat) {
+            dim_unplayable(&mut table[seat], &self.playable(seat));
+        }
+        table
+    }
+
+    fn labels(&self, seat: usize, _orientation: Orientation) -> Vec<Label> {
+        self.tricks.labels(seat, &SEAT_NAMES)
+    }
+
+    fn sweep(&self, seat: usize, _orientation: Orientation) -> Option<Sweep> {
+        self.tricks.sweep(seat)
+    }
+
+    /// The card the rules of thumb would play next.
+    fn hint(&self, seat: usize) -> Option<Hint> {
+        if self.turn() != Some(seat) {
+            return None;
+        }
+        self.tricks.hint(seat, self.choose_card(seat)?)
+    }
+
+    fn framing(&self, seat: usize) -> Framing {
+        self.tricks.framing(seat)
+    }
+
+    fn must_follow(&self, seat: usize) -> bool {
+        self.tricks.must_follow(seat)
+    }
+
+    fn trick_winners(&self) -> Vec<usize> {
+        self.tricks.winners().to_vec()
+    }
+
+    /// The Re party, who hold the queens of clubs.
+    fn declarers(&self) -> Vec<usize> {
+        (0..SEATS).filter(|&seat| self.re[seat]).collect()
+    }
+
+    fn move_at(&self, seat: usize, location: Location) -> Option<DoppelkopfMove> {
+        if location.pile != seat {
+            return None;
+        }
+
+        let card = *self.tricks.hand(seat).get(location.index?)?;
+        Some(DoppelkopfMove::Play { seat, card })
+    }
+
+    fn move_between(&self, _seat: usize, _from: Location, _to: Location) -> Option<DoppelkopfMove> {
+        None
+    }
+
+    fn seats(&self) -> usize {
+        SEATS
+    }
+
+    fn seats_to_move(&self) -> Vec<usize> {
+        self.turn().into_iter().collect()
+    }
+
+    fn automatic_move(&self) -> Option<DoppelkopfMove> {
+        (self.turn().is_none() && !self.tricks.trick().is_empty())
+            .then_some(DoppelkopfMove::Collect)
+    }
+
+    fn status(&self) -> Option<GameStatus> {
+        let result = self.result();
+
+        let players = (0..SEATS)
+            .map(|seat| match result {
+                // Parties are only revealed once the game is over.
+                Some(result) => PlayerStatus {
+                    name: format!(
+                        "{} ({})",
+                        SEAT_NAMES[seat],
+                        if self.re[seat] { "Re" } else { "Kontra" }
+                    ),
+                    score: result.scores[seat],
+                },
+                None => PlayerStatus {
+                    name: SEAT_NAMES[seat].to_owned(),
+                    score: self.tricks.points(seat),
+                },
+            })
+            .collect();
+
+        Some(GameStatus {
+            players,
+            tricks: Some(self.tricks.tricks_played()),
+            turn: self.turn().filter(|_| result.is_none()),
+        })
+    }
+}
+
+/// The parties are hidden too, so each guess at the others' hands puts them on whichever side the
+/// queens of clubs they're dealt, or have already played, would put them.
+impl HiddenInformation for Doppelkopf {
+    fn determinize(&self, seat: usize, rng: &mut Rng) -> Self {
+        let tricks = self.tricks.redeal_hidden(seat, &[], rng);
+        let re = std::array::from_fn(|other| {
+            tricks.hand(other).contains(&QUEEN_OF_CLUBS)
+                || tricks.history(other).contains(&QUEEN_OF_CLUBS)
+        });
+        Self { tricks, re }
+    }
+
+    /// Winning counts for most, and winning by more a little extra.
+    fn reward(&self, seat: usize) -> f64 {
+        self.result().map_or(0.0, |result| {
+            let won = result.scores[seat] > 0;
+            let margin = (result.scores[seat].abs() as f64 / 12.0).min(1.0);
+            if won {
+                0.75 + 0.25 * margin
+            } else {
+                0.25 - 0.25 * margin
+            }
+        })
+    }
+}
+
+impl AiPlayer<Doppelkopf> for RuleBasedPlayer {
+    fn choose_move(&mut self, view: &GameView<Doppelkopf>) -> DoppelkopfMove {
+        view.state
+            .choose_card(view.seat)
+            .map(|card| DoppelkopfMove::Play {
+                seat: view.seat,
+                card,
+            })
+            .unwrap_or(view.moves[0])
+    }
+}
@@ -0,0 +1,113 @@
+//! Not really a game: the whole deck laid out by suit, for looking at a card sheet. Clicking a
+//! card turns it over.
+
+use strum::IntoEnumIterator;
+
+use crate::{
+    card::{notation, Suit},
+    game::{CardView, Fan, GameState, Location, Orientation, Pile},
+};
+
+/// The gap between neighbouring cards, as a fraction of a card.
+const SPACING: f32 = 1.2;
+
+/// How much of each card in a portrait cascade is left showing beneath the next one.
+const CASCADE_OVERLAP: f32 = 0.3;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gallery {
+    /// One pile per suit, ace first.
+    suits: Vec<Vec<CardView>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Flip(pub Location);
+
+impl GameState for Gallery {
+    type Move = Flip;
+
+    fn setup(_seed: u64) -> Self {
+        let suits = Suit::iter()
+            .map(|suit| {
+                (0..13)
+                    .map(|rank| {
+                        let facedown = (rank + suit.doppelkopf_suit_strength()) % 3 == 0;
+                        // The aces are in foil, to show it off.
+                        CardView {
+                            foil: rank == 0,
+                            ..CardView::new(suit, rank, facedown)
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self { suits }
+    }
+
+    fn legal_moves(&self) -> Vec<Flip> {
+        self.suits
+            .iter()
+            .enumerate()
+            .flat_map(|(pile, cards)| {
+                (0..cards.len()).map(move |index| Location::card(pile, index))
+            })
+            .map(Flip)
+            .collect()
+    }
+
+    fn apply_move(&mut self, Flip(location): &Flip) {
+        if let Some(card) = location
+            .index
+            .and_then(|index| self.suits.get_mut(location.pile)?.get_mut(index))
+        {
+            card.facedown = !card.facedown;
+        }
+    }
+
+    fn describe_move(&self, Flip(location): &Flip) -> String {
+        match location
+            .index
+            .and_then(|index| self.suits.get(location.pile)?.get(index))
+        {
+            Some(card) => format!("Turn over {}", notation(card.suit, card.rank)),
+            None => format!("{:?}", Flip(*location)),
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        false
+    }
+
+    /// Landscape lays each suit out as a row. Portrait stacks each suit into a column of
+    /// overlapping cards, patience-style, so the whole deck fits a narrow screen.
+    fn table(&self, _seat: usize, orientation: Orientation) -> Vec<Pile> {
+        self.suits
+            .iter()
+            .map(|cards| {
+                let suit_offset = cards[0].suit.doppelkopf_suit_strength() as f32 - 2.5;
+                let (position, fan) = match orientation {
+                    Orientation::Landscape => (
+                        [-6.0 * SPACING, SPACING * suit_offset],
+                        Fan::Spread([SPACING, 0.0]),
+                    ),
+                    Orientation::Portrait => (
+                        [SPACING * suit_offset, 6.0 * CASCADE_OVERLAP],
+                        Fan::Spread([0.0, -CASCADE_OVERLAP]),
+                    ),
+                };
+                Pile::new(position, fan, cards.clone())
+            })
+            .collect()
+    }
+
+    fn move_at(&self, _seat: usize, location: Location) -> Option<Flip> {
+        location.index.map(|_| Flip(location))
+    }
+
+    fn move_between(&self, _seat: usize, _from: Location, _to: Location) -> Option<Flip> {
+        None
+    }
+}
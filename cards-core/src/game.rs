@@ -0,0 +1,944 @@
+//! Rules of games, and the loop that plays them, kept apart from how they're shown.
+//!
+//! A game describes its table as piles of cards. Whoever shows it turns clicks on those piles
+//! into input for an `Engine`, and redraws the table from the commands the engine sends back.
+
+pub mod poker;
+
+use std::{fmt::Debug, ops::Range, time::Duration};
+
+use crate::{
+    ai::{AiPlayer, GameView},
+    card::{Rank, Suit},
+    status::{GameStatus, Outcome},
+};
+
+/// How long the engine waits before each move a game or computer player makes, so players can
+/// follow them.
+const AUTOMATIC_MOVE_DELAY: Duration = Duration::from_millis(600);
+
+/// A card as it lies on the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CardView {
+    pub suit: Suit,
+    pub rank: Rank,
+    pub facedown: bool,
+    /// Whether the card is picked up, waiting for the player to say where it goes.
+    pub selected: bool,
+    /// Whether the card is special, and drawn in foil that shimmers as it moves.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub foil: bool,
+    /// Whether the card is greyed out, such as one in the player's hand they can't play now.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dimmed: bool,
+    /// Whether the card is tinted to pick out the player it's with, such as the one taking the
+    /// trick so far.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tinted: bool,
+    /// Whether the player can move the card now, by the rules, which the engine works out.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub playable: bool,
+    /// What the card is marked with in its corner, for one the player can move now, which the
+    /// engine works out along with `playable`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub badge: Option<Badge>,
+}
+
+impl CardView {
+    pub fn new(suit: Suit, rank: Rank, facedown: bool) -> Self {
+        Self {
+            suit,
+            rank,
+            facedown,
+            selected: false,
+            foil: false,
+            dimmed: false,
+            tinted: false,
+            playable: false,
+            badge: None,
+        }
+    }
+}
+
+/// A mark in the corner of a card the player can move now, saying what they can do with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Badge {
+    /// The card can be played, or moved somewhere.
+    Playable,
+    /// The card follows the suit led, which the player has to do while they can.
+    MustFollow,
+    /// The card can be laid down in a meld.
+    CanMeld,
+}
+
+/// How the cards in a pile are spread out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fan {
+    /// Squared up, so only the top card shows.
+    Stacked,
+    /// Each card offset from the one before, in card widths and heights.
+    Spread([f32; 2]),
+}
+
+/// A pile of cards, from the bottom up.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pile {
+    /// The centre of the bottom card, in card widths and heights from the centre of the table,
+    /// with y pointing up.
+    pub position: [f32; 2],
+    pub fan: Fan,
+    pub cards: Vec<CardView>,
+}
+
+impl Pile {
+    pub fn new(position: [f32; 2], fan: Fan, cards: Vec<CardView>) -> Self {
+        Self {
+            position,
+            fan,
+            cards,
+        }
+    }
+
+    /// Where the card at `index` sits, in the same units as `position`.
+    pub fn card_position(&self, index: usize) -> [f32; 2] {
+        match self.fan {
+            Fan::Stacked => self.position,
+            Fan::Spread([x, y]) => [
+                self.position[0] + x * index as f32,
+                self.position[1] + y * index as f32,
+            ],
+        }
+    }
+}
+
+/// Text shown on the table, such as a player's name by their seat.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Label {
+    /// The centre of the text, in the same units as a pile's `position`.
+    pub position: [f32; 2],
+    pub text: String,
+    pub style: LabelStyle,
+}
+
+impl Label {
+    pub fn new(position: [f32; 2], text: String) -> Self {
+        Self {
+            position,
+            text,
+            style: LabelStyle::Plain,
+        }
+    }
+
+    /// A label shown as a badge, such as the number of cards in a hand.
+    pub fn badge(position: [f32; 2], text: String) -> Self {
+        Self {
+            style: LabelStyle::Badge,
+            ..Self::new(position, text)
+        }
+    }
+}
+
+/// Cards on their way from some piles to others that are gathered together first, such as a
+/// finished trick swept up towards whoever won it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sweep {
+    /// The piles the cards are leaving, by index in the table before the move.
+    pub from: Range<usize>,
+    /// Where they gather, in the same units as a pile's `position`.
+    pub via: [f32; 2],
+    /// Whether it's a big trick, worth making a fuss of as it's taken.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub big: bool,
+}
+
+/// The part of the table the camera frames for a phase of a game, such as the player's own
+/// hand while they pick cards to pass, or the trick while it's played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Framing {
+    /// Every card on the table.
+    Table,
+    /// These piles, by index in the table, and the cards on them.
+    Piles(Vec<usize>),
+}
+
+/// A pile cards can be dragged onto, such as a foundation or a tableau column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Zone {
+    pub pile: usize,
+    pub kind: ZoneKind,
+}
+
+impl Zone {
+    pub fn new(pile: usize, kind: ZoneKind) -> Self {
+        Self { pile, kind }
+    }
+}
+
+/// What a zone is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ZoneKind {
+    /// Where a suit is built up, and where cards go to be done with.
+    Foundation,
+    /// A column of the layout cards are moved around in.
+    Tableau,
+    /// Where cards are thrown away.
+    Discard,
+    /// Where cards are laid down together to score.
+    Meld,
+}
+
+/// A move suggested to the player, by where it takes a card from and where it takes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hint {
+    /// The card to move, or the pile to click for moves made on a pile, such as drawing.
+    pub from: Location,
+    /// Where the card goes, for moves that take it somewhere.
+    pub to: Option<Location>,
+}
+
+/// How a label is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LabelStyle {
+    /// Straight onto the table.
+    Plain,
+    /// On a small backing of its own, to stand out over the cards under it.
+    Badge,
+}
+
+/// A place on the table: a card in a pile, or the pile itself when `index` is `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Location {
+    pub pile: usize,
+    pub index: Option<usize>,
+}
+
+impl Location {
+    pub fn card(pile: usize, index: usize) -> Self {
+        Self {
+            pile,
+            index: Some(index),
+        }
+    }
+
+    pub fn pile(pile: usize) -> Self {
+        Self { pile, index: None }
+    }
+}
+
+/// The shape of the window the table is shown in, which games can lay their piles out to suit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Orientation {
+    Landscape,
+    Portrait,
+}
+
+/// The rules of a game and where one is up to. Games are cloned to keep the states moves can be
+/// undone back to.
+pub trait GameState: Clone {
+    type Move: Debug + Clone + PartialEq;
+
+    /// Deals a new game. Games with anything random in them take it from `seed`, so the same seed
+    /// always gives the same game.
+    fn setup(seed: u64) -> Self
+    where
+        Self: Sized;
+
+    /// Every move that can be made from here.
+    fn legal_moves(&self) -> Vec<Self::Move>;
+
+    /// How many players the game has, each in their own seat.
+    fn seats(&self) -> usize {
+        1
+    }
+
+    /// The seats with a move to make, which is more than one when players move at the same time,
+    /// such as when passing cards in Hearts.
+    fn seats_to_move(&self) -> Vec<usize> {
+        Vec::new()
+    }
+
+    /// The moves `seat` can make from here, for games where whose move it is matters.
+    fn moves_for(&self, _seat: usize) -> Vec<Self::Move> {
+        self.legal_moves()
+    }
+
+    /// Makes a move, which must be one of `legal_moves`.
+    fn apply_move(&mut self, mv: &Self::Move);
+
+    /// A move, which must be one of `legal_moves`, written out for the move log, such as
+    /// `West: QS`.
+    fn describe_move(&self, mv: &Self::Move) -> String {
+        format!("{mv:?}")
+    }
+
+    fn is_over(&self) -> bool;
+
+    /// How the game went for `seat`, once it's over, for games someone can win.
+    fn outcome(&self, _seat: usize) -> Option<Outcome> {
+        None
+    }
+
+    /// The table as it is now, as seen from `seat`, the one played from the display showing it.
+    fn table(&self, seat: usize, orientation: Orientation) -> Vec<Pile>;
+
+    /// Text to show on the table as seen from `seat`, such as each player's name by their seat.
+    fn labels(&self, _seat: usize, _orientation: Orientation) -> Vec<Label> {
+        Vec::new()
+    }
+
+    /// The piles cards can be dragged onto, as seen from `seat`, for games played by moving cards
+    /// from one pile to another. Whether a drop makes a move is up to `move_between`.
+    fn zones(&self, _seat: usize, _orientation: Orientation) -> Vec<Zone> {
+        Vec::new()
+    }
+
+    /// The move a double-click on the card at `location` makes for `seat`, sending it somewhere
+    /// better: by default, the first of its `zones` it can be dropped on to make a legal move.
+    fn auto_move(
+        &self,
+        seat: usize,
+        location: Location,
+        orientation: Orientation,
+    ) -> Option<Self::Move> {
+        let legal = self.legal_moves();
+        self.zones(seat, orientation)
+            .into_iter()
+            .filter(|zone| zone.pile != location.pile)
+            .filter_map(|zone| self.move_between(seat, location, Location::pile(zone.pile)))
+            .find(|mv| legal.contains(mv))
+    }
+
+    /// A move to suggest to `seat`, if it has one to make and the game has an idea which.
+    fn hint(&self, _seat: usize) -> Option<Hint> {
+        None
+    }
+
+    /// How the cards the last move took away are swept together on their way, as seen from
+    /// `seat`, for moves that gather several up at once, such as collecting a trick.
+    fn sweep(&self, _seat: usize, _orientation: Orientation) -> Option<Sweep> {
+        None
+    }
+
+    /// What the camera frames for `seat` in the phase the game is in: by default, the whole table.
+    fn framing(&self, _seat: usize) -> Framing {
+        Framing::Table
+    }
+
+    /// Whether `seat`'s moves are held to following the suit led, for trick games, which marks
+    /// the cards it can play as ones it must follow with.
+    fn must_follow(&self, _seat: usize) -> bool {
+        false
+    }
+
+    /// The seat that took each trick so far, in order, for trick games.
+    fn trick_winners(&self) -> Vec<usize> {
+        Vec::new()
+    }
+
+    /// The seats playing as declarer, for games where one side takes on the others.
+    fn declarers(&self) -> Vec<usize> {
+        Vec::new()
+    }
+
+    /// The move `seat` makes by clicking `location` on its own, such as drawing from a stock, if
+    /// there is one.
+    fn move_at(&self, seat: usize, location: Location) -> Option<Self::Move>;
+
+    /// The move `seat` makes by dropping the card picked up at `from` onto `to`, if there is one.
+    fn move_between(&self, seat: usize, from: Location, to: Location) -> Option<Self::Move>;
+
+    /// A move the game makes by itself rather than waiting for a click, such as a finished trick
+    /// being collected or the dealer drawing.
+    fn automatic_move(&self) -> Option<Self::Move> {
+        None
+    }
+
+    /// How long the engine waits before each automatic or computer move.
+    fn automatic_move_delay(&self) -> Duration {
+        AUTOMATIC_MOVE_DELAY
+    }
+
+    /// Scores and turns for the HUD, for games that have them.
+    fn status(&self) -> Option<GameStatus> {
+        None
+    }
+
+    /// Moves made with a button rather than on the table, such as standing in blackjack, each with
+    /// the label for its button.
+    fn actions(&self) -> Vec<(String, Self::Move)> {
+        Vec::new()
+    }
+}
+
+/// What an `Engine` asks its display to do.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Command {
+    /// Show the table like this.
+    Table(Vec<Pile>),
+    /// Show these labels on the table, in place of any shown before.
+    Labels(Vec<Label>),
+    /// Let cards be dragged onto these zones, in place of any before.
+    Zones(Vec<Zone>),
+    /// Sweep the cards on their way to the next table together like this.
+    Sweep(Sweep),
+    /// Show this in the HUD, or hide it for `None`.
+    Status(Option<GameStatus>),
+    /// Offer these actions as buttons, by label, replacing any offered before.
+    Actions(Vec<String>),
+    /// The game has finished, going this way for the player if the game says.
+    GameOver(Option<Outcome>),
+    /// Frame this part of the table, the game having moved on to a phase that shows it best.
+    Framing(Framing),
+}
+
+/// Plays a game: turns clicks into moves, has the computer play its seats, and reports what
+/// changed as commands.
+pub struct Engine<G: GameState> {
+    state: G,
+    /// The computer players, by seat, with `None` for seats played from this device.
+    computer_players: Vec<Option<Box<dyn AiPlayer<G>>>>,
+    /// The seat played from this device, which the table is shown from and clicks move for.
+    player: usize,
+    /// For a game played elsewhere, such as on a server, the moves the player has asked for, by
+    /// index in `legal_moves`, waiting to be sent there. `None` for a game played here.
+    requested: Option<Vec<usize>>,
+    orientation: Orientation,
+    selected: Option<Location>,
+    commands: Vec<Command>,
+    /// Time since the last automatic move, or since the game was waiting on one.
+    waited: Duration,
+    /// Every move made, oldest first.
+    moves: Vec<MoveRecord>,
+    /// The moves made, most recent last, for undoing.
+    undo: Vec<Step<G>>,
+    /// The moves undone, most recently undone last, for redoing until a new move is made.
+    redo: Vec<Step<G>>,
+    /// How long the game has been played for, not counting time after it's over.
+    elapsed: Duration,
+    /// How the cards the last move took away are swept together, to go with the table it leaves.
+    sweep: Option<Sweep>,
+    /// What the camera was last asked to frame, if anything yet.
+    framing: Option<Framing>,
+}
+
+impl<G: GameState> Engine<G> {
+    pub fn new(state: G, orientation: Orientation) -> Self {
+        let mut engine = Self {
+            computer_players: (0..state.seats()).map(|_| None).collect(),
+            state,
+            player: 0,
+            requested: None,
+            orientation,
+            selected: None,
+            commands: Vec::new(),
+            waited: Duration::ZERO,
+            moves: Vec::new(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+            elapsed: Duration::ZERO,
+            sweep: None,
+            framing: None,
+        };
+        engine.push_table();
+        engine.push_status();
+        engine
+    }
+
+    /// Has the computer play every seat but `player`'s, with a player made for each seat by
+    /// `make_player`.
+    pub fn with_computer_players(
+        mut self,
+        player: usize,
+        mut make_player: impl FnMut(usize) -> Box<dyn AiPlayer<G>>,
+    ) -> Self {
+        for (seat, computer) in self.computer_players.iter_mut().enumerate() {
+            if seat != player {
+                *computer = Some(make_player(seat));
+            }
+        }
+        self
+    }
+
+    /// Leaves the game to be played elsewhere, such as on a server, with this device playing
+    /// `seat`. The player's moves are only asked for, through `Game::take_requested_moves`, and
+    /// every move is made by `Game::restore` once it's been made there.
+    pub fn played_elsewhere(mut self, seat: usize) -> Self {
+        self.player = seat;
+        self.requested = Some(Vec::new());
+        self.commands.clear();
+        self.push_table();
+        self.push_status();
+        self
+    }
+
+    /// Has `player` play `seat`, or nobody for `None`, leaving the seat to be played from
+    /// elsewhere.
+    pub fn set_computer_player(&mut self, seat: usize, player: Option<Box<dyn AiPlayer<G>>>) {
+        if let Some(computer) = self.computer_players.get_mut(seat) {
+            *computer = player;
+        }
+    }
+
+    pub fn state(&self) -> &G {
+        &self.state
+    }
+
+    /// Makes a move for the player if it's legal, returning whether it was. In a game played
+    /// elsewhere, the move is only asked for.
+    pub fn play(&mut self, mv: &G::Move) -> bool {
+        let Some(requested) = &mut self.requested else {
+            return self.make_move(mv, true);
+        };
+
+        let Some(index) = self
+            .state
+            .legal_moves()
+            .iter()
+            .position(|legal| legal == mv)
+        else {
+            return false;
+        };
+        requested.push(index);
+        self.select(None);
+        true
+    }
+
+    /// Makes a move if it's legal, on behalf of the player or otherwise, returning whether it was.
+    fn make_move(&mut self, mv: &G::Move, by_player: bool) -> bool {
+        let Some(index) = self
+            .state
+            .legal_moves()
+            .iter()
+            .position(|legal| legal == mv)
+        else {
+            return false;
+        };
+
+        self.redo.clear();
+        self.record(mv, index, by_player, self.elapsed);
+        self.after_move();
+
+        true
+    }
+
+    /// Makes a move, made `time` into the game, keeping a record of it and the state before it
+    /// for undoing.
+    fn record(&mut self, mv: &G::Move, index: usize, by_player: bool, time: Duration) {
+        self.undo.push(Step {
+            before: self.state.clone(),
+            index,
+            by_player,
+        });
+        self.moves.push(MoveRecord {
+            index,
+            notation: self.state.describe_move(mv),
+            time,
+        });
+        self.state.apply_move(mv);
+        self.sweep = self.state.sweep(self.player, self.orientation);
+    }
+
+    /// Makes an undone move again.
+    fn replay(&mut self, step: Step<G>) {
+        if let Some(mv) = step.before.legal_moves().get(step.index) {
+            self.record(mv, step.index, step.by_player, self.elapsed);
+        }
+    }
+
+    /// Whether the next move is the player's, rather than the game's or a computer player's.
+    fn player_to_move(&self) -> bool {
+        self.state.automatic_move().is_none() && self.computer_seat().is_none()
+    }
+
+    /// Shows the table as it is after a move, or after moves are undone or redone.
+    fn after_move(&mut self) {
+        self.selected = None;
+        if let Some(sweep) = self.sweep.take() {
+            self.commands.push(Command::Sweep(sweep));
+        }
+        self.push_table();
+        self.push_status();
+        if self.state.is_over() {
+            self.commands
+                .push(Command::GameOver(self.state.outcome(self.player)));
+        }
+    }
+
+    fn push_table(&mut self) {
+        let mut table = self.state.table(self.player, self.orientation);
+
+        if let Some(Location {
+            pile,
+            index: Some(index),
+        }) = self.selected
+        {
+            if let Some(card) = table
+                .get_mut(pile)
+                .and_then(|pile| pile.cards.get_mut(index))
+            {
+                card.selected = true;
+            }
+        }
+        self.mark_playable(&mut table);
+
+        self.commands.push(Command::Table(table));
+        self.commands.push(Command::Labels(
+            self.state.labels(self.player, self.orientation),
+        ));
+        self.commands.push(Command::Zones(
+            self.state.zones(self.player, self.orientation),
+        ));
+
+        let framing = self.state.framing(self.player);
+        if self.framing.as_ref() != Some(&framing) {
+            self.framing = Some(framing.clone());
+            self.commands.push(Command::Framing(framing));
+        }
+    }
+
+    /// Marks the face-up cards in `table` that the player can move now, on their own or onto a
+    /// pile, and badges each with what it can do: be laid in a meld, follow the suit led, or
+    /// just be played.
+    fn mark_playable(&self, table: &mut [Pile]) {
+        if !self.player_to_move() || self.state.is_over() {
+            return;
+        }
+
+        let legal = self.state.moves_for(self.player);
+        let is_legal = |mv: Option<G::Move>| mv.is_some_and(|mv| legal.contains(&mv));
+        let must_follow = self.state.must_follow(self.player);
+        let melds = self
+            .state
+            .zones(self.player, self.orientation)
+            .into_iter()
+            .filter(|zone| zone.kind == ZoneKind::Meld)
+            .map(|zone| zone.pile)
+            .collect::<Vec<_>>();
+        let piles = table.len();
+        for (pile_index, pile) in table.iter_mut().enumerate() {
+            for (index, card) in pile.cards.iter_mut().enumerate() {
+                let from = Location::card(pile_index, index);
+                let moves_to = |to: usize| {
+                    is_legal(
+                        self.state
+                            .move_between(self.player, from, Location::pile(to)),
+                    )
+                };
+                card.playable = !card.facedown
+                    && (is_legal(self.state.move_at(self.player, from))
+                        || (0..piles).any(moves_to));
+                card.badge = card.playable.then(|| {
+                    if melds.iter().any(|&to| moves_to(to)) {
+                        Badge::CanMeld
+                    } else if must_follow {
+                        Badge::MustFollow
+                    } else {
+                        Badge::Playable
+                    }
+                });
+            }
+        }
+    }
+
+    fn push_status(&mut self) {
+        self.commands.push(Command::Status(self.state.status()));
+        self.commands.push(Command::Actions(
+            self.state
+                .actions()
+                .into_iter()
+                .map(|(label, _)| label)
+                .collect(),
+        ));
+    }
+
+    /// The first seat with a move to make that the computer plays.
+    fn computer_seat(&self) -> Option<usize> {
+        self.state
+            .seats_to_move()
+            .into_iter()
+            .find(|&seat| matches!(self.computer_players.get(seat), Some(Some(_))))
+    }
+
+    fn computer_move(&mut self) -> Option<G::Move> {
+        let seat = self.computer_seat()?;
+        let view = GameView::new(&self.state, seat)?;
+        let player = self.computer_players[seat].as_mut()?;
+        Some(player.choose_move(&view))
+    }
+
+    fn select(&mut self, location: Option<Location>) {
+        if self.selected != location {
+            self.selected = location;
+            self.push_table();
+        }
+    }
+}
+
+/// A move made, as a game keeps it to save, show or play back.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveRecord {
+    /// The move's index in `legal_moves` when it was made. With the seed the game was dealt
+    /// from, the indices of every move are enough to play the game again.
+    pub index: usize,
+    /// The move as the game writes it out, such as `West: QS`, or nothing if it wasn't.
+    pub notation: String,
+    /// How long the game had been played for when the move was made.
+    pub time: Duration,
+}
+
+/// A move made, with the state before it to undo it back to.
+struct Step<G> {
+    before: G,
+    /// The move's index in `before.legal_moves()`.
+    index: usize,
+    by_player: bool,
+}
+
+/// A game being played, whatever its rules. This is what displays hold on to.
+pub trait Game {
+    /// Handles a click on the table, at `location` or on nothing in particular for `None`.
+    ///
+    /// A click that finishes a move from the card picked up, or makes a move on its own, makes
+    /// it. Otherwise a click on a card picks it up, and the next click says where it goes.
+    fn click(&mut self, location: Option<Location>);
+
+    /// Makes the move offered as action `index` in the last `Command::Actions`.
+    fn act(&mut self, index: usize);
+
+    /// Whether dropping the card picked up at `from` onto the pile `to` would make a move.
+    fn can_drop(&self, from: Location, to: usize) -> bool;
+
+    /// Sends the card at `location` wherever a double-click on it does, returning whether it
+    /// went anywhere.
+    fn auto_move(&mut self, location: Location) -> bool;
+
+    /// A move the player could make now, if the game has one to suggest.
+    fn hint(&self) -> Option<Hint>;
+
+    /// Every move made so far, oldest first.
+    fn moves(&self) -> &[MoveRecord];
+
+    /// The seat played from this device.
+    fn seat(&self) -> usize;
+
+    /// The seat that took each trick so far, in order, for trick games.
+    fn trick_winners(&self) -> Vec<usize>;
+
+    /// The seats playing as declarer, for games where one side takes on the others.
+    fn declarers(&self) -> Vec<usize>;
+
+    /// How long the game has been played for.
+    fn elapsed(&self) -> Duration;
+
+    /// The moves the player has asked for since the last call in a game played elsewhere, by
+    /// index in `legal_moves`, oldest first. Games played here make moves straight away instead.
+    fn take_requested_moves(&mut self) -> Vec<usize>;
+
+    /// Takes back the player's last move, along with any moves made after it by the game or the
+    /// computer, returning whether there was one. Games played elsewhere can't be undone.
+    fn undo(&mut self) -> bool;
+
+    /// Makes the last move undone again, along with the moves that followed it, returning
+    /// whether there was one. Making any other move forgets the moves undone.
+    fn redo(&mut self) -> bool;
+
+    /// Plays `moves`, as another game dealt from the same seed recorded them, and sets the time
+    /// played to `elapsed`, to carry on a saved game or play one back. Returns whether every move
+    /// could be made; if not, the game is left after the last one that could.
+    fn restore(&mut self, moves: &[MoveRecord], elapsed: Duration) -> bool;
+
+    /// Lets time pass, making any automatic moves that are due.
+    fn update(&mut self, dt: Duration);
+
+    /// Lays the table out again for a different window shape.
+    fn set_orientation(&mut self, orientation: Orientation);
+
+    /// Takes the commands sent since the last call, oldest first.
+    fn take_commands(&mut self) -> Vec<Command>;
+}
+
+impl<G: GameState> Game for Engine<G> {
+    fn click(&mut self, location: Option<Location>) {
+        let Some(location) = location else {
+            self.select(None);
+            return;
+        };
+
+        if let Some(from) = self.selected {
+            if from == location {
+                self.select(None);
+                return;
+            }
+
+            if let Some(mv) = self.state.move_between(self.player, from, location) {
+                if self.play(&mv) {
+                    return;
+                }
+            }
+        }
+
+        if let Some(mv) = self.state.move_at(self.player, location) {
+            if self.play(&mv) {
+                return;
+            }
+        }
+
+        // Only cards can be picked up.
+        self.select(location.index.map(|_| location));
+    }
+
+    fn act(&mut self, index: usize) {
+        if let Some((_, mv)) = self.state.actions().into_iter().nth(index) {
+            self.play(&mv);
+        }
+    }
+
+    fn can_drop(&self, from: Location, to: usize) -> bool {
+        self.state
+            .move_between(self.player, from, Location::pile(to))
+            .is_some_and(|mv| self.state.legal_moves().contains(&mv))
+    }
+
+    fn auto_move(&mut self, location: Location) -> bool {
+        self.state
+            .auto_move(self.player, location, self.orientation)
+            .is_some_and(|mv| self.play(&mv))
+    }
+
+    fn hint(&self) -> Option<Hint> {
+        self.state.hint(self.player)
+    }
+
+    fn moves(&self) -> &[MoveRecord] {
+        &self.moves
+    }
+
+    fn seat(&self) -> usize {
+        self.player
+    }
+
+    fn trick_winners(&self) -> Vec<usize> {
+        self.state.trick_winners()
+    }
+
+    fn declarers(&self) -> Vec<usize> {
+        self.state.declarers()
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    fn restore(&mut self, moves: &[MoveRecord], elapsed: Duration) -> bool {
+        let mut complete = true;
+        for record in moves {
+            let Some(mv) = self.state.legal_moves().into_iter().nth(record.index) else {
+                complete = false;
+                break;
+            };
+            self.record(&mv, record.index, self.player_to_move(), record.time);
+        }
+        self.elapsed = elapsed;
+
+        // Only the table as it ends up matters, not every step on the way.
+        self.commands.clear();
+        self.after_move();
+
+        complete
+    }
+
+    fn take_requested_moves(&mut self) -> Vec<usize> {
+        self.requested
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    fn undo(&mut self) -> bool {
+        if self.requested.is_some() || !self.undo.iter().any(|step| step.by_player) {
+            return false;
+        }
+
+        while let Some(step) = self.undo.pop() {
+            self.moves.pop();
+            self.state = step.before.clone();
+            let by_player = step.by_player;
+            self.redo.push(step);
+            if by_player {
+                break;
+            }
+        }
+
+        self.sweep = None;
+        self.waited = Duration::ZERO;
+        self.after_move();
+        true
+    }
+
+    fn redo(&mut self) -> bool {
+        let Some(step) = self.redo.pop() else {
+            return false;
+        };
+
+        self.replay(step);
+        while let Some(step) = self.redo.pop_if(|step| !step.by_player) {
+            self.replay(step);
+        }
+
+        self.waited = Duration::ZERO;
+        self.after_move();
+        true
+    }
+
+    fn update(&mut self, dt: Duration) {
+        if !self.state.is_over() {
+            self.elapsed += dt;
+        }
+
+        // Whoever plays the game elsewhere makes its automatic and computer moves.
+        if self.player_to_move() || self.requested.is_some() {
+            self.waited = Duration::ZERO;
+            return;
+        }
+
+        self.waited += dt;
+        if self.waited < self.state.automatic_move_delay() {
+            return;
+        }
+        self.waited = Duration::ZERO;
+
+        let mv = match self.state.automatic_move() {
+            Some(mv) => Some(mv),
+            None => self.computer_move(),
+        };
+        if let Some(mv) = mv {
+            self.make_move(&mv, false);
+        }
+    }
+
+    fn set_orientation(&mut self, orientation: Orientation) {
+        if self.orientation != orientation {
+            self.orientation = orientation;
+            self.push_table();
+        }
+    }
+
+    fn take_commands(&mut self) -> Vec<Command> {
+        std::mem::take(&mut self.commands)
+    }
+}
@@ -0,0 +1,208 @@
+//! Ranking poker hands, for any game or simulation that needs to know who holds the best five.
+//!
+//! Hands of five to seven cards are ranked in one pass over their ranks and suits, without
+//! trying each five-card combination, so ranking a seven-card hand is as cheap as a five-card one.
+
+use std::cmp::Ordering;
+
+use crate::trick::{ace_high, Card};
+
+/// The kinds of poker hand, weakest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Category {
+    HighCard,
+    Pair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+impl Category {
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::HighCard => "High card",
+            Category::Pair => "Pair",
+            Category::TwoPair => "Two pair",
+            Category::ThreeOfAKind => "Three of a kind",
+            Category::Straight => "Straight",
+            Category::Flush => "Flush",
+            Category::FullHouse => "Full house",
+            Category::FourOfAKind => "Four of a kind",
+            Category::StraightFlush => "Straight flush",
+        }
+    }
+}
+
+/// How good a hand is. Hands compare by category, then by the ranks that break ties between
+/// hands of the same category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandRank {
+    pub category: Category,
+    /// The ranks that decide between hands of the same category, most important first, counted
+    /// from 1 for a two up to 13 for an ace. Unused places are 0.
+    pub tiebreak: [u8; 5],
+}
+
+/// Bits 1 to 13 of a rank mask are the twos to the aces, and bit 0 is an ace again, low.
+type RankMask = u16;
+
+/// The highest rank of the best straight in `mask`, with 4 for a five-high straight.
+fn straight_high(mut mask: RankMask) -> Option<u8> {
+    if mask & (1 << 13) != 0 {
+        mask |= 1;
+    }
+
+    (4..=13)
+        .rev()
+        .find(|&high| (mask >> (high - 4)) & 0b11111 == 0b11111)
+}
+
+/// The `count` highest ranks in `mask`, highest first.
+fn highest(mask: RankMask, count: usize) -> impl Iterator<Item = u8> {
+    (1..=13)
+        .rev()
+        .filter(move |&value| mask & (1 << value) != 0)
+        .take(count)
+}
+
+fn tiebreak(values: impl IntoIterator<Item = u8>) -> [u8; 5] {
+    let mut tiebreak = [0; 5];
+    for (slot, value) in tiebreak.iter_mut().zip(values) {
+        *slot = value;
+    }
+    tiebreak
+}
+
+/// Ranks the best five cards among `cards`, which should number between five and seven.
+pub fn evaluate(cards: &[Card]) -> HandRank {
+    let mut counts = [0u8; 14];
+    let mut suits = [0 as RankMask; 4];
+    let mut ranks: RankMask = 0;
+
+    for &(suit, rank) in cards {
+        let value = ace_high(rank);
+        counts[value as usize] += 1;
+        suits[suit as usize] |= 1 << value;
+        ranks |= 1 << value;
+    }
+
+    let flush = suits.iter().copied().find(|mask| mask.count_ones() >= 5);
+    if let Some(high) = flush.and_then(straight_high) {
+        return HandRank {
+            category: Category::StraightFlush,
+            tiebreak: tiebreak([high]),
+        };
+    }
+
+    // Ranks held exactly `count` times, highest first.
+    let with_count = |count: u8| -> Vec<u8> {
+        (1..=13u8)
+            .rev()
+            .filter(|&value| counts[value as usize] == count)
+            .collect()
+    };
+    let (quads, trips, pairs) = (with_count(4), with_count(3), with_count(2));
+    let without = |values: &[u8]| {
+        values
+            .iter()
+            .fold(ranks, |mask, &value| mask & !(1 << value))
+    };
+
+    if let Some(&quad) = quads.first() {
+        return HandRank {
+            category: Category::FourOfAKind,
+            tiebreak: tiebreak([quad].into_iter().chain(highest(without(&[quad]), 1))),
+        };
+    }
+
+    if let Some(&trip) = trips.first() {
+        // A second set of three can make up the pair.
+        let pair = trips.get(1).into_iter().chain(&pairs).copied().max();
+        if let Some(pair) = pair {
+            return HandRank {
+                category: Category::FullHouse,
+                tiebreak: tiebreak([trip, pair]),
+            };
+        }
+    }
+
+    if let Some(mask) = flush {
+        return HandRank {
+            category: Category::Flush,
+            tiebreak: tiebreak(highest(mask, 5)),
+        };
+    }
+
+    if let Some(high) = straight_high(ranks) {
+        return HandRank {
+            category: Category::Straight,
+            tiebreak: tiebreak([high]),
+        };
+    }
+
+    if let Some(&trip) = trips.first() {
+        return HandRank {
+            category: Category::ThreeOfAKind,
+            tiebreak: tiebreak([trip].into_iter().chain(highest(without(&[trip]), 2))),
+        };
+    }
+
+    if let [high, low, ..] = pairs[..] {
+        return HandRank {
+            category: Category::TwoPair,
+            tiebreak: tiebreak(
+                [high, low]
+                    .into_iter()
+                    .chain(highest(without(&[high, low]), 1)),
+            ),
+        };
+    }
+
+    if let Some(&pair) = pairs.first() {
+        return HandRank {
+            category: Category::Pair,
+            tiebreak: tiebreak([pair].into_iter().chain(highest(without(&[pair]), 3))),
+        };
+    }
+
+    HandRank {
+        category: Category::HighCard,
+        tiebreak: tiebreak(highest(ranks, 5)),
+    }
+}
+
+/// Compares two hands by their best five cards.
+pub fn compare(a: &[Card], b: &[Card]) -> Ordering {
+    evaluate(a).cmp(&evaluate(b))
+}
+
+/// The five cards among `cards` that make the best hand, with its rank, or `None` if there are
+/// fewer than five.
+pub fn best_five(cards: &[Card]) -> Option<([Card; 5], HandRank)> {
+    let count = cards.len();
+    let mut best: Option<([Card; 5], HandRank)> = None;
+
+    for a in 0..count {
+        for b in a + 1..count {
+            for c in b + 1..count {
+                for d in c + 1..count {
+                    for e in d + 1..count {
+                        let hand = [cards[a], cards[b], cards[c], cards[d], cards[e]];
+                        let rank = evaluate(&hand);
+                        if best.is_none_or(|(_, best)| rank > best) {
+                            best = Some((hand, rank));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    best
+}
@@ -0,0 +1,567 @@
+//! Hearts for four players, as a single hand with three cards passed to the left.
+//!
+//! Every heart taken costs a point and the queen of spades thirteen, so the lowest score wins.
+//! Whoever holds the two of clubs leads it to the first trick, in which no points may be thrown
+//! unless there's nothing else to play. Hearts can't be led until one has been played. Taking
+//! every point in the deck shoots the moon, costing everyone else all of them instead.
+
+use crate::{
+    ai::{ismcts::HiddenInformation, AiPlayer, GameView, RuleBasedPlayer},
+    card::{notation, Suit, QUEEN, TWO},
+    deck::Deck,
+    game::{Framing, GameState, Hint, Label, Location, Orientation, Pile, Sweep},
+    rng::Rng,
+    status::{GameStatus, Outcome, PlayerStatus},
+    trick::{ace_high, dim_unplayable, Card, TrickRules, Tricks, SEAT_NAMES},
+};
+
+pub const SEATS: usize = 4;
+
+/// The seat played from this device, unless the game is played over the network. The computer
+/// plays the others.
+pub const PLAYER: usize = 0;
+
+/// How many cards each seat passes on.
+const PASS_SIZE: usize = 3;
+
+/// Every point in the deck, which is what shooting the moon takes.
+const MOON: i32 = 26;
+
+/// The order suits are held in, black and red in turn.
+const HAND_ORDER: [Suit; 4] = [Suit::Clubs, Suit::Hearts, Suit::Spades, Suit::Diamonds];
+
+const TWO_OF_CLUBS: Card = (Suit::Clubs, TWO);
+const QUEEN_OF_SPADES: Card = (Suit::Spades, QUEEN);
+
+/// No trumps, with hearts and the queen of spades counting against whoever takes them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeartsRules;
+
+impl TrickRules for HeartsRules {
+    fn trump_strength(&self, _card: Card) -> Option<u8> {
+        None
+    }
+
+    fn card_points(&self, card: Card) -> i32 {
+        match card {
+            QUEEN_OF_SPADES => 13,
+            (Suit::Hearts, _) => 1,
+            _ => 0,
+        }
+    }
+
+    /// A trick with the queen of spades in it.
+    fn is_big_trick(&self, points: i32) -> bool {
+        points >= 13
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HeartsMove {
+    /// Picks a card to pass on, or puts it back if it was already picked. Picking a fourth card
+    /// puts back the first.
+    Choose { seat: usize, card: Card },
+    /// Settles on the cards `seat` has picked, once it has picked enough, leaving them as they
+    /// are until they're passed.
+    Confirm { seat: usize },
+    /// Once every seat has confirmed its cards, passes them to the left.
+    Pass,
+    /// The player whose turn it is plays a card into the trick.
+    Play { seat: usize, card: Card },
+    /// Whoever won the finished trick takes it.
+    Collect,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hearts {
+    tricks: Tricks<HeartsRules>,
+    /// The cards each seat has picked to pass on, until they've been passed.
+    passing: Option<[Vec<Card>; SEATS]>,
+    /// Which seats have confirmed the cards they picked to pass.
+    confirmed: [bool; SEATS],
+    /// The cards each seat passed on, once they've been passed.
+    passed: [Vec<Card>; SEATS],
+}
+
+impl Hearts {
+    /// Whose turn it is to play, or `None` while cards are being passed or a finished trick
+    /// waits to be collected.
+    pub fn turn(&self) -> Option<usize> {
+        self.passing.is_none().then(|| self.tricks.turn()).flatten()
+    }
+
+    /// The cards `seat` is allowed to play.
+    pub fn playable(&self, seat: usize) -> Vec<Card> {
+        let playable = self.tricks.playable(seat);
+        let leading = self.tricks.trick().is_empty();
+
+        if self.tricks.tricks_played() == 0 {
+            if leading {
+                return playable
+                    .into_iter()
+                    .filter(|&card| card == TWO_OF_CLUBS)
+                    .collect();
+            }
+
+            return Self::unless_nothing_else(playable, |card| HeartsRules.card_points(card) > 0);
+        }
+
+        if leading && !self.hearts_broken() {
+            return Self::unless_nothing_else(playable, |(suit, _)| suit == Suit::Hearts);
+        }
+
+        playable
+    }
+
+    /// Each seat's points once every trick has been played, after anyone shooting the moon.
+    pub fn result(&self) -> Option<[i32; SEATS]> {
+        if !self.is_over() {
+            return None;
+        }
+
+        let taken: [i32; SEATS] = std::array::from_fn(|seat| self.tricks.points(seat));
+        Some(match taken.iter().position(|&points| points == MOON) {
+            Some(shooter) => std::array::from_fn(|seat| if seat == shooter { 0 } else { MOON }),
+            None => taken,
+        })
+    }
+
+    /// Whether a heart has been played yet, after which hearts may be led.
+    fn hearts_broken(&self) -> bool {
+        (0..SEATS)
+            .flat_map(|seat| self.tricks.taken(seat))
+            .chain(self.tricks.trick())
+            .any(|&(suit, _)| suit == Suit::Hearts)
+    }
+
+    /// The cards that aren't `ruled_out`, or all of them if every one is.
+    fn unless_nothing_else(cards: Vec<Card>, ruled_out: impl Fn(Card) -> bool) -> Vec<Card> {
+        let allowed = cards
+            .iter()
+            .copied()
+            .filter(|&card| !ruled_out(card))
+            .collect::<Vec<_>>();
+
+        if allowed.is_empty() {
+            cards
+        } else {
+            allowed
+        }
+    }
+
+    /// Suits in turn, alternating colours, each from the two up to the ace.
+    fn sort_hand(hand: &mut [Card]) {
+        hand.sort_by_key(|&(suit, rank)| {
+            let place = HAND_ORDER.iter().position(|&other| other == suit);
+            (place, ace_high(rank))
+        });
+    }
+
+    /// How much trouble a card is likely to cause whoever holds it: high spades that could catch
+    /// the queen, then hearts, then everything else by rank.
+    fn danger((suit, rank): Card) -> u8 {
+        match suit {
+            Suit::Spades if ace_high(rank) >= ace_high(QUEEN) => 40 + ace_high(rank),
+            Suit::Hearts => 20 + ace_high(rank),
+            _ => ace_high(rank),
+        }
+    }
+
+    /// A simple way to pick cards to pass: the most dangerous ones.
+    fn choose_pass(hand: &[Card]) -> Option<[Card; PASS_SIZE]> {
+        let mut by_danger = hand.to_vec();
+        by_danger.sort_by_key(|&card| std::cmp::Reverse(Self::danger(card)));
+
+        by_danger.get(..PASS_SIZE)?.try_into().ok()
+    }
+
+    /// The next card the rules of thumb would pick for `seat` to pass, while it's still picking.
+    /// Once it's picked enough there's none, and the pass is confirmed instead.
+    fn next_pass_choice(&self, seat: usize) -> Option<Card> {
+        let passing = self.passing.as_ref()?;
+        if passing[seat].len() >= PASS_SIZE {
+            return None;
+        }
+        Self::choose_pass(self.tricks.hand(seat))?
+            .into_iter()
+            .find(|card| !passing[seat].contains(card))
+    }
+
+    /// A simple way to play: lead low, duck under the trick with the highest card
+    /// that still loses it, and throw the most dangerous card when unable to follow suit.
+    fn choose_card(&self, seat: usize) -> Option<Card> {
+        let playable = self.playable(seat);
+        let trick = self.tricks.trick();
+
+        let Some(&(led, _)) = trick.first() else {
+            return playable
+                .into_iter()
+                .min_by_key(|&(suit, rank)| (suit == Suit::Hearts, ace_high(rank)));
+        };
+
+        if playable.iter().all(|&(suit, _)| suit == led) {
+            let ducking = playable
+                .iter()
+                .copied()
+                .filter(|&card| !self.tricks.would_win(card))
+                .max_by_key(|&(_, rank)| ace_high(rank));
+
+            // Taking a trick anyway, the last to play might as well take it with its highest card.
+            let last = trick.len() == SEATS - 1;
+            return ducking.or_else(|| {
+                let ranks = playable.iter().copied();
+                if last {
+                    ranks.max_by_key(|&(_, rank)| ace_high(rank))
+                } else {
+                    ranks.min_by_key(|&(_, rank)| ace_high(rank))
+                }
+            });
+        }
+
+        playable.into_iter().max_by_key(|&card| Self::danger(card))
+    }
+}
+
+impl GameState for Hearts {
+    type Move = HeartsMove;
+
+    fn setup(seed: u64) -> Self {
+        let mut deck = Deck::standard();
+        deck.shuffle_with_seed(seed);
+
+        let hands = (0..SEATS)
+            .map(|_| {
+                let mut hand = deck.deal(52 / SEATS);
+                Self::sort_hand(&mut hand);
+                hand
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            tricks: Tricks::new(HeartsRules, hands, 0),
+            passing: Some(Default::default()),
+            confirmed: [false; SEATS],
+            passed: Default::default(),
+        }
+    }
+
+    fn legal_moves(&self) -> Vec<HeartsMove> {
+        if self.passing.is_some() {
+            let mut moves = (0..SEATS)
+                .flat_map(|seat| self.moves_for(seat))
+                .collect::<Vec<_>>();
+            if self.confirmed.iter().all(|&confirmed| confirmed) {
+                moves.push(HeartsMove::Pass);
+            }
+            return moves;
+        }
+
+        match self.turn() {
+            Some(seat) => self
+                .playable(seat)
+                .into_iter()
+                .map(|card| HeartsMove::Play { seat, card })
+                .collect(),
+            None if !self.tricks.trick().is_empty() => vec![HeartsMove::Collect],
+            None => Vec::new(),
+        }
+    }
+
+    fn apply_move(&mut self, mv: &HeartsMove) {
+        match *mv {
+            HeartsMove::Choose { seat, card } => {
+                let Some(passing) = &mut self.passing else {
+                    return;
+                };
+                if self.confirmed[seat] {
+                    return;
+                }
+                let picked = &mut passing[seat];
+
+                if let Some(index) = picked.iter().position(|&held| held == card) {
+                    picked.remove(index);
+                } else if self.tricks.hand(seat).contains(&card) {
+                    picked.push(card);
+                    if picked.len() > PASS_SIZE {
+                        picked.remove(0);
+                    }
+                }
+            }
+            HeartsMove::Confirm { seat } => {
+                if let Some(passing) = &self.passing {
+                    self.confirmed[seat] = passing[seat].len() == PASS_SIZE;
+                }
+            }
+            HeartsMove::Pass => {
+                let Some(passing) = self.passing.take() else {
+                    return;
+                };
+
+                let hands = (0..SEATS)
+                    .map(|seat| {
+                        let mut hand = self.tricks.hand(seat).to_vec();
+                        hand.retain(|card| !passing[seat].contains(card));
+                        hand.extend(&passing[(seat + SEATS - 1) % SEATS]);
+                        Self::sort_hand(&mut hand);
+                        hand
+                    })
+                    .collect::<Vec<_>>();
+                let leader = hands
+                    .iter()
+                    .position(|hand| hand.contains(&TWO_OF_CLUBS))
+                    .unwrap_or(0);
+
+                self.tricks = Tricks::new(HeartsRules, hands, leader);
+                self.passed = passing;
+            }
+            HeartsMove::Play { seat, card } => {
+                self.tricks.play(seat, card);
+            }
+            HeartsMove::Collect => {
+                self.tricks.collect();
+            }
+        }
+    }
+
+    /// `North: 2C` for a card played, and `Trick to North` once it's taken.
+    fn describe_move(&self, mv: &HeartsMove) -> String {
+        match *mv {
+            HeartsMove::Choose { seat, card } => {
+                format!("{}: pick {}", SEAT_NAMES[seat], notation(card.0, card.1))
+            }
+            HeartsMove::Confirm { seat } => format!("{}: pass confirmed", SEAT_NAMES[seat]),
+            HeartsMove::Pass => "Cards passed left".to_owned(),
+            HeartsMove::Play { seat, card } => {
+                format!("{}: {}", SEAT_NAMES[seat], notation(card.0, card.1))
+            }
+            HeartsMove::Collect => match self.tricks.trick_winner() {
+                Some(seat) => format!("Trick to {}", SEAT_NAMES[seat]),
+                None => "Trick collected".to_owned(),
+            },
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.passing.is_none() && self.tricks.is_over()
+    }
+
+    /// The fewest points wins.
+    fn outcome(&self, seat: usize) -> Option<Outcome> {
+        let result = self.result()?;
+        Some(Outcome::by_score(result[seat], &result, i32::min))
+    }
+
+    /// Piles `0..SEATS` are the hands, with only `seat`'s own face up, any cards it's picked to
+    /// pass raised and, on its turn, those it can't play greyed out, `SEATS..2 * SEATS` the trick
+    /// and `2 * SEATS..3 * SEATS` the cards each seat has taken.
+    fn table(&self, seat: usize, _orientation: Orientation) -> Vec<Pile> {
+        let mut table = self.tricks.table(seat);
+
+        if self.turn() == Some(seat) {
+            dim_unplayable(&mut table[seat], &self.playable(seat));
+        }
+
+        if let Some(passing) = &self.passing {
+            for card in &mut table[seat].cards {
+                card.selected = passing[seat].contains(&(card.suit, card.rank));
+            }
+        }
+
+        table
+    }
+
+    /// The seats' names and how many cards the others hold and, once `seat` has picked its cards
+    /// to pass, `Pass` on its place in the trick, which confirms them.
+    fn labels(&self, seat: usize, _orientation: Orientation) -> Vec<Label> {
+        let mut labels = self.tricks.labels(seat, &SEAT_NAMES);
+        if self.moves_for(seat).contains(&HeartsMove::Confirm { seat }) {
+            let slot = &self.tricks.table(seat)[SEATS + seat];
+            labels.push(Label::new(slot.position, "Pass".to_owned()));
+        }
+        labels
+    }
+
+    fn sweep(&self, seat: usize, _orientation: Orientation) -> Option<Sweep> {
+        self.tricks.sweep(seat)
+    }
+
+    /// The card the rules of thumb would play next or, while passing, pick next, or once the
+    /// cards to pass are picked, the place in the trick that confirms them.
+    fn hint(&self, seat: usize) -> Option<Hint> {
+        if self.moves_for(seat).is_empty() {
+            return None;
+        }
+
+        if self.passing.is_none() {
+            return self.tricks.hint(seat, self.choose_card(seat)?);
+        }
+        let Some(card) = self.next_pass_choice(seat) else {
+            return Some(Hint {
+                from: Location::pile(SEATS + seat),
+                to: None,
+            });
+        };
+        let hand = self.tricks.hand(seat);
+        Some(Hint {
+            from: Location::card(seat, hand.iter().position(|&held| held == card)?),
+            to: None,
+        })
+    }
+
+    /// The player's own hand and its place in the trick while picking cards to pass, then the
+    /// trick.
+    fn framing(&self, seat: usize) -> Framing {
+        match self.passing {
+            Some(_) => Framing::Piles(vec![seat, SEATS + seat]),
+            None => self.tricks.framing(seat),
+        }
+    }
+
+    fn must_follow(&self, seat: usize) -> bool {
+        self.passing.is_none() && self.tricks.must_follow(seat)
+    }
+
+    fn trick_winners(&self) -> Vec<usize> {
+        self.tricks.winners().to_vec()
+    }
+
+    /// A card in `seat`'s hand picks it to pass or plays it, and while passing, `seat`'s place in
+    /// the trick confirms the cards picked.
+    fn move_at(&self, seat: usize, location: Location) -> Option<HeartsMove> {
+        if self.passing.is_some() && location == Location::pile(SEATS + seat) {
+            return Some(HeartsMove::Confirm { seat });
+        }
+        if location.pile != seat {
+            return None;
+        }
+
+        let card = *self.tricks.hand(seat).get(location.index?)?;
+        Some(match self.passing {
+            Some(_) => HeartsMove::Choose { seat, card },
+            None => HeartsMove::Play { seat, card },
+        })
+    }
+
+    fn move_between(&self, _seat: usize, _from: Location, _to: Location) -> Option<HeartsMove> {
+        None
+    }
+
+    fn seats(&self) -> usize {
+        SEATS
+    }
+
+    /// Everyone picks their cards to pass at the same time, then plays in turn.
+    fn seats_to_move(&self) -> Vec<usize> {
+        match &self.passing {
+            Some(_) => (0..SEATS).filter(|&seat| !self.confirmed[seat]).collect(),
+            None => self.turn().into_iter().collect(),
+        }
+    }
+
+    fn moves_for(&self, seat: usize) -> Vec<HeartsMove> {
+        if let Some(passing) = &self.passing {
+            if self.confirmed[seat] {
+                return Vec::new();
+            }
+            let mut moves = self
+                .tricks
+                .hand(seat)
+                .iter()
+                .map(|&card| HeartsMove::Choose { seat, card })
+                .collect::<Vec<_>>();
+            if passing[seat].len() == PASS_SIZE {
+                moves.push(HeartsMove::Confirm { seat });
+            }
+            return moves;
+        }
+
+        if self.turn() != Some(seat) {
+            return Vec::new();
+        }
+        self.playable(seat)
+            .into_iter()
+            .map(|card| HeartsMove::Play { seat, card })
+            .collect()
+    }
+
+    fn automatic_move(&self) -> Option<HeartsMove> {
+        if self.passing.is_some() {
+            return self
+                .confirmed
+                .iter()
+                .all(|&confirmed| confirmed)
+                .then_some(HeartsMove::Pass);
+        }
+
+        (self.turn().is_none() && !self.tricks.trick().is_empty()).then_some(HeartsMove::Collect)
+    }
+
+    fn status(&self) -> Option<GameStatus> {
+        let result = self.result();
+
+        let players = (0..SEATS)
+            .map(|seat| PlayerStatus {
+                name: SEAT_NAMES[seat].to_owned(),
+                score: match result {
+                    Some(result) => result[seat],
+                    None => self.tricks.points(seat),
+                },
+            })
+            .collect();
+
+        Some(GameStatus {
+            players,
+            tricks: Some(self.tricks.tricks_played()),
+            turn: self.turn(),
+        })
+    }
+}
+
+/// Each seat knows the cards it passed on are held to its left until they're played, but not
+/// what the others picked to pass until they've passed them.
+impl HiddenInformation for Hearts {
+    fn determinize(&self, seat: usize, rng: &mut Rng) -> Self {
+        let left = (seat + 1) % SEATS;
+        let pinned = self.passed[seat]
+            .iter()
+            .map(|&card| (left, card))
+            .collect::<Vec<_>>();
+
+        let mut guess = self.clone();
+        guess.tricks = self.tricks.redeal_hidden(seat, &pinned, rng);
+        if let Some(passing) = &mut guess.passing {
+            for (other, picked) in passing.iter_mut().enumerate() {
+                if other != seat {
+                    picked.clear();
+                    guess.confirmed[other] = false;
+                }
+            }
+        }
+        guess
+    }
+
+    fn reward(&self, seat: usize) -> f64 {
+        self.result()
+            .map_or(0.0, |result| 1.0 - result[seat] as f64 / MOON as f64)
+    }
+}
+
+impl AiPlayer<Hearts> for RuleBasedPlayer {
+    fn choose_move(&mut self, view: &GameView<Hearts>) -> HeartsMove {
+        let (state, seat) = (view.state, view.seat);
+
+        let chosen = match state.passing {
+            Some(_) => Some(state.next_pass_choice(seat).map_or(
+                HeartsMove::Confirm { seat },
+                |card| HeartsMove::Choose { seat, card },
+            )),
+            None => state
+                .choose_card(seat)
+                .map(|card| HeartsMove::Play { seat, card }),
+        };
+        chosen.unwrap_or(view.moves[0])
+    }
+}
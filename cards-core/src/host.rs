@@ -0,0 +1,726 @@
+//! Multiplayer games as a server hosts them, whatever carries the messages: the `cards-server`
+//! binary over WebSockets, or a browser hosting a game for another over a direct connection.
+//!
+//! Each room plays one game. Players open a room, or join one from the list of open rooms, and
+//! take its free seats. The game starts once everyone sitting down is ready, with the computer
+//! playing any seats left free if the room allows it, along with any seat whose player leaves. A
+//! seat whose player's connection drops is held for them for a while, with the computer playing it
+//! until they're back. A move is only made once the host has checked it against the rules, and is
+//! then sent on to everyone in the room, as is anything a player says.
+
+use std::{collections::HashMap, sync::mpsc::Sender, time::Duration};
+
+use log::{info, warn};
+
+#[cfg(feature = "doppelkopf")]
+use crate::doppelkopf::Doppelkopf;
+#[cfg(feature = "hearts")]
+use crate::hearts::Hearts;
+use crate::{
+    ai::{AiPlayer, RuleBasedPlayer},
+    game::{Engine, Game, GameState, MoveRecord, Orientation},
+    protocol::{
+        ClientMessage, Lobby, MoveDelta, RoomSummary, SeatState, ServerMessage, TableOptions,
+        MAX_SAY_LENGTH, PROTOCOL_VERSION,
+    },
+    rng::{daily_seed, Rng},
+};
+
+/// How long a seat is held for a player whose connection dropped, to `Resume` playing it.
+const RESUME_WINDOW: Duration = Duration::from_secs(120);
+
+/// Identifies a player's connection for as long as it's open.
+pub type ClientId = u64;
+
+/// What the connections tell the host.
+pub enum Event {
+    /// A player has connected, and anything sent to `outbox` goes to them.
+    Connected(ClientId, Sender<ServerMessage>),
+    Message(ClientId, ClientMessage),
+    Disconnected(ClientId),
+}
+
+/// The answer to the first message on a connection: `Welcome` if it's a `Hello` in the version of
+/// the protocol spoken here, or else why the connection is being turned away.
+pub fn greet(message: &ClientMessage) -> Result<ServerMessage, String> {
+    match *message {
+        ClientMessage::Hello { version } if version == PROTOCOL_VERSION => {
+            Ok(ServerMessage::Welcome { version })
+        }
+        ClientMessage::Hello { version } => Err(format!(
+            "this server speaks protocol version {PROTOCOL_VERSION}, not {version}"
+        )),
+        _ => Err("say hello first".to_owned()),
+    }
+}
+
+/// A game being played in a room, whatever the game.
+trait Table {
+    fn game(&self) -> &str;
+    fn seed(&self) -> u64;
+    fn seats(&self) -> usize;
+    fn moves(&self) -> &[MoveRecord];
+
+    /// Takes `seat`, or the first free seat if it's `None`, from the computer. Returns the seat
+    /// taken, or `None` if it isn't free.
+    fn sit(&mut self, seat: Option<usize>) -> Option<usize>;
+
+    /// Gives `seat` back to the computer, for anyone to take.
+    fn stand(&mut self, seat: usize);
+
+    /// Has the computer play `seat` while its player is away, keeping it for them, or stops once
+    /// they're back.
+    fn set_away(&mut self, seat: usize, away: bool);
+
+    /// Makes the move at `index` in `legal_moves` for `seat`, if it's one `seat` can make.
+    fn play(&mut self, seat: usize, index: usize) -> Result<(), String>;
+
+    /// Lets time pass, making any moves the game or the computer players have due.
+    fn update(&mut self, dt: Duration);
+}
+
+struct Seated<G: GameState> {
+    game: &'static str,
+    seed: u64,
+    engine: Engine<G>,
+    /// Whether each seat is played by someone connected, rather than the computer.
+    taken: Vec<bool>,
+}
+
+impl<G: GameState + 'static> Seated<G>
+where
+    RuleBasedPlayer: AiPlayer<G>,
+{
+    /// Deals `game` from `seed`, with the computer playing every seat until players sit down.
+    fn new(game: &'static str, seed: u64) -> Self {
+        let state = G::setup(seed);
+        let seats = state.seats();
+        let mut engine = Engine::new(state, Orientation::Landscape);
+        for seat in 0..seats {
+            engine.set_computer_player(seat, Some(Box::new(RuleBasedPlayer)));
+        }
+
+        Self {
+            game,
+            seed,
+            engine,
+            taken: vec![false; seats],
+        }
+    }
+}
+
+impl<G: GameState + 'static> Table for Seated<G>
+where
+    RuleBasedPlayer: AiPlayer<G>,
+{
+    fn game(&self) -> &str {
+        self.game
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn seats(&self) -> usize {
+        self.taken.len()
+    }
+
+    fn moves(&self) -> &[MoveRecord] {
+        self.engine.moves()
+    }
+
+    fn sit(&mut self, seat: Option<usize>) -> Option<usize> {
+        let seat = match seat {
+            Some(seat) => seat,
+            None => self.taken.iter().position(|&taken| !taken)?,
+        };
+        if *self.taken.get(seat)? {
+            return None;
+        }
+
+        self.taken[seat] = true;
+        self.engine.set_computer_player(seat, None);
+        Some(seat)
+    }
+
+    fn stand(&mut self, seat: usize) {
+        if let Some(taken) = self.taken.get_mut(seat) {
+            *taken = false;
+            self.engine
+                .set_computer_player(seat, Some(Box::new(RuleBasedPlayer)));
+        }
+    }
+
+    fn set_away(&mut self, seat: usize, away: bool) {
+        let player = away.then(|| Box::new(RuleBasedPlayer) as Box<dyn AiPlayer<G>>);
+        self.engine.set_computer_player(seat, player);
+    }
+
+    fn play(&mut self, seat: usize, index: usize) -> Result<(), String> {
+        let state = self.engine.state();
+        let mv = state
+            .legal_moves()
+            .into_iter()
+            .nth(index)
+            .ok_or_else(|| format!("there's no move {index}"))?;
+        if !state.moves_for(seat).contains(&mv) {
+            return Err(format!("{} isn't yours to make", state.describe_move(&mv)));
+        }
+
+        self.engine.play(&mv);
+        Ok(())
+    }
+
+    fn update(&mut self, dt: Duration) {
+        self.engine.update(dt);
+        // Players are sent the moves, and lay out their own tables from them.
+        self.engine.take_commands();
+    }
+}
+
+/// Deals a new game of `game` for a room, or returns `None` if it isn't one the server hosts.
+fn open_table(game: &str, seed: u64) -> Option<Box<dyn Table>> {
+    Some(match game {
+        #[cfg(feature = "hearts")]
+        "hearts" => Box::new(Seated::<Hearts>::new("hearts", seed)),
+        #[cfg(feature = "doppelkopf")]
+        "doppelkopf" => Box::new(Seated::<Doppelkopf>::new("doppelkopf", seed)),
+        _ => return None,
+    })
+}
+
+/// A seat taken by a player, which they can take back with `session` if their connection drops.
+#[derive(Debug, Clone, Copy)]
+struct Seat {
+    seat: usize,
+    session: u64,
+    /// Whether the player is ready for the game to start.
+    ready: bool,
+}
+
+struct Room {
+    table: Box<dyn Table>,
+    options: TableOptions,
+    /// Whether the game has started, which it does once everyone sitting down is ready. Until
+    /// then nobody moves, and players can change seats.
+    started: bool,
+    /// Where each player connected to the room is sitting.
+    players: HashMap<ClientId, Seat>,
+    /// Seats whose players' connections dropped, held for them until `Host::clock` reaches the
+    /// time given.
+    away: Vec<(Seat, Duration)>,
+    /// How many of the game's moves have been sent to the players.
+    sent: usize,
+}
+
+impl Room {
+    fn summary(&self, name: &str) -> RoomSummary {
+        RoomSummary {
+            name: name.to_owned(),
+            game: self.table.game().to_owned(),
+            options: self.options,
+            players: self.players.len() + self.away.len(),
+            seats: self.table.seats(),
+            started: self.started,
+        }
+    }
+
+    /// Sends the players any moves made since they were last sent some.
+    fn send_moves(&mut self, clients: &HashMap<ClientId, Client>) {
+        let moves = self.table.moves();
+        if moves.len() == self.sent {
+            return;
+        }
+
+        let start = match self.sent {
+            0 => Duration::ZERO,
+            sent => moves[sent - 1].time,
+        };
+        let deltas = MoveDelta::encode(&moves[self.sent..], start);
+        for id in self.players.keys() {
+            if let Some(client) = clients.get(id) {
+                client.send(ServerMessage::Moves {
+                    first: self.sent,
+                    moves: deltas.clone(),
+                });
+            }
+        }
+        self.sent = moves.len();
+    }
+
+    /// Sends the player `id` the game so far.
+    fn send_game(&self, name: &str, id: ClientId, clients: &HashMap<ClientId, Client>) {
+        let (Some(client), Some(seat)) = (clients.get(&id), self.players.get(&id)) else {
+            return;
+        };
+        client.send(ServerMessage::Joined {
+            room: name.to_owned(),
+            seat: seat.seat,
+            session: seat.session,
+            game: self.table.game().to_owned(),
+            seed: self.table.seed(),
+            moves: MoveDelta::encode(self.table.moves(), Duration::ZERO),
+        });
+    }
+
+    /// Starts the game if everyone sitting down is ready, and otherwise tells the players who's
+    /// sitting where.
+    fn start_or_wait(&mut self, name: &str, clients: &HashMap<ClientId, Client>) {
+        if self.started {
+            return;
+        }
+
+        let full = self.players.len() + self.away.len() == self.table.seats();
+        if !self.players.is_empty()
+            && self.away.is_empty()
+            && self.players.values().all(|seat| seat.ready)
+            && (full || self.options.computer_players)
+        {
+            info!("started the game in room {name:?}");
+            self.started = true;
+            for &id in self.players.keys() {
+                self.send_game(name, id, clients);
+            }
+            return;
+        }
+
+        let mut seats = vec![SeatState::Open; self.table.seats()];
+        for seat in self.players.values() {
+            seats[seat.seat] = match seat.ready {
+                true => SeatState::Ready,
+                false => SeatState::Waiting,
+            };
+        }
+        for (seat, _) in &self.away {
+            seats[seat.seat] = SeatState::Away;
+        }
+
+        for (id, seat) in &self.players {
+            if let Some(client) = clients.get(id) {
+                client.send(ServerMessage::Lobby(Lobby {
+                    room: name.to_owned(),
+                    game: self.table.game().to_owned(),
+                    options: self.options,
+                    seat: seat.seat,
+                    session: seat.session,
+                    seats: seats.clone(),
+                }));
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.players.is_empty() && self.away.is_empty()
+    }
+}
+
+struct Client {
+    outbox: Sender<ServerMessage>,
+    room: Option<String>,
+}
+
+impl Client {
+    fn send(&self, message: ServerMessage) {
+        // A closed outbox means the connection is on its way out, which it reports itself.
+        let _ = self.outbox.send(message);
+    }
+}
+
+/// The room the player `id` is in, and its name.
+fn room_of<'a>(
+    clients: &HashMap<ClientId, Client>,
+    rooms: &'a mut HashMap<String, Room>,
+    id: ClientId,
+) -> Option<(String, &'a mut Room)> {
+    let name = clients.get(&id)?.room.clone()?;
+    let room = rooms.get_mut(&name)?;
+    Some((name, room))
+}
+
+/// Every room, and the players connected to them.
+pub struct Host {
+    clients: HashMap<ClientId, Client>,
+    rooms: HashMap<String, Room>,
+    /// The room each session's seat is in.
+    sessions: HashMap<u64, String>,
+    /// Where seeds come from. Session tokens come from the system's randomness instead, since a
+    /// player can work out this generator's state from the seed they're sent, and then every
+    /// token that follows.
+    rng: Rng,
+    /// The time since the Unix epoch when the host started, in seconds, and how long it's been
+    /// running.
+    started: u64,
+    clock: Duration,
+}
+
+impl Host {
+    /// Starts hosting at `unix_time`, in seconds since the Unix epoch, with deals drawn from
+    /// `seed`.
+    pub fn new(seed: u64, unix_time: u64) -> Self {
+        Self {
+            clients: HashMap::new(),
+            rooms: HashMap::new(),
+            sessions: HashMap::new(),
+            rng: Rng::new(seed),
+            started: unix_time,
+            clock: Duration::ZERO,
+        }
+    }
+
+    /// Takes what a connection has to tell the host, answering through the outbox of any player
+    /// it concerns.
+    pub fn handle(&mut self, event: Event) {
+        match event {
+            Event::Connected(id, outbox) => {
+                self.clients.insert(id, Client { outbox, room: None });
+            }
+            Event::Message(id, ClientMessage::ListRooms) => self.list_rooms(id),
+            Event::Message(id, ClientMessage::Create { game, options }) => {
+                self.create(id, &game, options);
+            }
+            Event::Message(id, ClientMessage::Join { room }) => self.join(id, room),
+            Event::Message(id, ClientMessage::Sit { seat }) => self.sit(id, seat),
+            Event::Message(id, ClientMessage::Ready(ready)) => self.ready(id, ready),
+            Event::Message(id, ClientMessage::Resume { session }) => self.resume(id, session),
+            Event::Message(id, ClientMessage::Play { index }) => self.play(id, index),
+            Event::Message(id, ClientMessage::Say(text)) => self.say(id, text),
+            Event::Message(id, ClientMessage::Leave) => self.leave(id),
+            Event::Message(id, ClientMessage::Hello { .. }) => {
+                self.reject(id, "already said hello".to_owned());
+            }
+            Event::Disconnected(id) => {
+                self.step_away(id);
+                self.clients.remove(&id);
+            }
+        }
+    }
+
+    fn reject(&self, id: ClientId, reason: String) {
+        if let Some(client) = self.clients.get(&id) {
+            client.send(ServerMessage::Rejected(reason));
+        }
+    }
+
+    fn list_rooms(&self, id: ClientId) {
+        let mut rooms: Vec<_> = self
+            .rooms
+            .iter()
+            .map(|(name, room)| room.summary(name))
+            .collect();
+        rooms.sort_by(|a, b| a.name.cmp(&b.name));
+
+        if let Some(client) = self.clients.get(&id) {
+            client.send(ServerMessage::Rooms(rooms));
+        }
+    }
+
+    /// Opens a room for a new game of `game`, named after it, and sits the player in it.
+    fn create(&mut self, id: ClientId, game: &str, options: TableOptions) {
+        self.leave(id);
+
+        let seed = match options.daily {
+            true => daily_seed(self.started + self.clock.as_secs()),
+            false => self.rng.next_u64(),
+        };
+        let Some(table) = open_table(game, seed) else {
+            self.reject(id, format!("there's no game called {game:?}"));
+            return;
+        };
+
+        let name = (1..)
+            .map(|number| format!("{game} {number}"))
+            .find(|name| !self.rooms.contains_key(name))
+            .expect("there's always a free name");
+        info!("opened room {name:?} for {game} from seed {seed}");
+        self.rooms.insert(
+            name.clone(),
+            Room {
+                table,
+                options,
+                started: false,
+                players: HashMap::new(),
+                away: Vec::new(),
+                sent: 0,
+            },
+        );
+        self.take_seat(id, name);
+    }
+
+    fn join(&mut self, id: ClientId, name: String) {
+        self.leave(id);
+
+        if !self.rooms.contains_key(&name) {
+            self.reject(id, format!("there's no room called {name:?}"));
+            return;
+        }
+        self.take_seat(id, name);
+    }
+
+    /// Sits the player in the first free seat in the room called `name`.
+    fn take_seat(&mut self, id: ClientId, name: String) {
+        let Some(session) = self.new_session() else {
+            self.reject(id, "couldn't make a session for the seat".to_owned());
+            return;
+        };
+        let Some(room) = self.rooms.get_mut(&name) else {
+            return;
+        };
+        let Some(seat) = room.table.sit(None) else {
+            self.reject(id, format!("room {name:?} is full"));
+            return;
+        };
+
+        self.sessions.insert(session, name.clone());
+        info!("player {id} took seat {seat} in room {name:?}");
+        self.enter(
+            id,
+            name,
+            Seat {
+                seat,
+                session,
+                ready: false,
+            },
+        );
+    }
+
+    /// A session token nobody can guess, and nobody else has, or `None` if the system has no
+    /// randomness to give.
+    fn new_session(&self) -> Option<u64> {
+        loop {
+            let mut bytes = [0; 8];
+            if let Err(e) = getrandom::getrandom(&mut bytes) {
+                warn!("couldn't make a session token: {e}");
+                return None;
+            }
+            let session = u64::from_le_bytes(bytes);
+            if !self.sessions.contains_key(&session) {
+                return Some(session);
+            }
+        }
+    }
+
+    /// Moves the player to `seat`, before the game has started.
+    fn sit(&mut self, id: ClientId, seat: usize) {
+        let Some((name, room)) = room_of(&self.clients, &mut self.rooms, id) else {
+            self.reject(id, "join a room before picking a seat".to_owned());
+            return;
+        };
+        if room.started {
+            self.reject(id, "the game has already started".to_owned());
+            return;
+        }
+        if room.table.sit(Some(seat)).is_none() {
+            self.reject(id, format!("seat {seat} isn't free"));
+            return;
+        }
+
+        let player = room
+            .players
+            .get_mut(&id)
+            .expect("everyone in a room has a seat");
+        room.table.stand(player.seat);
+        player.seat = seat;
+        player.ready = false;
+        room.start_or_wait(&name, &self.clients);
+    }
+
+    fn ready(&mut self, id: ClientId, ready: bool) {
+        let Some((name, room)) = room_of(&self.clients, &mut self.rooms, id) else {
+            self.reject(id, "join a room before getting ready".to_owned());
+            return;
+        };
+
+        if let Some(player) = room.players.get_mut(&id) {
+            player.ready = ready;
+        }
+        room.start_or_wait(&name, &self.clients);
+    }
+
+    /// Gives a player back the seat held for `session`, which is theirs from an earlier
+    /// connection. A seat still played from another connection isn't taken over; the player is
+    /// told it's taken, and can ask again once that connection drops.
+    fn resume(&mut self, id: ClientId, session: u64) {
+        // A player still sitting there is just after the game again.
+        if let Some((name, room)) = room_of(&self.clients, &mut self.rooms, id) {
+            if room
+                .players
+                .get(&id)
+                .is_some_and(|seat| seat.session == session)
+            {
+                let seat = room.players.remove(&id).expect("the player was just found");
+                self.enter(id, name, seat);
+                return;
+            }
+        }
+        self.leave(id);
+
+        let Some(room) = self
+            .sessions
+            .get(&session)
+            .and_then(|name| self.rooms.get_mut(name))
+        else {
+            self.reject(id, "that seat has been given up".to_owned());
+            return;
+        };
+
+        let seat = if let Some(index) = room
+            .away
+            .iter()
+            .position(|(seat, _)| seat.session == session)
+        {
+            room.away.remove(index).0
+        } else if room.players.values().any(|seat| seat.session == session) {
+            if let Some(client) = self.clients.get(&id) {
+                client.send(ServerMessage::SeatTaken);
+            }
+            return;
+        } else {
+            self.reject(id, "that seat has been given up".to_owned());
+            return;
+        };
+        room.table.set_away(seat.seat, false);
+
+        let name = self.sessions[&session].clone();
+        info!(
+            "player {id} came back to seat {} in room {name:?}",
+            seat.seat
+        );
+        self.enter(id, name, seat);
+    }
+
+    /// Puts the player in `seat` in the room called `name`, and sends them the game so far, or
+    /// tells everyone in the room if it's still waiting to start.
+    fn enter(&mut self, id: ClientId, name: String, seat: Seat) {
+        let Some(room) = self.rooms.get_mut(&name) else {
+            return;
+        };
+        // Bring everyone else up to date first, since the new player is sent every move so far.
+        room.send_moves(&self.clients);
+        room.players.insert(id, seat);
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.room = Some(name.clone());
+        }
+
+        if room.started {
+            room.send_game(&name, id, &self.clients);
+        } else {
+            room.start_or_wait(&name, &self.clients);
+        }
+    }
+
+    fn play(&mut self, id: ClientId, index: usize) {
+        let Some((_, room)) = room_of(&self.clients, &mut self.rooms, id) else {
+            self.reject(id, "join a room before playing".to_owned());
+            return;
+        };
+        if !room.started {
+            self.reject(id, "the game hasn't started yet".to_owned());
+            return;
+        }
+
+        let seat = room.players[&id].seat;
+        match room.table.play(seat, index) {
+            Ok(()) => room.send_moves(&self.clients),
+            Err(reason) => self.reject(id, reason),
+        }
+    }
+
+    /// Passes on what the player said to everyone in their room, themselves included.
+    fn say(&mut self, id: ClientId, text: String) {
+        let Some((_, room)) = room_of(&self.clients, &mut self.rooms, id) else {
+            self.reject(id, "join a room before talking".to_owned());
+            return;
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        if text.chars().count() > MAX_SAY_LENGTH {
+            self.reject(id, "that's too long to say".to_owned());
+            return;
+        }
+
+        let seat = room.players[&id].seat;
+        for id in room.players.keys() {
+            if let Some(client) = self.clients.get(id) {
+                client.send(ServerMessage::Said {
+                    seat,
+                    text: text.to_owned(),
+                });
+            }
+        }
+    }
+
+    /// Takes the player out of their room for good, closing it once nobody's left in it.
+    fn leave(&mut self, id: ClientId) {
+        let Some((name, room)) = room_of(&self.clients, &mut self.rooms, id) else {
+            return;
+        };
+
+        if let Some(seat) = room.players.remove(&id) {
+            room.table.stand(seat.seat);
+            self.sessions.remove(&seat.session);
+            info!("player {id} left seat {} in room {name:?}", seat.seat);
+        }
+        if room.is_empty() {
+            self.rooms.remove(&name);
+            info!("closed room {name:?}");
+        } else {
+            room.start_or_wait(&name, &self.clients);
+        }
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.room = None;
+        }
+    }
+
+    /// Holds the seat of a player whose connection dropped for `RESUME_WINDOW`, with the
+    /// computer playing it in the meantime.
+    fn step_away(&mut self, id: ClientId) {
+        let Some((name, room)) = room_of(&self.clients, &mut self.rooms, id) else {
+            return;
+        };
+
+        if let Some(seat) = room.players.remove(&id) {
+            room.table.set_away(seat.seat, true);
+            room.away.push((seat, self.clock + RESUME_WINDOW));
+            info!(
+                "holding seat {} in room {name:?} for player {id}",
+                seat.seat
+            );
+            room.start_or_wait(&name, &self.clients);
+        }
+    }
+
+    /// Lets `dt` pass in every room, making any moves due and giving up seats held too long.
+    pub fn update(&mut self, dt: Duration) {
+        self.clock += dt;
+        for (name, room) in &mut self.rooms {
+            let held = room.away.len();
+            room.away.retain(|&(seat, until)| {
+                if until > self.clock {
+                    return true;
+                }
+                room.table.stand(seat.seat);
+                self.sessions.remove(&seat.session);
+                info!("gave up seat {} in room {name:?}", seat.seat);
+                false
+            });
+            if room.away.len() != held {
+                room.start_or_wait(name, &self.clients);
+            }
+
+            if room.started {
+                room.table.update(dt);
+                room.send_moves(&self.clients);
+            }
+        }
+
+        self.rooms.retain(|name, room| {
+            let open = !room.is_empty();
+            if !open {
+                info!("closed room {name:?}");
+            }
+            open
+        });
+    }
+}
@@ -0,0 +1,371 @@
+//! Klondike solitaire, dealing one card at a time from the stock with unlimited redeals.
+
+use std::time::Duration;
+
+use crate::{
+    card::{notation, ACE, KING},
+    deck::Deck,
+    game::{CardView, Fan, GameState, Hint, Location, Orientation, Pile, Zone, ZoneKind},
+    status::{GameStatus, Outcome, PlayerStatus},
+};
+
+pub const STOCK: usize = 0;
+pub const WASTE: usize = 1;
+/// The first of the four foundations, which are built up by suit from the ace.
+pub const FOUNDATIONS: usize = 2;
+/// The first of the seven tableau columns, which are built down in alternating colours.
+pub const TABLEAU: usize = FOUNDATIONS + 4;
+const PILE_COUNT: usize = TABLEAU + 7;
+
+/// Points for each card on a foundation.
+const FOUNDATION_SCORE: i32 = 10;
+
+/// The gap between neighbouring piles, as a fraction of a card.
+const SPACING: f32 = 1.2;
+
+/// How much of each card in a tableau column is left showing beneath the next one.
+const CASCADE_OVERLAP: f32 = 0.25;
+
+/// The wait between cards going up to the foundations once the game is being finished off.
+const FINISH_MOVE_DELAY: Duration = Duration::from_millis(120);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KlondikeMove {
+    /// Turns the top card of the stock onto the waste.
+    Draw,
+    /// Turns the waste back over to make a new stock, once the stock runs out.
+    Redeal,
+    /// Moves the card at `from`, and any cards on top of it, onto the pile `to`.
+    Move { from: Location, to: usize },
+    /// Plays the rest of a game that can't be lost, once every card in the tableau is face up,
+    /// sending the cards up to the foundations one at a time.
+    Finish,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Klondike {
+    piles: Vec<Vec<CardView>>,
+    /// Whether the game is being finished off by itself.
+    finishing: bool,
+}
+
+impl Klondike {
+    fn is_foundation(pile: usize) -> bool {
+        (FOUNDATIONS..TABLEAU).contains(&pile)
+    }
+
+    fn is_tableau(pile: usize) -> bool {
+        (TABLEAU..PILE_COUNT).contains(&pile)
+    }
+
+    /// Whether the face-up cards from `from` up can be picked up together.
+    fn can_pick_up(&self, from: Location) -> bool {
+        let Some(cards) = self.piles.get(from.pile) else {
+            return false;
+        };
+        let Some(index) = from.index.filter(|&index| index < cards.len()) else {
+            return false;
+        };
+
+        if cards[index].facedown {
+            return false;
+        }
+
+        // Only tableau columns give up more than their top card.
+        Self::is_tableau(from.pile) || index == cards.len() - 1
+    }
+
+    /// Whether `card` can go on top of the pile `to`, carrying `carried` cards in all.
+    fn can_place(&self, card: CardView, carried: usize, to: usize) -> bool {
+        let top = self.piles[to].last();
+
+        if Self::is_foundation(to) {
+            return carried == 1
+                && match top {
+                    None => card.rank == ACE,
+                    Some(top) => top.suit == card.suit && card.rank == top.rank + 1,
+                };
+        }
+
+        if Self::is_tableau(to) {
+            return match top {
+                None => card.rank == KING,
+                Some(top) => {
+                    !top.facedown
+                        && top.suit.is_red() != card.suit.is_red()
+                        && card.rank + 1 == top.rank
+                }
+            };
+        }
+
+        false
+    }
+
+    fn foundation_cards(&self) -> usize {
+        self.piles[FOUNDATIONS..TABLEAU].iter().map(Vec::len).sum()
+    }
+
+    /// Whether the game can no longer be lost, with every card in the tableau face up. Every
+    /// card left can then go up to the foundations in turn, lowest first, dealing through the
+    /// stock for those in it.
+    fn is_won(&self) -> bool {
+        self.piles[TABLEAU..]
+            .iter()
+            .flatten()
+            .all(|card| !card.facedown)
+    }
+}
+
+impl GameState for Klondike {
+    type Move = KlondikeMove;
+
+    fn setup(seed: u64) -> Self {
+        let mut deck = Deck::standard();
+        deck.shuffle_with_seed(seed);
+
+        let mut piles = vec![Vec::new(); PILE_COUNT];
+        for column in 0..7 {
+            piles[TABLEAU + column] = deck
+                .deal(column + 1)
+                .into_iter()
+                .enumerate()
+                .map(|(index, (suit, rank))| CardView::new(suit, rank, index < column))
+                .collect();
+        }
+        piles[STOCK] = deck
+            .deal(deck.len())
+            .into_iter()
+            .map(|(suit, rank)| CardView::new(suit, rank, true))
+            .collect();
+
+        Self {
+            piles,
+            finishing: false,
+        }
+    }
+
+    fn legal_moves(&self) -> Vec<KlondikeMove> {
+        let mut moves = Vec::new();
+
+        if !self.piles[STOCK].is_empty() {
+            moves.push(KlondikeMove::Draw);
+        } else if !self.piles[WASTE].is_empty() {
+            moves.push(KlondikeMove::Redeal);
+        }
+        if self.is_won() && !self.finishing && !self.is_over() {
+            moves.push(KlondikeMove::Finish);
+        }
+
+        for (pile, cards) in self.piles.iter().enumerate() {
+            for index in 0..cards.len() {
+                let from = Location::card(pile, index);
+                if !self.can_pick_up(from) {
+                    continue;
+                }
+
+                let carried = cards.len() - index;
+                moves.extend(
+                    (FOUNDATIONS..PILE_COUNT)
+                        .filter(|&to| to != pile && self.can_place(cards[index], carried, to))
+                        .map(|to| KlondikeMove::Move { from, to }),
+                );
+            }
+        }
+
+        moves
+    }
+
+    fn apply_move(&mut self, mv: &KlondikeMove) {
+        match *mv {
+            KlondikeMove::Draw => {
+                if let Some(mut card) = self.piles[STOCK].pop() {
+                    card.facedown = false;
+                    self.piles[WASTE].push(card);
+                }
+            }
+            KlondikeMove::Redeal => {
+                let mut waste = std::mem::take(&mut self.piles[WASTE]);
+                waste.reverse();
+                for card in &mut waste {
+                    card.facedown = true;
+                }
+                self.piles[STOCK] = waste;
+            }
+            KlondikeMove::Move { from, to } => {
+                let index = from.index.unwrap_or(0);
+                let moved = self.piles[from.pile].split_off(index);
+                self.piles[to].extend(moved);
+
+                // The card left on top of a column is turned over.
+                if let Some(top) = self.piles[from.pile].last_mut() {
+                    if Self::is_tableau(from.pile) {
+                        top.facedown = false;
+                    }
+                }
+            }
+            KlondikeMove::Finish => self.finishing = true,
+        }
+    }
+
+    /// Cards are named by `card::notation`, along with how many more came with them, and piles
+    /// by what they are: `7H+2 to column 3`, `QS to foundation 1`.
+    fn describe_move(&self, mv: &KlondikeMove) -> String {
+        match *mv {
+            KlondikeMove::Draw => "Draw".to_owned(),
+            KlondikeMove::Redeal => "Redeal".to_owned(),
+            KlondikeMove::Finish => "Finish".to_owned(),
+            KlondikeMove::Move { from, to } => {
+                let cards = &self.piles[from.pile][from.index.unwrap_or(0)..];
+                let Some(card) = cards.first() else {
+                    return format!("{mv:?}");
+                };
+
+                let more = match cards.len() {
+                    1 => String::new(),
+                    count => format!("+{}", count - 1),
+                };
+                let pile = if Self::is_foundation(to) {
+                    format!("foundation {}", to - FOUNDATIONS + 1)
+                } else {
+                    format!("column {}", to - TABLEAU + 1)
+                };
+                format!("{}{more} to {pile}", notation(card.suit, card.rank))
+            }
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.foundation_cards() == 52
+    }
+
+    /// The game is only over once every card is up on the foundations.
+    fn outcome(&self, _seat: usize) -> Option<Outcome> {
+        self.is_over().then_some(Outcome::Won)
+    }
+
+    /// The stock, waste and foundations along the top, with the tableau beneath. Seven columns
+    /// fit a portrait window as they are, so both orientations are laid out the same.
+    fn table(&self, _seat: usize, _orientation: Orientation) -> Vec<Pile> {
+        self.piles
+            .iter()
+            .enumerate()
+            .map(|(pile, cards)| {
+                let (column, row, fan) = match pile {
+                    STOCK | WASTE => (pile, 0, Fan::Stacked),
+                    pile if Self::is_foundation(pile) => (pile + 1, 0, Fan::Stacked),
+                    pile => (pile - TABLEAU, 1, Fan::Spread([0.0, -CASCADE_OVERLAP])),
+                };
+                let position = [
+                    (column as f32 - 3.0) * SPACING,
+                    (1.0 - row as f32) * SPACING,
+                ];
+                Pile::new(position, fan, cards.clone())
+            })
+            .collect()
+    }
+
+    /// The foundations come first, so that a double-click sends a card up to one if it can.
+    fn zones(&self, _seat: usize, _orientation: Orientation) -> Vec<Zone> {
+        let foundations = (FOUNDATIONS..TABLEAU).map(|pile| Zone::new(pile, ZoneKind::Foundation));
+        let tableau = (TABLEAU..PILE_COUNT).map(|pile| Zone::new(pile, ZoneKind::Tableau));
+        foundations.chain(tableau).collect()
+    }
+
+    /// Cards up to the foundations first, then cards off the waste or off face-down cards in the
+    /// tableau, and failing those, a card from the stock.
+    fn hint(&self, _seat: usize) -> Option<Hint> {
+        let moves = self.legal_moves();
+        let onto_foundation = |mv: &&KlondikeMove| match mv {
+            KlondikeMove::Move { to, .. } => Self::is_foundation(*to),
+            _ => false,
+        };
+        let uncovers = |mv: &&KlondikeMove| match mv {
+            KlondikeMove::Move { from, .. } => {
+                from.pile == WASTE
+                    || from
+                        .index
+                        .is_some_and(|index| index > 0 && self.piles[from.pile][index - 1].facedown)
+            }
+            _ => false,
+        };
+        let mv = moves
+            .iter()
+            .find(onto_foundation)
+            .or_else(|| moves.iter().find(uncovers))
+            .or_else(|| {
+                moves
+                    .iter()
+                    .find(|mv| matches!(mv, KlondikeMove::Draw | KlondikeMove::Redeal))
+            })?;
+
+        Some(match *mv {
+            KlondikeMove::Move { from, to } => Hint {
+                from,
+                to: Some(Location::pile(to)),
+            },
+            _ => Hint {
+                from: Location::pile(STOCK),
+                to: None,
+            },
+        })
+    }
+
+    fn move_at(&self, _seat: usize, location: Location) -> Option<KlondikeMove> {
+        (location.pile == STOCK).then(|| {
+            if self.piles[STOCK].is_empty() {
+                KlondikeMove::Redeal
+            } else {
+                KlondikeMove::Draw
+            }
+        })
+    }
+
+    fn move_between(&self, _seat: usize, from: Location, to: Location) -> Option<KlondikeMove> {
+        self.can_pick_up(from)
+            .then_some(KlondikeMove::Move { from, to: to.pile })
+    }
+
+    /// Once finishing, a card up to a foundation if one can go, and otherwise the next from the
+    /// stock.
+    fn automatic_move(&self) -> Option<KlondikeMove> {
+        if !self.finishing {
+            return None;
+        }
+
+        let moves = self.legal_moves();
+        moves
+            .iter()
+            .find(|mv| matches!(mv, KlondikeMove::Move { to, .. } if Self::is_foundation(*to)))
+            .or_else(|| {
+                moves
+                    .iter()
+                    .find(|mv| matches!(mv, KlondikeMove::Draw | KlondikeMove::Redeal))
+            })
+            .copied()
+    }
+
+    fn automatic_move_delay(&self) -> Duration {
+        FINISH_MOVE_DELAY
+    }
+
+    fn actions(&self) -> Vec<(String, KlondikeMove)> {
+        self.legal_moves()
+            .contains(&KlondikeMove::Finish)
+            .then(|| ("Finish".to_owned(), KlondikeMove::Finish))
+            .into_iter()
+            .collect()
+    }
+
+    fn status(&self) -> Option<GameStatus> {
+        Some(GameStatus {
+            players: vec![PlayerStatus {
+                name: "Score".to_owned(),
+                score: self.foundation_cards() as i32 * FOUNDATION_SCORE,
+            }],
+            tricks: None,
+            turn: None,
+        })
+    }
+}
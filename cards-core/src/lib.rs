@@ -0,0 +1,32 @@
+//! Card game logic with no dependency on windowing or the GPU, so that servers, bots and tests
+//! can use it without pulling in the renderer.
+
+pub mod ai;
+#[cfg(feature = "blackjack")]
+pub mod blackjack;
+pub mod card;
+pub mod deck;
+#[cfg(feature = "doppelkopf")]
+pub mod doppelkopf;
+#[cfg(feature = "gallery")]
+pub mod gallery;
+pub mod game;
+#[cfg(feature = "hearts")]
+pub mod hearts;
+#[cfg(all(feature = "net", any(feature = "hearts", feature = "doppelkopf")))]
+pub mod host;
+#[cfg(feature = "klondike")]
+pub mod klondike;
+#[cfg(feature = "memory")]
+pub mod memory;
+#[cfg(feature = "net")]
+pub mod protocol;
+pub mod puzzle;
+pub mod qr;
+pub mod rng;
+pub mod save;
+pub mod stats;
+pub mod status;
+pub mod table;
+pub mod trick;
+pub mod trumps;
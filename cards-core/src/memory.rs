@@ -0,0 +1,179 @@
+//! Memory, also called Concentration: the deck is dealt face down in a grid, and turning over two
+//! cards of the same rank and colour takes them off the table. The fewer turns it takes to clear
+//! the table, the better.
+
+use crate::{
+    card::notation,
+    deck::Deck,
+    game::{CardView, Fan, GameState, Location, Orientation, Pile},
+    status::{GameStatus, Outcome, PlayerStatus},
+    trick::Card,
+};
+
+/// The gap between neighbouring cards, as a fraction of a card.
+const SPACING: f32 = 1.2;
+
+/// How many cards are turned over on each turn.
+const TURN_SIZE: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MemoryMove {
+    /// Turns over the card in a cell of the grid.
+    Flip(usize),
+    /// Once two cards are face up, takes them away if they match or turns them back over if not.
+    Resolve,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Memory {
+    /// The grid, row by row, with `None` where a pair has been taken away.
+    cells: Vec<Option<Card>>,
+    /// The cells turned face up this turn.
+    face_up: Vec<usize>,
+    turns: u32,
+    pairs: u32,
+}
+
+impl Memory {
+    fn matches((suit, rank): Card, (other_suit, other_rank): Card) -> bool {
+        rank == other_rank && suit.is_red() == other_suit.is_red()
+    }
+
+    /// The grid is wide in landscape windows and narrow in portrait ones.
+    fn columns(orientation: Orientation) -> usize {
+        match orientation {
+            Orientation::Landscape => 13,
+            Orientation::Portrait => 7,
+        }
+    }
+}
+
+impl GameState for Memory {
+    type Move = MemoryMove;
+
+    fn setup(seed: u64) -> Self {
+        let mut deck = Deck::standard();
+        deck.shuffle_with_seed(seed);
+
+        Self {
+            cells: deck.deal(deck.len()).into_iter().map(Some).collect(),
+            face_up: Vec::new(),
+            turns: 0,
+            pairs: 0,
+        }
+    }
+
+    fn legal_moves(&self) -> Vec<MemoryMove> {
+        if self.face_up.len() == TURN_SIZE {
+            return vec![MemoryMove::Resolve];
+        }
+
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|&(cell, card)| card.is_some() && !self.face_up.contains(&cell))
+            .map(|(cell, _)| MemoryMove::Flip(cell))
+            .collect()
+    }
+
+    fn apply_move(&mut self, mv: &MemoryMove) {
+        match *mv {
+            MemoryMove::Flip(cell) => self.face_up.push(cell),
+            MemoryMove::Resolve => {
+                let [first, second] = self.face_up[..] else {
+                    return;
+                };
+                if let (Some(a), Some(b)) = (self.cells[first], self.cells[second]) {
+                    if Self::matches(a, b) {
+                        self.cells[first] = None;
+                        self.cells[second] = None;
+                        self.pairs += 1;
+                    }
+                }
+
+                self.face_up.clear();
+                self.turns += 1;
+            }
+        }
+    }
+
+    fn describe_move(&self, mv: &MemoryMove) -> String {
+        match *mv {
+            MemoryMove::Flip(cell) => match self.cells[cell] {
+                Some((suit, rank)) => format!("Turn over {} at {}", notation(suit, rank), cell + 1),
+                None => format!("Turn over {}", cell + 1),
+            },
+            MemoryMove::Resolve => {
+                let cards = self.face_up.iter().filter_map(|&cell| self.cells[cell]);
+                match cards.collect::<Vec<_>>()[..] {
+                    [a, b] if Self::matches(a, b) => "Pair found".to_owned(),
+                    _ => "No match".to_owned(),
+                }
+            }
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.cells.iter().all(Option::is_none)
+    }
+
+    /// Finding every pair is a win.
+    fn outcome(&self, _seat: usize) -> Option<Outcome> {
+        self.is_over().then_some(Outcome::Won)
+    }
+
+    /// A pile per cell of the grid, holding its card until the card's pair is found.
+    fn table(&self, _seat: usize, orientation: Orientation) -> Vec<Pile> {
+        let columns = Self::columns(orientation);
+        let rows = self.cells.len().div_ceil(columns);
+
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(cell, card)| {
+                let (row, column) = (cell / columns, cell % columns);
+                let position = [
+                    (column as f32 - (columns - 1) as f32 / 2.0) * SPACING,
+                    ((rows - 1) as f32 / 2.0 - row as f32) * SPACING,
+                ];
+                let cards = card
+                    .map(|(suit, rank)| CardView::new(suit, rank, !self.face_up.contains(&cell)))
+                    .into_iter()
+                    .collect();
+                Pile::new(position, Fan::Stacked, cards)
+            })
+            .collect()
+    }
+
+    fn move_at(&self, _seat: usize, location: Location) -> Option<MemoryMove> {
+        location.index.map(|_| MemoryMove::Flip(location.pile))
+    }
+
+    fn move_between(&self, _seat: usize, _from: Location, _to: Location) -> Option<MemoryMove> {
+        None
+    }
+
+    /// Two cards face up are left long enough to be seen before they're taken away or turned back.
+    fn automatic_move(&self) -> Option<MemoryMove> {
+        (self.face_up.len() == TURN_SIZE).then_some(MemoryMove::Resolve)
+    }
+
+    fn status(&self) -> Option<GameStatus> {
+        Some(GameStatus {
+            players: vec![
+                PlayerStatus {
+                    name: "Pairs".to_owned(),
+                    score: self.pairs as i32,
+                },
+                PlayerStatus {
+                    name: "Turns".to_owned(),
+                    score: self.turns as i32,
+                },
+            ],
+            tricks: None,
+            turn: None,
+        })
+    }
+}
@@ -0,0 +1,190 @@
+//! Messages between the `cards-server` binary and the players connected to it, each encoded with
+//! `encode` into a binary WebSocket frame.
+//!
+//! A connection opens with the client saying `Hello` with the version of the protocol it speaks,
+//! and the server answering `Welcome` if it speaks it too. Players then find a room with
+//! `ListRooms`, or `Create` one, and wait in it until everyone sitting down is `Ready`, being sent
+//! the room as a `Lobby` whenever it changes.
+//!
+//! The server holds the only copy of each game that counts. It sends each player the seed the
+//! game was dealt from and the moves made so far, and then every move as it's made, so players
+//! keep their own copy in step by making the same moves, as a saved game is played back. Moves
+//! are sent as `MoveDelta`s, which leave out everything a player can work out for themselves. A
+//! player whose connection drops can connect again and `Resume` their seat, and is sent the whole
+//! game again to catch up on.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::MoveRecord;
+
+/// The version of the protocol described here. Any change to the messages needs a new version,
+/// since clients and servers on different versions can't read each other. Moves are sent by
+/// index in `legal_moves`, like saved games, so a new `save::FORMAT_VERSION` needs one too.
+pub const PROTOCOL_VERSION: u32 = 6;
+
+/// The most characters a player can `Say` at once.
+pub const MAX_SAY_LENGTH: usize = 200;
+
+/// What a player asks of the server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// The first message on a connection, with the version of the protocol the client speaks.
+    Hello { version: u32 },
+    /// Asks for the open rooms, which come back as `Rooms`.
+    ListRooms,
+    /// Opens a room for a game of `game`, such as `hearts`, played by `options`, and takes a seat
+    /// in it.
+    Create { game: String, options: TableOptions },
+    /// Takes a free seat in the room called `room`.
+    Join { room: String },
+    /// Moves to another free seat, before the game has started.
+    Sit { seat: usize },
+    /// Says whether the player is ready for the game to start. It starts once everyone sitting
+    /// down is.
+    Ready(bool),
+    /// Takes back the seat given with `session` in `Lobby` or `Joined`, after the connection it
+    /// was taken on dropped. The seat is held for a while, with the computer playing it.
+    Resume { session: u64 },
+    /// Makes the move at `index` in the game's `legal_moves`, which has to be one the player's
+    /// own seat can make.
+    Play { index: usize },
+    /// Says something to everyone in the player's room.
+    Say(String),
+    /// Gives the player's seat up, to the computer if the game has started.
+    Leave,
+}
+
+/// What the server tells a player.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ServerMessage {
+    /// The answer to a `Hello` in a version the server speaks. A `Hello` in any other version is
+    /// answered with `Rejected`, and the connection closed.
+    Welcome { version: u32 },
+    /// The open rooms, by name.
+    Rooms(Vec<RoomSummary>),
+    /// The room the player is waiting in, whenever anything in it changes before the game starts.
+    Lobby(Lobby),
+    /// The player is playing `seat` in a game of `game`, dealt from `seed`, in which `moves` have
+    /// been made so far: on the game starting, or on joining or resuming one already going. The
+    /// seat can be taken back after a dropped connection with `session`.
+    Joined {
+        room: String,
+        seat: usize,
+        session: u64,
+        game: String,
+        seed: u64,
+        moves: Vec<MoveDelta>,
+    },
+    /// Moves made since the last update, by anyone at the table, oldest first. `first` is how
+    /// many moves were made before them, so a player can tell if they've missed any.
+    Moves { first: usize, moves: Vec<MoveDelta> },
+    /// The player in `seat` said something to the room, which might be the player being told.
+    Said { seat: usize, text: String },
+    /// The server couldn't do what the player asked, and why.
+    Rejected(String),
+    /// The seat asked for with `Resume` is still played from a connection that hasn't dropped,
+    /// so it isn't handed over. The player can ask again once that connection has dropped.
+    SeatTaken,
+}
+
+/// How a room's game is played, picked when the room is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct TableOptions {
+    /// Whether the game can start with seats nobody has taken, which the computer plays. Without
+    /// computer players, the game waits for a full table.
+    pub computer_players: bool,
+    /// Whether to deal from the day's seed, the same for everyone on the same day, rather than a
+    /// new one.
+    pub daily: bool,
+}
+
+/// A room, as it's listed for players looking for one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoomSummary {
+    pub name: String,
+    pub game: String,
+    pub options: TableOptions,
+    /// How many seats players have taken, out of how many there are.
+    pub players: usize,
+    pub seats: usize,
+    pub started: bool,
+}
+
+/// Who's in a seat while a room waits for its game to start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeatState {
+    Open,
+    Waiting,
+    Ready,
+    /// The player's connection dropped, and the seat is held for them.
+    Away,
+}
+
+/// A room waiting for its game to start, as it's shown to a player in it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lobby {
+    pub room: String,
+    pub game: String,
+    pub options: TableOptions,
+    /// The player's own seat, which they can take back with `session` after a dropped
+    /// connection.
+    pub seat: usize,
+    pub session: u64,
+    pub seats: Vec<SeatState>,
+}
+
+/// A move as it's sent: its index in `legal_moves`, and how long after the move before it it was
+/// made, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveDelta {
+    pub index: usize,
+    pub after: u32,
+}
+
+impl MoveDelta {
+    /// `moves` as deltas, the first of them timed from `start`, when the move before them was
+    /// made.
+    pub fn encode(moves: &[MoveRecord], start: Duration) -> Vec<Self> {
+        let mut last = start;
+        moves
+            .iter()
+            .map(|mv| {
+                let after = mv.time.saturating_sub(last).as_millis();
+                last = mv.time;
+                Self {
+                    index: mv.index,
+                    after: after.try_into().unwrap_or(u32::MAX),
+                }
+            })
+            .collect()
+    }
+
+    /// The moves `encode` was given back, bar their notation, which `Game::restore` writes out
+    /// again as it makes them.
+    pub fn decode(deltas: &[Self], start: Duration) -> Vec<MoveRecord> {
+        let mut time = start;
+        deltas
+            .iter()
+            .map(|delta| {
+                time += Duration::from_millis(delta.after.into());
+                MoveRecord {
+                    index: delta.index,
+                    notation: String::new(),
+                    time,
+                }
+            })
+            .collect()
+    }
+}
+
+/// A message as the bytes of a binary frame.
+pub fn encode<M: Serialize>(message: &M) -> Vec<u8> {
+    postcard::to_stdvec(message).expect("messages always encode")
+}
+
+/// A message back from the bytes `encode` made of it, or `None` if they aren't one.
+pub fn decode<'a, M: Deserialize<'a>>(bytes: &'a [u8]) -> Option<M> {
+    postcard::from_bytes(bytes).ok()
+}
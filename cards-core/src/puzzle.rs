@@ -0,0 +1,189 @@
+//! Puzzles: a particular deal of a game to play to a goal, such as winning a Klondike deal in so
+//! many moves, and which of them the player has solved.
+//!
+//! A puzzle is written like a saved game, a line per field, with a line for each of its goals,
+//! all of which have to be met:
+//!
+//! ```text
+//! name Last word
+//! game doppelkopf
+//! seed 15
+//! goal declarer
+//! goal last-tricks 4
+//! solution 4 2 3 3 1 6 0 4 1 0 0 0
+//! ```
+//!
+//! The solution is the player's moves that solve the puzzle, each by its index in `legal_moves`,
+//! against the rule-based computer players, over as many `solution` lines as it takes.
+//! `cards-sim --game puzzles` plays every bundled puzzle's solution through to check it does.
+//!
+//! Progress is kept as a line per puzzle solved, with the fewest moves it was solved in:
+//!
+//! ```text
+//! klondike-tight best 125
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::{game::Game, status::Outcome};
+
+/// The puzzles that come with the game, by id and text.
+const BUNDLED: &[(&str, &str)] = &[
+    (
+        "klondike-steady",
+        include_str!("../puzzles/klondike-steady.txt"),
+    ),
+    (
+        "klondike-tight",
+        include_str!("../puzzles/klondike-tight.txt"),
+    ),
+    ("hearts-queen", include_str!("../puzzles/hearts-queen.txt")),
+    (
+        "doppelkopf-comeback",
+        include_str!("../puzzles/doppelkopf-comeback.txt"),
+    ),
+    (
+        "doppelkopf-last-word",
+        include_str!("../puzzles/doppelkopf-last-word.txt"),
+    ),
+];
+
+/// Something a game has to end with to solve a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// The player wins.
+    Win,
+    /// The game is over within this many moves, counting every move in `Game::moves`, the
+    /// game's own and the computer's as well as the player's.
+    Moves(usize),
+    /// The player takes the last this many tricks, for trick games.
+    LastTricks(usize),
+    /// The player is the declarer, or one of them, for games where one side takes on the others.
+    Declarer,
+}
+
+impl Goal {
+    /// Whether `game`, which ended with `outcome`, meets the goal.
+    pub fn is_met(self, outcome: Option<Outcome>, game: &dyn Game) -> bool {
+        match self {
+            Self::Win => outcome == Some(Outcome::Won),
+            Self::Moves(most) => game.moves().len() <= most,
+            Self::LastTricks(count) => {
+                let winners = game.trick_winners();
+                winners.len() >= count
+                    && winners[winners.len() - count..]
+                        .iter()
+                        .all(|&winner| winner == game.seat())
+            }
+            Self::Declarer => game.declarers().contains(&game.seat()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    /// What the puzzle's progress is kept under.
+    pub id: String,
+    pub name: String,
+    /// The name the game is picked by, such as `klondike`.
+    pub game: String,
+    pub seed: u64,
+    pub goals: Vec<Goal>,
+    /// The player's moves that solve the puzzle, by index in `legal_moves`.
+    pub solution: Vec<usize>,
+}
+
+impl Puzzle {
+    /// Reads the puzzle kept under `id` from its text, or `None` if the text isn't a puzzle.
+    pub fn from_text(id: &str, text: &str) -> Option<Self> {
+        let (mut name, mut game, mut seed) = (None, None, None);
+        let (mut goals, mut solution) = (Vec::new(), Vec::new());
+
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let (field, value) = line.split_once(' ').unwrap_or((line, ""));
+            match field {
+                "name" => name = Some(value.trim().to_owned()),
+                "game" => game = Some(value.trim().to_owned()),
+                "seed" => seed = value.trim().parse().ok(),
+                "goal" => goals.push(match value.split_once(' ') {
+                    None if value.trim() == "win" => Goal::Win,
+                    None if value.trim() == "declarer" => Goal::Declarer,
+                    Some(("moves", most)) => Goal::Moves(most.trim().parse().ok()?),
+                    Some(("last-tricks", count)) => Goal::LastTricks(count.trim().parse().ok()?),
+                    _ => return None,
+                }),
+                "solution" => {
+                    for index in value.split_whitespace() {
+                        solution.push(index.parse().ok()?);
+                    }
+                }
+                _ => return None,
+            }
+        }
+
+        Some(Self {
+            id: id.to_owned(),
+            name: name.unwrap_or_else(|| id.to_owned()),
+            game: game.filter(|game| !game.is_empty())?,
+            seed: seed?,
+            goals,
+            solution,
+        })
+    }
+
+    /// The puzzles that come with the game, easiest first. Every one of them has to read, so one
+    /// that doesn't panics rather than going missing.
+    pub fn bundled() -> Vec<Self> {
+        BUNDLED
+            .iter()
+            .map(|(id, text)| Self::from_text(id, text).expect("bundled puzzles are puzzles"))
+            .collect()
+    }
+
+    /// Whether `game`, a game of the puzzle that ended with `outcome`, solves it.
+    pub fn is_solved(&self, outcome: Option<Outcome>, game: &dyn Game) -> bool {
+        self.goals.iter().all(|goal| goal.is_met(outcome, game))
+    }
+}
+
+/// The puzzles the player has solved, by id, with the fewest moves each was solved in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Progress {
+    solved: BTreeMap<String, usize>,
+}
+
+impl Progress {
+    /// Counts `puzzle` as solved in `moves` moves.
+    pub fn record(&mut self, puzzle: &str, moves: usize) {
+        self.solved
+            .entry(puzzle.to_owned())
+            .and_modify(|best| *best = (*best).min(moves))
+            .or_insert(moves);
+    }
+
+    /// The fewest moves `puzzle` has been solved in, if it has been.
+    pub fn best(&self, puzzle: &str) -> Option<usize> {
+        self.solved.get(puzzle).copied()
+    }
+
+    pub fn to_text(&self) -> String {
+        self.solved
+            .iter()
+            .map(|(puzzle, best)| format!("{puzzle} best {best}\n"))
+            .collect()
+    }
+
+    /// Reads progress back from `to_text`, or `None` if the text isn't progress.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let mut solved = BTreeMap::new();
+
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [puzzle, "best", best] => solved.insert(puzzle.to_owned(), best.parse().ok()?),
+                _ => return None,
+            };
+        }
+
+        Some(Self { solved })
+    }
+}
@@ -0,0 +1,429 @@
+//! QR codes, for carrying a game over to another device with its camera: text is encoded as bytes
+//! at the lowest level of error correction, in the smallest version of the code it fits in, with
+//! whichever mask the standard's penalty rules score best.
+
+/// Error correction codewords in each block, and how many blocks there are, by version, at the
+/// lowest level of error correction. Version 0 doesn't exist.
+const ECC_CODEWORDS_PER_BLOCK: [usize; 41] = [
+    0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28, 30,
+    30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+];
+const ERROR_CORRECTION_BLOCKS: [usize; 41] = [
+    0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13, 14,
+    15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25,
+];
+
+/// The format bits for the lowest level of error correction, before the mask's are added.
+const LOW_ECC_FORMAT: u32 = 1;
+
+/// The codewords that fill out the data, in turn, once it's been written.
+const PADDING: [u8; 2] = [0xEC, 0x11];
+
+/// A QR code: a square of dark and light modules, without the quiet zone that goes around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+    /// Which modules are finder, timing, alignment or format patterns, rather than data, which
+    /// masks leave alone.
+    function: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encodes `data`, or `None` if it's too long for even the largest version.
+    pub fn encode(data: &[u8]) -> Option<Self> {
+        let version = (1..=40).find(|&version| {
+            let count_bits = if version < 10 { 8 } else { 16 };
+            data.len() < 1 << count_bits
+                && 4 + count_bits + 8 * data.len() <= 8 * data_codewords(version)
+        })?;
+
+        let mut bits = Bits::default();
+        bits.push(0b0100, 4);
+        bits.push(data.len() as u32, if version < 10 { 8 } else { 16 });
+        for &byte in data {
+            bits.push(byte.into(), 8);
+        }
+        let capacity = 8 * data_codewords(version);
+        bits.push(0, (capacity - bits.len).min(4));
+        bits.push(0, (8 - bits.len % 8) % 8);
+        let mut codewords = bits.bytes;
+        for &pad in PADDING.iter().cycle() {
+            if codewords.len() == data_codewords(version) {
+                break;
+            }
+            codewords.push(pad);
+        }
+
+        let size = 4 * version + 17;
+        let mut code = Self {
+            size,
+            modules: vec![false; size * size],
+            function: vec![false; size * size],
+        };
+        code.draw_function_patterns(version);
+        code.draw_codewords(&add_error_correction(&codewords, version));
+
+        let best = (0..8)
+            .min_by_key(|&mask| {
+                code.apply_mask(mask);
+                code.draw_format_bits(mask);
+                let penalty = code.penalty();
+                code.apply_mask(mask);
+                penalty
+            })
+            .unwrap_or(0);
+        code.apply_mask(best);
+        code.draw_format_bits(best);
+        Some(code)
+    }
+
+    /// How many modules wide and high the code is.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether the module `x` across and `y` down from the top-left is dark.
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4..=4_isize {
+                for dx in -4..=4_isize {
+                    let (Some(xx), Some(yy)) = (x.checked_add_signed(dx), y.checked_add_signed(dy))
+                    else {
+                        continue;
+                    };
+                    if xx < size && yy < size {
+                        let distance = dx.abs().max(dy.abs());
+                        self.set_function(xx, yy, distance != 2 && distance != 4);
+                    }
+                }
+            }
+        }
+
+        let positions = alignment_positions(version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                // The corners the finder patterns are in have no alignment pattern.
+                if (i, j) == (0, 0) || (i, j) == (0, last) || (i, j) == (last, 0) {
+                    continue;
+                }
+                for dy in -2..=2_isize {
+                    for dx in -2..=2_isize {
+                        let dark = dx.abs().max(dy.abs()) != 1;
+                        self.set_function(
+                            x.wrapping_add_signed(dx),
+                            y.wrapping_add_signed(dy),
+                            dark,
+                        );
+                    }
+                }
+            }
+        }
+
+        // The format bits are drawn for real once the mask is picked.
+        self.draw_format_bits(0);
+
+        if version >= 7 {
+            let mut remainder = version as u32;
+            for _ in 0..12 {
+                remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1F25);
+            }
+            let bits = (version as u32) << 12 | remainder;
+            for i in 0..18 {
+                let dark = (bits >> i) & 1 != 0;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: u32) {
+        let data = LOW_ECC_FORMAT << 3 | mask;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (data << 10 | remainder) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+
+        let size = self.size;
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Fills the data modules with `codewords`, two columns at a time in a zigzag from the
+    /// bottom-right corner.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size - 1;
+        loop {
+            // The vertical timing pattern is stepped over.
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..size {
+                for x in [right, right - 1] {
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward {
+                        size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !self.function[y * size + x] && i < codewords.len() * 8 {
+                        self.modules[y * size + x] = (codewords[i / 8] >> (7 - i % 8)) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            if right < 3 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    /// Flips the data modules `mask` picks out, which also undoes it.
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let flip = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let index = y * self.size + x;
+                self.modules[index] ^= flip && !self.function[index];
+            }
+        }
+    }
+
+    /// How hard the code is likely to be to read, by the standard's rules: long runs, blocks of
+    /// one colour, patterns that look like finders, and too much or too little dark.
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let rows = (0..size).map(|y| (0..size).map(|x| self.is_dark(x, y)).collect::<Vec<_>>());
+        let columns = (0..size).map(|x| (0..size).map(|y| self.is_dark(x, y)).collect::<Vec<_>>());
+        let mut penalty: usize = rows.chain(columns).map(|line| line_penalty(&line)).sum();
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = self.is_dark(x, y);
+                if [(x + 1, y), (x, y + 1), (x + 1, y + 1)]
+                    .iter()
+                    .all(|&(x, y)| self.is_dark(x, y) == dark)
+                {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let total = size * size;
+        let dark = self.modules.iter().filter(|&&dark| dark).count();
+        let k = (dark * 20)
+            .abs_diff(total * 10)
+            .div_ceil(total)
+            .saturating_sub(1);
+        penalty + k * 10
+    }
+}
+
+/// The penalties for a row or column: for runs of five or more modules of one colour, and for
+/// anything like a finder pattern, light on one side or the other.
+fn line_penalty(line: &[bool]) -> usize {
+    let mut penalty = 0;
+    let mut run = 1;
+    for i in 1..=line.len() {
+        if i < line.len() && line[i] == line[i - 1] {
+            run += 1;
+            continue;
+        }
+        if run >= 5 {
+            penalty += run - 2;
+        }
+        run = 1;
+    }
+
+    const FINDER: [bool; 7] = [true, false, true, true, true, false, true];
+    let light = |i: isize| i < 0 || i as usize >= line.len() || !line[i as usize];
+    for start in 0..line.len().saturating_sub(6) {
+        if line[start..start + 7] == FINDER {
+            let start = start as isize;
+            let before = (start - 4..start).all(light);
+            let after = (start + 7..start + 11).all(light);
+            if before || after {
+                penalty += 40;
+            }
+        }
+    }
+    penalty
+}
+
+/// Where the alignment patterns' centres go, across and down, for `version`.
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let count = version / 7 + 2;
+    let step = (version * 8 + count * 3 + 5) / (count * 4 - 4) * 2;
+    let mut positions = (0..count - 1)
+        .map(|i| 4 * version + 10 - i * step)
+        .collect::<Vec<_>>();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+/// How many modules of a `version` code hold data and error correction, rather than patterns.
+fn raw_data_modules(version: usize) -> usize {
+    let mut modules = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        modules -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            modules -= 36;
+        }
+    }
+    modules
+}
+
+/// How many codewords of data a `version` code holds, leaving out its error correction.
+fn data_codewords(version: usize) -> usize {
+    raw_data_modules(version) / 8
+        - ECC_CODEWORDS_PER_BLOCK[version] * ERROR_CORRECTION_BLOCKS[version]
+}
+
+/// Splits `data` into the version's blocks, adds each block's error correction, and interleaves
+/// the lot.
+fn add_error_correction(data: &[u8], version: usize) -> Vec<u8> {
+    let blocks = ERROR_CORRECTION_BLOCKS[version];
+    let ecc_length = ECC_CODEWORDS_PER_BLOCK[version];
+    let raw_codewords = raw_data_modules(version) / 8;
+    let short_blocks = blocks - raw_codewords % blocks;
+    let short_length = raw_codewords / blocks;
+
+    let divisor = reed_solomon_divisor(ecc_length);
+    let mut start = 0;
+    let blocks = (0..blocks)
+        .map(|i| {
+            let length = short_length - ecc_length + usize::from(i >= short_blocks);
+            let mut block = data[start..start + length].to_vec();
+            start += length;
+            let ecc = reed_solomon_remainder(&block, &divisor);
+            // Short blocks are padded to line up with the long ones, and the padding skipped.
+            if i < short_blocks {
+                block.push(0);
+            }
+            block.extend(ecc);
+            block
+        })
+        .collect::<Vec<_>>();
+
+    let mut interleaved = Vec::with_capacity(raw_codewords);
+    for i in 0..blocks[0].len() {
+        for (j, block) in blocks.iter().enumerate() {
+            if i != short_length - ecc_length || j >= short_blocks {
+                interleaved.push(block[i]);
+            }
+        }
+    }
+    interleaved
+}
+
+/// The generator polynomial for `degree` error correction codewords, highest power first,
+/// leaving out its leading 1.
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut divisor = vec![0; degree];
+    divisor[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            divisor[j] = gf_multiply(divisor[j], root);
+            if j + 1 < degree {
+                divisor[j] ^= divisor[j + 1];
+            }
+        }
+        root = gf_multiply(root, 2);
+    }
+    divisor
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut remainder = vec![0; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ remainder.remove(0);
+        remainder.push(0);
+        for (term, &coefficient) in remainder.iter_mut().zip(divisor) {
+            *term ^= gf_multiply(coefficient, factor);
+        }
+    }
+    remainder
+}
+
+/// Multiplies in GF(2^8), modulo the QR code's polynomial, x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut product = 0u8;
+    for i in (0..8).rev() {
+        product = (product << 1) ^ ((product >> 7) * 0x1D);
+        product ^= ((y >> i) & 1) * x;
+    }
+    product
+}
+
+/// Bits written most significant first, into bytes.
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bits {
+    /// Writes the low `count` bits of `value`.
+    fn push(&mut self, value: u32, count: usize) {
+        for i in (0..count).rev() {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            let bit = ((value >> i) & 1) as u8;
+            *self.bytes.last_mut().expect("a byte was just pushed") |= bit << (7 - self.len % 8);
+            self.len += 1;
+        }
+    }
+}
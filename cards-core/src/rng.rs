@@ -0,0 +1,45 @@
+//! A small seeded random number generator. Games are dealt from it rather than from an outside
+//! crate so that a seed gives the same deal on every platform and in every version.
+
+/// Seconds in a day, ignoring leap seconds as Unix time does.
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The seed for the day `unix_time`, in seconds, falls on in UTC. Everyone dealing a game from it
+/// on the same day gets the same deal, so they can compare how they did.
+pub fn daily_seed(unix_time: u64) -> u64 {
+    Rng::new(unix_time / SECONDS_PER_DAY).next_u64()
+}
+
+/// SplitMix64: fast, statistically sound for shuffling, and a single `u64` of state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`, which must not be zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        // Widening multiply rather than `%`, which would favour small numbers.
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+
+    /// Puts `items` in a random order, each order equally likely.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Games saved to be carried on with, or played back, later.
+//!
+//! A game is saved as what it takes to play it again: which game it was, the seed it was dealt
+//! from, and every move since, each as its index in `legal_moves` when it was made and the time
+//! into the game it was made at, after an `@`. Moves also carry how the game wrote them out,
+//! which is only there for people reading the record and is ignored when playing it back. The
+//! text format is the format's version, a line per field, then a line per move:
+//!
+//! ```text
+//! version 2
+//! game hearts
+//! seed 1234
+//! elapsed 3.1
+//! move 19 @0.7 West: pick AH
+//! move 18 @1.4 West: pick JH
+//! move 17 @2.1 West: pick 4H
+//! move 26 @2.8 West: pass confirmed
+//! ```
+//!
+//! A game can also be written as a short code, for carrying it on on another device, by link or
+//! by QR code. It keeps only what playing on needs: the format's version and the game, then the
+//! seed, the whole seconds played and each move's index, in base 36, with dots between the
+//! fields and dashes between the moves:
+//!
+//! ```text
+//! 2.hearts.ya.3.j-i-h-q
+//! ```
+
+use std::time::Duration;
+
+use crate::game::MoveRecord;
+
+/// The version of the formats games are saved in. Moves are kept by index in `legal_moves`, so
+/// besides any change to the formats, a game changing the order of its `legal_moves` needs a new
+/// version too. Games saved in any other version aren't read, rather than being played back
+/// wrong.
+pub const FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedGame {
+    /// The name the game was picked by, such as `klondike`.
+    pub game: String,
+    pub seed: u64,
+    /// How long the game had been played for.
+    pub elapsed: Duration,
+    pub moves: Vec<MoveRecord>,
+}
+
+impl SavedGame {
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "version {FORMAT_VERSION}\ngame {}\nseed {}\nelapsed {}\n",
+            self.game,
+            self.seed,
+            self.elapsed.as_secs_f64()
+        );
+
+        for mv in &self.moves {
+            let line = format!(
+                "move {} @{} {}",
+                mv.index,
+                mv.time.as_secs_f64(),
+                mv.notation
+            );
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+
+        text
+    }
+
+    /// Reads a game back from `to_text`, or `None` if the text isn't a game saved in this
+    /// version of the format.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let (mut version, mut game, mut seed, mut elapsed) = (None, None, None, None);
+        let mut moves = Vec::new();
+
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let (field, value) = line.split_once(' ').unwrap_or((line, ""));
+            match field {
+                "version" => version = value.trim().parse::<u32>().ok(),
+                "game" => game = Some(value.trim().to_owned()),
+                "seed" => seed = value.trim().parse().ok(),
+                "elapsed" => elapsed = Duration::try_from_secs_f64(value.trim().parse().ok()?).ok(),
+                "move" => {
+                    let (index, timed) = value.split_once(" @")?;
+                    let (secs, written) = timed.split_once(' ').unwrap_or((timed, ""));
+                    moves.push(MoveRecord {
+                        index: index.trim().parse().ok()?,
+                        notation: written.trim().to_owned(),
+                        time: Duration::try_from_secs_f64(secs.parse().ok()?).ok()?,
+                    });
+                }
+                _ => return None,
+            }
+        }
+
+        if version? != FORMAT_VERSION {
+            return None;
+        }
+        Some(Self {
+            game: game.filter(|game| !game.is_empty())?,
+            seed: seed?,
+            elapsed: elapsed.unwrap_or_default(),
+            moves,
+        })
+    }
+
+    /// The game as a code made only of characters that can go in a URL as they are.
+    pub fn to_code(&self) -> String {
+        let moves = self
+            .moves
+            .iter()
+            .map(|mv| base36(mv.index as u64))
+            .collect::<Vec<_>>();
+        format!(
+            "{FORMAT_VERSION}.{}.{}.{}.{}",
+            self.game,
+            base36(self.seed),
+            base36(self.elapsed.as_secs()),
+            moves.join("-")
+        )
+    }
+
+    /// Reads a game back from `to_code`, or `None` if the text isn't a code for one in this
+    /// version of the format. The moves are taken as made at the start, and written out afresh as
+    /// they're played.
+    pub fn from_code(code: &str) -> Option<Self> {
+        let [version, game, seed, elapsed, moves] = code.trim().split('.').collect::<Vec<_>>()[..]
+        else {
+            return None;
+        };
+        if version.parse() != Ok(FORMAT_VERSION) {
+            return None;
+        }
+        let moves = moves
+            .split('-')
+            .filter(|index| !index.is_empty())
+            .map(|index| {
+                Some(MoveRecord {
+                    index: usize::from_str_radix(index, 36).ok()?,
+                    notation: String::new(),
+                    time: Duration::ZERO,
+                })
+            })
+            .collect::<Option<_>>()?;
+
+        Some(Self {
+            game: Some(game.to_owned()).filter(|game| !game.is_empty())?,
+            seed: u64::from_str_radix(seed, 36).ok()?,
+            elapsed: Duration::from_secs(u64::from_str_radix(elapsed, 36).ok()?),
+            moves,
+        })
+    }
+}
+
+/// `n` in base 36, in lower case.
+fn base36(mut n: u64) -> String {
+    let mut digits = Vec::new();
+    loop {
+        digits.push(std::char::from_digit((n % 36) as u32, 36).expect("a digit below 36"));
+        n /= 36;
+        if n == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
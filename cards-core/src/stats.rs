@@ -0,0 +1,144 @@
+//! How the player has done at each game over every run: how many they've played and won, their
+//! quickest win, and their winning streaks.
+//!
+//! Statistics are kept as a line per game, with each figure after its name and the quickest win
+//! left out until there is one:
+//!
+//! ```text
+//! klondike played 12 won 5 best 183.2 streak 2 longest 3
+//! hearts played 4 won 1 streak 0 longest 1
+//! ```
+
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::status::Outcome;
+
+/// How the player has done at one game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GameStats {
+    pub played: u32,
+    pub won: u32,
+    /// The quickest win.
+    pub best: Option<Duration>,
+    /// How many games in a row have been won, up to the last one.
+    pub streak: u32,
+    pub longest_streak: u32,
+}
+
+impl GameStats {
+    /// Counts a game that finished with `outcome` after `elapsed`. A draw doesn't end a streak,
+    /// and a game that doesn't say how it went is counted as played and nothing more.
+    pub fn record(&mut self, outcome: Option<Outcome>, elapsed: Duration) {
+        self.played += 1;
+        match outcome {
+            Some(Outcome::Won) => {
+                self.won += 1;
+                self.best = Some(self.best.map_or(elapsed, |best| best.min(elapsed)));
+                self.streak += 1;
+                self.longest_streak = self.longest_streak.max(self.streak);
+            }
+            Some(Outcome::Lost) => self.streak = 0,
+            Some(Outcome::Drawn) | None => {}
+        }
+    }
+
+    /// The share of games played that were won, from 0 to 1.
+    pub fn win_rate(&self) -> f32 {
+        match self.played {
+            0 => 0.0,
+            played => self.won as f32 / played as f32,
+        }
+    }
+
+    /// The figures of both together, as though they were one game, taking the longer of their
+    /// streaks rather than adding them.
+    fn combine(self, other: Self) -> Self {
+        Self {
+            played: self.played + other.played,
+            won: self.won + other.won,
+            best: match (self.best, other.best) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+            streak: self.streak.max(other.streak),
+            longest_streak: self.longest_streak.max(other.longest_streak),
+        }
+    }
+}
+
+/// How the player has done at every game they've finished one of, by the name it was picked by.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    games: BTreeMap<String, GameStats>,
+}
+
+impl Stats {
+    /// Counts a game of `game` that finished with `outcome` after `elapsed`.
+    pub fn record(&mut self, game: &str, outcome: Option<Outcome>, elapsed: Duration) {
+        self.games
+            .entry(game.to_owned())
+            .or_default()
+            .record(outcome, elapsed);
+    }
+
+    /// Each game that's been played, by name, in alphabetical order.
+    pub fn games(&self) -> impl Iterator<Item = (&str, &GameStats)> {
+        self.games
+            .iter()
+            .map(|(name, stats)| (name.as_str(), stats))
+    }
+
+    pub fn game(&self, game: &str) -> GameStats {
+        self.games.get(game).copied().unwrap_or_default()
+    }
+
+    /// Every game's figures together.
+    pub fn total(&self) -> GameStats {
+        self.games
+            .values()
+            .fold(GameStats::default(), |total, &stats| total.combine(stats))
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (name, stats) in &self.games {
+            text.push_str(&format!("{name} played {} won {}", stats.played, stats.won));
+            if let Some(best) = stats.best {
+                text.push_str(&format!(" best {}", best.as_secs_f64()));
+            }
+            text.push_str(&format!(
+                " streak {} longest {}\n",
+                stats.streak, stats.longest_streak
+            ));
+        }
+        text
+    }
+
+    /// Reads statistics back from `to_text`, or `None` if the text isn't statistics. Figures
+    /// missing from a line are taken as nothing yet.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let mut games = BTreeMap::new();
+
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let mut words = line.split_whitespace();
+            let name = words.next()?;
+            let mut stats = GameStats::default();
+            while let Some(field) = words.next() {
+                let value = words.next()?;
+                match field {
+                    "played" => stats.played = value.parse().ok()?,
+                    "won" => stats.won = value.parse().ok()?,
+                    "best" => {
+                        stats.best = Some(Duration::try_from_secs_f64(value.parse().ok()?).ok()?)
+                    }
+                    "streak" => stats.streak = value.parse().ok()?,
+                    "longest" => stats.longest_streak = value.parse().ok()?,
+                    _ => return None,
+                }
+            }
+            games.insert(name.to_owned(), stats);
+        }
+
+        Some(Self { games })
+    }
+}
@@ -0,0 +1,43 @@
+//! A summary of a game in progress, for showing to the players.
+
+/// What a game reports about itself for the HUD: the players' scores, how many tricks have been
+/// played, and whose turn it is.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameStatus {
+    pub players: Vec<PlayerStatus>,
+    /// Tricks played so far, or `None` in games without tricks.
+    pub tricks: Option<u32>,
+    /// The index into `players` of whoever is to play, if anyone.
+    pub turn: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerStatus {
+    pub name: String,
+    pub score: i32,
+}
+
+/// How a finished game went for a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    Won,
+    Lost,
+    /// Tied for the win with someone else, or neither won nor lost.
+    Drawn,
+}
+
+impl Outcome {
+    /// How a player with `score` did against everyone's `scores`, their own included, where
+    /// `best` picks the better of two scores.
+    pub fn by_score(score: i32, scores: &[i32], best: fn(i32, i32) -> i32) -> Self {
+        let top = scores.iter().copied().reduce(best).unwrap_or(score);
+        match scores.iter().filter(|&&other| other == top).count() {
+            _ if score != top => Self::Lost,
+            1 => Self::Won,
+            _ => Self::Drawn,
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Where the players sit around the table in games for 2 to 6 players: the player's own seat along
+//! the bottom edge, and the others clockwise from it up the left side, along the top and down the
+//! right side, each with their hand, their name and the cards they've taken.
+
+use crate::game::Fan;
+
+/// The fewest and most seats there's room for around the table.
+pub const MIN_SEATS: usize = 2;
+pub const MAX_SEATS: usize = 6;
+
+/// How far apart the cards in the player's own hand are at most, as a fraction of a card, across
+/// the table and down its sides. Hands too long for their seat's share of the edge are squeezed
+/// up.
+const HAND_SPREAD: f32 = 0.4;
+const SIDE_HAND_SPREAD: f32 = 0.2;
+
+/// How far apart the cards in the other players' hands are, which are face down, so they're only
+/// fanned enough to show there are several, with a badge giving how many.
+const COMPACT_SPREAD: f32 = 0.1;
+
+/// The distance from the centre of the table to the middle of the seats along its sides, and
+/// along its top and bottom, in card widths and heights.
+const SEAT_DISTANCE: [f32; 2] = [4.0, 2.2];
+
+/// How far along the edge the seats along the top and bottom reach either way, and those down
+/// the sides, clear of the corners.
+const ROW_REACH: f32 = 4.5;
+const SIDE_REACH: f32 = 1.6;
+
+/// The distance from the centre of the table to each seat's card in the trick.
+const TRICK_DISTANCE: [f32; 2] = [0.7, 0.5];
+
+/// How far a seat's name is from its hand, towards the middle of the table.
+const NAME_DISTANCE: [f32; 2] = [1.2, 0.85];
+
+/// How far the cards a seat down the side has taken are from its hand, towards the middle of the
+/// table. Seats along the top and bottom stack them at the end of their share of the edge
+/// instead.
+const TAKEN_DISTANCE: f32 = 2.2;
+
+/// The space left between a stack of taken cards and the hand beside it.
+const TAKEN_GAP: f32 = 0.2;
+
+/// A seat's share of the edge of the table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Place {
+    /// The middle of the share.
+    centre: [f32; 2],
+    /// How far it reaches either way along the edge.
+    reach: f32,
+}
+
+impl Place {
+    const fn new(x: f32, y: f32, reach: f32) -> Self {
+        Self {
+            centre: [x * SEAT_DISTANCE[0], y * SEAT_DISTANCE[1]],
+            reach,
+        }
+    }
+
+    /// Whether the place is down a side of the table, rather than along the top or bottom.
+    fn at_side(&self) -> bool {
+        self.centre[0].abs() >= SEAT_DISTANCE[0]
+    }
+
+    /// Which axis the edge runs along, 0 across the table or 1 up it.
+    fn along(&self) -> usize {
+        usize::from(self.at_side())
+    }
+
+    /// Which way along the edge is clockwise around the table: left along the bottom, up the
+    /// left side, right along the top and down the right side.
+    fn clockwise(&self) -> f32 {
+        match self.at_side() {
+            true => -self.centre[0].signum(),
+            false => self.centre[1].signum(),
+        }
+    }
+
+    /// Which way across the edge is towards the middle of the table.
+    fn inwards(&self) -> f32 {
+        -self.centre[1 - self.along()].signum()
+    }
+}
+
+const BOTTOM: Place = Place::new(0.0, -1.0, ROW_REACH);
+const TOP: Place = Place::new(0.0, 1.0, ROW_REACH);
+const LEFT: Place = Place::new(-1.0, 0.0, SIDE_REACH);
+const RIGHT: Place = Place::new(1.0, 0.0, SIDE_REACH);
+
+/// The places for each number of seats, clockwise from the player's own.
+fn places(count: usize) -> Vec<Place> {
+    let half_row = ROW_REACH / 2.0 / SEAT_DISTANCE[0];
+    let half_side = SIDE_REACH / 2.0 / SEAT_DISTANCE[1];
+    match count {
+        2 => vec![BOTTOM, TOP],
+        3 => vec![BOTTOM, LEFT, RIGHT],
+        4 => vec![BOTTOM, LEFT, TOP, RIGHT],
+        5 => vec![
+            BOTTOM,
+            LEFT,
+            Place::new(-half_row, 1.0, ROW_REACH / 2.0),
+            Place::new(half_row, 1.0, ROW_REACH / 2.0),
+            RIGHT,
+        ],
+        _ => vec![
+            BOTTOM,
+            Place::new(-1.0, -half_side, SIDE_REACH / 2.0),
+            Place::new(-1.0, half_side, SIDE_REACH / 2.0),
+            TOP,
+            Place::new(1.0, half_side, SIDE_REACH / 2.0),
+            Place::new(1.0, -half_side, SIDE_REACH / 2.0),
+        ],
+    }
+}
+
+/// The seats around a table, as seen from one of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Seats {
+    player: usize,
+    places: Vec<Place>,
+}
+
+impl Seats {
+    /// `count` seats, between `MIN_SEATS` and `MAX_SEATS`, as seen from `player`'s.
+    pub fn new(count: usize, player: usize) -> Self {
+        Self {
+            player,
+            places: places(count.clamp(MIN_SEATS, MAX_SEATS)),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.places.len()
+    }
+
+    /// How many places clockwise from the player `seat` sits, with the player's own at 0.
+    pub fn place(&self, seat: usize) -> usize {
+        let count = self.count();
+        (seat + count - self.player % count) % count
+    }
+
+    fn place_of(&self, seat: usize) -> Place {
+        self.places[self.place(seat)]
+    }
+
+    /// Where the first of `cards` cards in `seat`'s hand goes, and how the rest fan out from it:
+    /// across the table along the top and bottom, and down it at the sides.
+    pub fn hand(&self, seat: usize, cards: usize) -> ([f32; 2], Fan) {
+        let place = self.place_of(seat);
+        let along = place.along();
+
+        // Along the top and bottom, the far end of the edge is kept for the cards taken.
+        let mut ends = [
+            place.centre[along] - place.reach,
+            place.centre[along] + place.reach,
+        ];
+        if !place.at_side() {
+            let end = usize::from(place.clockwise() > 0.0);
+            ends[end] -= place.clockwise() * (1.0 + TAKEN_GAP);
+        }
+
+        let gaps = (cards.max(1) - 1) as f32;
+        let room = ends[1] - ends[0] - 1.0;
+        let spread = match place.at_side() {
+            _ if self.place(seat) != 0 => COMPACT_SPREAD,
+            true => SIDE_HAND_SPREAD,
+            false => HAND_SPREAD,
+        };
+        let spread = match gaps > 0.0 {
+            true => spread.min(room / gaps),
+            false => spread,
+        };
+        let half = spread * gaps / 2.0 + 0.5;
+        let middle =
+            place.centre[along].clamp(ends[0] + half, (ends[1] - half).max(ends[0] + half));
+
+        let mut start = place.centre;
+        let mut step = [0.0; 2];
+        match place.at_side() {
+            // Down the sides, the first card is at the top.
+            true => {
+                start[1] = middle + spread * gaps / 2.0;
+                step[1] = -spread;
+            }
+            false => {
+                start[0] = middle - spread * gaps / 2.0;
+                step[0] = spread;
+            }
+        }
+        (start, Fan::Spread(step))
+    }
+
+    /// Where the badge with how many cards are in `seat`'s hand goes, over the middle of the
+    /// hand.
+    pub fn badge(&self, seat: usize) -> [f32; 2] {
+        self.place_of(seat).centre
+    }
+
+    /// Where `seat`'s card goes in the trick in the middle of the table.
+    pub fn trick(&self, seat: usize) -> [f32; 2] {
+        let [x, y] = self.place_of(seat).centre;
+        let [x, y] = [x / SEAT_DISTANCE[0], y / SEAT_DISTANCE[1]];
+        let length = x.hypot(y).max(f32::EPSILON);
+        [
+            x / length * TRICK_DISTANCE[0],
+            y / length * TRICK_DISTANCE[1],
+        ]
+    }
+
+    /// Where `seat`'s name goes, between the middle of its share of the edge and the middle of
+    /// the table.
+    pub fn name(&self, seat: usize) -> [f32; 2] {
+        let place = self.place_of(seat);
+        let across = 1 - place.along();
+        let mut position = place.centre;
+        position[across] += place.inwards() * NAME_DISTANCE[across];
+        position
+    }
+
+    /// Where the cards `seat` has taken are stacked: at the clockwise end of its share of the
+    /// edge, beside its hand along the top and bottom, and nearer the middle down the sides.
+    pub fn taken(&self, seat: usize) -> [f32; 2] {
+        let place = self.place_of(seat);
+        let along = place.along();
+        let mut position = place.centre;
+        position[along] += place.clockwise() * (place.reach - 0.5);
+        if place.at_side() {
+            position[0] += place.inwards() * TAKEN_DISTANCE;
+        }
+        position
+    }
+}
@@ -0,0 +1,491 @@
+//! What trick-taking games have in common: following suit, trumps, deciding who wins a trick, and
+//! counting what each player has taken. Each game supplies its own `TrickRules`, and keeps its
+//! bidding, parties and scoring to itself.
+
+use crate::{
+    card::{Rank, Suit, ACE, JACK, KING, QUEEN, TEN},
+    game::{CardView, Fan, Framing, Hint, Label, Location, Pile, Sweep},
+    rng::Rng,
+    table::Seats,
+};
+
+pub type Card = (Suit, Rank);
+
+/// What to call the four seats around a table, going clockwise from the player's own.
+pub const SEAT_NAMES: [&str; 4] = ["You", "West", "North", "East"];
+
+/// The rules that differ from one trick-taking game to the next.
+pub trait TrickRules {
+    /// How strong a card is as a trump, with higher beating lower, or `None` if it isn't a trump.
+    fn trump_strength(&self, card: Card) -> Option<u8>;
+
+    /// How strong a card that isn't a trump is within its suit, with higher beating lower.
+    fn plain_strength(&self, rank: Rank) -> u8 {
+        ace_high(rank)
+    }
+
+    /// What a card is worth to whoever takes it in a trick.
+    fn card_points(&self, _card: Card) -> i32 {
+        0
+    }
+
+    /// Whether a trick worth `points` is a big one, to make a fuss of as it's taken.
+    fn is_big_trick(&self, _points: i32) -> bool {
+        false
+    }
+
+    /// The suit a card belongs to when following suit, or `None` for a trump, since trumps make
+    /// up a suit of their own.
+    fn follows_as(&self, card: Card) -> Option<Suit> {
+        self.trump_strength(card).is_none().then_some(card.0)
+    }
+
+    /// Which card in a trick wins it: the strongest trump or, without trumps, the strongest card
+    /// of the suit led. The first of two equal cards wins. Returns `None` for an empty trick.
+    fn trick_winner(&self, trick: &[Card]) -> Option<usize> {
+        let led = self.follows_as(*trick.first()?);
+
+        let strength = |&card: &Card| match self.trump_strength(card) {
+            Some(strength) => Some(100 + strength as u32),
+            None if Some(card.0) == led => Some(self.plain_strength(card.1) as u32),
+            None => None,
+        };
+
+        trick
+            .iter()
+            .enumerate()
+            .filter_map(|(index, card)| Some((index, strength(card)?)))
+            .rev()
+            .max_by_key(|&(_, strength)| strength)
+            .map(|(index, _)| index)
+    }
+}
+
+/// Aces high, then kings down to twos.
+pub fn ace_high(rank: Rank) -> u8 {
+    if rank == ACE {
+        13
+    } else {
+        rank
+    }
+}
+
+/// The order of the German games: ace and ten above the king, queen and jack, then the rest.
+pub fn ten_high(rank: Rank) -> u8 {
+    match rank {
+        ACE => 19,
+        TEN => 18,
+        KING => 17,
+        QUEEN => 16,
+        JACK => 15,
+        _ => rank,
+    }
+}
+
+/// Card points in the German games: 11 for an ace, 10 for a ten, 4 for a king, 3 for a queen and
+/// 2 for a jack, making 120 in a 32-card deck.
+pub fn german_card_points(rank: Rank) -> i32 {
+    match rank {
+        ACE => 11,
+        TEN => 10,
+        KING => 4,
+        QUEEN => 3,
+        JACK => 2,
+        _ => 0,
+    }
+}
+
+/// Greys out the cards in `hand` that aren't among `playable`.
+pub fn dim_unplayable(hand: &mut Pile, playable: &[Card]) {
+    for card in &mut hand.cards {
+        card.dimmed = !playable.contains(&(card.suit, card.rank));
+    }
+}
+
+/// No trumps at all, as in Hearts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoTrumps;
+
+impl TrickRules for NoTrumps {
+    fn trump_strength(&self, _card: Card) -> Option<u8> {
+        None
+    }
+}
+
+/// A whole suit as trumps, ranked like the others, as in Spades.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SuitTrumps(pub Suit);
+
+impl TrickRules for SuitTrumps {
+    fn trump_strength(&self, (suit, rank): Card) -> Option<u8> {
+        (suit == self.0).then(|| ace_high(rank))
+    }
+}
+
+/// A hand of tricks being played out: who holds what, the trick on the table and what each seat
+/// has taken so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tricks<R> {
+    pub rules: R,
+    hands: Vec<Vec<Card>>,
+    /// The cards played into the current trick, in order, starting with the leader's.
+    trick: Vec<Card>,
+    leader: usize,
+    /// The seat that took each trick collected so far, in order.
+    winners: Vec<usize>,
+    /// The cards each seat has taken in tricks.
+    taken: Vec<Vec<Card>>,
+    /// The cards each seat has played, in order.
+    history: Vec<Vec<Card>>,
+    /// The suits each seat has shown it has none of by not following them, as `follows_as` gives
+    /// them.
+    voids: Vec<Vec<Option<Suit>>>,
+}
+
+impl<R: TrickRules> Tricks<R> {
+    /// Starts playing out `hands`, one per seat, with `leader` leading the first trick.
+    pub fn new(rules: R, hands: Vec<Vec<Card>>, leader: usize) -> Self {
+        let seats = hands.len();
+
+        Self {
+            rules,
+            hands,
+            trick: Vec::new(),
+            leader,
+            winners: Vec::new(),
+            taken: vec![Vec::new(); seats],
+            history: vec![Vec::new(); seats],
+            voids: vec![Vec::new(); seats],
+        }
+    }
+
+    pub fn seats(&self) -> usize {
+        self.hands.len()
+    }
+
+    pub fn hand(&self, seat: usize) -> &[Card] {
+        &self.hands[seat]
+    }
+
+    /// Gives a seat a different hand, for games that pass or exchange cards before play.
+    pub fn set_hand(&mut self, seat: usize, hand: Vec<Card>) {
+        self.hands[seat] = hand;
+    }
+
+    pub fn trick(&self) -> &[Card] {
+        &self.trick
+    }
+
+    pub fn leader(&self) -> usize {
+        self.leader
+    }
+
+    pub fn tricks_played(&self) -> u32 {
+        self.winners.len() as u32
+    }
+
+    /// The seat that took each trick collected so far, in order.
+    pub fn winners(&self) -> &[usize] {
+        &self.winners
+    }
+
+    /// Whose turn it is to play, or `None` while a finished trick waits to be collected.
+    pub fn turn(&self) -> Option<usize> {
+        (self.trick.len() < self.seats()).then(|| (self.leader + self.trick.len()) % self.seats())
+    }
+
+    /// The card a seat played into the current trick, if it has yet.
+    pub fn trick_card(&self, seat: usize) -> Option<Card> {
+        let position = (seat + self.seats() - self.leader) % self.seats();
+        self.trick.get(position).copied()
+    }
+
+    /// Whether `seat` has to follow the suit led, having a card of it.
+    pub fn must_follow(&self, seat: usize) -> bool {
+        let Some(&first) = self.trick.first() else {
+            return false;
+        };
+        let led = self.rules.follows_as(first);
+        self.hands[seat]
+            .iter()
+            .any(|&card| self.rules.follows_as(card) == led)
+    }
+
+    /// The cards `seat` may play: one of the suit led if it has any, otherwise anything.
+    pub fn playable(&self, seat: usize) -> Vec<Card> {
+        let hand = &self.hands[seat];
+        let Some(&first) = self.trick.first() else {
+            return hand.clone();
+        };
+
+        let led = self.rules.follows_as(first);
+        let following = hand
+            .iter()
+            .copied()
+            .filter(|&card| self.rules.follows_as(card) == led)
+            .collect::<Vec<_>>();
+
+        if following.is_empty() {
+            hand.clone()
+        } else {
+            following
+        }
+    }
+
+    /// Plays a card from `seat`'s hand into the trick, returning whether it was there to play.
+    pub fn play(&mut self, seat: usize, card: Card) -> bool {
+        let hand = &mut self.hands[seat];
+        let Some(index) = hand.iter().position(|&held| held == card) else {
+            return false;
+        };
+
+        hand.remove(index);
+
+        if let Some(&first) = self.trick.first() {
+            let led = self.rules.follows_as(first);
+            if self.rules.follows_as(card) != led && !self.voids[seat].contains(&led) {
+                self.voids[seat].push(led);
+            }
+        }
+
+        self.trick.push(card);
+        self.history[seat].push(card);
+        true
+    }
+
+    /// The seat whose card is taking the trick so far, or `None` if nothing has been played.
+    pub fn trick_winner(&self) -> Option<usize> {
+        let position = self.rules.trick_winner(&self.trick)?;
+        Some((self.leader + position) % self.seats())
+    }
+
+    /// Gives a finished trick to whoever won it, who leads the next. Returns the winner, or
+    /// `None` if the trick isn't finished.
+    pub fn collect(&mut self) -> Option<usize> {
+        if self.turn().is_some() {
+            return None;
+        }
+
+        let winner = self.trick_winner()?;
+
+        self.taken[winner].append(&mut self.trick);
+        self.winners.push(winner);
+        self.leader = winner;
+
+        Some(winner)
+    }
+
+    /// Whether every card has been played and collected.
+    pub fn is_over(&self) -> bool {
+        self.trick.is_empty() && self.hands.iter().all(Vec::is_empty)
+    }
+
+    /// The cards a seat has taken in tricks.
+    pub fn taken(&self, seat: usize) -> &[Card] {
+        &self.taken[seat]
+    }
+
+    pub fn tricks_won(&self, seat: usize) -> u32 {
+        self.winners
+            .iter()
+            .filter(|&&winner| winner == seat)
+            .count() as u32
+    }
+
+    /// The card points a seat has taken in tricks.
+    pub fn points(&self, seat: usize) -> i32 {
+        self.taken[seat]
+            .iter()
+            .map(|&card| self.rules.card_points(card))
+            .sum()
+    }
+
+    /// The cards a seat has played so far, in order.
+    pub fn history(&self, seat: usize) -> &[Card] {
+        &self.history[seat]
+    }
+
+    /// Whether a seat has shown it can't follow the suit `card` would follow as.
+    pub fn is_void(&self, seat: usize, card: Card) -> bool {
+        self.voids[seat].contains(&self.rules.follows_as(card))
+    }
+
+    /// Deals the cards `seat` can't see out again at random, as a guess at what the others might
+    /// be holding. Each seat keeps as many cards as it had, any of `pinned` that are still held
+    /// stay where they are, and no seat is dealt a suit it's shown it has none of, if that can be
+    /// helped.
+    pub fn redeal_hidden(&self, seat: usize, pinned: &[(usize, Card)], rng: &mut Rng) -> Self
+    where
+        R: Clone,
+    {
+        // Tries dealing each card to a seat with room that could hold it, giving up on those
+        // rules if they can't all be kept.
+        const ATTEMPTS: usize = 20;
+
+        let others = (0..self.seats()).filter(|&other| other != seat);
+        let held =
+            |&(other, card): &(usize, Card)| other != seat && self.hands[other].contains(&card);
+        let pinned = pinned.iter().copied().filter(held).collect::<Vec<_>>();
+
+        let mut pool = Vec::new();
+        let mut kept = vec![Vec::new(); self.seats()];
+        for other in others.clone() {
+            for &card in &self.hands[other] {
+                if pinned.contains(&(other, card)) {
+                    kept[other].push(card);
+                } else {
+                    pool.push(card);
+                }
+            }
+        }
+        let room = |hands: &[Vec<Card>], other: usize| self.hands[other].len() - hands[other].len();
+
+        let mut dealt = None;
+        for attempt in 0..=ATTEMPTS {
+            let strict = attempt < ATTEMPTS;
+            rng.shuffle(&mut pool);
+
+            let mut hands = kept.clone();
+            let complete = pool.iter().all(|&card| {
+                let eligible = others
+                    .clone()
+                    .filter(|&other| room(&hands, other) > 0)
+                    .filter(|&other| !strict || !self.is_void(other, card))
+                    .collect::<Vec<_>>();
+                if eligible.is_empty() {
+                    return false;
+                }
+                hands[eligible[rng.below(eligible.len())]].push(card);
+                true
+            });
+
+            if complete {
+                dealt = Some(hands);
+                break;
+            }
+        }
+
+        let mut tricks = self.clone();
+        if let Some(hands) = dealt {
+            for other in (0..self.seats()).filter(|&other| other != seat) {
+                tricks.hands[other] = hands[other].clone();
+            }
+        }
+        tricks
+    }
+
+    /// Whether `card` would take the trick as it stands if played next.
+    pub fn would_win(&self, card: Card) -> bool {
+        let mut trick = self.trick.clone();
+        trick.push(card);
+        self.rules.trick_winner(&trick) == Some(trick.len() - 1)
+    }
+
+    /// The table as `player` sees it, with every seat laid out by `Seats`: each hand, with only
+    /// `player`'s face up and at the bottom, the trick in the middle, with the card taking it so
+    /// far tinted, and the cards each seat has taken stacked face down by its hand. Piles
+    /// `0..seats` are the hands, by seat, `seats..2 * seats` each seat's card in the trick, and
+    /// `2 * seats..3 * seats` the cards each seat has taken.
+    pub fn table(&self, player: usize) -> Vec<Pile> {
+        let seats = Seats::new(self.seats(), player);
+
+        let hands = (0..self.seats()).map(|seat| {
+            let cards = self.hand(seat);
+            let (start, fan) = seats.hand(seat, cards.len());
+            let views = cards
+                .iter()
+                .map(|&(suit, rank)| CardView::new(suit, rank, seat != player))
+                .collect();
+            Pile::new(start, fan, views)
+        });
+
+        let winning = (self.trick.len() > 1)
+            .then(|| self.trick_winner())
+            .flatten();
+        let trick = (0..self.seats()).map(|seat| {
+            let cards = self
+                .trick_card(seat)
+                .map(|(suit, rank)| CardView {
+                    tinted: winning == Some(seat),
+                    ..CardView::new(suit, rank, false)
+                })
+                .into_iter()
+                .collect();
+            Pile::new(seats.trick(seat), Fan::Stacked, cards)
+        });
+
+        let taken = (0..self.seats()).map(|seat| {
+            let cards = self
+                .taken(seat)
+                .iter()
+                .map(|&(suit, rank)| CardView::new(suit, rank, true))
+                .collect();
+            Pile::new(seats.taken(seat), Fan::Stacked, cards)
+        });
+
+        hands.chain(trick).chain(taken).collect()
+    }
+
+    /// The trick in the middle of the table, with `player`'s hand below it to play from, laid
+    /// out by `table`, or the whole table once every trick is played.
+    pub fn framing(&self, player: usize) -> Framing {
+        match self.is_over() {
+            true => Framing::Table,
+            false => Framing::Piles(
+                std::iter::once(player)
+                    .chain(self.seats()..2 * self.seats())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Playing `card` from `seat`'s hand into the trick, as a hint, laid out by `table`.
+    pub fn hint(&self, seat: usize, card: Card) -> Option<Hint> {
+        let index = self.hand(seat).iter().position(|&held| held == card)?;
+        Some(Hint {
+            from: Location::card(seat, index),
+            to: Some(Location::pile(self.seats() + seat)),
+        })
+    }
+
+    /// A trick just collected, swept together onto the winner's card in the middle of the table
+    /// on its way to them, as `player` sees the table laid out by `table`.
+    pub fn sweep(&self, player: usize) -> Option<Sweep> {
+        if !self.trick.is_empty() || self.winners.is_empty() {
+            return None;
+        }
+
+        // The trick is the last cards the winner took, one from each seat.
+        let points = self.taken[self.leader]
+            .iter()
+            .rev()
+            .take(self.seats())
+            .map(|&card| self.rules.card_points(card))
+            .sum();
+
+        let seats = Seats::new(self.seats(), player);
+        Some(Sweep {
+            from: self.seats()..2 * self.seats(),
+            via: seats.trick(self.leader),
+            big: self.rules.is_big_trick(points),
+        })
+    }
+
+    /// Each seat's name, from `names` by seat, by its hand as `player` sees the table, and a
+    /// badge over each of the others' hands with how many cards are left in it.
+    pub fn labels(&self, player: usize, names: &[&str]) -> Vec<Label> {
+        let seats = Seats::new(self.seats(), player);
+        let names = names
+            .iter()
+            .take(self.seats())
+            .enumerate()
+            .map(|(seat, name)| Label::new(seats.name(seat), name.to_string()));
+        let badges = (0..self.seats())
+            .filter(|&seat| seat != player && !self.hand(seat).is_empty())
+            .map(|seat| Label::badge(seats.badge(seat), self.hand(seat).len().to_string()));
+        names.chain(badges).collect()
+    }
+}
@@ -0,0 +1,77 @@
+//! Trumps in the German trick-taking games, where the game mode alone decides which cards are
+//! trumps and how they rank.
+//!
+//! These games are played with French-suited cards here, so an Ober is a queen, an Unter is a jack
+//! and the Eichel, Gras, Herz and Schellen suits are clubs, spades, hearts and diamonds.
+
+use std::cmp::Reverse;
+
+use crate::{
+    card::{Rank, Suit, JACK, QUEEN, TEN},
+    trick::{german_card_points, ten_high, Card, TrickRules},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrumpMode {
+    /// A normal Doppelkopf game: the ten of hearts, then queens, jacks and diamonds.
+    Doppelkopf,
+    /// A Skat suit game: jacks, then the declared suit.
+    Skat(Suit),
+    /// A Schafkopf partner game: queens, jacks, then hearts.
+    Schafkopf,
+}
+
+impl TrumpMode {
+    /// How strong a card is as a trump, with higher beating lower, or `None` if it isn't a trump.
+    pub fn trump_strength(&self, suit: Suit, rank: Rank) -> Option<u8> {
+        // All three games rank the suits of their queens and jacks the same way.
+        let suit_strength = suit.doppelkopf_suit_strength();
+
+        match (self, rank) {
+            (TrumpMode::Doppelkopf, TEN) if suit == Suit::Hearts => Some(40),
+            (TrumpMode::Doppelkopf | TrumpMode::Schafkopf, QUEEN) => Some(30 + suit_strength),
+            (_, JACK) => Some(20 + suit_strength),
+            (TrumpMode::Doppelkopf, _) if suit == Suit::Diamonds => Some(ten_high(rank)),
+            (TrumpMode::Skat(trumps), _) if suit == *trumps => Some(ten_high(rank)),
+            (TrumpMode::Schafkopf, _) if suit == Suit::Hearts => Some(ten_high(rank)),
+            _ => None,
+        }
+    }
+
+    pub fn is_trump(&self, suit: Suit, rank: Rank) -> bool {
+        self.trump_strength(suit, rank).is_some()
+    }
+
+    /// Sorts a hand with its trumps first, strongest to weakest, followed by the rest of the cards
+    /// grouped by suit.
+    pub fn sort_hand(&self, hand: &mut [(Suit, Rank)]) {
+        hand.sort_by_key(|&(suit, rank)| match self.trump_strength(suit, rank) {
+            Some(strength) => (false, Reverse(strength), Reverse(0)),
+            None => (
+                true,
+                Reverse(suit.doppelkopf_suit_strength()),
+                Reverse(ten_high(rank)),
+            ),
+        });
+    }
+}
+
+impl TrickRules for TrumpMode {
+    fn trump_strength(&self, (suit, rank): Card) -> Option<u8> {
+        TrumpMode::trump_strength(self, suit, rank)
+    }
+
+    fn plain_strength(&self, rank: Rank) -> u8 {
+        ten_high(rank)
+    }
+
+    fn card_points(&self, (_, rank): Card) -> i32 {
+        german_card_points(rank)
+    }
+
+    /// A Doppelkopf: a trick worth 40 points or more.
+    fn is_big_trick(&self, points: i32) -> bool {
+        points >= 40
+    }
+}
@@ -0,0 +1,38 @@
+#!/bin/sh
+# Checks that every feature set the app and cards-core are meant to be built with still builds,
+# without warnings, so a slim build (such as a web build with nothing but Klondike) isn't broken
+# by a change only the default build was tried with. Runs anywhere cargo does:
+#
+#     ./check-features.sh
+#
+# The audio and gamepad features need ALSA's and libudev's development files on Linux.
+
+set -e
+
+check() {
+    echo "checking ${*:-the default features}"
+    cargo clippy --quiet --all-targets "$@" -- -D warnings
+}
+
+# The app.
+check
+for game in klondike hearts doppelkopf blackjack memory gallery; do
+    check --no-default-features --features "render,$game"
+done
+check --no-default-features --features "render,all-games"
+check --no-default-features --features "render,hearts,multiplayer"
+check --features serde
+check --features audio
+check --features gamepad
+
+# The game logic on its own, and its binaries.
+check -p cards-core --no-default-features
+for game in klondike hearts doppelkopf blackjack memory gallery; do
+    check -p cards-core --no-default-features --features "$game"
+done
+check -p cards-core --features serde
+check -p cards-core --features net
+check -p cards-core --features sim
+check -p cards-core --features server
+
+echo "every feature set builds"
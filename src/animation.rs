@@ -0,0 +1,763 @@
+//! Cards gliding across the table when a game moves them, and turning over when it flips them,
+//! rather than jumping into place. Cards the game sweeps up together, such as a finished trick,
+//! gather in one place first and go on from there as one. A new deal is riffle shuffled and then
+//! goes out from the deck card by card. Cards let go of mid-drag slide on and spin until they
+//! settle. Winning a game sets the cards cascading off the table.
+//!
+//! A `Timeline` lays these out one step after another, handing cues back as it gets through
+//! them.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Range,
+    time::Duration,
+};
+
+use cards_core::game::Location;
+use cgmath::{Point2, Rad, Vector2, Vector3, VectorSpace};
+use instant::Instant;
+
+use crate::{
+    bounds::Bounds,
+    camera::Camera,
+    card::{self, Card, Rank, Suit},
+};
+
+/// How long a card takes to get where it's going, or to turn over.
+const MOTION_DURATION: Duration = Duration::from_millis(250);
+
+/// How long a swept card takes to gather with the others and get where it's going from there.
+const SWEEP_DURATION: Duration = Duration::from_millis(600);
+
+/// The delay between cards setting off when several move at once, so that a deal goes out card
+/// by card.
+const SLIDE_STAGGER: Duration = Duration::from_millis(80);
+
+/// The longest the last of several cards waits to set off, however many there are.
+const MAX_STAGGER: Duration = Duration::from_millis(400);
+
+/// The delay between cards going out in a deal, and the longest a whole deal takes to go out
+/// however many cards there are.
+const DEAL_STAGGER: Duration = Duration::from_millis(60);
+const MAX_DEAL_TIME: Duration = Duration::from_secs(2);
+
+/// How long a riffle shuffle takes to split the deck, to let the halves fall back together, and
+/// to square the deck up afterwards. The falling matches the clicks of the shuffle's sound, so a
+/// riffle doesn't speed up with the cards.
+const RIFFLE_SPLIT: Duration = Duration::from_millis(300);
+const RIFFLE_FALL: Duration = Duration::from_millis(800);
+const RIFFLE_SETTLE: Duration = Duration::from_millis(200);
+
+/// How long one card takes to fall back in, how far the halves spread apart, in card widths, and
+/// how far they tip, in radians.
+const RIFFLE_DROP: f32 = 0.08;
+const RIFFLE_SPREAD: f32 = 0.6;
+const RIFFLE_TILT: f32 = 0.2;
+
+/// The slowest cards are let move, as a multiple of their usual speed, so that they always get
+/// there.
+const MIN_SPEED: f32 = 0.1;
+
+/// How long after one card the next sets off in a cascade.
+const CASCADE_INTERVAL: Duration = Duration::from_millis(120);
+
+/// How fast cascading cards fall, in card heights per second per second, and how much of their
+/// speed they keep each time they bounce.
+const CASCADE_GRAVITY: f32 = 12.0;
+const CASCADE_BOUNCE: f32 = 0.75;
+
+/// The longest step a cascade takes at once, so that a slow frame doesn't send cards through
+/// the floor.
+const CASCADE_STEP: Duration = Duration::from_millis(20);
+
+/// How fast cards have to be going when they're let go of to be tossed, in card heights a second.
+const TOSS_SPEED: f32 = 8.0;
+
+/// How quickly friction slows tossed cards, as the fraction of their speed lost each second, and
+/// how slow they have to get, in card heights a second, to settle.
+const TOSS_FRICTION: f32 = 4.0;
+const TOSS_SETTLE_SPEED: f32 = 0.5;
+
+/// How fast tossed cards spin, in radians a second for each card width a second they're going
+/// across, and how much of their speed they keep when they hit the edge of the view.
+const TOSS_SPIN: f32 = 0.15;
+const TOSS_BOUNCE: f32 = 0.5;
+
+/// A card on its way from one place to another, turning over on the way if it's been flipped.
+pub struct Motion {
+    /// The index of the card on the table.
+    pub card: usize,
+    from: Vector3<f32>,
+    /// Where the card gathers with the others on the way, if it's swept.
+    via: Option<Vector3<f32>>,
+    to: Vector3<f32>,
+    start: Instant,
+    duration: Duration,
+    /// How far the card is lifted once it lands.
+    elevation: f32,
+    /// Whether the card was face down before turning over, if it's turning over.
+    flipped_from: Option<bool>,
+    /// How far through its time the card lands. It turns over on the way if that's at the very
+    /// end, or once it's landed otherwise.
+    landing: f32,
+}
+
+/// How a moving card looks at some moment.
+pub struct Frame {
+    pub position: Vector3<i32>,
+    pub elevation: f32,
+    /// The card's width as a fraction of its full width, narrowing to nothing halfway through
+    /// turning over.
+    pub width: f32,
+    /// Which side is showing, if the card is turning over.
+    pub facedown: Option<bool>,
+    pub arrived: bool,
+}
+
+impl Motion {
+    /// When the card sets off.
+    pub fn start(&self) -> Instant {
+        self.start
+    }
+
+    /// When the card gets where it's going.
+    pub fn end(&self) -> Instant {
+        self.start + self.duration
+    }
+
+    /// Whether the card goes anywhere, rather than only turning over where it is.
+    pub fn moves(&self) -> bool {
+        self.from != self.to || self.via.is_some()
+    }
+
+    /// How the card looks at `now`.
+    pub fn sample(&self, now: Instant) -> Frame {
+        let elapsed = now.saturating_duration_since(self.start);
+        let t = (elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0);
+        let travel = (t / self.landing).min(1.0);
+        let turn = match self.landing < 1.0 {
+            true => ((t - self.landing) / (1.0 - self.landing)).max(0.0),
+            false => t,
+        };
+
+        // A swept card spends the first half of its time gathering and the second going on.
+        let position = match self.via {
+            Some(via) if travel < 0.5 => self.from.lerp(via, ease(travel * 2.0)),
+            Some(via) => via.lerp(self.to, ease(travel * 2.0 - 1.0)),
+            None => self.from.lerp(self.to, ease(travel)),
+        };
+        // Cards rise off the table on their way and settle back down as they land.
+        let lift = 4.0 * travel * (1.0 - travel);
+
+        // A card turning over shows its old side until it's edge-on, then its new one.
+        let eased = ease(turn);
+        let (width, facedown) = match self.flipped_from {
+            Some(was_facedown) => (
+                (std::f32::consts::PI * eased).cos().abs(),
+                Some(if eased < 0.5 {
+                    was_facedown
+                } else {
+                    !was_facedown
+                }),
+            ),
+            None => (1.0, None),
+        };
+
+        Frame {
+            position: position.cast().unwrap_or(Vector3::new(0, 0, 0)),
+            elevation: self.elevation.max(lift),
+            width,
+            facedown,
+            arrived: t >= 1.0,
+        }
+    }
+}
+
+/// Smooths out a motion's progress, starting and finishing gently.
+fn ease(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Motions for the cards on a new table that were somewhere else on the old one, or the other way
+/// up.
+///
+/// Cards are matched up by suit and rank. A card still in the same place keeps it, so that only
+/// cards that actually moved are slid, and where a deck holds several of a card the others are
+/// taken in table order. Cards leaving the piles in `sweep` gather at its position on the way,
+/// all setting off at once. Old cards in `in_flight`, already on their way somewhere, head
+/// straight for their new places rather than waiting their turn. Everything happens `speed` times
+/// as fast as usual.
+#[allow(clippy::too_many_arguments)]
+pub fn motions(
+    old_cards: &[Card],
+    old_locations: &[Location],
+    in_flight: &[usize],
+    new_cards: &[Card],
+    new_locations: &[Location],
+    sweep: Option<(Range<usize>, Vector3<i32>)>,
+    speed: f32,
+    now: Instant,
+) -> Vec<Motion> {
+    let speed = speed.max(MIN_SPEED);
+    let mut used = vec![false; old_cards.len()];
+    let mut origins = vec![None; new_cards.len()];
+    let same = |a: &Card, b: &Card| a.suit == b.suit && a.rank == b.rank;
+
+    let old_at = old_locations
+        .iter()
+        .enumerate()
+        .map(|(index, &location)| (location, index))
+        .collect::<HashMap<_, _>>();
+    for (index, card) in new_cards.iter().enumerate() {
+        if let Some(&old) = old_at.get(&new_locations[index]) {
+            if same(&old_cards[old], card) {
+                used[old] = true;
+                origins[index] = Some(old);
+            }
+        }
+    }
+
+    let mut unused = HashMap::<(Suit, Rank), Vec<usize>>::new();
+    for (index, card) in old_cards.iter().enumerate().rev() {
+        if !used[index] {
+            unused
+                .entry((card.suit, card.rank))
+                .or_default()
+                .push(index);
+        }
+    }
+    for (index, card) in new_cards.iter().enumerate() {
+        if origins[index].is_none() {
+            origins[index] = unused.get_mut(&(card.suit, card.rank)).and_then(Vec::pop);
+        }
+    }
+
+    let moved = origins
+        .iter()
+        .enumerate()
+        .filter_map(|(index, origin)| {
+            let origin = (*origin)?;
+            let old = &old_cards[origin];
+            let new = &new_cards[index];
+            let via = sweep
+                .as_ref()
+                .filter(|(piles, _)| piles.contains(&old_locations[origin].pile))
+                .and_then(|(_, via)| via.cast());
+            let hurried = in_flight.contains(&origin);
+            (old.position != new.position || old.facedown != new.facedown)
+                .then_some((index, old, via, hurried))
+        })
+        .collect::<Vec<_>>();
+    let slid = moved
+        .iter()
+        .filter(|(_, _, via, hurried)| via.is_none() && !hurried)
+        .count();
+    let stagger = SLIDE_STAGGER
+        .min(MAX_STAGGER / slid.max(1) as u32)
+        .div_f32(speed);
+
+    let mut order = 0;
+    moved
+        .into_iter()
+        .map(|(index, old, via, hurried)| {
+            let (start, duration) = match via {
+                Some(_) => (now, SWEEP_DURATION),
+                None if hurried => (now, MOTION_DURATION),
+                None => {
+                    order += 1;
+                    (now + stagger * (order - 1), MOTION_DURATION)
+                }
+            };
+            Motion {
+                card: index,
+                from: old.position.cast().unwrap_or(Vector3::new(0.0, 0.0, 0.0)),
+                via,
+                to: new_cards[index]
+                    .position
+                    .cast()
+                    .unwrap_or(Vector3::new(0.0, 0.0, 0.0)),
+                start,
+                duration: duration.div_f32(speed),
+                elevation: new_cards[index].elevation,
+                flipped_from: (old.facedown != new_cards[index].facedown).then_some(old.facedown),
+                landing: 1.0,
+            }
+        })
+        .collect()
+}
+
+/// The cards in `targets`, on a new table, that a deal from a deck at `deck` sends out: all but
+/// those left face down in the deck's own place.
+pub fn dealt(deck: Vector3<i32>, targets: &[Card]) -> Vec<usize> {
+    (0..targets.len())
+        .filter(|&index| targets[index].position != deck || !targets[index].facedown)
+        .collect()
+}
+
+/// How a deal goes out from the deck.
+#[derive(Clone, Copy, Debug)]
+pub struct DealTiming {
+    /// The delay between one card setting off and the next.
+    pub stagger: Duration,
+    /// How long a card takes to fly from the deck to its place.
+    pub flight: Duration,
+    /// How long a card dealt face up takes to turn over once it's landed.
+    pub flip: Duration,
+}
+
+impl DealTiming {
+    /// The usual timing for dealing `count` cards, `speed` times as fast. The more cards there
+    /// are, the closer together they go out, so that a big deal doesn't drag on.
+    pub fn new(count: usize, speed: f32) -> Self {
+        let speed = speed.max(MIN_SPEED);
+        Self {
+            stagger: DEAL_STAGGER
+                .min(MAX_DEAL_TIME / count.max(1) as u32)
+                .div_f32(speed),
+            flight: MOTION_DURATION.div_f32(speed),
+            flip: MOTION_DURATION.div_f32(speed),
+        }
+    }
+}
+
+/// Motions dealing `targets`, the cards on a new table at `locations`, out one after another from
+/// a deck at `deck`.
+///
+/// Cards go round the table as a dealer would deal them: the first card of every pile, then the
+/// second, and so on. Each flies face down to its place and turns over once it's there if it's
+/// dealt face up. Cards left face down in the deck's own place stay put.
+pub fn deal(
+    deck: Vector3<i32>,
+    targets: &[Card],
+    locations: &[Location],
+    timing: DealTiming,
+    now: Instant,
+) -> Vec<Motion> {
+    let mut order = dealt(deck, targets);
+    order.sort_by_key(|&index| (locations[index].index, locations[index].pile));
+
+    let from = deck.cast().unwrap_or(Vector3::new(0.0, 0.0, 0.0));
+    order
+        .into_iter()
+        .enumerate()
+        .map(|(order, index)| {
+            let card = &targets[index];
+            let flip = match card.facedown {
+                true => Duration::ZERO,
+                false => timing.flip,
+            };
+            let duration = timing.flight + flip;
+            Motion {
+                card: index,
+                from,
+                via: None,
+                to: card.position.cast().unwrap_or(Vector3::new(0.0, 0.0, 0.0)),
+                start: now + timing.stagger * order as u32,
+                duration,
+                elevation: card.elevation,
+                flipped_from: (!card.facedown).then_some(true),
+                landing: match duration.is_zero() {
+                    true => 1.0,
+                    false => timing.flight.as_secs_f32() / duration.as_secs_f32(),
+                },
+            }
+        })
+        .collect()
+}
+
+/// A deck being riffle shuffled where it sits: split into two halves that spread apart, then let
+/// fall back together a card at a time from either side, quicker and quicker, and squared up.
+pub struct Riffle {
+    /// The cards in the deck, from the bottom.
+    cards: Vec<usize>,
+    deck: Vector3<f32>,
+    start: Instant,
+}
+
+impl Riffle {
+    /// How long a riffle takes, from splitting the deck to squaring it up.
+    pub const DURATION: Duration = RIFFLE_SPLIT
+        .saturating_add(RIFFLE_FALL)
+        .saturating_add(RIFFLE_SETTLE);
+
+    /// Shuffles `cards`, sitting in a deck at `deck`, starting at `now`.
+    pub fn new(cards: Vec<usize>, deck: Vector3<i32>, now: Instant) -> Self {
+        Self {
+            cards,
+            deck: deck.cast().unwrap_or(Vector3::new(0.0, 0.0, 0.0)),
+            start: now,
+        }
+    }
+
+    /// When the halves start falling back together, which is when the shuffle is heard.
+    pub fn falling(&self) -> Instant {
+        self.start + RIFFLE_SPLIT
+    }
+
+    /// Moves the cards in the deck to where the shuffle has them at `now`, returning whether it's
+    /// still going.
+    fn advance(&self, now: Instant, cards: &mut [Card]) -> bool {
+        let elapsed = now.saturating_duration_since(self.start);
+        let split = ease((elapsed.as_secs_f32() / RIFFLE_SPLIT.as_secs_f32()).min(1.0));
+
+        for (order, &index) in self.cards.iter().enumerate() {
+            // Cards fall back in from the bottom up, in time with the clicks of the sound.
+            let falls = RIFFLE_SPLIT.as_secs_f32()
+                + RIFFLE_FALL.as_secs_f32() * (order as f32 / self.cards.len() as f32).powf(0.8);
+            let fallen = ease(((elapsed.as_secs_f32() - falls) / RIFFLE_DROP).clamp(0.0, 1.0));
+            let spread = split * (1.0 - fallen);
+
+            let card = &mut cards[index];
+            let side = if order % 2 == 0 { -1.0 } else { 1.0 };
+            let across = side * spread * RIFFLE_SPREAD * card::WIDTH as f32 * card.scale.y;
+            let position = self.deck + Vector3::new(across, 0.0, 0.0);
+            card.position = position.cast().unwrap_or(Vector3::new(0, 0, 0));
+            card.rotation = Rad(side * spread * RIFFLE_TILT);
+            card.elevation = spread;
+        }
+
+        elapsed < Self::DURATION
+    }
+}
+
+/// Something a timeline plays, made to start when the timeline's step does.
+pub enum Tween {
+    /// Cards moving or turning over, each in its own time.
+    Motions(Vec<Motion>),
+    /// A deck being shuffled.
+    Riffle(Riffle),
+    /// The camera easing over to look at `target`.
+    Camera {
+        start: Instant,
+        target: Point2<f32>,
+        duration: Duration,
+    },
+}
+
+impl Tween {
+    /// When the tween finishes, if it does anything at all.
+    fn end(&self) -> Option<Instant> {
+        match self {
+            Tween::Motions(motions) => motions.iter().map(Motion::end).max(),
+            Tween::Riffle(riffle) => Some(riffle.start + Riffle::DURATION),
+            Tween::Camera {
+                start, duration, ..
+            } => Some(*start + *duration),
+        }
+    }
+
+    /// Plays the tween at `now`, returning whether there's more of it to play.
+    fn advance(&mut self, now: Instant, cards: &mut [Card], camera: &mut Camera) -> bool {
+        match self {
+            Tween::Motions(motions) => {
+                motions.retain(|motion| {
+                    let frame = motion.sample(now);
+                    let card = &mut cards[motion.card];
+                    card.position = frame.position;
+                    card.elevation = frame.elevation;
+                    card.scale.x = frame.width * card.scale.y;
+                    if let Some(facedown) = frame.facedown {
+                        card.facedown = facedown;
+                    }
+                    !frame.arrived
+                });
+                !motions.is_empty()
+            }
+            Tween::Riffle(riffle) => riffle.advance(now, cards),
+            // The camera eases itself once it's been set off.
+            Tween::Camera {
+                start,
+                target,
+                duration,
+            } => {
+                if now < *start {
+                    return true;
+                }
+                camera.ease_to(*target, *duration);
+                false
+            }
+        }
+    }
+}
+
+/// Tweens laid out one step after another, the tweens of each step playing together, with cues
+/// handed back once everything laid out before them has finished.
+///
+/// Steps are laid out ahead of time: each step's tweens are made to start at `cursor`, which
+/// `then` moves on to when they've all finished.
+pub struct Timeline<C> {
+    tweens: Vec<Tween>,
+    cues: VecDeque<(Instant, C)>,
+    /// When everything laid out so far has finished, and so when the next step starts.
+    cursor: Instant,
+}
+
+impl<C> Timeline<C> {
+    /// An empty timeline, whose first step starts at `now`.
+    pub fn new(now: Instant) -> Self {
+        Self {
+            tweens: Vec::new(),
+            cues: VecDeque::new(),
+            cursor: now,
+        }
+    }
+
+    /// When the next step starts.
+    pub fn cursor(&self) -> Instant {
+        self.cursor
+    }
+
+    /// Lays out a step of `tweens`, made to start at `cursor`, to play together. The next step
+    /// starts once they've all finished.
+    pub fn then(&mut self, tweens: impl IntoIterator<Item = Tween>) -> &mut Self {
+        for tween in tweens {
+            if let Some(end) = tween.end() {
+                self.cursor = self.cursor.max(end);
+            }
+            self.tweens.push(tween);
+        }
+        self
+    }
+
+    /// Hands `cue` back once everything laid out so far has finished.
+    pub fn cue(&mut self, cue: C) -> &mut Self {
+        self.cues.push_back((self.cursor, cue));
+        self
+    }
+
+    /// Whether there's nothing left to play or hand back.
+    pub fn is_empty(&self) -> bool {
+        self.tweens.is_empty() && self.cues.is_empty()
+    }
+
+    /// Plays the tweens at `now`, moving `cards` and the `camera`, and returns the cues that have
+    /// come due, in order.
+    pub fn advance(&mut self, now: Instant, cards: &mut [Card], camera: &mut Camera) -> Vec<C> {
+        self.tweens
+            .retain_mut(|tween| tween.advance(now, cards, camera));
+
+        let mut due = Vec::new();
+        while self.cues.front().is_some_and(|&(at, _)| at <= now) {
+            due.extend(self.cues.pop_front().map(|(_, cue)| cue));
+        }
+        due
+    }
+
+    /// The cards that have set off for somewhere by `now` and haven't got there yet.
+    pub fn in_flight(&self, now: Instant) -> Vec<usize> {
+        self.tweens
+            .iter()
+            .filter_map(|tween| match tween {
+                Tween::Motions(motions) => Some(motions),
+                _ => None,
+            })
+            .flatten()
+            .filter(|motion| motion.start <= now)
+            .map(|motion| motion.card)
+            .collect()
+    }
+
+    /// Plays everything to its end at once, leaving `cards` and the `camera` where they'd end up,
+    /// and returns every cue still to come.
+    pub fn finish(&mut self, cards: &mut [Card], camera: &mut Camera) -> Vec<C> {
+        let cues = self.advance(self.cursor, cards, camera);
+        camera.finish_ease();
+        cues
+    }
+
+    /// Stops moving the cards that `released` picks, such as ones the player has picked up.
+    pub fn release(&mut self, released: impl Fn(usize) -> bool) {
+        for tween in &mut self.tweens {
+            match tween {
+                Tween::Motions(motions) => motions.retain(|motion| !released(motion.card)),
+                Tween::Riffle(riffle) => riffle.cards.retain(|&card| !released(card)),
+                Tween::Camera { .. } => {}
+            }
+        }
+    }
+
+    /// The cues still to come, for a timeline that's being cut short.
+    pub fn into_cues(self) -> Vec<C> {
+        self.cues.into_iter().map(|(_, cue)| cue).collect()
+    }
+}
+
+/// Cards let go of mid-drag with some speed, sliding on across the table and spinning, slowed by
+/// friction until they settle, and kept within the view.
+pub struct Toss {
+    /// The cards, each with where it is from the middle of the toss.
+    cards: Vec<(usize, Vector2<f32>)>,
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    angle: f32,
+    /// How fast the cards spin, in radians a second, anticlockwise.
+    spin: f32,
+    /// The part of the table in view, which the cards bounce off the edges of.
+    view: Bounds,
+}
+
+impl Toss {
+    /// Whether cards let go of at `velocity`, in world units a second, are tossed.
+    pub fn is_toss(velocity: Vector2<f32>) -> bool {
+        velocity.x.hypot(velocity.y) >= TOSS_SPEED * card::HEIGHT as f32
+    }
+
+    /// Tosses `cards`, each with where it is from `at`, at `velocity`, within `view`. Cards going
+    /// right spin clockwise, and cards going left anticlockwise, as if they'd been flicked.
+    pub fn new(
+        cards: Vec<(usize, Vector2<f32>)>,
+        at: Point2<f32>,
+        velocity: Vector2<f32>,
+        view: Bounds,
+    ) -> Self {
+        Self {
+            cards,
+            position: Vector2::new(at.x, at.y),
+            velocity,
+            angle: 0.0,
+            spin: -velocity.x / card::WIDTH as f32 * TOSS_SPIN,
+            view,
+        }
+    }
+
+    /// Where the middle of the toss has got to.
+    pub fn position(&self) -> Point2<f32> {
+        Point2::new(self.position.x, self.position.y)
+    }
+
+    /// Slides the cards on by `dt`, returning whether they're still moving.
+    pub fn advance(&mut self, dt: Duration, cards: &mut [Card]) -> bool {
+        let settle_speed = TOSS_SETTLE_SPEED * card::HEIGHT as f32;
+
+        let mut left = dt;
+        while !left.is_zero() {
+            let step = left.min(CASCADE_STEP);
+            left -= step;
+
+            let dt = step.as_secs_f32();
+            let slowing = (-TOSS_FRICTION * dt).exp();
+            self.velocity *= slowing;
+            self.spin *= slowing;
+            self.position += self.velocity * dt;
+            self.angle += self.spin * dt;
+
+            // The middle of the toss stays in view, so the cards can't be lost off the table.
+            let (min, max) = (self.view.min, self.view.max);
+            if self.position.x < min.x || self.position.x > max.x {
+                self.position.x = self.position.x.clamp(min.x, max.x);
+                self.velocity.x = -self.velocity.x * TOSS_BOUNCE;
+            }
+            if self.position.y < min.y || self.position.y > max.y {
+                self.position.y = self.position.y.clamp(min.y, max.y);
+                self.velocity.y = -self.velocity.y * TOSS_BOUNCE;
+            }
+        }
+
+        let moving = self.velocity.x.hypot(self.velocity.y) > settle_speed;
+        let (sin, cos) = self.angle.sin_cos();
+        for &(index, offset) in &self.cards {
+            let turned = Vector2::new(
+                offset.x * cos - offset.y * sin,
+                offset.x * sin + offset.y * cos,
+            );
+            let position = self.position + turned;
+            let card = &mut cards[index];
+            card.position = Vector3::new(position.x as i32, position.y as i32, 0);
+            card.rotation = Rad(self.angle);
+            card.elevation = if moving { 1.0 } else { 0.0 };
+        }
+        moving
+    }
+}
+
+/// The cards leaping off the table one after another once a game is won, the top card first,
+/// each bouncing along the bottom of the view until it's gone out of one side.
+pub struct Cascade {
+    cards: Vec<Bounce>,
+    /// The part of the table in view, whose bottom the cards bounce on.
+    view: Bounds,
+    elapsed: Duration,
+}
+
+/// A card in a cascade.
+struct Bounce {
+    card: usize,
+    /// When the card sets off, after the cascade starts.
+    start: Duration,
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+}
+
+impl Cascade {
+    /// Sets `cards` off from where they are, within `view`.
+    pub fn new(cards: &[Card], view: Bounds) -> Self {
+        let [width, height] = [card::WIDTH as f32, card::HEIGHT as f32];
+        let cards = cards
+            .iter()
+            .enumerate()
+            .rev()
+            .enumerate()
+            .map(|(order, (index, card))| {
+                // Every other card heads the other way, each a little faster or higher than the
+                // last until the pattern starts again.
+                let direction = if order % 2 == 0 { -1.0 } else { 1.0 };
+                let speed = (2.0 + (order % 5) as f32 * 0.5) * width;
+                let lift = (1.0 + (order % 3) as f32) * height;
+                Bounce {
+                    card: index,
+                    start: CASCADE_INTERVAL * order as u32,
+                    position: Vector2::new(card.position.x as f32, card.position.y as f32),
+                    velocity: Vector2::new(direction * speed, lift),
+                }
+            })
+            .collect();
+
+        Self {
+            cards,
+            view,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Moves the cards that have set off on by `dt`, returning whether any are still in view.
+    pub fn advance(&mut self, dt: Duration, cards: &mut [Card]) -> bool {
+        let half = Vector2::new(card::WIDTH as f32, card::HEIGHT as f32) / 2.0;
+        let floor = self.view.min.y + half.y;
+        let gravity = CASCADE_GRAVITY * card::HEIGHT as f32;
+
+        let mut left = dt;
+        while !left.is_zero() {
+            let step = left.min(CASCADE_STEP);
+            left -= step;
+            self.elapsed += step;
+
+            for bounce in &mut self.cards {
+                if self.elapsed < bounce.start {
+                    continue;
+                }
+                let dt = step.as_secs_f32();
+                bounce.velocity.y -= gravity * dt;
+                bounce.position += bounce.velocity * dt;
+                if bounce.position.y < floor {
+                    bounce.position.y = floor;
+                    bounce.velocity.y = -bounce.velocity.y * CASCADE_BOUNCE;
+                }
+            }
+        }
+
+        let mut in_view = false;
+        for bounce in &self.cards {
+            let card = &mut cards[bounce.card];
+            card.position.x = bounce.position.x as i32;
+            card.position.y = bounce.position.y as i32;
+            card.elevation = if self.elapsed < bounce.start {
+                0.0
+            } else {
+                1.0
+            };
+            in_view |= bounce.position.x + half.x > self.view.min.x
+                && bounce.position.x - half.x < self.view.max.x;
+        }
+        in_view
+    }
+}
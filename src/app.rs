@@ -0,0 +1,162 @@
+//! The window and event loop the game runs in.
+
+use log::{debug, error};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+use wgpu::SurfaceError;
+use winit::{
+    event::*,
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+use crate::{config::Config, diagnostics, errors::*, state::State};
+
+/// Logs to the browser console.
+#[cfg(target_arch = "wasm32")]
+struct ConsoleLogger;
+
+#[cfg(target_arch = "wasm32")]
+impl log::Log for ConsoleLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        console_log::log(record);
+    }
+
+    fn flush(&self) {}
+}
+
+fn init_logging() {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+            diagnostics::install_logger(ConsoleLogger, log::LevelFilter::Warn);
+        } else {
+            let logger = env_logger::Builder::from_default_env().build();
+            let level = logger.filter();
+            diagnostics::install_logger(logger, level);
+        }
+    }
+}
+
+fn handle_window_event(state: &mut State, event: &WindowEvent) -> Option<ControlFlow> {
+    match event {
+        WindowEvent::CloseRequested => {
+            state.request_exit();
+            None
+        }
+
+        WindowEvent::Resized(physical_size) => {
+            state.resize(*physical_size);
+            None
+        }
+
+        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+            state.resize(**new_inner_size);
+            None
+        }
+
+        _ => None,
+    }
+}
+
+fn handle_redraw_event(state: &mut State) -> Option<ControlFlow> {
+    if state.exit_requested() {
+        return Some(ControlFlow::Exit);
+    }
+
+    state.update();
+    match state.render() {
+        Ok(_) => None,
+        Err(SurfaceError::Lost) => {
+            state.resize(state.size);
+            None
+        }
+        Err(SurfaceError::OutOfMemory) => Some(ControlFlow::Exit),
+        Err(e) => {
+            eprintln!("{:?}", e);
+            None
+        }
+    }
+}
+
+fn handle_event(state: &mut State, event: &Event<()>) -> Option<ControlFlow> {
+    debug!("{event:?}");
+
+    match event {
+        Event::WindowEvent {
+            ref event,
+            window_id,
+        } if *window_id == state.window().id() && !state.input(event) => {
+            handle_window_event(state, event)
+        }
+
+        Event::RedrawRequested(window_id) if *window_id == state.window().id() => {
+            handle_redraw_event(state)
+        }
+
+        Event::MainEventsCleared => {
+            state.window().request_redraw();
+            None
+        }
+
+        // Every way of exiting ends up here, so this is the one place to clean up.
+        Event::LoopDestroyed => {
+            state.shutdown();
+            None
+        }
+
+        _ => None,
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
+pub async fn run() {
+    init_logging();
+
+    match run_inner().await {
+        Ok(_) => (),
+        Err(e) => error!("{e:?}"),
+    }
+}
+
+async fn run_inner() -> Result<()> {
+    let config = Config::load();
+    let size = config.settings.window;
+    let size = winit::dpi::LogicalSize::new(size.width, size.height);
+
+    let event_loop = EventLoop::new();
+    let window = WindowBuilder::new()
+        .with_inner_size(size)
+        .build(&event_loop)
+        .chain_err(|| "couldn't create new window")?;
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        // Winit prevents sizing with CSS, so we have to set
+        // the size manually when on web.
+        window.set_inner_size(size);
+
+        use winit::platform::web::WindowExtWebSys;
+        web_sys::window()
+            .and_then(|win| win.document())
+            .and_then(|doc| {
+                let dst = doc.get_element_by_id("wasm-example")?;
+                let canvas = web_sys::Element::from(window.canvas());
+                dst.append_child(&canvas).ok()?;
+                Some(())
+            })
+            .expect("Couldn't append canvas to document body.");
+    }
+
+    let mut state = State::new(window, config).await?;
+
+    event_loop.run(move |event, _, control_flow| {
+        if let Some(new_flow) = handle_event(&mut state, &event) {
+            *control_flow = new_flow;
+        }
+    });
+}
@@ -0,0 +1,437 @@
+//! Sound effects for what happens to the cards, and music to play behind them.
+//!
+//! Every sound is made up rather than loaded from a file, so there's nothing to download or
+//! decode, once the first frame is up so as not to hold it back. Native builds play them through
+//! rodio, and the web through Web Audio, both only with the `audio` feature; without it the app
+//! is silent. Browsers don't let a page make a sound until it's been clicked on or typed into,
+//! so on the web the music starts with the first press of a key, a button or the screen.
+//!
+//! Sound effects are panned between the speakers, from -1 for all the way left to 1 for all the
+//! way right, so that a card put down on one side of the screen is heard from that side.
+
+use std::time::Duration;
+
+#[cfg(feature = "audio")]
+use instant::Instant;
+
+use crate::config::AudioSettings;
+
+/// Something that happens to the cards, with a sound of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    /// A new deck being shuffled.
+    Shuffle,
+    /// A card being dealt out.
+    Deal,
+    /// A card turning over.
+    Flip,
+    /// A card being put down.
+    Place,
+}
+
+impl Sound {
+    #[cfg(feature = "audio")]
+    const ALL: [Sound; 4] = [Sound::Shuffle, Sound::Deal, Sound::Flip, Sound::Place];
+}
+
+/// The sound effects and music, at the volumes they're set to.
+pub struct Audio {
+    volumes: AudioSettings,
+    /// Where the sounds are played: `None` until `start`, then `Some` of anywhere there is to
+    /// play them.
+    #[cfg(feature = "audio")]
+    output: Option<Option<output::Output>>,
+    /// Sounds asked for before `start`, with when they're due and where they're panned to.
+    #[cfg(feature = "audio")]
+    pending: Vec<(Sound, Instant, f32)>,
+}
+
+impl Audio {
+    /// Sound at `volumes`, with nothing made up or opened until `start`.
+    pub fn new(volumes: &AudioSettings) -> Self {
+        Self {
+            volumes: *volumes,
+            #[cfg(feature = "audio")]
+            output: None,
+            #[cfg(feature = "audio")]
+            pending: Vec::new(),
+        }
+    }
+
+    /// Makes up the sounds, opens somewhere to play them and starts the music, unless that's
+    /// been done already. Sounds asked for before then that aren't over yet are played late.
+    pub fn start(&mut self) {
+        #[cfg(feature = "audio")]
+        if self.output.is_none() {
+            let sounds = Sound::ALL.map(synth::sound).to_vec();
+            let output = output::Output::new(sounds, synth::music());
+            if let Some(output) = &output {
+                output.set_volumes(effects_volume(&self.volumes), music_volume(&self.volumes));
+            }
+            self.output = Some(output);
+
+            let now = Instant::now();
+            for (sound, due, pan) in std::mem::take(&mut self.pending) {
+                if due >= now {
+                    self.play(sound, due - now, pan);
+                }
+            }
+        }
+    }
+
+    /// Lets sound be heard on the web, where the browser only allows it once the page has been
+    /// clicked on or typed into, so this is called on every press of a key, a button or the
+    /// screen. Natively sound is always allowed.
+    pub fn resume(&self) {
+        #[cfg(all(feature = "audio", target_arch = "wasm32"))]
+        if let Some(Some(output)) = &self.output {
+            output.resume();
+        }
+    }
+
+    /// Plays `sound` after `delay`, panned to `pan`.
+    pub fn play(&mut self, sound: Sound, delay: Duration, pan: f32) {
+        if effects_volume(&self.volumes) <= 0.0 {
+            return;
+        }
+        #[cfg(feature = "audio")]
+        match &self.output {
+            Some(Some(output)) => output.play(sound as usize, delay, pan.clamp(-1.0, 1.0)),
+            Some(None) => {}
+            None => self.pending.push((sound, Instant::now() + delay, pan)),
+        }
+        #[cfg(not(feature = "audio"))]
+        let _ = (sound, delay, pan);
+    }
+
+    /// Stops the music and any sound effects playing, for good.
+    pub fn stop(&mut self) {
+        #[cfg(feature = "audio")]
+        if let Some(Some(output)) = self.output.replace(None) {
+            output.stop();
+        }
+    }
+
+    /// Turns the sound effects and music up or down to `volumes`.
+    pub fn set_volumes(&mut self, volumes: &AudioSettings) {
+        if *volumes == self.volumes {
+            return;
+        }
+        self.volumes = *volumes;
+        #[cfg(feature = "audio")]
+        if let Some(Some(output)) = &self.output {
+            output.set_volumes(effects_volume(volumes), music_volume(volumes));
+        }
+    }
+}
+
+fn effects_volume(volumes: &AudioSettings) -> f32 {
+    volumes.master * volumes.effects
+}
+
+#[cfg(feature = "audio")]
+fn music_volume(volumes: &AudioSettings) -> f32 {
+    volumes.master * volumes.music
+}
+
+/// Playing sounds through rodio.
+#[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+mod output {
+    use std::{cell::Cell, f32::consts::FRAC_PI_4, time::Duration};
+
+    use rodio::{buffer::SamplesBuffer, OutputStream, OutputStreamHandle, Sink, Source};
+
+    use super::synth::SAMPLE_RATE;
+
+    pub struct Output {
+        /// The stream stops when it's dropped, so it's kept for as long as there's sound.
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+        music: Sink,
+        sounds: Vec<Vec<f32>>,
+        effects_volume: Cell<f32>,
+    }
+
+    impl Output {
+        /// Opens the default output device and starts `music` looping on it.
+        pub fn new(sounds: Vec<Vec<f32>>, music: Vec<f32>) -> Option<Self> {
+            let (stream, handle) = OutputStream::try_default()
+                .map_err(|e| log::warn!("playing without sound: {e}"))
+                .ok()?;
+            let sink = Sink::try_new(&handle)
+                .map_err(|e| log::warn!("playing without music: {e}"))
+                .ok()?;
+            sink.append(SamplesBuffer::new(1, SAMPLE_RATE, music).repeat_infinite());
+
+            Some(Self {
+                _stream: stream,
+                handle,
+                music: sink,
+                sounds,
+                effects_volume: Cell::new(1.0),
+            })
+        }
+
+        pub fn play(&self, sound: usize, delay: Duration, pan: f32) {
+            // Equal-power panning, so a sound is as loud in the middle as at either side.
+            let angle = (pan + 1.0) * FRAC_PI_4;
+            let (left, right) = (angle.cos(), angle.sin());
+            let samples = self.sounds[sound]
+                .iter()
+                .flat_map(|&sample| [sample * left, sample * right])
+                .collect::<Vec<_>>();
+            let source = SamplesBuffer::new(2, SAMPLE_RATE, samples)
+                .amplify(self.effects_volume.get())
+                .delay(delay);
+            if let Err(e) = self.handle.play_raw(source) {
+                log::warn!("couldn't play a sound: {e}");
+            }
+        }
+
+        pub fn set_volumes(&self, effects: f32, music: f32) {
+            self.effects_volume.set(effects);
+            self.music.set_volume(music);
+        }
+
+        /// Stops the music, and the sound effects with the stream they're played on.
+        pub fn stop(self) {
+            self.music.stop();
+        }
+    }
+}
+
+/// Playing sounds through Web Audio.
+#[cfg(all(feature = "audio", target_arch = "wasm32"))]
+mod output {
+    use std::time::Duration;
+
+    use web_sys::{AudioBuffer, AudioContext, AudioContextState, GainNode};
+
+    use super::synth::SAMPLE_RATE;
+
+    pub struct Output {
+        context: AudioContext,
+        /// Where the sound effects and the music go on their way out, to be turned up or down.
+        effects: GainNode,
+        music: GainNode,
+        sounds: Vec<AudioBuffer>,
+    }
+
+    impl Output {
+        /// Sets up an audio context with `music` looping on it, which starts once it's resumed.
+        pub fn new(sounds: Vec<Vec<f32>>, music: Vec<f32>) -> Option<Self> {
+            let context = AudioContext::new().ok()?;
+            let effects = context.create_gain().ok()?;
+            effects
+                .connect_with_audio_node(&context.destination())
+                .ok()?;
+            let music_gain = context.create_gain().ok()?;
+            music_gain
+                .connect_with_audio_node(&context.destination())
+                .ok()?;
+
+            let sounds = sounds
+                .iter()
+                .map(|samples| buffer(&context, samples))
+                .collect::<Option<Vec<_>>>()?;
+
+            let source = context.create_buffer_source().ok()?;
+            source.set_buffer(Some(&buffer(&context, &music)?));
+            source.set_loop(true);
+            source.connect_with_audio_node(&music_gain).ok()?;
+            source.start().ok()?;
+
+            Some(Self {
+                context,
+                effects,
+                music: music_gain,
+                sounds,
+            })
+        }
+
+        /// Resumes the context, which stays suspended until the page has been clicked on or
+        /// typed into.
+        pub fn resume(&self) {
+            if self.context.state() == AudioContextState::Suspended {
+                let _ = self.context.resume();
+            }
+        }
+
+        pub fn play(&self, sound: usize, delay: Duration, pan: f32) {
+            let (Ok(source), Ok(panner)) = (
+                self.context.create_buffer_source(),
+                self.context.create_stereo_panner(),
+            ) else {
+                return;
+            };
+            source.set_buffer(Some(&self.sounds[sound]));
+            panner.pan().set_value(pan);
+            if source.connect_with_audio_node(&panner).is_err()
+                || panner.connect_with_audio_node(&self.effects).is_err()
+            {
+                return;
+            }
+            let when = self.context.current_time() + delay.as_secs_f64();
+            let _ = source.start_with_when(when);
+        }
+
+        pub fn set_volumes(&self, effects: f32, music: f32) {
+            self.effects.gain().set_value(effects);
+            self.music.gain().set_value(music);
+        }
+
+        pub fn stop(self) {
+            let _ = self.context.close();
+        }
+    }
+
+    /// A buffer on `context` holding `samples`.
+    fn buffer(context: &AudioContext, samples: &[f32]) -> Option<AudioBuffer> {
+        let buffer = context
+            .create_buffer(1, samples.len() as u32, SAMPLE_RATE as f32)
+            .ok()?;
+        buffer.copy_to_channel(samples, 0).ok()?;
+        Some(buffer)
+    }
+}
+
+/// Making up the sounds, as mono samples from -1 to 1.
+#[cfg(feature = "audio")]
+mod synth {
+    use std::f32::consts::TAU;
+
+    use super::Sound;
+
+    pub const SAMPLE_RATE: u32 = 22050;
+
+    /// The chords the music goes round, a bar each, as MIDI notes from the root up.
+    const CHORDS: [[u8; 3]; 4] = [[60, 64, 67], [57, 60, 64], [53, 57, 60], [55, 59, 62]];
+
+    /// How long a beat of the music lasts, in seconds, and how many there are to a bar.
+    const BEAT: f32 = 0.5;
+    const BEATS_PER_BAR: usize = 4;
+
+    /// Which of the chord's notes is played on each beat.
+    const ARPEGGIO: [usize; BEATS_PER_BAR] = [0, 1, 2, 1];
+
+    /// A noise source that's the same every time, so the sounds are too.
+    struct Noise(u32);
+
+    impl Noise {
+        fn next(&mut self) -> f32 {
+            // Xorshift.
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+        }
+    }
+
+    /// Rises to full over `attack` seconds and then dies away, by a factor of e every `decay`
+    /// seconds.
+    fn envelope(t: f32, attack: f32, decay: f32) -> f32 {
+        match t < attack {
+            true => t / attack,
+            false => (-(t - attack) / decay).exp(),
+        }
+    }
+
+    fn samples(seconds: f32) -> usize {
+        (seconds * SAMPLE_RATE as f32) as usize
+    }
+
+    /// The sound's samples.
+    pub fn sound(sound: Sound) -> Vec<f32> {
+        let mut noise = Noise(0x2545_f491);
+        match sound {
+            Sound::Flip => click(&mut noise, 0.07, 0.015, 0.5),
+            Sound::Deal => {
+                // A soft swish, the noise smoothed less as the card speeds up.
+                let mut smoothed = 0.0;
+                (0..samples(0.12))
+                    .map(|index| {
+                        let t = index as f32 / SAMPLE_RATE as f32;
+                        let smoothing = 0.1 + t * 3.0;
+                        smoothed += (noise.next() - smoothed) * smoothing.min(0.6);
+                        smoothed * envelope(t, 0.03, 0.03) * 0.6
+                    })
+                    .collect()
+            }
+            Sound::Place => {
+                // A dull tap, with a low thump under it.
+                let mut smoothed = 0.0;
+                (0..samples(0.12))
+                    .map(|index| {
+                        let t = index as f32 / SAMPLE_RATE as f32;
+                        smoothed += (noise.next() - smoothed) * 0.2;
+                        let tap = smoothed * envelope(t, 0.002, 0.02) * 0.9;
+                        let thump = (TAU * 150.0 * t).sin() * envelope(t, 0.002, 0.04) * 0.4;
+                        tap + thump
+                    })
+                    .collect()
+            }
+            Sound::Shuffle => {
+                // A riffle: clicks coming quicker and quicker as the halves run together.
+                let mut riffle = vec![0.0; samples(0.9)];
+                let clicks = 30;
+                for index in 0..clicks {
+                    let at = 0.8 * (index as f32 / clicks as f32).powf(0.8);
+                    let volume = 0.35 + 0.1 * noise.next();
+                    let click = click(&mut noise, 0.02, 0.004, volume);
+                    for (offset, sample) in click.into_iter().enumerate() {
+                        if let Some(into) = riffle.get_mut(samples(at) + offset) {
+                            *into += sample;
+                        }
+                    }
+                }
+                riffle
+            }
+        }
+    }
+
+    /// A sharp burst of noise `seconds` long, dying away every `decay` seconds from `volume`.
+    fn click(noise: &mut Noise, seconds: f32, decay: f32, volume: f32) -> Vec<f32> {
+        let mut last = 0.0;
+        (0..samples(seconds))
+            .map(|index| {
+                let t = index as f32 / SAMPLE_RATE as f32;
+                // Taking the difference of the noise leaves its highs, for a crisper click.
+                let next = noise.next();
+                let high = next - last;
+                last = next;
+                high * envelope(t, 0.002, decay) * volume
+            })
+            .collect()
+    }
+
+    /// A gentle loop of broken chords over a bass note for each bar, which loops without a
+    /// seam.
+    pub fn music() -> Vec<f32> {
+        let bar = BEAT * BEATS_PER_BAR as f32;
+        let mut music = vec![0.0; samples(bar * CHORDS.len() as f32)];
+
+        for (bar_index, chord) in CHORDS.iter().enumerate() {
+            let start = bar_index as f32 * bar;
+            add_note(&mut music, start, chord[0] - 24, 1.6, 0.15);
+            for (beat, &note) in ARPEGGIO.iter().enumerate() {
+                let at = start + beat as f32 * BEAT;
+                add_note(&mut music, at, chord[note], 0.35, 0.12);
+            }
+        }
+        music
+    }
+
+    /// Adds a soft note at `start` seconds in, dying away every `decay` seconds from `volume`,
+    /// carrying on from the beginning if it runs past the end.
+    fn add_note(music: &mut [f32], start: f32, note: u8, decay: f32, volume: f32) {
+        let frequency = 440.0 * 2f32.powf((note as f32 - 69.0) / 12.0);
+        let length = music.len();
+        let first = samples(start);
+        for index in 0..samples(decay * 5.0).min(length) {
+            let t = index as f32 / SAMPLE_RATE as f32;
+            let phase = TAU * frequency * t;
+            let tone = phase.sin() + 0.2 * (2.0 * phase).sin();
+            music[(first + index) % length] += tone * envelope(t, 0.01, decay) * volume;
+        }
+    }
+}
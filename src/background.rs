@@ -0,0 +1,333 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+use bytemuck::{cast_slice, Pod, Zeroable};
+use image::{DynamicImage, Rgba, RgbaImage};
+use log::warn;
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType,
+    BufferBindingType, BufferUsages, CompareFunction, DepthStencilState, Device, FilterMode,
+    FragmentState, MultisampleState, PipelineLayoutDescriptor, Queue, RenderPass, RenderPipeline,
+    RenderPipelineDescriptor, SamplerBindingType, ShaderStages, TextureFormat, TextureSampleType,
+    TextureViewDimension, VertexState,
+};
+
+use crate::{
+    diagnostics::DrawStats,
+    errors::*,
+    texture::{self, Texture, TextureOptions},
+    theme::{self, Look},
+};
+
+/// The table colour used when `CARDS_TABLE_COLOR` doesn't set one.
+const DEFAULT_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.1,
+    g: 0.2,
+    b: 0.3,
+    a: 1.0,
+};
+
+/// The table's colour and vignette, as they're fed to the background shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct TableUniform {
+    color: [f32; 4],
+    vignette: f32,
+    /// 1 for a plain table of `color`, 0 for one with an image tiled across it.
+    plain: f32,
+    _padding: [f32; 2],
+}
+
+/// What's drawn behind the cards: the table's colour, or an image tiled across it, darkening
+/// towards the corners as the theme's look says, with the clear colour filling the window around
+/// it.
+pub struct Background {
+    pub clear_color: wgpu::Color,
+    /// The table's colour before the look turns its hue.
+    color: wgpu::Color,
+    layout: BindGroupLayout,
+    pipeline: RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    uniform: TableUniform,
+    /// The image tiled across the table, or a single white texel for a plain table.
+    #[allow(dead_code)]
+    texture: Texture,
+    bind_group: BindGroup,
+}
+
+impl Background {
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        format: TextureFormat,
+        depth_format: TextureFormat,
+        sample_count: u32,
+    ) -> Result<Self> {
+        let layout = create_bind_group_layout(device);
+        let pipeline = create_pipeline(device, &layout, format, depth_format, sample_count);
+        let color = table_color().unwrap_or(DEFAULT_COLOR);
+        let uniform = TableUniform {
+            color: [color.r as f32, color.g as f32, color.b as f32, 1.0],
+            vignette: 0.0,
+            plain: 1.0,
+            _padding: [0.0; 2],
+        };
+        let uniform_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Table Buffer"),
+            contents: cast_slice(&[uniform]),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+        let white = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, Rgba([255; 4])));
+        let texture = create_texture(device, queue, &white)?;
+        let bind_group = create_bind_group(device, &layout, &texture, &uniform_buffer);
+
+        Ok(Self {
+            clear_color: color,
+            color,
+            layout,
+            pipeline,
+            uniform_buffer,
+            uniform,
+            texture,
+            bind_group,
+        })
+    }
+
+    /// Colours the table, and darkens it towards the corners, as `look` says.
+    pub fn set_look(&mut self, queue: &Queue, look: &Look) {
+        let base = [self.color.r, self.color.g, self.color.b].map(|channel| channel as f32);
+        let [r, g, b] = theme::with_hue(base, theme::hue(base) + look.table_hue);
+        self.clear_color = wgpu::Color {
+            r: r.into(),
+            g: g.into(),
+            b: b.into(),
+            a: 1.0,
+        };
+        self.uniform.color = [r, g, b, 1.0];
+        self.uniform.vignette = look.vignette;
+        queue.write_buffer(&self.uniform_buffer, 0, cast_slice(&[self.uniform]));
+    }
+
+    /// Rebuilds the pipeline for a new MSAA sample count.
+    pub fn set_sample_count(
+        &mut self,
+        device: &Device,
+        format: TextureFormat,
+        depth_format: TextureFormat,
+        sample_count: u32,
+    ) {
+        self.pipeline = create_pipeline(device, &self.layout, format, depth_format, sample_count);
+    }
+
+    /// Tiles an image across the table, or goes back to the plain table colour for `None`.
+    pub fn set_image(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        image: Option<&DynamicImage>,
+    ) -> Result<()> {
+        let white = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, Rgba([255; 4])));
+        self.texture = create_texture(device, queue, image.unwrap_or(&white))?;
+        self.bind_group =
+            create_bind_group(device, &self.layout, &self.texture, &self.uniform_buffer);
+        self.uniform.plain = if image.is_some() { 0.0 } else { 1.0 };
+        queue.write_buffer(&self.uniform_buffer, 0, cast_slice(&[self.uniform]));
+        Ok(())
+    }
+
+    /// Draws the table into the current viewport.
+    pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>) -> DrawStats {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+
+        DrawStats {
+            draw_calls: 1,
+            instances: 1,
+        }
+    }
+}
+
+/// Loads the table image named by `CARDS_TABLE_IMAGE` (native) or the `table` query parameter
+/// (web), if there is one.
+pub async fn load_table_image() -> Option<DynamicImage> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let (name, image) = {
+        let path = std::env::var_os("CARDS_TABLE_IMAGE")?;
+        let image = texture::load_image(Path::new(&path));
+        (path.to_string_lossy().into_owned(), image)
+    };
+
+    #[cfg(target_arch = "wasm32")]
+    let (name, image) = {
+        let url = table_url()?;
+        let image = texture::fetch_image(&url).await;
+        (url, image)
+    };
+
+    image
+        .map_err(|e| warn!("couldn't load table image {name}, leaving it plain: {e:?}"))
+        .ok()
+}
+
+/// The URL in the page's `table` query parameter, if it has one.
+#[cfg(target_arch = "wasm32")]
+pub fn table_url() -> Option<String> {
+    crate::util::query_param("table")
+}
+
+/// The table colour from `CARDS_TABLE_COLOR`, given as `#rrggbb`.
+#[cfg(not(target_arch = "wasm32"))]
+fn table_color() -> Option<wgpu::Color> {
+    let value = std::env::var("CARDS_TABLE_COLOR").ok()?;
+    let parsed = parse_hex_color(&value);
+    if parsed.is_none() {
+        warn!("ignoring CARDS_TABLE_COLOR value {value:?}, expected #rrggbb");
+    }
+    parsed
+}
+
+#[cfg(target_arch = "wasm32")]
+fn table_color() -> Option<wgpu::Color> {
+    None
+}
+
+/// Parses an sRGB `#rrggbb` colour into the linear colour the surface expects.
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_hex_color(value: &str) -> Option<wgpu::Color> {
+    let hex = value.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+
+    let channel = |index: usize| {
+        let srgb = u8::from_str_radix(hex.get(index..index + 2)?, 16).ok()? as f64 / 255.0;
+        Some(if srgb <= 0.04045 {
+            srgb / 12.92
+        } else {
+            ((srgb + 0.055) / 1.055).powf(2.4)
+        })
+    };
+
+    Some(wgpu::Color {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+        a: 1.0,
+    })
+}
+
+fn create_texture(device: &Device, queue: &Queue, image: &DynamicImage) -> Result<Texture> {
+    let options = TextureOptions {
+        filter: FilterMode::Linear,
+        address_mode: AddressMode::Repeat,
+        anisotropy: 1,
+    };
+    Texture::from_layers(device, queue, &[image], Some("table"), &options)
+}
+
+fn create_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    texture: &Texture,
+    uniform_buffer: &wgpu::Buffer,
+) -> BindGroup {
+    device.create_bind_group(&BindGroupDescriptor {
+        label: Some("background_bind_group"),
+        layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(&texture.view),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(&texture.sampler),
+            },
+            BindGroupEntry {
+                binding: 2,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+fn create_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("background_bind_group_layout"),
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                count: None,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: TextureViewDimension::D2Array,
+                    sample_type: TextureSampleType::Float { filterable: true },
+                },
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                count: None,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(SamplerBindingType::Filtering),
+            },
+            BindGroupLayoutEntry {
+                binding: 2,
+                count: None,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+            },
+        ],
+    })
+}
+
+fn create_pipeline(
+    device: &Device,
+    layout: &BindGroupLayout,
+    format: TextureFormat,
+    depth_format: TextureFormat,
+    sample_count: u32,
+) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::include_wgsl!("background.wgsl"));
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+        label: Some("Background Pipeline Layout"),
+        bind_group_layouts: &[layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("Background Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(format.into())],
+        }),
+        primitive: Default::default(),
+        // The table is behind everything, so it neither tests nor writes depth.
+        depth_stencil: Some(DepthStencilState {
+            format: depth_format,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::Always,
+            stencil: Default::default(),
+            bias: Default::default(),
+        }),
+        multisample: MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        multiview: None,
+    })
+}
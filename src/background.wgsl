@@ -0,0 +1,46 @@
+// The table surface, drawn behind everything as a triangle covering the viewport: the table's
+// colour, or an image tiled across it, darkening towards the corners by the vignette.
+
+struct TableUniform {
+    color: vec4<f32>,
+    vignette: f32,
+    // 1 for a plain table of `color`, 0 for one with an image tiled across it.
+    plain: f32,
+    _padding: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    // Where the fragment is across the viewport, from -1 to 1 each way.
+    @location(0) across: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    // Vertices at (-1, -1), (3, -1) and (-1, 3) in clip space, covering the viewport twice over.
+    let corner = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4(corner * 2.0 - 1.0, 1.0, 1.0);
+    out.across = corner * 2.0 - 1.0;
+    return out;
+}
+
+@group(0) @binding(0)
+var t_table: texture_2d_array<f32>;
+
+@group(0) @binding(1)
+var s_table: sampler;
+
+@group(0) @binding(2)
+var<uniform> table: TableUniform;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Tiled in screen pixels, so the texture keeps its size as the camera moves and zooms.
+    let size = vec2<f32>(textureDimensions(t_table));
+    let texel = textureSample(t_table, s_table, in.position.xy / size, 0);
+    let color = mix(texel.rgb, table.color.rgb, table.plain);
+    // Half the diagonal is 1, so the corners darken by the whole vignette.
+    let shade = 1.0 - table.vignette * dot(in.across, in.across) / 2.0;
+    return vec4(color * shade, 1.0);
+}
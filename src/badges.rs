@@ -0,0 +1,295 @@
+use std::mem::size_of;
+
+use bytemuck::{cast_slice, Pod, Zeroable};
+use cards_core::game::Badge;
+use cgmath::{Angle, Vector2};
+use wgpu::{
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, BlendState, BufferAddress,
+    BufferDescriptor, BufferUsages, ColorTargetState, ColorWrites, CompareFunction,
+    DepthStencilState, Device, FilterMode, FragmentState, MultisampleState,
+    PipelineLayoutDescriptor, Queue, RenderPass, RenderPipeline, RenderPipelineDescriptor,
+    SamplerBindingType, ShaderStages, TextureFormat, TextureSampleType, TextureViewDimension,
+    VertexAttribute, VertexBufferLayout, VertexState, VertexStepMode,
+};
+
+use crate::{
+    card::{self, Card},
+    diagnostics::DrawStats,
+    errors::*,
+    texture::{Texture, TextureOptions},
+};
+
+/// A disc for each badge, side by side in 32x32 cells: a tick for `Playable`, an exclamation mark
+/// for `MustFollow` and a plus for `CanMeld`.
+const ATLAS: &[u8] = include_bytes!("badges.png");
+
+/// How wide a badge is, in world units at a card scale of 1.
+const SIZE: f32 = 12.0;
+
+/// Where a badge sits on its card, from the card's centre at a card scale of 1: over the top-left
+/// corner, which shows in a hand fanned to the right or a column spread downwards.
+const OFFSET: [f32; 2] = [
+    -(card::WIDTH as f32) / 2.0 + 4.0,
+    card::HEIGHT as f32 / 2.0 - 2.0,
+];
+
+/// The colour of each badge's disc.
+const PLAYABLE_COLOR: [f32; 4] = [0.2, 0.65, 0.3, 1.0];
+const MUST_FOLLOW_COLOR: [f32; 4] = [0.95, 0.6, 0.1, 1.0];
+const CAN_MELD_COLOR: [f32; 4] = [0.25, 0.5, 0.95, 1.0];
+
+/// One badge, as it's fed to the badge shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct Instance {
+    /// The centre, in world units.
+    position: [f32; 2],
+    size: f32,
+    angle: f32,
+    color: [f32; 4],
+    /// The badge's cell in the atlas.
+    cell: u32,
+}
+
+impl Instance {
+    const ATTRIBUTES: [VertexAttribute; 5] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32,
+        2 => Float32,
+        3 => Float32x4,
+        4 => Uint32,
+    ];
+
+    const BUFFER_LAYOUT: VertexBufferLayout<'static> = VertexBufferLayout {
+        array_stride: size_of::<Instance>() as BufferAddress,
+        step_mode: VertexStepMode::Instance,
+        attributes: &Self::ATTRIBUTES,
+    };
+
+    /// The badge for `card`, wherever it is on the table, or a badge with nothing to show for a
+    /// card without one.
+    fn for_card(card: &Card) -> Self {
+        let (cell, color) = match card.badge {
+            Some(Badge::Playable) => (0, PLAYABLE_COLOR),
+            Some(Badge::MustFollow) => (1, MUST_FOLLOW_COLOR),
+            Some(Badge::CanMeld) => (2, CAN_MELD_COLOR),
+            None => (0, [0.0; 4]),
+        };
+        let (sin, cos) = card.rotation.sin_cos();
+        let offset = Vector2::new(OFFSET[0] * card.scale.x, OFFSET[1] * card.scale.y);
+        let offset = Vector2::new(
+            offset.x * cos - offset.y * sin,
+            offset.x * sin + offset.y * cos,
+        );
+        Self {
+            position: [
+                card.position.x as f32 + offset.x,
+                card.position.y as f32 + offset.y,
+            ],
+            size: SIZE * card.scale.x,
+            angle: card.rotation.0,
+            color,
+            cell,
+        }
+    }
+}
+
+/// Marks in the corners of the cards the player can move now, saying what they can do with them,
+/// drawn from a small atlas in an instanced pass beside the card pipeline. There's an instance
+/// for every card, in the order the cards are drawn, and each card's badge is drawn just after
+/// the card, so that the cards above it cover it as they cover the card.
+pub struct Badges {
+    pipeline: RenderPipeline,
+    layout: BindGroupLayout,
+    bind_group: BindGroup,
+    #[allow(dead_code)]
+    atlas: Texture,
+    instance_buffer: wgpu::Buffer,
+    capacity: usize,
+}
+
+impl Badges {
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        camera_layout: &BindGroupLayout,
+        format: TextureFormat,
+        depth_format: TextureFormat,
+        sample_count: u32,
+    ) -> Result<Self> {
+        let image =
+            image::load_from_memory(ATLAS).chain_err(|| "couldn't decode the badge atlas")?;
+        let options = TextureOptions {
+            filter: FilterMode::Linear,
+            ..Default::default()
+        };
+        let atlas = Texture::from_layers(device, queue, &[&image], Some("badges"), &options)?;
+
+        let layout = create_bind_group_layout(device);
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("badge_bind_group"),
+            layout: &layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&atlas.view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(&atlas.sampler),
+                },
+            ],
+        });
+        let pipeline = create_pipeline(
+            device,
+            &layout,
+            camera_layout,
+            format,
+            depth_format,
+            sample_count,
+        );
+
+        Ok(Self {
+            pipeline,
+            layout,
+            bind_group,
+            atlas,
+            instance_buffer: create_instance_buffer(device, 0),
+            capacity: 0,
+        })
+    }
+
+    /// Rebuilds the pipeline for a new MSAA sample count.
+    pub fn set_sample_count(
+        &mut self,
+        device: &Device,
+        camera_layout: &BindGroupLayout,
+        format: TextureFormat,
+        depth_format: TextureFormat,
+        sample_count: u32,
+    ) {
+        self.pipeline = create_pipeline(
+            device,
+            &self.layout,
+            camera_layout,
+            format,
+            depth_format,
+            sample_count,
+        );
+    }
+
+    /// Makes room for a badge for each of `cards` cards.
+    pub fn reserve(&mut self, device: &Device, cards: usize) {
+        if cards > self.capacity {
+            self.capacity = cards;
+            self.instance_buffer = create_instance_buffer(device, cards);
+        }
+    }
+
+    /// Uploads the badges of `cards`, in the order they're drawn.
+    pub fn write<'a>(&self, queue: &Queue, cards: impl Iterator<Item = &'a Card>) {
+        let instances = cards.map(Instance::for_card).collect::<Vec<_>>();
+        if !instances.is_empty() {
+            queue.write_buffer(&self.instance_buffer, 0, cast_slice(&instances));
+        }
+    }
+
+    /// Draws the badge of the card drawn as `instance`, in the world the camera, already bound
+    /// as group 1 for the cards, shows. Leaves its own pipeline, bind group 0 and vertex buffer 0
+    /// set, for the cards to set theirs again.
+    pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>, instance: u32) -> DrawStats {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.draw(0..6, instance..instance + 1);
+
+        DrawStats {
+            draw_calls: 1,
+            instances: 1,
+        }
+    }
+}
+
+fn create_instance_buffer(device: &Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&BufferDescriptor {
+        label: Some("Badge Instance Buffer"),
+        size: (capacity * size_of::<Instance>()) as BufferAddress,
+        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+fn create_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("badge_bind_group_layout"),
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                count: None,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: TextureViewDimension::D2Array,
+                    sample_type: TextureSampleType::Float { filterable: true },
+                },
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                count: None,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(SamplerBindingType::Filtering),
+            },
+        ],
+    })
+}
+
+fn create_pipeline(
+    device: &Device,
+    layout: &BindGroupLayout,
+    camera_layout: &BindGroupLayout,
+    format: TextureFormat,
+    depth_format: TextureFormat,
+    sample_count: u32,
+) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::include_wgsl!("badges.wgsl"));
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+        label: Some("Badge Pipeline Layout"),
+        bind_group_layouts: &[layout, camera_layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("Badge Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[Instance::BUFFER_LAYOUT],
+        },
+        fragment: Some(FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(ColorTargetState {
+                format,
+                blend: Some(BlendState::ALPHA_BLENDING),
+                write_mask: ColorWrites::ALL,
+            })],
+        }),
+        primitive: Default::default(),
+        // A badge is drawn straight after its card, so it only has to cover that card, and
+        // doesn't write depth, so the cards drawn after it still cover it.
+        depth_stencil: Some(DepthStencilState {
+            format: depth_format,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::Always,
+            stencil: Default::default(),
+            bias: Default::default(),
+        }),
+        multisample: MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        multiview: None,
+    })
+}
@@ -0,0 +1,68 @@
+// Badges in the corners of cards the player can move now, saying what they can do with them.
+// Each instance is one badge: a disc from the badge atlas in the badge's colour, with its mark
+// left white.
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(0) @binding(0)
+var t_badges: texture_2d_array<f32>;
+
+@group(0) @binding(1)
+var s_badges: sampler;
+
+struct BadgeInput {
+    @location(0) position: vec2<f32>,
+    @location(1) size: f32,
+    @location(2) angle: f32,
+    @location(3) color: vec4<f32>,
+    @location(4) cell: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+// How many badges there are, side by side along the atlas.
+const CELLS: f32 = 3.0;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, badge: BadgeInput) -> VertexOutput {
+    // Two triangles covering the quad.
+    var corners = array<vec2<f32>, 6>(
+        vec2(-1.0, -1.0),
+        vec2(1.0, -1.0),
+        vec2(1.0, 1.0),
+        vec2(-1.0, -1.0),
+        vec2(1.0, 1.0),
+        vec2(-1.0, 1.0),
+    );
+    let corner = corners[index];
+
+    let c = cos(badge.angle);
+    let s = sin(badge.angle);
+    let offset = corner * badge.size / 2.0;
+    let turned = vec2(offset.x * c - offset.y * s, offset.x * s + offset.y * c);
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4(badge.position + turned, 0.0, 1.0);
+    // The atlas runs top down, the table bottom up.
+    let across = (corner + 1.0) / 2.0;
+    out.tex_coords = vec2((f32(badge.cell) + across.x) / CELLS, 1.0 - across.y);
+    out.color = badge.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // The atlas's red is 1 over the disc and 0 over its mark, and its alpha the disc's edge.
+    let texel = textureSample(t_badges, s_badges, in.tex_coords, 0);
+    let color = mix(vec3(1.0), in.color.rgb, texel.r);
+    return vec4(color, texel.a * in.color.a);
+}
@@ -0,0 +1,56 @@
+use cgmath::{EuclideanSpace, Point2};
+
+/// An axis-aligned rectangle in world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: Point2<f32>,
+    pub max: Point2<f32>,
+}
+
+impl Bounds {
+    /// The smallest bounds containing every point, or `None` if there are no points.
+    pub fn from_points(points: impl IntoIterator<Item = Point2<f32>>) -> Option<Self> {
+        points.into_iter().fold(None, |bounds, point| {
+            Some(match bounds {
+                None => Bounds {
+                    min: point,
+                    max: point,
+                },
+                Some(bounds) => bounds.union(Bounds {
+                    min: point,
+                    max: point,
+                }),
+            })
+        })
+    }
+
+    pub fn union(&self, other: Bounds) -> Bounds {
+        Bounds {
+            min: Point2::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            max: Point2::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        }
+    }
+
+    pub fn contains(&self, point: Point2<f32>) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn center(&self) -> Point2<f32> {
+        self.min.midpoint(self.max)
+    }
+
+    /// How far `point` is from the nearest point of the bounds, or 0 if it's inside them.
+    pub fn distance(&self, point: Point2<f32>) -> f32 {
+        let dx = (self.min.x - point.x).max(point.x - self.max.x).max(0.0);
+        let dy = (self.min.y - point.y).max(point.y - self.max.y).max(0.0);
+        dx.hypot(dy)
+    }
+}
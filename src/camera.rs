@@ -1,10 +1,17 @@
+use std::{ops::RangeInclusive, time::Duration};
+
 use bytemuck::{Pod, Zeroable};
-use cgmath::{ortho, Matrix4, Point2, Point3, SquareMatrix, Vector2, Vector3};
+use cgmath::{
+    ortho, perspective, Angle, Deg, EuclideanSpace, InnerSpace, Matrix4, Point2, Point3, Rad,
+    SquareMatrix, Vector2, Vector3, Vector4, Zero,
+};
 use winit::{
-    dpi::PhysicalSize,
-    event::{ElementState, KeyboardInput, VirtualKeyCode, WindowEvent},
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{MouseScrollDelta, TouchPhase, WindowEvent},
 };
 
+use crate::{bounds::Bounds, keymap::Action};
+
 #[rustfmt::skip]
 pub const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
     1.0, 0.0, 0.0, 0.0,
@@ -15,30 +22,420 @@ pub const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
 
 pub struct Camera {
     pub eye: Point2<f32>,
+    /// Size of the window's drawable area, in physical pixels.
     pub viewport_size: PhysicalSize<u32>,
+    /// The window's DPI scale factor, so that zoom is the same on high-DPI screens.
+    pub scale_factor: f64,
+    /// Logical pixels per world unit.
     pub zoom: f32,
+    /// A fixed width-to-height ratio to draw at, with bars filling the rest of the window. `None`
+    /// uses the whole window.
+    pub aspect_ratio: Option<f32>,
+    pub projection: Projection,
     pub znear: f32,
     pub zfar: f32,
+    /// An in-progress eased move of the eye, and perhaps the zoom, if any.
+    pub ease: Option<CameraEase>,
+    /// A close look being taken, to come back from once the move in has finished.
+    pub focus: Option<CameraFocus>,
+    /// Brief effects layered over the view, such as a shake.
+    pub effects: Vec<ActiveEffect>,
+    /// How strongly effects are shown, from 0 for not at all to 1 for in full.
+    pub effect_intensity: f32,
+}
+
+/// Vertical field of view used by the perspective projection.
+const PERSPECTIVE_FOVY: Deg<f32> = Deg(45.0);
+
+/// How the table is projected onto the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Looking straight down at the table.
+    Orthographic,
+    /// Looking down at the table from a seat, tilted towards the far edge by `tilt`.
+    Perspective { tilt: Rad<f32> },
+}
+
+impl Projection {
+    /// The perspective projection with a gentle tilt, as if sitting at a real table.
+    pub const TABLE: Projection = Projection::Perspective { tilt: Rad(0.35) };
+
+    /// Switches between the orthographic and table perspective projections.
+    pub fn toggled(&self) -> Projection {
+        match self {
+            Projection::Orthographic => Projection::TABLE,
+            Projection::Perspective { .. } => Projection::Orthographic,
+        }
+    }
+}
+
+/// The part of the window the table is drawn into, in physical pixels from the top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// A brief effect layered over the view. Effects move what's drawn, not where the camera looks,
+/// so what's under the pointer stays put.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// Jolting about by up to `strength` physical pixels, dying away.
+    Shake { strength: f32 },
+    /// Zooming in by `strength`, as a fraction of the view, and easing back out.
+    Punch { strength: f32 },
+}
+
+/// An effect being shown.
+#[derive(Debug, Clone, Copy)]
+pub struct ActiveEffect {
+    effect: Effect,
+    elapsed: Duration,
+    duration: Duration,
+}
+
+/// How many times a second a shake jolts back and forth, along each axis.
+const SHAKE_FREQUENCY: [f32; 2] = [23.0, 19.0];
+
+/// How far through a punch it's zoomed in furthest.
+const PUNCH_PEAK: f32 = 0.2;
+
+impl ActiveEffect {
+    /// The effect as a transform of clip space, at `intensity`, for a viewport `viewport`.
+    fn transform(&self, intensity: f32, viewport: &Viewport) -> Matrix4<f32> {
+        let t = (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0);
+        let ease = |t: f32| t * t * (3.0 - 2.0 * t);
+
+        match self.effect {
+            Effect::Shake { strength } => {
+                let seconds = self.elapsed.as_secs_f32();
+                let fade = (1.0 - t) * (1.0 - t) * strength * intensity;
+                let [x, y] = SHAKE_FREQUENCY
+                    .map(|frequency| (std::f32::consts::TAU * frequency * seconds).sin() * fade);
+                // Clip space is two units across the viewport.
+                Matrix4::from_translation(Vector3::new(
+                    2.0 * x / viewport.width,
+                    2.0 * y / viewport.height,
+                    0.0,
+                ))
+            }
+            Effect::Punch { strength } => {
+                let amount = match t < PUNCH_PEAK {
+                    true => ease(t / PUNCH_PEAK),
+                    false => 1.0 - ease((t - PUNCH_PEAK) / (1.0 - PUNCH_PEAK)),
+                };
+                let scale = 1.0 + strength * intensity * amount;
+                Matrix4::from_nonuniform_scale(scale, scale, 1.0)
+            }
+        }
+    }
+}
+
+/// Moves the camera's eye between two points over a fixed time, starting and finishing gently.
+pub struct CameraEase {
+    from: Point2<f32>,
+    to: Point2<f32>,
+    /// The zoom to ease from and to, or `None` to leave it be.
+    zoom: Option<(f32, f32)>,
+    elapsed: Duration,
+    duration: Duration,
+}
+
+impl CameraEase {
+    fn progress(&self) -> f32 {
+        let t = if self.duration.is_zero() {
+            1.0
+        } else {
+            (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+        };
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    fn position(&self) -> Point2<f32> {
+        self.from + (self.to - self.from) * self.progress()
+    }
+
+    /// The zoom so far, if it's being eased. Zooming goes by ratio, so that it feels as quick
+    /// close up as it does far away.
+    fn zoom(&self) -> Option<f32> {
+        self.zoom
+            .map(|(from, to)| from * (to / from).powf(self.progress()))
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+/// Where the camera was before taking a close look, and how to come back to it.
+pub struct CameraFocus {
+    eye: Point2<f32>,
+    zoom: f32,
+    /// How long is left to linger before coming back.
+    hold: Duration,
+    duration: Duration,
 }
 
 impl Camera {
+    /// Centres the view on `bounds` and zooms so that all of it, plus `padding` world units on
+    /// every side, is visible whatever the viewport's aspect ratio.
+    pub fn fit_to_bounds(&mut self, bounds: Bounds, padding: f32) {
+        self.cancel_ease();
+        self.eye = bounds.center();
+        self.zoom = self.zoom_to_fit(bounds, padding);
+    }
+
+    /// The zoom at which all of `bounds`, plus `padding` world units on every side, just fits in
+    /// the viewport.
+    pub fn zoom_to_fit(&self, bounds: Bounds, padding: f32) -> f32 {
+        let width = bounds.width() + 2.0 * padding;
+        let height = bounds.height() + 2.0 * padding;
+
+        let viewport = self.viewport();
+        (viewport.width / width).min(viewport.height / height) / self.scale_factor as f32
+    }
+
+    /// The area of the window that is drawn into: all of it, or the largest centred rectangle
+    /// with the requested aspect ratio, leaving letterbox or pillarbox bars either side.
+    pub fn viewport(&self) -> Viewport {
+        let window_width = self.viewport_size.width as f32;
+        let window_height = self.viewport_size.height as f32;
+
+        let (width, height) = match self.aspect_ratio {
+            Some(aspect) if window_width / window_height > aspect => {
+                (window_height * aspect, window_height)
+            }
+            Some(aspect) => (window_width, window_width / aspect),
+            None => (window_width, window_height),
+        };
+
+        Viewport {
+            x: (window_width - width) / 2.0,
+            y: (window_height - height) / 2.0,
+            width,
+            height,
+        }
+    }
+
+    /// Physical pixels per world unit.
+    pub fn pixels_per_unit(&self) -> f32 {
+        self.zoom * self.scale_factor as f32
+    }
+
+    /// Smoothly moves the eye to `target` over `duration`, replacing any move already underway.
+    pub fn ease_to(&mut self, target: Point2<f32>, duration: Duration) {
+        self.focus = None;
+        self.ease = Some(CameraEase {
+            from: self.eye,
+            to: target,
+            zoom: None,
+            elapsed: Duration::ZERO,
+            duration,
+        });
+    }
+
+    /// Smoothly moves the eye to `target` while zooming to `zoom` over `duration`, replacing any
+    /// move already underway.
+    pub fn zoom_to(&mut self, target: Point2<f32>, zoom: f32, duration: Duration) {
+        self.focus = None;
+        self.ease = Some(CameraEase {
+            from: self.eye,
+            to: target,
+            zoom: Some((self.zoom, zoom)),
+            elapsed: Duration::ZERO,
+            duration,
+        });
+    }
+
+    /// Zooms onto `target` over `duration`, lingers there for `hold`, then comes back to where
+    /// the camera was just as smoothly.
+    pub fn focus_on(&mut self, target: Point2<f32>, zoom: f32, duration: Duration, hold: Duration) {
+        // Coming back from one close look straight into another still ends where it started.
+        let (eye, from_zoom) = match self.focus.take() {
+            Some(focus) => (focus.eye, focus.zoom),
+            None => (self.eye, self.zoom),
+        };
+        let focus = CameraFocus {
+            eye,
+            zoom: from_zoom,
+            hold,
+            duration,
+        };
+        self.zoom_to(target, zoom, duration);
+        self.focus = Some(focus);
+    }
+
+    /// Jumps to the end of any eased move, back out of any close look.
+    pub fn finish_ease(&mut self) {
+        if let Some(ease) = self.ease.take() {
+            self.eye = ease.to;
+            if let Some((_, zoom)) = ease.zoom {
+                self.zoom = zoom;
+            }
+        }
+        if let Some(focus) = self.focus.take() {
+            self.eye = focus.eye;
+            self.zoom = focus.zoom;
+        }
+    }
+
+    /// The zoom the camera settles back to: the one from before any close look being taken, or
+    /// else the zoom as it is.
+    pub fn resting_zoom(&self) -> f32 {
+        self.focus.as_ref().map_or(self.zoom, |focus| focus.zoom)
+    }
+
+    /// Stops any eased move where it is, without coming back from any close look.
+    pub fn cancel_ease(&mut self) {
+        self.ease = None;
+        self.focus = None;
+    }
+
+    /// Shows `effect` over the view for `duration`, along with any others already showing.
+    pub fn add_effect(&mut self, effect: Effect, duration: Duration) {
+        self.effects.push(ActiveEffect {
+            effect,
+            elapsed: Duration::ZERO,
+            duration,
+        });
+    }
+
+    /// Advances any eased move and effects by `dt`.
+    pub fn update(&mut self, dt: Duration) {
+        if let Some(ease) = &mut self.ease {
+            ease.elapsed += dt;
+            self.eye = ease.position();
+            if let Some(zoom) = ease.zoom() {
+                self.zoom = zoom;
+            }
+
+            if ease.is_finished() {
+                self.ease = None;
+            }
+        } else if let Some(focus) = &mut self.focus {
+            focus.hold = focus.hold.saturating_sub(dt);
+            if focus.hold.is_zero() {
+                let (eye, zoom, duration) = (focus.eye, focus.zoom, focus.duration);
+                self.zoom_to(eye, zoom, duration);
+            }
+        }
+
+        self.effects.retain_mut(|effect| {
+            effect.elapsed += dt;
+            effect.elapsed < effect.duration
+        });
+    }
+
+    /// The view and projection with the effects showing layered over them, for drawing with.
+    pub fn build_effects_view_projection_matrix(&self) -> Matrix4<f32> {
+        let viewport = self.viewport();
+        self.effects
+            .iter()
+            .map(|effect| effect.transform(self.effect_intensity, &viewport))
+            .fold(Matrix4::identity(), |combined, effect| effect * combined)
+            * self.build_view_projection_matrix()
+    }
+
     pub fn build_view_projection_matrix(&self) -> Matrix4<f32> {
-        let eye_3d = Point3::new(self.eye.x, self.eye.y, 0.0);
-        let view = Matrix4::look_at_rh(eye_3d + Vector3::unit_z(), eye_3d, Vector3::unit_y());
-
-        let width = (self.viewport_size.width as f32) / self.zoom;
-        let height = (self.viewport_size.height as f32) / self.zoom;
-        let proj = ortho(
-            -width / 2.0,
-            width / 2.0,
-            -height / 2.0,
-            height / 2.0,
-            self.znear,
-            self.zfar,
-        );
+        let viewport = self.viewport();
+        let width = viewport.width / self.pixels_per_unit();
+        let height = viewport.height / self.pixels_per_unit();
+        let target = Point3::new(self.eye.x, self.eye.y, 0.0);
+
+        let (view, proj) = match self.projection {
+            Projection::Orthographic => {
+                let view =
+                    Matrix4::look_at_rh(target + Vector3::unit_z(), target, Vector3::unit_y());
+                let proj = ortho(
+                    -width / 2.0,
+                    width / 2.0,
+                    -height / 2.0,
+                    height / 2.0,
+                    self.znear,
+                    self.zfar,
+                );
+
+                (view, proj)
+            }
+            Projection::Perspective { tilt } => {
+                // Back off far enough that the table at the eye is shown at the same scale as the
+                // orthographic projection would show it.
+                let distance = height / 2.0 / (PERSPECTIVE_FOVY / 2.0).tan();
+                let (sin, cos) = tilt.sin_cos();
+                let position = target + Vector3::new(0.0, -sin, cos) * distance;
+
+                let view = Matrix4::look_at_rh(position, target, Vector3::unit_y());
+                let proj = perspective(
+                    PERSPECTIVE_FOVY,
+                    width / height,
+                    distance * 0.1,
+                    distance * 10.0,
+                );
+
+                (view, proj)
+            }
+        };
 
         OPENGL_TO_WGPU_MATRIX * proj * view
     }
+
+    /// Converts a position in window pixels (origin top-left, y down) into the point on the
+    /// table (z = 0) under it.
+    pub fn screen_to_world(&self, position: PhysicalPosition<f64>) -> Point2<f32> {
+        let viewport = self.viewport();
+        let ndc_x = (position.x as f32 - viewport.x) / viewport.width * 2.0 - 1.0;
+        let ndc_y = 1.0 - (position.y as f32 - viewport.y) / viewport.height * 2.0;
+
+        let Some(inverse) = self.build_view_projection_matrix().invert() else {
+            return self.eye;
+        };
+        let unproject = |depth: f32| {
+            let point = inverse * Vector4::new(ndc_x, ndc_y, depth, 1.0);
+            point.truncate() / point.w
+        };
+
+        // Cast a ray from the near plane to the far plane and see where it crosses the table.
+        let near = unproject(0.0);
+        let far = unproject(1.0);
+        let t = near.z / (near.z - far.z);
+        let hit = near + (far - near) * t;
+
+        Point2::new(hit.x, hit.y)
+    }
+
+    /// Converts a point on the table (z = 0) into a position in window pixels (origin top-left,
+    /// y down), the other way to `screen_to_world`.
+    pub fn world_to_screen(&self, point: Point2<f32>) -> PhysicalPosition<f32> {
+        let viewport = self.viewport();
+        let clip = self.build_view_projection_matrix() * Vector4::new(point.x, point.y, 0.0, 1.0);
+        let ndc = clip.truncate() / clip.w;
+
+        PhysicalPosition::new(
+            viewport.x + (ndc.x + 1.0) / 2.0 * viewport.width,
+            viewport.y + (1.0 - ndc.y) / 2.0 * viewport.height,
+        )
+    }
+
+    /// The rectangle on screen that `bounds` covers, as its corner and size in physical pixels.
+    pub fn bounds_to_screen(&self, bounds: Bounds) -> ([f32; 2], [f32; 2]) {
+        let Bounds { min, max } = bounds;
+        let corners = [
+            min,
+            Point2::new(max.x, min.y),
+            Point2::new(min.x, max.y),
+            max,
+        ]
+        .map(|corner| self.world_to_screen(corner))
+        .map(|corner| Point2::new(corner.x, corner.y));
+        let screen = Bounds::from_points(corners).expect("bounds always have corners");
+
+        (
+            [screen.min.x, screen.min.y],
+            [screen.width(), screen.height()],
+        )
+    }
 }
 
 #[repr(C)]
@@ -55,78 +452,271 @@ impl CameraUniform {
     }
 
     pub fn update_view_proj(&mut self, camera: &Camera) {
-        self.view_proj = camera.build_view_projection_matrix().into();
+        self.view_proj = camera.build_effects_view_projection_matrix().into();
     }
 }
 
+/// Seconds taken to accelerate from rest to full speed.
+const ACCELERATION_TIME: f32 = 0.15;
+
+/// Rate at which the camera coasts to a stop once movement keys are released, per second.
+const DAMPING: f32 = 12.0;
+
+/// How long pressing Home takes to bring the camera back to the centre of the table.
+const RECENTRE_DURATION: Duration = Duration::from_millis(400);
+
+/// How much one notch of the scroll wheel zooms by.
+const ZOOM_STEP: f32 = 1.1;
+
+/// How many pixels of a pixel-precise scroll (touchpads, pinch gestures) count as one notch.
+const PIXELS_PER_NOTCH: f32 = 50.0;
+
+/// Two fingers moving on the table since the camera was last updated.
+#[derive(Debug, Clone, Copy)]
+struct Pinch {
+    /// Where the point between the fingers was, and where it is now.
+    from: PhysicalPosition<f64>,
+    to: PhysicalPosition<f64>,
+    /// How many times further apart the fingers are now.
+    scale: f32,
+}
+
 pub struct CameraController {
+    /// Top panning speed, in world units per second.
     speed: f32,
+    zoom_limits: RangeInclusive<f32>,
     is_forward_pressed: bool,
     is_backward_pressed: bool,
     is_left_pressed: bool,
     is_right_pressed: bool,
+    is_recentre_requested: bool,
+    is_projection_toggle_requested: bool,
+    velocity: Vector2<f32>,
+    /// Which way a stick is panning, if one is.
+    stick: Vector2<f32>,
+    cursor_position: PhysicalPosition<f64>,
+    pending_zoom_notches: f32,
+    /// The fingers on the screen, by id, and where each last was, in the order they came down.
+    touches: Vec<(u64, PhysicalPosition<f64>)>,
+    /// Whether two fingers have been down together since the screen was last let go of, which
+    /// makes every touch until then part of a pan and pinch.
+    pinching: bool,
+    pending_pinch: Option<Pinch>,
+    /// Whether the camera jumps where it's sent rather than easing there.
+    reduced_motion: bool,
 }
 
 impl CameraController {
-    pub fn new(speed: f32) -> Self {
+    pub fn new(speed: f32, zoom_limits: RangeInclusive<f32>) -> Self {
         Self {
             speed,
+            zoom_limits,
             is_forward_pressed: false,
             is_backward_pressed: false,
             is_left_pressed: false,
             is_right_pressed: false,
+            is_recentre_requested: false,
+            is_projection_toggle_requested: false,
+            velocity: Vector2::zero(),
+            stick: Vector2::zero(),
+            cursor_position: PhysicalPosition::new(0.0, 0.0),
+            pending_zoom_notches: 0.0,
+            touches: Vec::new(),
+            pinching: false,
+            pending_pinch: None,
+            reduced_motion: false,
         }
     }
 
+    /// Has the camera jump where it's sent rather than easing there, or not.
+    pub fn set_reduced_motion(&mut self, reduced_motion: bool) {
+        self.reduced_motion = reduced_motion;
+    }
+
+    /// Pans, zooms or recentres for `action`, its key having been pressed or released. Returns
+    /// whether the action moves the camera.
+    pub fn act(&mut self, action: Action, pressed: bool) -> bool {
+        match action {
+            Action::PanUp => self.is_forward_pressed = pressed,
+            Action::PanLeft => self.is_left_pressed = pressed,
+            Action::PanDown => self.is_backward_pressed = pressed,
+            Action::PanRight => self.is_right_pressed = pressed,
+            Action::ZoomIn if pressed => self.pending_zoom_notches += 1.0,
+            Action::ZoomOut if pressed => self.pending_zoom_notches -= 1.0,
+            Action::Recentre => self.is_recentre_requested |= pressed,
+            Action::ToggleProjection => self.is_projection_toggle_requested |= pressed,
+            Action::ZoomIn | Action::ZoomOut => {}
+            _ => return false,
+        }
+        true
+    }
+
+    /// Pans `stick`'s way, as well as any way the keys say, until it's set back to zero.
+    #[cfg(feature = "gamepad")]
+    pub fn set_stick(&mut self, stick: Vector2<f32>) {
+        self.stick = stick;
+    }
+
     pub fn process_events(&mut self, event: &WindowEvent) -> bool {
         match event {
-            WindowEvent::KeyboardInput {
-                input:
-                    KeyboardInput {
-                        state,
-                        virtual_keycode: Some(keycode),
-                        ..
-                    },
-                ..
-            } => {
-                let is_pressed = *state == ElementState::Pressed;
-                match keycode {
-                    VirtualKeyCode::W | VirtualKeyCode::Up => {
-                        self.is_forward_pressed = is_pressed;
-                        true
-                    }
-                    VirtualKeyCode::A | VirtualKeyCode::Left => {
-                        self.is_left_pressed = is_pressed;
-                        true
-                    }
-                    VirtualKeyCode::S | VirtualKeyCode::Down => {
-                        self.is_backward_pressed = is_pressed;
-                        true
-                    }
-                    VirtualKeyCode::D | VirtualKeyCode::Right => {
-                        self.is_right_pressed = is_pressed;
-                        true
-                    }
-                    _ => false,
-                }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = *position;
+                false
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.pending_zoom_notches += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_NOTCH,
+                };
+                true
+            }
+            WindowEvent::Touch(touch) => {
+                let was_pinching = self.pinching;
+                self.touch(touch.id, touch.phase, touch.location);
+                was_pinching || self.pinching
             }
             _ => false,
         }
     }
 
-    pub fn update_camera(&self, camera: &mut Camera) {
+    /// Follows a finger, panning and zooming for two fingers moving together. With more than two
+    /// down, only the first two count.
+    fn touch(&mut self, id: u64, phase: TouchPhase, location: PhysicalPosition<f64>) {
+        let before = self.finger_span();
+        match phase {
+            TouchPhase::Started => self.touches.push((id, location)),
+            TouchPhase::Moved => {
+                if let Some(touch) = self.touches.iter_mut().find(|(touch, _)| *touch == id) {
+                    touch.1 = location;
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.touches.retain(|(touch, _)| *touch != id);
+            }
+        }
+
+        if self.touches.len() >= 2 {
+            self.pinching = true;
+        } else if self.touches.is_empty() {
+            self.pinching = false;
+        }
+
+        let (Some((from, from_distance)), Some((to, to_distance)), TouchPhase::Moved) =
+            (before, self.finger_span(), phase)
+        else {
+            return;
+        };
+        let scale = match from_distance > 0.0 {
+            true => (to_distance / from_distance) as f32,
+            false => 1.0,
+        };
+        self.pending_pinch = Some(match self.pending_pinch {
+            Some(pinch) => Pinch {
+                to,
+                scale: pinch.scale * scale,
+                ..pinch
+            },
+            None => Pinch { from, to, scale },
+        });
+    }
+
+    /// The point between the first two fingers down, and how far apart they are, if there are
+    /// two.
+    fn finger_span(&self) -> Option<(PhysicalPosition<f64>, f64)> {
+        let [(_, a), (_, b)] = self.touches.get(..2)? else {
+            return None;
+        };
+        let middle = PhysicalPosition::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+        Some((middle, (a.x - b.x).hypot(a.y - b.y)))
+    }
+
+    pub fn update_camera(&mut self, camera: &mut Camera, dt: Duration) {
+        if self.pending_zoom_notches != 0.0 {
+            camera.cancel_ease();
+            let zoom = camera.zoom * ZOOM_STEP.powf(self.pending_zoom_notches);
+            self.zoom_about(camera, zoom, self.cursor_position, self.cursor_position);
+            self.pending_zoom_notches = 0.0;
+        }
+
+        if let Some(pinch) = self.pending_pinch.take() {
+            self.velocity = Vector2::zero();
+            camera.cancel_ease();
+            let zoom = camera.zoom * pinch.scale;
+            self.zoom_about(camera, zoom, pinch.from, pinch.to);
+        }
+
+        if self.is_recentre_requested {
+            self.velocity = Vector2::zero();
+            match self.reduced_motion {
+                true => {
+                    camera.cancel_ease();
+                    camera.eye = Point2::origin();
+                }
+                false => camera.ease_to(Point2::origin(), RECENTRE_DURATION),
+            }
+            self.is_recentre_requested = false;
+        }
+
+        if self.is_projection_toggle_requested {
+            camera.projection = camera.projection.toggled();
+            self.is_projection_toggle_requested = false;
+        }
+
+        let dt = dt.as_secs_f32();
+        let direction = self.input_direction();
+
+        if direction.is_zero() {
+            self.velocity *= (-DAMPING * dt).exp();
+        } else {
+            camera.cancel_ease();
+            self.velocity += direction.normalize() * (self.speed / ACCELERATION_TIME * dt);
+            if self.velocity.magnitude() > self.speed {
+                self.velocity = self.velocity.normalize_to(self.speed);
+            }
+        }
+
+        camera.eye += self.velocity * dt;
+    }
+
+    /// The direction the held movement keys and any stick point in, which is zero if none are
+    /// held or they cancel out.
+    fn input_direction(&self) -> Vector2<f32> {
+        let mut direction = self.stick;
+
         if self.is_forward_pressed {
-            camera.eye += Vector2::unit_y() * self.speed;
+            direction += Vector2::unit_y();
         }
         if self.is_backward_pressed {
-            camera.eye -= Vector2::unit_y() * self.speed;
+            direction -= Vector2::unit_y();
         }
 
         if self.is_right_pressed {
-            camera.eye += Vector2::unit_x() * self.speed;
+            direction += Vector2::unit_x();
         }
         if self.is_left_pressed {
-            camera.eye -= Vector2::unit_x() * self.speed;
+            direction -= Vector2::unit_x();
         }
+
+        direction
+    }
+
+    /// Changes the camera's zoom, moving the eye so the point that was at `from` on screen ends up
+    /// at `to`: for the scroll wheel, the point under the cursor stays put, and for a pinch, the
+    /// point between the fingers follows them.
+    /// `zoom` brought within the limits the view can be zoomed to.
+    pub fn clamp_zoom(&self, zoom: f32) -> f32 {
+        zoom.clamp(*self.zoom_limits.start(), *self.zoom_limits.end())
+    }
+
+    fn zoom_about(
+        &self,
+        camera: &mut Camera,
+        zoom: f32,
+        from: PhysicalPosition<f64>,
+        to: PhysicalPosition<f64>,
+    ) {
+        let anchor = camera.screen_to_world(from);
+        camera.zoom = self.clamp_zoom(zoom);
+        camera.eye += anchor - camera.screen_to_world(to);
     }
 }
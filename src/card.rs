@@ -1,71 +1,151 @@
-use cgmath::Vector3;
-use strum::EnumIter;
+use cgmath::{Angle, Matrix4, Point2, Rad, Vector2, Vector3};
+use strum::IntoEnumIterator;
 use wgpu::{
     BufferUsages, Device, VertexAttribute, VertexBufferLayout, VertexFormat, VertexStepMode,
 };
 
-use crate::{attributes, errors::*, util::create_buffer};
+use crate::{attributes, bounds::Bounds, errors::*, texture::Atlas, util::create_buffer};
 
-#[derive(Debug, Clone, Copy, EnumIter)]
-pub enum Suit {
-    Clubs,
-    Spades,
-    Hearts,
-    Diamonds,
-}
-
-impl Suit {
-    pub fn doppelkopf_suit_strength(&self) -> u8 {
-        match self {
-            Suit::Clubs => 4,
-            Suit::Spades => 3,
-            Suit::Hearts => 2,
-            Suit::Diamonds => 1,
-        }
-    }
+pub use cards_core::{
+    card::{Rank, Suit},
+    game::Badge,
+};
 
-    pub fn texture_index(&self) -> u8 {
-        match self {
-            Suit::Clubs => 3,
-            Suit::Spades => 2,
-            Suit::Hearts => 0,
-            Suit::Diamonds => 1,
-        }
+/// The row of the card sheet holding a suit's faces.
+pub fn texture_index(suit: Suit) -> u8 {
+    match suit {
+        Suit::Clubs => 3,
+        Suit::Spades => 2,
+        Suit::Hearts => 0,
+        Suit::Diamonds => 1,
     }
 }
 
-type Rank = u8;
-
 pub struct Card {
     pub position: Vector3<i32>,
+    /// Rotation about the card's centre, anticlockwise.
+    pub rotation: Rad<f32>,
+    pub scale: Vector2<f32>,
     pub facedown: bool,
+    /// Whether the card is under the pointer and should be drawn brightened.
+    pub highlighted: bool,
+    /// How far the card is brightened as part of a hint, from 0 to 1, pulsing while it's shown.
+    pub glow: f32,
+    /// Whether the card is drawn with a selection outline.
+    pub selected: bool,
+    /// Whether the card is underlined as a trump.
+    pub trump: bool,
+    /// Whether the card's face shimmers like foil.
+    pub foil: bool,
+    /// Whether the card glows around its edge as one the player can play now.
+    pub playable: bool,
+    /// What the card is marked with in its corner, for one the player can move now.
+    pub badge: Option<Badge>,
+    /// A colour the card is multiplied by, white to leave it as it is.
+    pub tint: [f32; 3],
+    /// How far the card is greyed out, from 0 (in full colour) to 1 (all grey).
+    pub desaturation: f32,
+    /// The layer of the card texture array to draw the card from.
+    pub layer: u32,
+    /// How far the card is lifted off the table, from 0 (resting) to 1 (held), which spreads and
+    /// softens its shadow.
+    pub elevation: f32,
     pub rank: Rank,
     pub suit: Suit,
 }
 
 impl Card {
+    /// The world-space bounding box of the card as drawn, including rotation and scale.
+    pub fn bounds(&self) -> Bounds {
+        let centre = Point2::new(self.position.x as f32, self.position.y as f32);
+        let (sin, cos) = self.rotation.sin_cos();
+        let half_width = WIDTH as f32 / 2.0 * self.scale.x;
+        let half_height = HEIGHT as f32 / 2.0 * self.scale.y;
+
+        let corners = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)].map(|(x, y)| {
+            let (x, y) = (x * half_width, y * half_height);
+            centre + Vector2::new(x * cos - y * sin, x * sin + y * cos)
+        });
+
+        Bounds::from_points(corners).expect("a card always has corners")
+    }
+
+    /// Transforms a world-space point into the card's own frame, where the card spans
+    /// `-WIDTH / 2..WIDTH / 2` horizontally and `-HEIGHT / 2..HEIGHT / 2` vertically.
+    pub fn to_local(&self, point: Point2<f32>) -> Point2<f32> {
+        let offset = Vector2::new(
+            point.x - self.position.x as f32,
+            point.y - self.position.y as f32,
+        );
+        let (sin, cos) = (-self.rotation).sin_cos();
+
+        Point2::new(
+            (offset.x * cos - offset.y * sin) / self.scale.x,
+            (offset.x * sin + offset.y * cos) / self.scale.y,
+        )
+    }
+
+    /// How far a world-space point lies outside the card's edges. Points on or inside the card
+    /// give zero.
+    pub fn distance_outside(&self, point: Point2<f32>) -> f32 {
+        let local = self.to_local(point);
+        let dx = (local.x.abs() - WIDTH as f32 / 2.0).max(0.0) * self.scale.x;
+        let dy = (local.y.abs() - HEIGHT as f32 / 2.0).max(0.0) * self.scale.y;
+
+        (dx * dx + dy * dy).sqrt()
+    }
+
     pub fn to_instance(&self) -> Result<Instance> {
+        let translation = Matrix4::from_translation(
+            self.position
+                .cast()
+                .chain_err(|| "couldn't cast card position vector")?,
+        );
+        let rotation = Matrix4::from_angle_z(self.rotation);
+        let scale = Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, 1.0);
+
         Ok(Instance {
-            model: cgmath::Matrix4::from_translation(
-                self.position
-                    .cast()
-                    .chain_err(|| "couldn't cast card position vector")?,
-            )
-            .into(),
+            model: (translation * rotation * scale).into(),
             rank: self.rank as u32,
-            suit: self.suit.texture_index() as u32,
-            facedown: self.facedown as u32,
+            suit: texture_index(self.suit) as u32,
+            flags: [
+                (self.facedown, FLAG_FACEDOWN),
+                (self.selected, FLAG_SELECTED),
+                (self.trump, FLAG_TRUMP),
+                (self.foil, FLAG_FOIL),
+                (self.playable, FLAG_PLAYABLE),
+            ]
+            .into_iter()
+            .filter(|&(set, _)| set)
+            .fold(0, |flags, (_, flag)| flags | flag),
+            highlight: if self.highlighted { 1.0 } else { self.glow },
+            layer: self.layer,
+            elevation: self.elevation,
+            modulate: [self.tint[0], self.tint[1], self.tint[2], self.desaturation],
         })
     }
 }
 
+/// The bits of an instance's `flags`. Mirrors the shaders' `FLAG_` constants.
+const FLAG_FACEDOWN: u32 = 1;
+const FLAG_SELECTED: u32 = 2;
+const FLAG_TRUMP: u32 = 4;
+const FLAG_FOIL: u32 = 8;
+const FLAG_PLAYABLE: u32 = 16;
+
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Instance {
     model: [[f32; 4]; 4],
     rank: u32,
     suit: u32,
-    facedown: u32,
+    /// The `FLAG_` bits for what's true of the card.
+    flags: u32,
+    highlight: f32,
+    layer: u32,
+    elevation: f32,
+    /// The tint, with the desaturation in place of alpha.
+    modulate: [f32; 4],
 }
 
 impl Instance {
@@ -84,6 +164,10 @@ impl Instance {
                 VertexFormat::Uint32,
                 VertexFormat::Uint32,
                 VertexFormat::Uint32,
+                VertexFormat::Float32,
+                VertexFormat::Uint32,
+                VertexFormat::Float32,
+                VertexFormat::Float32x4,
             ),
         }
     };
@@ -112,6 +196,84 @@ impl Vertex {
 pub const WIDTH: u32 = 34;
 pub const HEIGHT: u32 = 48;
 
+/// The radius of a card's rounded corners, in texels.
+pub const CORNER_RADIUS: f32 = 3.0;
+
+/// How card edges are drawn, in texels, and how the suits are told apart. This is the layout of
+/// the shader's `style` uniform.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CardStyle {
+    border_color: [f32; 4],
+    corner_radius: f32,
+    border_width: f32,
+    /// Whether diamonds are drawn blue and clubs green, so that no two suits share a colour.
+    four_colour: u32,
+    /// Whether a large mark for the suit is drawn in the top-right corner of each card.
+    suit_glyphs: u32,
+    /// Whether the rank is drawn again in large print over the top-left corner of each card.
+    large_ranks: u32,
+    /// How many frames the card back's animation has, along the sheet from the back's cell.
+    back_frames: u32,
+    /// How long each frame of the card back's animation is shown, in seconds.
+    back_frame_time: f32,
+    _padding: [u32; 1],
+}
+
+impl CardStyle {
+    /// Rounded corners and no border.
+    pub const fn rounded(corner_radius: f32) -> Self {
+        Self {
+            border_color: [0.0; 4],
+            corner_radius,
+            border_width: 0.0,
+            four_colour: 0,
+            suit_glyphs: 0,
+            large_ranks: 0,
+            back_frames: 1,
+            back_frame_time: 1.0,
+            _padding: [0; 1],
+        }
+    }
+
+    /// Adds a border inside the card's edge, blended over the card by the colour's alpha.
+    pub const fn with_border(self, width: f32, color: [f32; 4]) -> Self {
+        Self {
+            border_color: color,
+            border_width: width,
+            ..self
+        }
+    }
+
+    /// Tells the suits apart by four colours rather than two, by a large mark in the corner, or
+    /// both.
+    pub const fn with_suit_marks(self, four_colour: bool, suit_glyphs: bool) -> Self {
+        Self {
+            four_colour: four_colour as u32,
+            suit_glyphs: suit_glyphs as u32,
+            ..self
+        }
+    }
+
+    /// Cycles the card back through `frames` cells of the sheet, starting at the back's own and
+    /// going right, showing each for `frame_time` seconds.
+    pub const fn with_back_animation(self, frames: u32, frame_time: f32) -> Self {
+        Self {
+            back_frames: frames,
+            back_frame_time: frame_time,
+            ..self
+        }
+    }
+
+    /// Draws each card's rank in large print, for cards that are hard to read.
+    pub const fn with_large_ranks(self, large_ranks: bool) -> Self {
+        Self {
+            large_ranks: large_ranks as u32,
+            ..self
+        }
+    }
+}
+
 pub const VERTICES: &[Vertex] = {
     macro_rules! vert {
         ($x:expr, $y:expr $(,)?) => {{
@@ -136,6 +298,58 @@ pub const INDICES: &[u16] = &[
     0, 3, 1,
 ];
 
+/// The card sheet has a column per rank and a row per suit, with the card backs on the row below.
+pub const SHEET_COLUMNS: u32 = 13;
+pub const SHEET_ROWS: u32 = 5;
+pub const BACK_ROW: u32 = 4;
+
+/// The atlas for a card sheet laid out in the standard grid.
+pub fn sheet_atlas(width: u32, height: u32) -> Atlas {
+    Atlas::grid([width, height], SHEET_COLUMNS, SHEET_ROWS, [0, BACK_ROW])
+}
+
+/// Checks that an atlas has a correctly shaped cell for every card face and the card back, so a bad
+/// sheet is reported up front rather than sampled at the wrong offsets.
+pub fn validate_sheet(atlas: &Atlas) -> Result<()> {
+    let mut problems = Vec::new();
+
+    let [cell_width, cell_height] = atlas.cell_size;
+    if cell_width * HEIGHT != cell_height * WIDTH {
+        problems.push(format!(
+            "cells are {cell_width}x{cell_height}, which isn't the {WIDTH}:{HEIGHT} shape of a card"
+        ));
+    }
+
+    let [columns, rows] = atlas.cell_counts();
+    if columns < SHEET_COLUMNS {
+        problems.push(format!(
+            "only {columns} columns fit on the sheet, but there are {SHEET_COLUMNS} ranks"
+        ));
+    }
+
+    let [back_column, back_row] = atlas.back;
+    if back_column >= columns || back_row >= rows {
+        problems.push(format!(
+            "the card back at column {back_column}, row {back_row} is off the sheet"
+        ));
+    }
+
+    for suit in Suit::iter() {
+        let row = texture_index(suit) as u32;
+        if row >= rows {
+            problems.push(format!("{suit:?} faces at row {row} are off the sheet"));
+        } else if row == back_row {
+            problems.push(format!("{suit:?} faces share row {row} with the card back"));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        bail!(ErrorKind::InvalidCardSheet(problems))
+    }
+}
+
 pub fn create_vertex_buffer(device: &Device) -> wgpu::Buffer {
     create_buffer(device, "Card Vertex Buffer", VERTICES, BufferUsages::VERTEX)
 }
@@ -0,0 +1,203 @@
+//! Talking to the other players in a room on a server: a panel down the right of the window with
+//! what's been said, above a line to type into. The panel takes the keyboard while it's open,
+//! including text composed with an input method.
+
+use cards_core::protocol::MAX_SAY_LENGTH;
+use winit::event::{ElementState, Ime, KeyboardInput, VirtualKeyCode, WindowEvent};
+
+use crate::{
+    text::TextBrush,
+    ui::{Rect, Ui},
+};
+
+/// The height of a line of chat, in logical pixels.
+const TEXT_SIZE: f32 = 16.0;
+
+/// Space between the edge of the window, the panel and its text, in logical pixels.
+const MARGIN: f32 = 8.0;
+
+/// How many characters fit across the panel; longer lines carry on onto the next.
+const COLUMNS: usize = 36;
+
+/// How many lines of chat are kept.
+const HISTORY: usize = 200;
+
+/// What the chat made of an input event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatInput {
+    /// The event wasn't for the chat.
+    Ignored,
+    /// The chat took the event.
+    Taken,
+    /// The player asked to say this.
+    Say(String),
+    /// The player asked to close the chat.
+    Close,
+}
+
+pub struct Chat {
+    /// What's been said, each as who said it and what they said, oldest first.
+    lines: Vec<(String, String)>,
+    /// What the player is typing, and the text an input method is still composing after it.
+    draft: String,
+    preedit: String,
+    open: bool,
+    /// How many lines have come in since the chat was last open.
+    unread: usize,
+}
+
+impl Chat {
+    pub fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            draft: String::new(),
+            preedit: String::new(),
+            open: false,
+            unread: 0,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Opens or closes the chat, returning whether it's now open.
+    pub fn toggle(&mut self) -> bool {
+        self.open = !self.open;
+        self.unread = 0;
+        self.preedit.clear();
+        self.open
+    }
+
+    pub fn unread(&self) -> usize {
+        self.unread
+    }
+
+    /// Adds a line said by `who`.
+    pub fn receive(&mut self, who: String, text: String) {
+        self.lines.push((who, text));
+        if self.lines.len() > HISTORY {
+            self.lines.remove(0);
+        }
+        if !self.open {
+            self.unread += 1;
+        }
+    }
+
+    /// Takes keyboard and input method events while the chat is open. Enter says what's been
+    /// typed and Escape closes the chat; other keys are kept from the table.
+    pub fn input(&mut self, event: &WindowEvent) -> ChatInput {
+        if !self.open {
+            return ChatInput::Ignored;
+        }
+
+        match event {
+            // Control characters, such as those Enter and Backspace send on some platforms, are
+            // handled as keys below instead.
+            WindowEvent::ReceivedCharacter(c) if !c.is_control() => {
+                self.type_text(&c.to_string());
+                ChatInput::Taken
+            }
+            WindowEvent::ReceivedCharacter(_) => ChatInput::Taken,
+            WindowEvent::Ime(Ime::Preedit(text, _)) => {
+                self.preedit = text.clone();
+                ChatInput::Taken
+            }
+            WindowEvent::Ime(Ime::Commit(text)) => {
+                self.preedit.clear();
+                self.type_text(text);
+                ChatInput::Taken
+            }
+            WindowEvent::Ime(_) => {
+                self.preedit.clear();
+                ChatInput::Taken
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } => match key {
+                VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                    let text = std::mem::take(&mut self.draft);
+                    match text.trim() {
+                        "" => ChatInput::Taken,
+                        text => ChatInput::Say(text.to_owned()),
+                    }
+                }
+                VirtualKeyCode::Back => {
+                    self.draft.pop();
+                    ChatInput::Taken
+                }
+                VirtualKeyCode::Escape => ChatInput::Close,
+                _ => ChatInput::Taken,
+            },
+            WindowEvent::KeyboardInput { .. } => ChatInput::Taken,
+            _ => ChatInput::Ignored,
+        }
+    }
+
+    fn type_text(&mut self, text: &str) {
+        let room = MAX_SAY_LENGTH.saturating_sub(self.draft.chars().count());
+        self.draft.extend(text.chars().take(room));
+    }
+
+    /// Shows the chat in a panel down the right of a `window_width` physical pixels wide window,
+    /// above `bottom` pixels down, with the latest lines over the one being typed. Returns where
+    /// the text being typed ends, for the input method to show its window by.
+    pub fn draw(
+        &self,
+        ui: &mut Ui,
+        brush: &mut TextBrush,
+        window_width: f32,
+        bottom: f32,
+        scale: f32,
+    ) -> [f32; 2] {
+        let size = TEXT_SIZE * scale;
+        let margin = MARGIN * scale;
+
+        let width = COLUMNS as f32 * TextBrush::measure("M", size)[0];
+        let panel = Rect::new(
+            window_width - width - 3.0 * margin,
+            margin,
+            width + 2.0 * margin,
+            (bottom - 2.0 * margin).max(0.0),
+        );
+        ui.panel(brush, panel);
+        let inner = panel.inset(margin);
+
+        let draft = wrap(&format!("> {}{}_", self.draft, self.preedit));
+        let rows = (inner.height / size).max(0.0) as usize;
+        let history = rows.saturating_sub(draft.len());
+
+        let mut lines: Vec<String> = self
+            .lines
+            .iter()
+            .flat_map(|(who, text)| wrap(&format!("{who}: {text}")))
+            .collect();
+        lines.drain(..lines.len().saturating_sub(history));
+        let text = lines.join("\n");
+        ui.label(brush, &text, [inner.x, inner.y], size);
+
+        let draft_top = inner.y + inner.height - draft.len() as f32 * size;
+        ui.label(brush, &draft.join("\n"), [inner.x, draft_top], size);
+
+        let last = draft.last().map_or("", String::as_str);
+        [
+            inner.x + TextBrush::measure(last, size)[0],
+            inner.y + inner.height,
+        ]
+    }
+}
+
+/// `text` broken into lines of at most `COLUMNS` characters.
+fn wrap(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(COLUMNS)
+        .map(|line| line.iter().collect())
+        .collect()
+}
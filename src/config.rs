@@ -0,0 +1,267 @@
+//! The player's settings, kept as TOML in `cards.toml` in the working directory (native) or the
+//! browser's local storage (web). They're read at startup, written back when they're changed from
+//! the settings screen, and read again whenever they're changed from outside the app, so that the
+//! file can be edited while it's running.
+//!
+//! Every setting can be left out, for its default:
+//!
+//! ```toml
+//! vsync = true
+//! theme = "classic"
+//! language = "english"
+//! animation_speed = 1.0
+//! four_colour_suits = false
+//! suit_glyphs = false
+//! large_cards = false
+//! reduced_motion = false
+//! playable_glow = true
+//! auto_camera = true
+//!
+//! [window]
+//! width = 800
+//! height = 600
+//!
+//! [keys]
+//!
+//! [audio]
+//! master = 1.0
+//! effects = 1.0
+//! music = 0.5
+//!
+//! [look]
+//! table_hue = 0.0
+//! highlight = [1.0, 0.85, 0.3]
+//! vignette = 0.0
+//! shadow_softness = 1.0
+//! ```
+//!
+//! Themes saved from the theme screen are kept alongside, each with the built-in theme its cards
+//! come from and how it looks beyond them, and can be written by hand the same way:
+//!
+//! ```toml
+//! [themes.dusk]
+//! cards = "classic"
+//! table_hue = 200.0
+//! highlight = [0.4, 0.8, 1.0]
+//! vignette = 0.5
+//! shadow_softness = 1.5
+//! ```
+
+use std::{collections::BTreeMap, time::Duration};
+
+use instant::Instant;
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::*, locale::Language, theme::Look};
+
+#[cfg(not(target_arch = "wasm32"))]
+const CONFIG_PATH: &str = "cards.toml";
+
+#[cfg(target_arch = "wasm32")]
+const CONFIG_PATH: &str = "cards-config";
+
+/// How often the settings are looked at again for changes made outside the app.
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub window: WindowSettings,
+    /// Whether frames wait for the display to be ready for them, rather than going out as soon
+    /// as they're drawn.
+    pub vsync: bool,
+    /// The theme to start with, by name, or the first there is.
+    pub theme: Option<String>,
+    /// The language everything's shown in, or if it's not set, the system's if there are words
+    /// for it.
+    pub language: Option<Language>,
+    /// How fast cards move, as a multiple of their usual speed.
+    pub animation_speed: f32,
+    /// Whether diamonds are drawn blue and clubs green, so that no two suits share a colour.
+    pub four_colour_suits: bool,
+    /// Whether each card has a large mark for its suit in the corner.
+    pub suit_glyphs: bool,
+    /// Whether the cards are drawn bigger, with their ranks and suits in large print.
+    pub large_cards: bool,
+    /// Whether cards jump straight into place rather than moving there, and effects are left out,
+    /// or if it's not set, whether the system asks for less motion.
+    pub reduced_motion: Option<bool>,
+    /// Whether the cards the player can play glow around their edges.
+    pub playable_glow: bool,
+    /// Whether the camera moves onto the part of the table each phase of a game is played on,
+    /// such as the hand while picking cards to pass, or is left to the player.
+    pub auto_camera: bool,
+    /// The keys bound to each action, by the action's name, where they're not the usual ones.
+    pub keys: BTreeMap<String, String>,
+    pub audio: AudioSettings,
+    /// How the table, the glow and the shadows look, tuned from the theme screen. Picking a theme
+    /// sets it to the theme's own.
+    pub look: Look,
+    /// The themes saved from the theme screen, by name.
+    pub themes: BTreeMap<String, SavedTheme>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            window: WindowSettings::default(),
+            vsync: true,
+            theme: None,
+            language: None,
+            animation_speed: 1.0,
+            four_colour_suits: false,
+            suit_glyphs: false,
+            large_cards: false,
+            reduced_motion: None,
+            playable_glow: true,
+            auto_camera: true,
+            keys: BTreeMap::new(),
+            audio: AudioSettings::default(),
+            look: Look::DEFAULT,
+            themes: BTreeMap::new(),
+        }
+    }
+}
+
+/// A theme saved from the theme screen: the cards of a built-in theme, with a look of its own.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedTheme {
+    /// The name of the built-in theme the cards come from.
+    pub cards: String,
+    #[serde(flatten)]
+    pub look: Look,
+}
+
+/// The size the window opens at, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+        }
+    }
+}
+
+/// How loud each kind of sound is, from 0 to 1. Effects and music are both scaled by the master
+/// volume.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub effects: f32,
+    pub music: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            effects: 1.0,
+            music: 0.5,
+        }
+    }
+}
+
+/// The settings, and where they're kept.
+pub struct Config {
+    pub settings: Settings,
+    /// The settings as they were last read or written, to tell when they've been changed from
+    /// outside the app.
+    text: Option<String>,
+    checked: Instant,
+}
+
+impl Config {
+    /// Reads the settings, or starts from the defaults if there aren't any or they can't be read.
+    pub fn load() -> Self {
+        let text = read();
+        let settings = text.as_deref().map_or_else(Settings::default, |text| {
+            parse(text).unwrap_or_else(|e| {
+                log::warn!("ignoring settings that couldn't be read: {e}");
+                Settings::default()
+            })
+        });
+
+        Self {
+            settings,
+            text,
+            checked: Instant::now(),
+        }
+    }
+
+    /// Writes the settings back.
+    pub fn store(&mut self) -> Result<()> {
+        let text = toml::to_string(&self.settings).chain_err(|| "couldn't write the settings")?;
+        write(&text).chain_err(|| "couldn't save the settings")?;
+        self.text = Some(text);
+        Ok(())
+    }
+
+    /// Reads the settings again if they've been changed since they were last read or written,
+    /// checking every so often. Returns whether they were. Settings that can't be read are
+    /// ignored, keeping the ones from before.
+    pub fn reload(&mut self) -> bool {
+        if self.checked.elapsed() < RELOAD_INTERVAL {
+            return false;
+        }
+        self.checked = Instant::now();
+
+        let text = read();
+        if text == self.text {
+            return false;
+        }
+
+        let settings = match text.as_deref().map(parse) {
+            Some(Ok(settings)) => settings,
+            Some(Err(e)) => {
+                log::warn!("ignoring changed settings that couldn't be read: {e}");
+                self.text = text;
+                return false;
+            }
+            None => Settings::default(),
+        };
+        log::info!("settings changed, reloading");
+        self.text = text;
+        self.settings = settings;
+        true
+    }
+}
+
+fn parse(text: &str) -> std::result::Result<Settings, toml::de::Error> {
+    toml::from_str(text)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read() -> Option<String> {
+    std::fs::read_to_string(CONFIG_PATH).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read() -> Option<String> {
+    local_storage()?.get_item(CONFIG_PATH).ok()?
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(text: &str) -> Result<()> {
+    std::fs::write(CONFIG_PATH, text).chain_err(|| format!("couldn't write {CONFIG_PATH}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(text: &str) -> Result<()> {
+    local_storage()
+        .ok_or_else(|| Error::from("local storage isn't available"))?
+        .set_item(CONFIG_PATH, text)
+        .map_err(|_| "local storage refused the write".into())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
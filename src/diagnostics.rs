@@ -0,0 +1,236 @@
+use std::{collections::VecDeque, fmt::Write, sync::Mutex, time::Duration};
+
+use instant::Instant;
+use log::{LevelFilter, Log, Metadata, Record};
+use wgpu::AdapterInfo;
+
+use crate::{errors::*, quality::QualityPreset};
+
+/// How many recent log lines are kept for bug reports.
+const LOG_TAIL_LINES: usize = 200;
+
+/// Where new issues are filed.
+const ISSUE_URL: &str = "https://github.com/magnostherobot/cards/issues/new";
+
+/// Issue bodies longer than this are cut short, since browsers and GitHub both limit URL length.
+const MAX_ISSUE_BODY: usize = 6000;
+
+/// How much each new frame counts towards the average frame time. Lower is steadier but slower to
+/// follow changes.
+const FRAME_TIME_SMOOTHING: f32 = 0.1;
+
+static LOG_TAIL: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Passes records on to another logger, keeping a copy of the most recent ones.
+struct TailLogger<L> {
+    inner: L,
+}
+
+impl<L: Log> Log for TailLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        if let Ok(mut tail) = LOG_TAIL.lock() {
+            if tail.len() == LOG_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(format!(
+                "[{} {}] {}",
+                record.level(),
+                record.target(),
+                record.args()
+            ));
+        }
+
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Installs `logger` as the global logger, keeping its most recent lines for bug reports.
+pub fn install_logger(logger: impl Log + 'static, level: LevelFilter) {
+    log::set_boxed_logger(Box::new(TailLogger { inner: logger }))
+        .expect("Couldn't initialise logger");
+    log::set_max_level(level);
+}
+
+/// Everything gathered about the running game for a bug report.
+pub struct BugReport {
+    adapter: AdapterInfo,
+    quality: QualityPreset,
+    log_tail: Vec<String>,
+}
+
+impl BugReport {
+    pub fn gather(adapter: &AdapterInfo, quality: QualityPreset) -> Self {
+        let log_tail = LOG_TAIL
+            .lock()
+            .map(|tail| tail.iter().cloned().collect())
+            .unwrap_or_default();
+
+        Self {
+            adapter: adapter.clone(),
+            quality,
+            log_tail,
+        }
+    }
+
+    /// The report exactly as it will be shared.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        // Writing to a String can't fail.
+        let _ = writeln!(text, "cards {}", env!("CARGO_PKG_VERSION"));
+        let _ = writeln!(
+            text,
+            "target: {} {}",
+            std::env::consts::ARCH,
+            std::env::consts::OS
+        );
+        let _ = writeln!(
+            text,
+            "adapter: {} ({:?}, {:?} backend, driver {} {})",
+            self.adapter.name,
+            self.adapter.device_type,
+            self.adapter.backend,
+            self.adapter.driver,
+            self.adapter.driver_info
+        );
+        let _ = writeln!(text, "quality: {:?}", self.quality);
+        let _ = writeln!(text, "\nrecent log:");
+        for line in &self.log_tail {
+            let _ = writeln!(text, "{line}");
+        }
+
+        text
+    }
+
+    /// A link to a new GitHub issue with the report filled in.
+    pub fn issue_url(&self) -> String {
+        let mut body = self.to_text();
+        if body.len() > MAX_ISSUE_BODY {
+            let mut end = MAX_ISSUE_BODY;
+            while !body.is_char_boundary(end) {
+                end -= 1;
+            }
+            body.truncate(end);
+            body.push_str("\n[truncated]");
+        }
+
+        format!(
+            "{ISSUE_URL}?title={}&body={}",
+            percent_encode("Problem report"),
+            percent_encode(&format!("Describe the problem here.\n\n```\n{body}```\n"))
+        )
+    }
+
+    /// Writes the report into the working directory, returning the file's name.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self) -> Result<std::path::PathBuf> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let path = std::path::PathBuf::from(format!("cards-report-{timestamp}.txt"));
+
+        std::fs::write(&path, self.to_text()).chain_err(|| "couldn't write bug report")?;
+
+        Ok(path)
+    }
+
+    /// Opens the prefilled issue in a new browser tab.
+    #[cfg(target_arch = "wasm32")]
+    pub fn open_issue(&self) -> Result<()> {
+        web_sys::window()
+            .and_then(|window| {
+                window
+                    .open_with_url_and_target(&self.issue_url(), "_blank")
+                    .ok()
+            })
+            .map(|_| ())
+            .ok_or_else(|| "couldn't open issue page".into())
+    }
+}
+
+/// What went into drawing a frame, for the debug overlay.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrawStats {
+    pub draw_calls: u32,
+    pub instances: u32,
+}
+
+impl std::ops::AddAssign for DrawStats {
+    fn add_assign(&mut self, other: Self) {
+        self.draw_calls += other.draw_calls;
+        self.instances += other.instances;
+    }
+}
+
+/// Keeps a running average of the time between frames.
+pub struct FrameTimer {
+    last_frame: Option<Instant>,
+    average: Duration,
+}
+
+impl FrameTimer {
+    pub fn new() -> Self {
+        Self {
+            last_frame: None,
+            average: Duration::ZERO,
+        }
+    }
+
+    /// Records that a frame is starting.
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        if let Some(last_frame) = self.last_frame {
+            let elapsed = now - last_frame;
+            self.average = if self.average.is_zero() {
+                elapsed
+            } else {
+                self.average.mul_f32(1.0 - FRAME_TIME_SMOOTHING)
+                    + elapsed.mul_f32(FRAME_TIME_SMOOTHING)
+            };
+        }
+        self.last_frame = Some(now);
+    }
+
+    pub fn average(&self) -> Duration {
+        self.average
+    }
+}
+
+/// The lines of the debug overlay.
+pub fn overlay_text(adapter: &AdapterInfo, frame_time: Duration, stats: DrawStats) -> String {
+    let millis = frame_time.as_secs_f32() * 1000.0;
+    let fps = if millis > 0.0 { 1000.0 / millis } else { 0.0 };
+
+    format!(
+        "frame     {millis:6.2} ms\n\
+         fps       {fps:6.1}\n\
+         draws     {:6}\n\
+         instances {:6}\n\
+         {} ({:?})",
+        stats.draw_calls, stats.instances, adapter.name, adapter.backend
+    )
+}
+
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
@@ -0,0 +1,186 @@
+//! Dragging the cards a game has picked up from one pile to another, and the zones they can be
+//! dropped on, which light up green under the pointer where a drop would make a move and red
+//! where it wouldn't.
+
+use std::time::Duration;
+
+use cards_core::game::{Location, Pile, Zone};
+use cgmath::{Point2, Vector2, VectorSpace};
+use instant::Instant;
+use winit::dpi::PhysicalPosition;
+
+use crate::{bounds::Bounds, card, layout};
+
+/// How far the pointer has to move while pressed, in physical pixels, before it's dragging the
+/// cards rather than clicking on them.
+const DRAG_THRESHOLD: f64 = 6.0;
+
+/// How much of each new measurement of the cards' velocity is taken in, smoothing out the jitter
+/// of the pointer.
+const VELOCITY_SMOOTHING: f32 = 0.5;
+
+/// How long the pointer can be held still before letting go for the cards to count as stopped.
+const RELEASE_WINDOW: Duration = Duration::from_millis(60);
+
+/// How tightly the cards follow the pointer: the natural frequency, in radians a second, of the
+/// critically damped spring pulling them along.
+const FOLLOW_STIFFNESS: f32 = 30.0;
+
+/// How far the cards tip as they're swung sideways, in radians for each card width a second, and
+/// the furthest they tip.
+const SWING_TILT: f32 = 0.01;
+const MAX_TILT: f32 = 0.25;
+
+/// A zone as it's laid out on the table.
+pub struct DropZone {
+    pub zone: Zone,
+    /// The space the zone's pile and every card on it take up, in world units.
+    pub bounds: Bounds,
+}
+
+impl DropZone {
+    /// The game's zones, each covering its pile on the table with the cards drawn at `scale`.
+    pub fn lay_out(zones: &[Zone], piles: &[Pile], scale: f32) -> Vec<DropZone> {
+        zones
+            .iter()
+            .filter_map(|&zone| {
+                Some(DropZone {
+                    zone,
+                    bounds: layout::pile_extent(piles.get(zone.pile)?, scale),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Cards picked up from a pile, following the pointer once it's moved far enough.
+pub struct Drag {
+    /// Where the cards were picked up from.
+    pub from: Location,
+    /// The cards carried, by index on the table, each with where it is from the pointer, in
+    /// world units.
+    pub cards: Vec<(usize, Vector2<f32>)>,
+    /// The piles the cards can be dropped on to make a move.
+    pub allowed: Vec<usize>,
+    /// Where the pointer was pressed, and where it is now.
+    pressed: PhysicalPosition<f64>,
+    pub pointer: PhysicalPosition<f64>,
+    /// Whether the pointer has moved far enough for the cards to follow it.
+    pub moving: bool,
+    /// Where on the table the pointer last moved to and when, and how fast it was going, in
+    /// world units a second.
+    last_moved: Option<(Point2<f32>, Instant)>,
+    velocity: Vector2<f32>,
+    /// Where the cards are held, trailing the pointer on a spring, and how fast they're going.
+    held: Point2<f32>,
+    held_velocity: Vector2<f32>,
+}
+
+impl Drag {
+    /// Picks up `cards` from `from`, pressed at `pressed` on the screen, which is `point` on the
+    /// table.
+    pub fn new(
+        from: Location,
+        cards: Vec<(usize, Vector2<f32>)>,
+        allowed: Vec<usize>,
+        pressed: PhysicalPosition<f64>,
+        point: Point2<f32>,
+    ) -> Self {
+        Self {
+            from,
+            cards,
+            allowed,
+            pressed,
+            pointer: pressed,
+            moving: false,
+            last_moved: None,
+            velocity: Vector2::new(0.0, 0.0),
+            held: point,
+            held_velocity: Vector2::new(0.0, 0.0),
+        }
+    }
+
+    /// Where the cards are held on the table.
+    pub fn held(&self) -> Point2<f32> {
+        self.held
+    }
+
+    /// Pulls the cards along for `dt` towards `target`, where the pointer is, returning how far
+    /// they tip, in radians anticlockwise, as they swing sideways.
+    pub fn pull(&mut self, target: Point2<f32>, dt: Duration) -> f32 {
+        // The exact step of a critically damped spring, so that it's steady however long the
+        // frame.
+        let omega = FOLLOW_STIFFNESS;
+        let decay = (-omega * dt.as_secs_f32()).exp();
+        let offset = self.held - target;
+        let change = (self.held_velocity + offset * omega) * dt.as_secs_f32();
+        self.held_velocity = (self.held_velocity - change * omega) * decay;
+        self.held = target + (offset + change) * decay;
+
+        let across = self.held_velocity.x / card::WIDTH as f32;
+        (-across * SWING_TILT).clamp(-MAX_TILT, MAX_TILT)
+    }
+
+    /// Holds the cards right at `target`, without trailing or tipping.
+    pub fn hold_at(&mut self, target: Point2<f32>) {
+        self.held = target;
+        self.held_velocity = Vector2::new(0.0, 0.0);
+    }
+
+    /// Notes that the pointer is over `point` on the table at `now`, to tell how fast the cards
+    /// are going.
+    pub fn track(&mut self, point: Point2<f32>, now: Instant) {
+        if let Some((last, at)) = self.last_moved {
+            let dt = now.saturating_duration_since(at).as_secs_f32();
+            if dt > 0.0 {
+                let velocity = (point - last) / dt;
+                self.velocity = self.velocity.lerp(velocity, VELOCITY_SMOOTHING);
+            }
+        }
+        self.last_moved = Some((point, now));
+    }
+
+    /// How fast the cards are going if they're let go of at `now`, in world units a second.
+    pub fn release_velocity(&self, now: Instant) -> Vector2<f32> {
+        match self.last_moved {
+            Some((_, at)) if now.saturating_duration_since(at) <= RELEASE_WINDOW => self.velocity,
+            _ => Vector2::new(0.0, 0.0),
+        }
+    }
+
+    /// Moves the pointer to `position`, returning whether the cards are following it.
+    pub fn follow(&mut self, position: PhysicalPosition<f64>) -> bool {
+        self.pointer = position;
+        let distance = (position.x - self.pressed.x).hypot(position.y - self.pressed.y);
+        self.moving |= distance >= DRAG_THRESHOLD;
+        self.moving
+    }
+
+    pub fn carries(&self, card: usize) -> bool {
+        self.cards.iter().any(|&(carried, _)| carried == card)
+    }
+
+    /// The zone under `point`, in world units, other than the one the cards came from.
+    pub fn target<'a>(&self, zones: &'a [DropZone], point: Point2<f32>) -> Option<&'a DropZone> {
+        zones
+            .iter()
+            .find(|zone| zone.zone.pile != self.from.pile && zone.bounds.contains(point))
+    }
+
+    /// The nearest zone to `point` the cards can be dropped on to make a move, if there's one
+    /// within `reach`, in world units.
+    pub fn nearest_allowed<'a>(
+        &self,
+        zones: &'a [DropZone],
+        point: Point2<f32>,
+        reach: f32,
+    ) -> Option<&'a DropZone> {
+        zones
+            .iter()
+            .filter(|zone| self.allowed.contains(&zone.zone.pile))
+            .map(|zone| (zone, zone.bounds.distance(point)))
+            .filter(|&(_, distance)| distance <= reach)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(zone, _)| zone)
+    }
+}
@@ -0,0 +1,175 @@
+//! What's shown once a game is over: how it went, how long it took and how the session has gone
+//! so far, with buttons to deal again, go back to the lobby, or close the panel to look at the
+//! table.
+
+use std::time::Duration;
+
+use cards_core::{stats::GameStats, status::Outcome};
+
+use crate::{
+    locale::{tr, tr_with},
+    text::TextBrush,
+    ui::{Rect, Ui, BUTTON_SIZE, SPACING},
+};
+
+/// How the games finished since the window opened have gone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Session {
+    played: u32,
+    won: u32,
+    /// The quickest win.
+    best: Option<Duration>,
+}
+
+impl Session {
+    pub fn record(&mut self, outcome: Option<Outcome>, elapsed: Duration) {
+        self.played += 1;
+        if outcome == Some(Outcome::Won) {
+            self.won += 1;
+            self.best = Some(self.best.map_or(elapsed, |best| best.min(elapsed)));
+        }
+    }
+}
+
+/// What the player picked on the end screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    /// A new game of the same kind, from a new seed.
+    DealAgain,
+    /// Back to the lobby, for a game on a server.
+    Lobby,
+    /// Put the panel away and leave the table as it is.
+    Close,
+}
+
+/// A finished game, as the end screen tells it.
+#[derive(Debug, Clone, Copy)]
+pub struct EndScreen {
+    pub outcome: Option<Outcome>,
+    elapsed: Duration,
+    moves: usize,
+    /// The session, and how the player has done at the game over every run, as they stood with
+    /// this game counted.
+    session: Session,
+    record: GameStats,
+}
+
+impl EndScreen {
+    pub fn new(
+        outcome: Option<Outcome>,
+        elapsed: Duration,
+        moves: usize,
+        session: Session,
+        record: GameStats,
+    ) -> Self {
+        Self {
+            outcome,
+            elapsed,
+            moves,
+            session,
+            record,
+        }
+    }
+
+    /// Shows the panel in the middle of the window above `bottom` physical pixels down it,
+    /// offering the lobby rather than another deal for a game played `online`. Returns what was
+    /// picked, if anything.
+    pub fn draw(
+        &self,
+        ui: &mut Ui,
+        brush: &mut TextBrush,
+        online: bool,
+        window_width: f32,
+        bottom: f32,
+        scale: f32,
+    ) -> Option<Choice> {
+        let [button_width, row_height] = BUTTON_SIZE.map(|length| length * scale);
+        let spacing = SPACING * scale;
+        let text_size = row_height * 0.6;
+
+        let title = tr(match self.outcome {
+            Some(Outcome::Won) => "You won!",
+            Some(Outcome::Lost) => "You lost",
+            Some(Outcome::Drawn) => "A draw",
+            None => "Game over",
+        });
+        let session = &self.session;
+        let mut lines = vec![
+            tr_with("Time: {0}", &[&clock(self.elapsed)]),
+            tr_with("Moves: {0}", &[&self.moves]),
+            tr_with(
+                "This session: {0} won of {1}",
+                &[&session.won, &session.played],
+            ),
+        ];
+        if let Some(best) = session.best {
+            lines.push(tr_with("Quickest win: {0}", &[&clock(best)]));
+        }
+        lines.push(tr_with(
+            "Winning streak: {0} (longest {1})",
+            &[&self.record.streak, &self.record.longest_streak],
+        ));
+
+        let width = 2.0 * button_width + 3.0 * spacing;
+        let height = (lines.len() + 2) as f32 * (row_height + spacing) + spacing;
+        let panel = Rect::new(
+            (window_width - width) / 2.0,
+            ((bottom - height) / 2.0).max(0.0),
+            width,
+            height,
+        );
+        ui.panel(brush, panel);
+        let inner = panel.inset(spacing);
+
+        let row = |index: usize| inner.y + index as f32 * (row_height + spacing);
+        let label_offset = (row_height - text_size) / 2.0;
+        let title_width = TextBrush::measure(title, text_size)[0];
+        ui.label(
+            brush,
+            title,
+            [
+                inner.x + (inner.width - title_width) / 2.0,
+                row(0) + label_offset,
+            ],
+            text_size,
+        );
+        for (index, line) in lines.iter().enumerate() {
+            ui.label(
+                brush,
+                line,
+                [inner.x, row(index + 1) + label_offset],
+                text_size,
+            );
+        }
+
+        let y = row(lines.len() + 1);
+        let (label, choice) = match online {
+            true => ("Lobby", Choice::Lobby),
+            false => ("Deal again", Choice::DealAgain),
+        };
+        let mut picked = None;
+        if ui.button(
+            brush,
+            tr(label),
+            Rect::new(inner.x, y, button_width, row_height),
+        ) {
+            picked = Some(choice);
+        }
+        let close = Rect::new(
+            inner.x + button_width + spacing,
+            y,
+            button_width,
+            row_height,
+        );
+        if ui.button(brush, tr("Close"), close) {
+            picked = Some(Choice::Close);
+        }
+        picked
+    }
+}
+
+/// A duration as minutes and seconds, such as `3:07`.
+pub fn clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -1,3 +1,16 @@
+pub use error_chain::bail;
 use error_chain::error_chain;
 
-error_chain! {}
+error_chain! {
+    errors {
+        TextureTooLarge(width: u32, height: u32, max: u32) {
+            description("texture is larger than the device supports")
+            display("texture is {}x{}, but the device supports at most {}x{}", width, height, max, max)
+        }
+
+        InvalidCardSheet(problems: Vec<String>) {
+            description("card sheet doesn't match the deck layout")
+            display("card sheet doesn't match the deck layout: {}", problems.join("; "))
+        }
+    }
+}
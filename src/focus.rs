@@ -0,0 +1,117 @@
+//! A cursor picking out a place on the table, for playing without a pointer: a card that's face
+//! up or on top of its pile, or an empty pile. It's moved to the nearest place in the direction
+//! asked for, or on through the places in table order, and picking the place it's on clicks
+//! there.
+
+use cards_core::game::{Location, Pile};
+use cgmath::{Point2, Vector2};
+
+use crate::layout;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// A unit step this way, in world units with y pointing up.
+    fn step(self) -> Vector2<f32> {
+        match self {
+            Direction::Up => Vector2::new(0.0, 1.0),
+            Direction::Down => Vector2::new(0.0, -1.0),
+            Direction::Left => Vector2::new(-1.0, 0.0),
+            Direction::Right => Vector2::new(1.0, 0.0),
+        }
+    }
+}
+
+/// The places the cursor can be on `piles`, in table order.
+fn places(piles: &[Pile]) -> Vec<Location> {
+    let mut places = Vec::new();
+    for (index, pile) in piles.iter().enumerate() {
+        if pile.cards.is_empty() {
+            places.push(Location::pile(index));
+            continue;
+        }
+
+        let top = pile.cards.len() - 1;
+        for (card, view) in pile.cards.iter().enumerate() {
+            if !view.facedown || card == top {
+                places.push(Location::card(index, card));
+            }
+        }
+    }
+    places
+}
+
+/// The places the cursor can be on `piles`, each with where it is on the table with the cards
+/// drawn at `scale`.
+pub fn targets(piles: &[Pile], scale: f32) -> Vec<(Location, Point2<f32>)> {
+    places(piles)
+        .into_iter()
+        .map(|at| {
+            let pile = &piles[at.pile];
+            let position = match at.index {
+                Some(card) => {
+                    let position = layout::card_position(pile, card, scale);
+                    Point2::new(position.x as f32, position.y as f32)
+                }
+                None => layout::pile_bounds(pile, scale).center(),
+            };
+            (at, position)
+        })
+        .collect()
+}
+
+/// Where the cursor goes from `from` on `piles`, with the cards drawn at `scale`, when moved
+/// `direction`: the nearest place that way, favouring those straight ahead over those off to the
+/// side. A cursor that isn't anywhere yet starts on the first place there is, and one that can't
+/// go any further stays put.
+pub fn step(
+    piles: &[Pile],
+    from: Option<Location>,
+    direction: Direction,
+    scale: f32,
+) -> Option<Location> {
+    let targets = targets(piles, scale);
+    let Some(&(_, origin)) = from.and_then(|from| targets.iter().find(|(at, _)| *at == from))
+    else {
+        return targets.first().map(|&(location, _)| location);
+    };
+
+    let ahead = direction.step();
+    let across = Vector2::new(ahead.y, ahead.x);
+    targets
+        .iter()
+        .filter_map(|&(location, position)| {
+            let offset = position - origin;
+            let forward = offset.x * ahead.x + offset.y * ahead.y;
+            let sideways = (offset.x * across.x + offset.y * across.y).abs();
+            (forward > 0.5).then_some((location, forward + 2.0 * sideways))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(location, _)| location)
+        .or(from)
+}
+
+/// Where the cursor goes from `from` on `piles` when moved `step` places on in table order,
+/// wrapping around. A cursor that isn't anywhere yet starts on the first place going forwards,
+/// or the last going back.
+pub fn cycle(piles: &[Pile], from: Option<Location>, step: isize) -> Option<Location> {
+    let places = places(piles);
+    let count = places.len() as isize;
+    let index = match from.and_then(|from| places.iter().position(|&at| at == from)) {
+        Some(index) => (index as isize + step).rem_euclid(count.max(1)),
+        None if step < 0 => count - 1,
+        None => 0,
+    };
+    places.get(index as usize).copied()
+}
+
+/// Whether `location` is still somewhere the cursor can be on `piles`.
+pub fn is_target(piles: &[Pile], location: Location) -> bool {
+    places(piles).contains(&location)
+}
@@ -0,0 +1,135 @@
+//! Controllers, for playing from the sofa: the D-pad or left stick moves the cursor on the table
+//! or between the buttons of a menu, the face buttons pick, cancel, undo and ask for a hint, the
+//! shoulder buttons zoom, the right stick pans, and Start pauses.
+
+use cgmath::{InnerSpace, Vector2, Zero};
+use gilrs::{Axis, Button, EventType, Gilrs};
+
+use crate::focus::Direction;
+
+/// How far a stick has to be pushed, from 0 to 1, to count as pointing somewhere, and how far
+/// back it has to come before it can point again.
+const STICK_PUSHED: f32 = 0.6;
+const STICK_RELEASED: f32 = 0.3;
+
+/// How far the right stick has to be pushed before it pans, so that a stick resting a little
+/// off centre doesn't drift the camera.
+const DEAD_ZONE: f32 = 0.2;
+
+/// Something done with a controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadInput {
+    Move(Direction),
+    /// Pick the card or button under the cursor.
+    Select,
+    /// Put down what's picked up, or go back a screen.
+    Cancel,
+    Undo,
+    Hint,
+    ZoomIn,
+    ZoomOut,
+    Pause,
+}
+
+pub struct Gamepads {
+    /// The controllers, unless they couldn't be listened to.
+    gilrs: Option<Gilrs>,
+    /// The direction the left stick was last pushed, until it comes back to the middle.
+    stick: Option<Direction>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(e) => {
+                log::warn!("controllers can't be used: {e}");
+                None
+            }
+        };
+        Self { gilrs, stick: None }
+    }
+
+    /// What's been done with the controllers since they were last asked.
+    pub fn poll(&mut self) -> Vec<PadInput> {
+        let Self { gilrs, stick: held } = self;
+        let Some(gilrs) = gilrs else {
+            return Vec::new();
+        };
+
+        let mut inputs = Vec::new();
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) => inputs.extend(button_input(button)),
+                EventType::AxisChanged(Axis::LeftStickX | Axis::LeftStickY, _, _) => {
+                    let gamepad = gilrs.gamepad(event.id);
+                    let stick = Vector2::new(
+                        gamepad.value(Axis::LeftStickX),
+                        gamepad.value(Axis::LeftStickY),
+                    );
+                    inputs.extend(push_stick(held, stick));
+                }
+                EventType::Connected => log::info!("controller {} connected", event.id),
+                EventType::Disconnected => log::info!("controller {} disconnected", event.id),
+                _ => {}
+            }
+        }
+        inputs
+    }
+
+    /// How far the right sticks are pushed together, with y pointing up, as a panning direction.
+    pub fn pan(&self) -> Vector2<f32> {
+        let Some(gilrs) = &self.gilrs else {
+            return Vector2::zero();
+        };
+
+        gilrs
+            .gamepads()
+            .map(|(_, gamepad)| {
+                Vector2::new(
+                    gamepad.value(Axis::RightStickX),
+                    gamepad.value(Axis::RightStickY),
+                )
+            })
+            .filter(|stick| stick.magnitude() > DEAD_ZONE)
+            .fold(Vector2::zero(), |total, stick| total + stick)
+    }
+}
+
+/// Moves the cursor once as the left stick is pushed over to `stick`, and not again until it
+/// comes back to the middle, `held` being the way it was last pushed until then.
+fn push_stick(held: &mut Option<Direction>, stick: Vector2<f32>) -> Option<PadInput> {
+    if stick.magnitude() < STICK_RELEASED {
+        *held = None;
+        return None;
+    }
+    if held.is_some() || stick.magnitude() < STICK_PUSHED {
+        return None;
+    }
+
+    let direction = match stick.x.abs() > stick.y.abs() {
+        true if stick.x > 0.0 => Direction::Right,
+        true => Direction::Left,
+        false if stick.y > 0.0 => Direction::Up,
+        false => Direction::Down,
+    };
+    *held = Some(direction);
+    Some(PadInput::Move(direction))
+}
+
+fn button_input(button: Button) -> Option<PadInput> {
+    Some(match button {
+        Button::DPadUp => PadInput::Move(Direction::Up),
+        Button::DPadDown => PadInput::Move(Direction::Down),
+        Button::DPadLeft => PadInput::Move(Direction::Left),
+        Button::DPadRight => PadInput::Move(Direction::Right),
+        Button::South => PadInput::Select,
+        Button::East => PadInput::Cancel,
+        Button::West => PadInput::Undo,
+        Button::North => PadInput::Hint,
+        Button::RightTrigger => PadInput::ZoomIn,
+        Button::LeftTrigger => PadInput::ZoomOut,
+        Button::Start => PadInput::Pause,
+        _ => return None,
+    })
+}
@@ -0,0 +1,128 @@
+//! Carrying the game on on another device: a panel with a QR code of what does it, for the other
+//! device's camera, and the same written out, for typing in or copying.
+
+use cards_core::qr::QrCode;
+
+use crate::{
+    locale::tr,
+    text::TextBrush,
+    ui::{Rect, Ui, BUTTON_SIZE, SPACING},
+};
+
+/// How big each of the code's modules is drawn, in logical pixels, unless the window's too short
+/// for it.
+const MODULE_SIZE: f32 = 4.0;
+
+/// How much of the window's height the code can take up, light border and all.
+const MAX_CODE_HEIGHT: f32 = 0.6;
+
+/// The light border readers need around the code, in modules.
+const QUIET_ZONE: usize = 4;
+
+/// How many characters of the link are written on each line.
+const LINK_COLUMNS: usize = 48;
+
+const LIGHT: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const DARK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+/// Shows `code`, with the `link` it holds written out under it, in a panel in the middle of a
+/// `window_width` by `window_height` physical pixel window. Returns whether it was closed.
+pub fn draw(
+    ui: &mut Ui,
+    brush: &mut TextBrush,
+    code: &QrCode,
+    link: &str,
+    [window_width, window_height]: [f32; 2],
+    scale: f32,
+) -> bool {
+    let [button_width, row_height] = BUTTON_SIZE.map(|length| length * scale);
+    let spacing = SPACING * scale;
+    let text_size = row_height * 0.6;
+    let link_size = text_size * 0.75;
+
+    // Whole pixels per module keep the code sharp for the camera.
+    let modules = code.size() + 2 * QUIET_ZONE;
+    let module = (MODULE_SIZE * scale)
+        .min(window_height * MAX_CODE_HEIGHT / modules as f32)
+        .floor()
+        .max(1.0);
+    let code_size = modules as f32 * module;
+
+    let lines = link
+        .chars()
+        .collect::<Vec<_>>()
+        .chunks(LINK_COLUMNS)
+        .map(|line| line.iter().collect::<String>())
+        .collect::<Vec<_>>();
+    let link_width = TextBrush::measure(&"0".repeat(LINK_COLUMNS), link_size)[0];
+
+    let width = code_size.max(link_width).max(button_width) + 2.0 * spacing;
+    let height =
+        row_height + code_size + lines.len() as f32 * link_size + row_height + 5.0 * spacing;
+    let panel = Rect::new(
+        (window_width - width) / 2.0,
+        ((window_height - height) / 2.0).max(0.0),
+        width,
+        height,
+    );
+    ui.panel(brush, panel);
+    let inner = panel.inset(spacing);
+
+    let title = tr("Continue elsewhere");
+    let title_width = TextBrush::measure(title, text_size)[0];
+    ui.label(
+        brush,
+        title,
+        [
+            inner.x + (inner.width - title_width) / 2.0,
+            inner.y + (row_height - text_size) / 2.0,
+        ],
+        text_size,
+    );
+
+    let top = inner.y + row_height + spacing;
+    let left = inner.x + (inner.width - code_size) / 2.0;
+    brush.fill([left, top], [code_size, code_size], LIGHT);
+    let origin = [
+        left + QUIET_ZONE as f32 * module,
+        top + QUIET_ZONE as f32 * module,
+    ];
+    draw_modules(brush, code, origin, module);
+
+    let mut y = top + code_size + spacing;
+    for line in &lines {
+        ui.label(brush, line, [inner.x, y], link_size);
+        y += link_size;
+    }
+
+    let back = Rect::new(
+        inner.x + inner.width - button_width,
+        y + spacing,
+        button_width,
+        row_height,
+    );
+    ui.button(brush, tr("Back"), back)
+}
+
+/// Fills in the dark modules of `code`, `module` pixels each, from `origin`, a run along a row at
+/// a time.
+fn draw_modules(brush: &mut TextBrush, code: &QrCode, [x, y]: [f32; 2], module: f32) {
+    for row in 0..code.size() {
+        let mut column = 0;
+        while column < code.size() {
+            if !code.is_dark(column, row) {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < code.size() && code.is_dark(column, row) {
+                column += 1;
+            }
+            brush.fill(
+                [x + start as f32 * module, y + row as f32 * module],
+                [(column - start) as f32 * module, module],
+                DARK,
+            );
+        }
+    }
+}
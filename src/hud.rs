@@ -0,0 +1,61 @@
+use cards_core::status::GameStatus;
+
+use crate::{
+    locale::{tr, tr_with},
+    text::TextBrush,
+    ui::{Rect, Ui},
+};
+
+/// The height of a line of HUD text, in logical pixels.
+const TEXT_SIZE: f32 = 18.0;
+
+/// Space between the edge of the window, the panel and its text, in logical pixels.
+const MARGIN: f32 = 8.0;
+
+/// Shows the scores, the trick count and whose turn it is in a panel in the top-right corner of a
+/// `window_width` physical pixels wide window.
+pub fn draw(
+    ui: &mut Ui,
+    brush: &mut TextBrush,
+    status: &GameStatus,
+    window_width: f32,
+    scale: f32,
+) {
+    let size = TEXT_SIZE * scale;
+    let margin = MARGIN * scale;
+
+    // The font is monospace, so padding the names lines the scores up.
+    let name_width = status
+        .players
+        .iter()
+        .map(|player| tr(&player.name).chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = status
+        .players
+        .iter()
+        .enumerate()
+        .map(|(index, player)| {
+            let marker = if status.turn == Some(index) { '>' } else { ' ' };
+            let name = tr(&player.name);
+            format!("{marker} {name:<name_width$} {:>4}", player.score)
+        })
+        .collect::<Vec<_>>();
+    if let Some(tricks) = status.tricks {
+        lines.push(format!("  {}", tr_with("Tricks: {0}", &[&tricks])));
+    }
+    let text = lines.join("\n");
+
+    let [width, height] = TextBrush::measure(&text, size);
+    let panel = Rect::new(
+        window_width - width - 3.0 * margin,
+        margin,
+        width + 2.0 * margin,
+        height + 2.0 * margin,
+    );
+    ui.panel(brush, panel);
+
+    let inner = panel.inset(margin);
+    ui.label(brush, &text, [inner.x, inner.y], size);
+}
@@ -0,0 +1,338 @@
+//! What the keys do: each key, with Ctrl or Shift held or not, stands for an action, which the
+//! settings can bind to other keys.
+//!
+//! Bindings are written as the key's name, after `Ctrl+` and `Shift+` if they're held, such as
+//! `H` or `Ctrl+Shift+Z`. In the settings, each action rebound lists its keys by name, separated
+//! by commas, in place of the usual ones:
+//!
+//! ```toml
+//! [keys]
+//! PanUp = "I, Up"
+//! Undo = "Ctrl+Z, Backspace"
+//! ```
+//!
+//! A key can stand for several actions, which are tried in the order they're listed here until
+//! one does something: the arrow keys move the focus once something has it, and pan otherwise.
+
+use std::collections::BTreeMap;
+
+use strum::{EnumIter, EnumString, IntoEnumIterator, IntoStaticStr};
+use winit::event::{ModifiersState, VirtualKeyCode};
+
+use crate::focus::Direction;
+
+/// Something a key can be bound to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumString, IntoStaticStr)]
+pub enum Action {
+    /// Move the focus on to the next card, pile or button, or back to the one before.
+    FocusNext,
+    FocusPrevious,
+    /// Move the focus to the nearest card or pile that way, or between buttons.
+    FocusUp,
+    FocusLeft,
+    FocusDown,
+    FocusRight,
+    /// Click the card, pile or button with the focus.
+    Select,
+    PanUp,
+    PanLeft,
+    PanDown,
+    PanRight,
+    ZoomIn,
+    ZoomOut,
+    Recentre,
+    ToggleProjection,
+    Undo,
+    Redo,
+    Hint,
+    /// Play every animation of the cards to its end at once.
+    SkipAnimation,
+    /// Go back a screen, pause the game, or on the menu close the window.
+    Back,
+    /// Play or pause a replay.
+    PlayPause,
+    /// Open or close the chat, in a game on a server.
+    Chat,
+    NextTheme,
+    NextTrumps,
+    ToggleFilter,
+    DebugOverlay,
+    ReportProblem,
+    DownloadOffline,
+}
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
+
+    /// Whether the action lasts for as long as its key is held, rather than happening once as
+    /// it's pressed.
+    pub fn is_held(self) -> bool {
+        matches!(
+            self,
+            Action::PanUp | Action::PanLeft | Action::PanDown | Action::PanRight
+        )
+    }
+
+    /// The way the action moves the focus, if it's one that does.
+    pub fn focus_direction(self) -> Option<Direction> {
+        match self {
+            Action::FocusUp => Some(Direction::Up),
+            Action::FocusLeft => Some(Direction::Left),
+            Action::FocusDown => Some(Direction::Down),
+            Action::FocusRight => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    /// The keys the action is bound to unless the settings say otherwise.
+    fn default_keys(self) -> &'static str {
+        match self {
+            Action::FocusNext => "Tab",
+            Action::FocusPrevious => "Shift+Tab",
+            Action::FocusUp => "Up",
+            Action::FocusLeft => "Left",
+            Action::FocusDown => "Down",
+            Action::FocusRight => "Right",
+            Action::Select => "Return, Space",
+            Action::PanUp => "W, Up",
+            Action::PanLeft => "A, Left",
+            Action::PanDown => "S, Down",
+            Action::PanRight => "D, Right",
+            Action::ZoomIn => "Equals, NumpadAdd",
+            Action::ZoomOut => "Minus, NumpadSubtract",
+            Action::Recentre => "Home",
+            Action::ToggleProjection => "P",
+            Action::Undo => "Ctrl+Z",
+            Action::Redo => "Ctrl+Y, Ctrl+Shift+Z",
+            Action::Hint => "H",
+            Action::SkipAnimation => "F",
+            Action::Back => "Escape",
+            Action::PlayPause => "Space",
+            Action::Chat => "Return",
+            Action::NextTheme => "F2",
+            Action::NextTrumps => "T",
+            Action::ToggleFilter => "F3",
+            Action::DebugOverlay => "F4",
+            Action::ReportProblem => "F8",
+            Action::DownloadOffline => "F9",
+        }
+    }
+}
+
+/// A key, and the modifiers held with it. Cmd stands in for Ctrl on macOS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub key: VirtualKeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+}
+
+impl Binding {
+    pub fn new(key: VirtualKeyCode, modifiers: ModifiersState) -> Self {
+        Self {
+            key,
+            ctrl: modifiers.ctrl() || modifiers.logo(),
+            shift: modifiers.shift(),
+        }
+    }
+
+    /// Whether `key` has a name, so can be bound. Modifier keys themselves can't be.
+    pub fn can_bind(key: VirtualKeyCode) -> bool {
+        KEYS.iter().any(|&(_, named)| named == key)
+    }
+
+    /// Reads a binding back from its name, or `None` if it isn't one.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut rest = text.trim();
+        let (mut ctrl, mut shift) = (false, false);
+        loop {
+            if let Some(after) = rest.strip_prefix("Ctrl+") {
+                ctrl = true;
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("Shift+") {
+                shift = true;
+                rest = after;
+            } else {
+                break;
+            }
+        }
+
+        let &(_, key) = KEYS.iter().find(|(name, _)| *name == rest)?;
+        Some(Self { key, ctrl, shift })
+    }
+
+    pub fn name(&self) -> String {
+        let key = KEYS
+            .iter()
+            .find(|(_, key)| *key == self.key)
+            .map_or("?", |(name, _)| name);
+        let ctrl = if self.ctrl { "Ctrl+" } else { "" };
+        let shift = if self.shift { "Shift+" } else { "" };
+        format!("{ctrl}{shift}{key}")
+    }
+}
+
+/// The keys each action is bound to.
+pub struct Keymap {
+    bindings: Vec<(Binding, Action)>,
+}
+
+impl Keymap {
+    /// The usual bindings, with the actions named in `keys` bound to the keys listed for them
+    /// instead. Names that don't mean anything are ignored.
+    pub fn new(keys: &BTreeMap<String, String>) -> Self {
+        let mut bindings = Vec::new();
+        for action in Action::iter() {
+            let listed = match keys.get(action.name()) {
+                Some(listed) => listed.as_str(),
+                None => action.default_keys(),
+            };
+            for name in listed.split(',').filter(|name| !name.trim().is_empty()) {
+                match Binding::parse(name) {
+                    Some(binding) => bindings.push((binding, action)),
+                    None => log::warn!("ignoring unknown key {name:?} for {}", action.name()),
+                }
+            }
+        }
+        for name in keys.keys() {
+            if name.parse::<Action>().is_err() {
+                log::warn!("ignoring keys for unknown action {name:?}");
+            }
+        }
+
+        Self { bindings }
+    }
+
+    /// The actions `key` stands for with `modifiers` held, in the order they're tried. A key
+    /// bound without modifiers still counts with them held, when nothing is bound to it with
+    /// them, so that panning carries on with Shift held.
+    pub fn actions(&self, key: VirtualKeyCode, modifiers: ModifiersState) -> Vec<Action> {
+        let pressed = Binding::new(key, modifiers);
+        let unmodified = Binding {
+            ctrl: false,
+            shift: false,
+            ..pressed
+        };
+        [pressed, unmodified]
+            .into_iter()
+            .map(|wanted| {
+                self.bindings
+                    .iter()
+                    .filter(|(binding, _)| *binding == wanted)
+                    .map(|&(_, action)| action)
+                    .collect::<Vec<_>>()
+            })
+            .find(|actions| !actions.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// The keys `action` is bound to, by name and separated by commas, as the settings list them.
+    pub fn keys(&self, action: Action) -> String {
+        self.bindings
+            .iter()
+            .filter(|&&(_, bound)| bound == action)
+            .map(|(binding, _)| binding.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Binds `action` to `binding` alone, taking the binding from whatever had it before.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        self.bindings
+            .retain(|&(bound, bound_action)| bound != binding && bound_action != action);
+        self.bindings.push((binding, action));
+    }
+
+    /// The bindings as the settings keep them: only those of actions that aren't bound to their
+    /// usual keys.
+    pub fn to_settings(&self) -> BTreeMap<String, String> {
+        let usual = Keymap::new(&BTreeMap::new());
+        Action::iter()
+            .filter_map(|action| {
+                let keys = self.keys(action);
+                (keys != usual.keys(action)).then(|| (action.name().to_owned(), keys))
+            })
+            .collect()
+    }
+}
+
+/// The keys that can be bound, by name.
+const KEYS: &[(&str, VirtualKeyCode)] = {
+    use VirtualKeyCode::*;
+    &[
+        ("A", A),
+        ("B", B),
+        ("C", C),
+        ("D", D),
+        ("E", E),
+        ("F", F),
+        ("G", G),
+        ("H", H),
+        ("I", I),
+        ("J", J),
+        ("K", K),
+        ("L", L),
+        ("M", M),
+        ("N", N),
+        ("O", O),
+        ("P", P),
+        ("Q", Q),
+        ("R", R),
+        ("S", S),
+        ("T", T),
+        ("U", U),
+        ("V", V),
+        ("W", W),
+        ("X", X),
+        ("Y", Y),
+        ("Z", Z),
+        ("0", Key0),
+        ("1", Key1),
+        ("2", Key2),
+        ("3", Key3),
+        ("4", Key4),
+        ("5", Key5),
+        ("6", Key6),
+        ("7", Key7),
+        ("8", Key8),
+        ("9", Key9),
+        ("F1", F1),
+        ("F2", F2),
+        ("F3", F3),
+        ("F4", F4),
+        ("F5", F5),
+        ("F6", F6),
+        ("F7", F7),
+        ("F8", F8),
+        ("F9", F9),
+        ("F10", F10),
+        ("F11", F11),
+        ("F12", F12),
+        ("Up", Up),
+        ("Down", Down),
+        ("Left", Left),
+        ("Right", Right),
+        ("Home", Home),
+        ("End", End),
+        ("PageUp", PageUp),
+        ("PageDown", PageDown),
+        ("Insert", Insert),
+        ("Delete", Delete),
+        ("Backspace", Back),
+        ("Tab", Tab),
+        ("Space", Space),
+        ("Return", Return),
+        ("Escape", Escape),
+        ("Minus", Minus),
+        ("Equals", Equals),
+        ("Comma", Comma),
+        ("Period", Period),
+        ("Slash", Slash),
+        ("Semicolon", Semicolon),
+        ("NumpadAdd", NumpadAdd),
+        ("NumpadSubtract", NumpadSubtract),
+        ("NumpadEnter", NumpadEnter),
+    ]
+};
@@ -0,0 +1,120 @@
+//! The key bindings screen: every action with the keys bound to it, each a button that waits for
+//! the next key pressed to bind to it instead.
+
+use strum::IntoEnumIterator;
+
+use crate::{
+    keymap::{Action, Keymap},
+    locale::{tr, tr_with},
+    text::TextBrush,
+    ui::{Rect, Ui, SPACING},
+};
+
+/// The width of an action's name and of its keys, and the height of a row, in logical pixels.
+const LABEL_WIDTH: f32 = 144.0;
+const KEYS_SIZE: [f32; 2] = [176.0, 24.0];
+
+/// How many columns the actions are listed in.
+const COLUMNS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    /// Wait for a key to bind to the action.
+    Rebind(Action),
+    /// Go back to the usual keys for everything.
+    Reset,
+    Back,
+}
+
+/// Shows the bindings in `keymap` in a panel in the middle of a `window_width` by
+/// `window_height` physical pixel window, with the action `rebinding` waiting for a key if there
+/// is one. Returns what was picked, if anything.
+pub fn draw(
+    ui: &mut Ui,
+    brush: &mut TextBrush,
+    keymap: &Keymap,
+    rebinding: Option<Action>,
+    [window_width, window_height]: [f32; 2],
+    scale: f32,
+) -> Option<Choice> {
+    let label_width = LABEL_WIDTH * scale;
+    let [keys_width, row_height] = KEYS_SIZE.map(|length| length * scale);
+    let spacing = SPACING * scale;
+    let text_size = row_height * 0.6;
+    let label_offset = (row_height - text_size) / 2.0;
+
+    let actions = Action::iter().collect::<Vec<_>>();
+    let per_column = actions.len().div_ceil(COLUMNS);
+    let column_width = label_width + keys_width + 2.0 * spacing;
+    let width = COLUMNS as f32 * column_width + spacing;
+    let height = (per_column + 2) as f32 * (row_height + spacing) + spacing;
+    let panel = Rect::new(
+        (window_width - width) / 2.0,
+        ((window_height - height) / 2.0).max(0.0),
+        width,
+        height,
+    );
+    ui.panel(brush, panel);
+    let inner = panel.inset(spacing);
+    let row = |index: usize| inner.y + index as f32 * (row_height + spacing);
+
+    let title = match rebinding {
+        Some(action) => tr_with(
+            "Press a key for {0} (Escape to cancel)",
+            &[&describe(action)],
+        ),
+        None => tr("Keys").to_owned(),
+    };
+    ui.label(brush, &title, [inner.x, row(0) + label_offset], text_size);
+
+    let mut choice = None;
+    for (index, &action) in actions.iter().enumerate() {
+        let x = inner.x + (index / per_column) as f32 * column_width;
+        let y = row(index % per_column + 1);
+        ui.label(brush, &describe(action), [x, y + label_offset], text_size);
+
+        let keys = match rebinding == Some(action) {
+            true => "...".to_owned(),
+            false => keymap.keys(action),
+        };
+        let rect = Rect::new(x + label_width + spacing, y, keys_width, row_height);
+        if ui.button_with_id(brush, action.name(), &keys, rect) {
+            choice = Some(Choice::Rebind(action));
+        }
+    }
+
+    let y = row(per_column + 1);
+    let button_width = (inner.width - spacing) / 2.0;
+    if ui.button(
+        brush,
+        tr("Reset"),
+        Rect::new(inner.x, y, button_width, row_height),
+    ) {
+        choice = Some(Choice::Reset);
+    }
+    let back = Rect::new(
+        inner.x + button_width + spacing,
+        y,
+        button_width,
+        row_height,
+    );
+    if ui.button(brush, tr("Back"), back) {
+        choice = Some(Choice::Back);
+    }
+    choice
+}
+
+/// The action's name as it's shown, such as `Pan left` for `PanLeft`, in the current language.
+fn describe(action: Action) -> String {
+    let mut text = String::new();
+    for (index, c) in action.name().chars().enumerate() {
+        match (index, c.is_uppercase()) {
+            (0, _) | (_, false) => text.push(c),
+            (_, true) => {
+                text.push(' ');
+                text.extend(c.to_lowercase());
+            }
+        }
+    }
+    tr(&text).to_owned()
+}
@@ -0,0 +1,92 @@
+use cards_core::game::{Label, Pile, Sweep};
+use cgmath::{EuclideanSpace, Point2, Vector3};
+use winit::dpi::PhysicalSize;
+
+use crate::{bounds::Bounds, card};
+
+pub use cards_core::game::Orientation;
+
+/// Aspect ratio (height over width) above which a landscape viewport switches to portrait.
+const PORTRAIT_THRESHOLD: f32 = 1.1;
+
+/// Aspect ratio below which a portrait viewport switches back to landscape.
+const LANDSCAPE_THRESHOLD: f32 = 0.9;
+
+/// Picks the orientation for a viewport of the given size.
+///
+/// Near-square viewports keep `current`, so that a window being dragged around the threshold (or
+/// a phone's browser chrome appearing and disappearing) doesn't make the layout flip back and
+/// forth.
+pub fn orientation_for_viewport(size: PhysicalSize<u32>, current: Orientation) -> Orientation {
+    let aspect = size.height as f32 / size.width.max(1) as f32;
+
+    if aspect > PORTRAIT_THRESHOLD {
+        Orientation::Portrait
+    } else if aspect < LANDSCAPE_THRESHOLD {
+        Orientation::Landscape
+    } else {
+        current
+    }
+}
+
+/// Converts a position on the table, in card widths and heights, into world units.
+///
+/// Every function here takes the `scale` the cards are drawn at. Places on the table are spread
+/// out by it, so that bigger cards don't run into each other, but the cards fanned out in a pile
+/// stay as close together as ever: their corners still show, and the table doesn't outgrow the
+/// window as fast as the cards do.
+fn to_world([x, y]: [f32; 2], scale: f32) -> Point2<f32> {
+    Point2::new(
+        x * card::WIDTH as f32 * scale,
+        y * card::HEIGHT as f32 * scale,
+    )
+}
+
+/// Where the centre of the card at `index` in a pile is, in world units.
+fn card_centre(pile: &Pile, index: usize, scale: f32) -> Point2<f32> {
+    let [x, y] = pile.card_position(index);
+    let [pile_x, pile_y] = pile.position;
+    let fanned = to_world([x - pile_x, y - pile_y], 1.0);
+    to_world(pile.position, scale) + fanned.to_vec()
+}
+
+/// Where the card at `index` in a pile is drawn, in world units.
+pub fn card_position(pile: &Pile, index: usize, scale: f32) -> Vector3<i32> {
+    let position = card_centre(pile, index, scale);
+    Vector3::new(position.x as i32, position.y as i32, 0)
+}
+
+/// Where the centre of a label on the table is, in world units.
+pub fn label_position(label: &Label, scale: f32) -> Point2<f32> {
+    to_world(label.position, scale)
+}
+
+/// Where the cards in a sweep gather, in world units.
+pub fn sweep_position(sweep: &Sweep, scale: f32) -> Vector3<i32> {
+    let position = to_world(sweep.via, scale);
+    Vector3::new(position.x as i32, position.y as i32, 0)
+}
+
+/// The space a pile's bottom card takes up, which is where clicks land on an empty pile.
+pub fn pile_bounds(pile: &Pile, scale: f32) -> Bounds {
+    card_bounds(to_world(pile.position, scale), scale)
+}
+
+/// The space a pile and every card on it take up.
+pub fn pile_extent(pile: &Pile, scale: f32) -> Bounds {
+    (0..pile.cards.len())
+        .map(|index| card_bounds(card_centre(pile, index, scale), scale))
+        .fold(pile_bounds(pile, scale), |extent, bounds| {
+            extent.union(bounds)
+        })
+}
+
+/// The space a card drawn at `scale` takes up with its centre at `centre`.
+fn card_bounds(centre: Point2<f32>, scale: f32) -> Bounds {
+    let half_size = cgmath::Vector2::new(card::WIDTH as f32, card::HEIGHT as f32) * scale / 2.0;
+
+    Bounds {
+        min: centre - half_size,
+        max: centre + half_size,
+    }
+}
@@ -1,144 +1,118 @@
-mod texture;
-use log::{debug, error};
-use state::State;
-
-#[cfg(target_arch = "wasm32")]
-use wasm_bindgen::prelude::*;
-
+//! A renderer for card games, built on the game logic in `cards-core`.
+//!
+//! Everything to do with windows and the GPU is behind the `render` feature, which is on by
+//! default. Without it this crate is just the game logic, for servers and for CI machines with no
+//! GPU.
+
+pub use cards_core as logic;
+
+#[cfg(all(
+    feature = "render",
+    not(any(
+        feature = "klondike",
+        feature = "hearts",
+        feature = "doppelkopf",
+        feature = "blackjack",
+        feature = "memory",
+        feature = "gallery",
+    ))
+))]
+compile_error!("there's nothing to play without at least one game's feature, or `all-games`");
+
+#[cfg(all(
+    feature = "multiplayer",
+    not(any(feature = "hearts", feature = "doppelkopf"))
+))]
+compile_error!("`multiplayer` needs a game that can be played online: `hearts` or `doppelkopf`");
+
+#[cfg(feature = "render")]
+mod animation;
+#[cfg(feature = "render")]
+mod app;
+#[cfg(feature = "render")]
+mod audio;
+#[cfg(feature = "render")]
+mod background;
+#[cfg(feature = "render")]
+mod badges;
+#[cfg(feature = "render")]
+mod bounds;
+#[cfg(feature = "render")]
 mod camera;
+#[cfg(feature = "render")]
 mod card;
+#[cfg(feature = "multiplayer")]
+mod chat;
+#[cfg(feature = "render")]
+mod config;
+#[cfg(feature = "render")]
+mod diagnostics;
+#[cfg(feature = "render")]
+mod drag;
+#[cfg(feature = "render")]
+mod end_screen;
+#[cfg(feature = "render")]
+mod errors;
+#[cfg(feature = "render")]
+mod focus;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+#[cfg(feature = "render")]
+mod handoff_screen;
+#[cfg(feature = "render")]
+mod hud;
+#[cfg(feature = "render")]
+mod keymap;
+#[cfg(feature = "render")]
+mod keys_screen;
+#[cfg(feature = "render")]
+mod layout;
+#[cfg(feature = "multiplayer")]
+mod lobby;
+#[cfg(feature = "render")]
+mod locale;
+#[cfg(feature = "render")]
+mod look_screen;
+#[cfg(feature = "render")]
+mod move_log;
+#[cfg(feature = "multiplayer")]
+mod net;
+#[cfg(all(feature = "render", target_arch = "wasm32"))]
+mod offline;
+#[cfg(feature = "render")]
+mod particles;
+#[cfg(feature = "render")]
+mod picking;
+#[cfg(feature = "render")]
+mod puzzle_screen;
+#[cfg(feature = "render")]
+mod quality;
+#[cfg(feature = "render")]
+mod replay;
+#[cfg(feature = "render")]
+mod save;
+#[cfg(feature = "render")]
+mod scene;
+#[cfg(feature = "render")]
+mod settings_screen;
+#[cfg(feature = "render")]
+mod sheet;
+#[cfg(feature = "render")]
+mod speech;
+#[cfg(feature = "render")]
 mod state;
+#[cfg(feature = "render")]
+mod stats_screen;
+#[cfg(feature = "render")]
+mod text;
+#[cfg(feature = "render")]
+mod texture;
+#[cfg(feature = "render")]
+mod theme;
+#[cfg(feature = "render")]
+mod ui;
+#[cfg(feature = "render")]
 mod util;
 
-use wgpu::SurfaceError;
-
-use winit::{
-    event::*,
-    event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
-};
-
-mod errors;
-use errors::*;
-
-fn init_logging() {
-    cfg_if::cfg_if! {
-        if #[cfg(target_arch = "wasm32")] {
-            std::panic::set_hook(Box::new(console_error_panic_hook::hook));
-            console_log::init_with_level(log::Level::Warn)
-                .expect("Couldn't initialise logger");
-        } else {
-            env_logger::init();
-        }
-    }
-}
-
-fn handle_window_event(state: &mut State, event: &WindowEvent) -> Option<ControlFlow> {
-    match event {
-        WindowEvent::CloseRequested
-        | WindowEvent::KeyboardInput {
-            input:
-                KeyboardInput {
-                    state: ElementState::Pressed,
-                    virtual_keycode: Some(VirtualKeyCode::Escape),
-                    ..
-                },
-            ..
-        } => Some(ControlFlow::Exit),
-
-        WindowEvent::Resized(physical_size) => {
-            state.resize(*physical_size);
-            None
-        }
-
-        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-            state.resize(**new_inner_size);
-            None
-        }
-
-        _ => None,
-    }
-}
-
-fn handle_redraw_event(state: &mut State) -> Option<ControlFlow> {
-    state.update();
-    match state.render() {
-        Ok(_) => None,
-        Err(SurfaceError::Lost) => {
-            state.resize(state.size);
-            None
-        }
-        Err(SurfaceError::OutOfMemory) => Some(ControlFlow::Exit),
-        Err(e) => {
-            eprintln!("{:?}", e);
-            None
-        }
-    }
-}
-
-fn handle_event(state: &mut State, event: &Event<()>) -> Option<ControlFlow> {
-    debug!("{event:?}");
-
-    match event {
-        Event::WindowEvent {
-            ref event,
-            window_id,
-        } if *window_id == state.window().id() && !state.input(event) => {
-            handle_window_event(state, event)
-        }
-
-        Event::RedrawRequested(window_id) if *window_id == state.window().id() => {
-            handle_redraw_event(state)
-        }
-
-        Event::MainEventsCleared => {
-            state.window().request_redraw();
-            None
-        }
-
-        _ => None,
-    }
-}
-
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
-pub async fn run() {
-    init_logging();
-
-    match run_inner().await {
-        Ok(_) => (),
-        Err(e) => error!("{e:?}"),
-    }
-}
-
-async fn run_inner() -> Result<()> {
-    let event_loop = EventLoop::new();
-    let window = WindowBuilder::new()
-        .build(&event_loop)
-        .chain_err(|| "couldn't create new window")?;
-
-    #[cfg(target_arch = "wasm32")]
-    {
-        // Winit prevents sizing with CSS, so we have to set
-        // the size manually when on web.
-        window.set_inner_size(winit::dpi::LogicalSize::new(800, 600));
-
-        use winit::platform::web::WindowExtWebSys;
-        web_sys::window()
-            .and_then(|win| win.document())
-            .and_then(|doc| {
-                let dst = doc.get_element_by_id("wasm-example")?;
-                let canvas = web_sys::Element::from(window.canvas());
-                dst.append_child(&canvas).ok()?;
-                Some(())
-            })
-            .expect("Couldn't append canvas to document body.");
-    }
-
-    let mut state = State::new(window).await?;
-
-    event_loop.run(move |event, _, control_flow| {
-        if let Some(new_flow) = handle_event(&mut state, &event) {
-            *control_flow = new_flow;
-        }
-    });
-}
+#[cfg(feature = "render")]
+pub use app::run;
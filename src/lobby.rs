@@ -0,0 +1,248 @@
+//! Finding a game on a server: a panel listing the open rooms to join, with buttons to open a new
+//! one, and once in a room, its seats to pick from and a button to say the player's ready.
+
+use std::time::Duration;
+
+use cards_core::protocol::{self, ClientMessage, RoomSummary, SeatState, TableOptions};
+use instant::Instant;
+
+use crate::{
+    locale::{tr, tr_with},
+    scene::title_case,
+    text::TextBrush,
+    ui::{Rect, Ui, BUTTON_SIZE, SPACING},
+};
+
+/// The games a server hosts, which a room can be opened for.
+const GAMES: &[&str] = &[
+    #[cfg(feature = "hearts")]
+    "hearts",
+    #[cfg(feature = "doppelkopf")]
+    "doppelkopf",
+];
+
+/// How often the open rooms are asked for again while they're listed.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(3);
+
+/// How many rooms are listed at once.
+const MAX_ROOMS: usize = 8;
+
+/// How many buttons fit across the panel.
+const COLUMNS: usize = 4;
+
+pub struct Lobby {
+    rooms: Vec<RoomSummary>,
+    /// The room the player is waiting in, once they're in one.
+    room: Option<protocol::Lobby>,
+    /// The index in `GAMES` of the game a room opened from here is for, and how it's played.
+    game: usize,
+    options: TableOptions,
+    /// When the open rooms were last asked for.
+    refreshed: Option<Instant>,
+}
+
+impl Lobby {
+    /// Starts out listing the open rooms, with `game` picked for opening a new one if the server
+    /// hosts it.
+    pub fn new(game: &str) -> Self {
+        Self {
+            rooms: Vec::new(),
+            room: None,
+            game: GAMES.iter().position(|&name| name == game).unwrap_or(0),
+            options: TableOptions {
+                computer_players: true,
+                daily: false,
+            },
+            refreshed: None,
+        }
+    }
+
+    pub fn set_rooms(&mut self, rooms: Vec<RoomSummary>) {
+        self.rooms = rooms;
+    }
+
+    /// Shows the room the player is waiting in, or goes back to listing the open rooms.
+    pub fn set_room(&mut self, room: Option<protocol::Lobby>) {
+        if room.is_none() {
+            self.refreshed = None;
+        }
+        self.room = room;
+    }
+
+    /// Whether it's time to ask for the open rooms again, which it is every so often while
+    /// they're listed.
+    pub fn wants_rooms(&mut self) -> bool {
+        if self.room.is_some() {
+            return false;
+        }
+
+        let due = self
+            .refreshed
+            .is_none_or(|refreshed| refreshed.elapsed() >= REFRESH_INTERVAL);
+        if due {
+            self.refreshed = Some(Instant::now());
+        }
+        due
+    }
+
+    /// Shows the lobby in a panel centred across a `window_width` physical pixels wide window,
+    /// with its bottom `bottom` pixels down. Returns what to ask of the server, if anything.
+    pub fn draw(
+        &mut self,
+        ui: &mut Ui,
+        brush: &mut TextBrush,
+        window_width: f32,
+        bottom: f32,
+        scale: f32,
+    ) -> Option<ClientMessage> {
+        let [button_width, row_height] = BUTTON_SIZE.map(|length| length * scale);
+        let spacing = SPACING * scale;
+
+        let rows = match &self.room {
+            Some(room) => room.seats.len() + 2,
+            None => self.rooms.len().clamp(1, MAX_ROOMS) + 3,
+        };
+        let width = COLUMNS as f32 * (button_width + spacing) + spacing;
+        let height = rows as f32 * (row_height + spacing) + spacing;
+        let panel = Rect::new(
+            (window_width - width) / 2.0,
+            ((bottom - height) / 2.0).max(0.0),
+            width,
+            height,
+        );
+        ui.panel(brush, panel);
+        let inner = panel.inset(spacing);
+
+        let row = |index: usize| inner.y + index as f32 * (row_height + spacing);
+        let button = |row: f32, column: usize| {
+            Rect::new(
+                inner.x + column as f32 * (button_width + spacing),
+                row,
+                button_width,
+                row_height,
+            )
+        };
+        let last_column = COLUMNS - 1;
+        let text_size = row_height * 0.6;
+        let label_offset = (row_height - text_size) / 2.0;
+
+        match &self.room {
+            Some(room) => {
+                let title = format!(
+                    "{}: {}, {}",
+                    room.room,
+                    title_case(&room.game),
+                    describe(room.options)
+                );
+                ui.label(brush, &title, [inner.x, row(0) + label_offset], text_size);
+
+                let mut request = None;
+                for (seat, state) in room.seats.iter().enumerate() {
+                    let y = row(seat + 1);
+                    let who = match state {
+                        _ if seat == room.seat => "you",
+                        SeatState::Open => "open",
+                        SeatState::Waiting => "not ready",
+                        SeatState::Ready => "ready",
+                        SeatState::Away => "away",
+                    };
+                    let text = tr_with("Seat {0}: {1}", &[&(seat + 1), &tr(who)]);
+                    ui.label(brush, &text, [inner.x, y + label_offset], text_size);
+                    if *state == SeatState::Open
+                        && ui.button(brush, tr("Sit"), button(y, last_column))
+                    {
+                        request = Some(ClientMessage::Sit { seat });
+                    }
+                }
+
+                let y = row(room.seats.len() + 1);
+                let ready = room.seats.get(room.seat) == Some(&SeatState::Ready);
+                let label = if ready { "Not ready" } else { "Ready" };
+                if ui.button(brush, tr(label), button(y, 0)) {
+                    request = Some(ClientMessage::Ready(!ready));
+                }
+                if ui.button(brush, tr("Leave"), button(y, last_column)) {
+                    request = Some(ClientMessage::Leave);
+                }
+
+                if request == Some(ClientMessage::Leave) {
+                    self.set_room(None);
+                }
+                request
+            }
+            None => {
+                ui.label(
+                    brush,
+                    tr("Open tables"),
+                    [inner.x, row(0) + label_offset],
+                    text_size,
+                );
+
+                let mut request = None;
+                if self.rooms.is_empty() {
+                    let text = tr("None yet; open one below");
+                    ui.label(brush, text, [inner.x, row(1) + label_offset], text_size);
+                }
+                for (index, room) in self.rooms.iter().take(MAX_ROOMS).enumerate() {
+                    let y = row(index + 1);
+                    let text = format!(
+                        "{}: {}/{} {}",
+                        room.name,
+                        room.players,
+                        room.seats,
+                        tr(if room.started { "playing" } else { "waiting" })
+                    );
+                    ui.label(brush, &text, [inner.x, y + label_offset], text_size);
+                    if ui.button(brush, tr("Join"), button(y, last_column)) {
+                        request = Some(ClientMessage::Join {
+                            room: room.name.clone(),
+                        });
+                    }
+                }
+
+                let y = row(rows - 2);
+                let game = title_case(GAMES[self.game]);
+                if ui.button(brush, &game, button(y, 0)) {
+                    self.game = (self.game + 1) % GAMES.len();
+                }
+                let computer = match self.options.computer_players {
+                    true => "Computer: on",
+                    false => "Computer: off",
+                };
+                if ui.button(brush, tr(computer), button(y, 1)) {
+                    self.options.computer_players = !self.options.computer_players;
+                }
+                let deal = match self.options.daily {
+                    true => "Deal: daily",
+                    false => "Deal: new",
+                };
+                if ui.button(brush, tr(deal), button(y, 2)) {
+                    self.options.daily = !self.options.daily;
+                }
+                if ui.button(brush, tr("Open table"), button(y, last_column)) {
+                    request = Some(ClientMessage::Create {
+                        game: GAMES[self.game].to_owned(),
+                        options: self.options,
+                    });
+                }
+
+                let y = row(rows - 1);
+                if ui.button(brush, tr("Refresh"), button(y, last_column)) {
+                    self.refreshed = None;
+                }
+                request
+            }
+        }
+    }
+}
+
+fn describe(options: TableOptions) -> String {
+    let seats = match options.computer_players {
+        true => "computer fills free seats",
+        false => "full table only",
+    };
+    match options.daily {
+        true => format!("{seats}, daily deal"),
+        false => seats.to_owned(),
+    }
+}
@@ -0,0 +1,320 @@
+//! The languages the app can be shown in.
+//!
+//! Text is written in English where it's shown and looked up in the current language's table as
+//! it's drawn or read out, falling back to the English where there's no translation. That goes
+//! for text from the games too, such as their actions and the names by the seats. Text with
+//! values in it is looked up as a template, with `{0}`, `{1}` and so on where the values go, so
+//! that a translation can put them in another order.
+
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    English,
+    German,
+}
+
+/// Every language, in the order they're gone through on the settings screen.
+const LANGUAGES: [Language; 2] = [Language::English, Language::German];
+
+/// The language text is shown in, by its place in `LANGUAGES`.
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+impl Language {
+    /// The language's name in itself.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    /// The language after this one, wrapping around.
+    pub fn next(self) -> Self {
+        let index = LANGUAGES.iter().position(|&language| language == self);
+        LANGUAGES[index.map_or(0, |index| (index + 1) % LANGUAGES.len())]
+    }
+
+    /// The language the system is set to, if there are words for it, or English.
+    pub fn system() -> Self {
+        match system_locale() {
+            Some(locale) if locale.starts_with("de") => Language::German,
+            _ => Language::English,
+        }
+    }
+
+    /// Translations from English, by the English.
+    fn table(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => &[],
+            Language::German => GERMAN,
+        }
+    }
+}
+
+/// The locale the user picked for the system, such as `de-DE`: the browser's language on the
+/// web, or the usual environment variables natively.
+#[cfg(not(target_arch = "wasm32"))]
+fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn system_locale() -> Option<String> {
+    web_sys::window()?.navigator().language()
+}
+
+/// Shows text in `language` from now on.
+pub fn set_language(language: Language) {
+    let index = LANGUAGES.iter().position(|&other| other == language);
+    CURRENT.store(index.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// The language text is being shown in.
+pub fn language() -> Language {
+    LANGUAGES[CURRENT.load(Ordering::Relaxed) % LANGUAGES.len()]
+}
+
+/// `text` in the current language, or as it is if there's no translation.
+pub fn tr(text: &str) -> &str {
+    language()
+        .table()
+        .iter()
+        .find(|(english, _)| *english == text)
+        .map_or(text, |&(_, translated)| translated)
+}
+
+/// `template` in the current language, with each `{n}` replaced by the `n`th of `values`.
+pub fn tr_with(template: &str, values: &[&dyn Display]) -> String {
+    let mut text = tr(template).to_owned();
+    for (index, value) in values.iter().enumerate() {
+        text = text.replace(&format!("{{{index}}}"), &value.to_string());
+    }
+    text
+}
+
+const GERMAN: &[(&str, &str)] = &[
+    // Menus.
+    ("Cards", "Karten"),
+    ("Play", "Spielen"),
+    ("Continue", "Fortsetzen"),
+    ("Puzzles", "Rätsel"),
+    ("Statistics", "Statistik"),
+    ("Settings", "Einstellungen"),
+    ("Pick a game", "Spiel wählen"),
+    ("Deal: daily", "Geben: täglich"),
+    ("Deal: random", "Geben: zufällig"),
+    ("Back", "Zurück"),
+    ("Paused", "Pause"),
+    ("Resume", "Weiter"),
+    ("Save this game?", "Spiel speichern?"),
+    ("Save and quit", "Speichern, beenden"),
+    ("Don't save", "Nicht speichern"),
+    ("Cancel", "Abbrechen"),
+    ("Continue elsewhere", "Anderswo fortsetzen"),
+    ("Restart", "Neu starten"),
+    ("Menu", "Menü"),
+    // Puzzles.
+    ("Puzzle", "Rätsel"),
+    ("Goal", "Ziel"),
+    ("Solved", "Gelöst"),
+    ("Win", "Gewinnen"),
+    ("In {0} moves", "In {0} Zügen"),
+    ("Last {0} tricks", "Die letzten {0} Stiche"),
+    ("As declarer", "Als Re-Partei"),
+    ("{0} moves", "{0} Züge"),
+    ("Puzzle solved!", "Rätsel gelöst!"),
+    ("Puzzle not solved", "Rätsel nicht gelöst"),
+    // Settings and keys.
+    ("Vsync", "VSync"),
+    ("Theme", "Design"),
+    ("Language", "Sprache"),
+    ("Four colours", "Vier Farben"),
+    ("Suit marks", "Farbzeichen"),
+    ("Large cards", "Große Karten"),
+    ("Reduce motion", "Weniger Bewegung"),
+    ("Playable glow", "Spielbare leuchten"),
+    ("Auto camera", "Automatische Kamera"),
+    ("On", "An"),
+    ("Off", "Aus"),
+    ("Speed: {0}x", "Tempo: {0}x"),
+    ("Volume", "Lautstärke"),
+    ("Effects", "Effekte"),
+    ("Music", "Musik"),
+    ("Keys", "Tasten"),
+    ("Reset", "Zurücksetzen"),
+    ("Tune", "Anpassen"),
+    ("Tune theme", "Design anpassen"),
+    ("Table hue", "Tischfarbton"),
+    ("Highlight", "Hervorhebung"),
+    ("Vignette", "Vignette"),
+    ("Soft shadows", "Weiche Schatten"),
+    ("Save as theme", "Als Design speichern"),
+    ("Saved theme {0}", "Design {0} gespeichert"),
+    (
+        "Press a key for {0} (Escape to cancel)",
+        "Taste für {0} drücken (Escape bricht ab)",
+    ),
+    ("Focus next", "Fokus weiter"),
+    ("Focus previous", "Fokus zurück"),
+    ("Focus up", "Fokus hoch"),
+    ("Focus left", "Fokus links"),
+    ("Focus down", "Fokus runter"),
+    ("Focus right", "Fokus rechts"),
+    ("Select", "Auswählen"),
+    ("Skip animation", "Animation überspringen"),
+    ("Pan up", "Schwenk hoch"),
+    ("Pan left", "Schwenk links"),
+    ("Pan down", "Schwenk runter"),
+    ("Pan right", "Schwenk rechts"),
+    ("Zoom in", "Vergrößern"),
+    ("Zoom out", "Verkleinern"),
+    ("Recentre", "Zentrieren"),
+    ("Toggle projection", "Projektion wechseln"),
+    ("Play pause", "Abspielen/Pause"),
+    ("Next theme", "Nächstes Design"),
+    ("Next trumps", "Nächste Trümpfe"),
+    ("Toggle filter", "Filter wechseln"),
+    ("Debug overlay", "Debug-Anzeige"),
+    ("Report problem", "Problem melden"),
+    ("Download offline", "Offline laden"),
+    // The toolbar and notices.
+    ("Trumps", "Trümpfe"),
+    ("Trumps: off", "Trümpfe: aus"),
+    ("Filter", "Filter"),
+    ("Undo", "Rückgängig"),
+    ("Redo", "Wiederholen"),
+    ("Hint", "Tipp"),
+    ("Moves", "Züge"),
+    ("Stats", "Statistik"),
+    ("Chat", "Chat"),
+    ("Chat ({0})", "Chat ({0})"),
+    ("Leave", "Verlassen"),
+    ("Export", "Exportieren"),
+    ("Theme: {0}", "Design: {0}"),
+    ("No hint", "Kein Tipp"),
+    ("Game record exported", "Spielprotokoll exportiert"),
+    (
+        "Too many moves to carry over",
+        "Zu viele Züge zum Mitnehmen",
+    ),
+    ("Replay stopped", "Wiedergabe beendet"),
+    (
+        "Connection lost; reconnecting",
+        "Verbindung verloren; verbinde neu",
+    ),
+    (
+        "The other player's connection was lost",
+        "Die Verbindung des anderen Spielers ist abgebrochen",
+    ),
+    ("Reconnected", "Wieder verbunden"),
+    ("Step", "Schritt"),
+    ("Pause", "Pause"),
+    ("Exit", "Beenden"),
+    // The end of a game.
+    ("You won!", "Gewonnen!"),
+    ("You won", "Gewonnen"),
+    ("You lost", "Verloren"),
+    ("A draw", "Unentschieden"),
+    ("Drawn", "Unentschieden"),
+    ("Game over", "Spiel vorbei"),
+    ("Time: {0}", "Zeit: {0}"),
+    ("Moves: {0}", "Züge: {0}"),
+    (
+        "This session: {0} won of {1}",
+        "Diese Sitzung: {0} von {1} gewonnen",
+    ),
+    ("Quickest win: {0}", "Schnellster Sieg: {0}"),
+    (
+        "Winning streak: {0} (longest {1})",
+        "Siegesserie: {0} (längste {1})",
+    ),
+    ("Deal again", "Neu geben"),
+    ("Close", "Schließen"),
+    // Statistics.
+    ("Game", "Spiel"),
+    ("Played", "Gespielt"),
+    ("Won", "Gewonnen"),
+    ("Rate", "Quote"),
+    ("Best", "Bestzeit"),
+    ("Streak", "Serie"),
+    ("Longest", "Längste"),
+    ("All games", "Alle Spiele"),
+    // The lobby.
+    ("Seat {0}", "Platz {0}"),
+    ("Seat {0}: {1}", "Platz {0}: {1}"),
+    ("Sit", "Setzen"),
+    ("Ready", "Bereit"),
+    ("Not ready", "Nicht bereit"),
+    ("Open tables", "Offene Tische"),
+    ("Join", "Beitreten"),
+    ("Computer: on", "Computer: an"),
+    ("Computer: off", "Computer: aus"),
+    ("Deal: new", "Geben: neu"),
+    ("Open table", "Tisch öffnen"),
+    ("Refresh", "Aktualisieren"),
+    ("you", "du"),
+    ("open", "frei"),
+    ("not ready", "nicht bereit"),
+    ("ready", "bereit"),
+    ("away", "weg"),
+    ("playing", "spielt"),
+    ("waiting", "wartet"),
+    ("None yet; open one below", "Noch keine; unten einen öffnen"),
+    // The table, and what the games show on it.
+    ("You", "Du"),
+    ("West", "West"),
+    ("North", "Nord"),
+    ("East", "Ost"),
+    ("Your turn", "Du bist dran"),
+    ("Pass", "Abgeben"),
+    ("Empty pile", "Leerer Stapel"),
+    ("Face-down card", "Verdeckte Karte"),
+    ("{0}, picked up", "{0}, aufgenommen"),
+    ("{0}, pile {1}", "{0}, Stapel {1}"),
+    ("Tricks: {0}", "Stiche: {0}"),
+    ("Score", "Punkte"),
+    ("Pairs", "Paare"),
+    ("Turns", "Züge"),
+    ("Chips", "Chips"),
+    ("Bet", "Einsatz"),
+    ("Dealer", "Bank"),
+    ("Hand", "Hand"),
+    ("Deal", "Geben"),
+    ("Hit", "Karte"),
+    ("Stand", "Halten"),
+    ("Double", "Verdoppeln"),
+    ("Split", "Teilen"),
+    ("Draw", "Ziehen"),
+    ("Redeal", "Neu geben"),
+    ("Finish", "Abschließen"),
+    // Cards, as they're read out.
+    ("{0} of {1}", "{1} {0}"),
+    ("{0} and {1} more", "{0} und {1} weitere"),
+    ("Ace", "Ass"),
+    ("Two", "Zwei"),
+    ("Three", "Drei"),
+    ("Four", "Vier"),
+    ("Five", "Fünf"),
+    ("Six", "Sechs"),
+    ("Seven", "Sieben"),
+    ("Eight", "Acht"),
+    ("Nine", "Neun"),
+    ("Ten", "Zehn"),
+    ("Jack", "Bube"),
+    ("Queen", "Dame"),
+    ("King", "König"),
+    ("Hearts", "Herz"),
+    ("Diamonds", "Karo"),
+    ("Spades", "Pik"),
+    ("Clubs", "Kreuz"),
+];
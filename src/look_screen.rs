@@ -0,0 +1,125 @@
+//! Tuning how the theme looks beyond its cards: sliders for the table's colour, the glow around
+//! playable cards, the vignette and the cards' shadows, which change the look as they're dragged,
+//! and a button to save it as a theme of its own.
+
+use crate::{
+    locale::tr,
+    text::TextBrush,
+    theme::{self, Look},
+    ui::{Rect, Ui, SPACING},
+};
+
+/// The width of a label and of the slider beside it, and the height of a row, in logical pixels.
+const LABEL_WIDTH: f32 = 176.0;
+const CONTROL_SIZE: [f32; 2] = [192.0, 32.0];
+
+/// The softest the cards' shadows can be made, as a multiple of the usual. They're kept well
+/// inside the margin the shadow shader leaves around each card.
+const MAX_SHADOW_SOFTNESS: f32 = 2.0;
+
+/// What was picked on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    /// Save the look, with the cards of the theme in use, as a new theme.
+    Save,
+    Back,
+}
+
+/// Shows the look's sliders in a panel in the middle of a `window_width` by `window_height`
+/// physical pixel window. The sliders change `look` as they're dragged; anything else picked is
+/// returned.
+pub fn draw(
+    ui: &mut Ui,
+    brush: &mut TextBrush,
+    look: &mut Look,
+    [window_width, window_height]: [f32; 2],
+    scale: f32,
+) -> Option<Choice> {
+    let label_width = LABEL_WIDTH * scale;
+    let [control_width, row_height] = CONTROL_SIZE.map(|length| length * scale);
+    let spacing = SPACING * scale;
+    let text_size = row_height * 0.6;
+    let label_offset = (row_height - text_size) / 2.0;
+
+    let rows = 7;
+    let width = label_width + control_width + 3.0 * spacing;
+    let height = rows as f32 * (row_height + spacing) + spacing;
+    let panel = Rect::new(
+        (window_width - width) / 2.0,
+        ((window_height - height) / 2.0).max(0.0),
+        width,
+        height,
+    );
+    ui.panel(brush, panel);
+    let inner = panel.inset(spacing);
+
+    let row = |index: usize| inner.y + index as f32 * (row_height + spacing);
+    let control = |index: usize| {
+        Rect::new(
+            inner.x + label_width + spacing,
+            row(index),
+            control_width,
+            row_height,
+        )
+    };
+    let label = |ui: &mut Ui, brush: &mut TextBrush, text: &str, index: usize| {
+        ui.label(brush, text, [inner.x, row(index) + label_offset], text_size);
+    };
+
+    label(ui, brush, tr("Tune theme"), 0);
+
+    label(
+        ui,
+        brush,
+        &format!("{}: {:.0}°", tr("Table hue"), look.table_hue),
+        1,
+    );
+    if let Some(fraction) = ui.slider(brush, "table hue", control(1), look.table_hue / 360.0) {
+        look.table_hue = fraction * 360.0;
+    }
+
+    // A swatch of the glow's colour at the end of its label, since the slider only shows where
+    // it is round the colour wheel.
+    label(ui, brush, tr("Highlight"), 2);
+    let swatch = text_size;
+    let [r, g, b] = look.highlight;
+    brush.fill(
+        [inner.x + label_width - swatch, row(2) + label_offset],
+        [swatch, swatch],
+        [r, g, b, 1.0],
+    );
+    let hue = theme::hue(look.highlight);
+    if let Some(fraction) = ui.slider(brush, "highlight", control(2), hue / 360.0) {
+        look.highlight = theme::with_hue(look.highlight, fraction * 360.0);
+    }
+
+    label(
+        ui,
+        brush,
+        &format!("{}: {:.0}%", tr("Vignette"), look.vignette * 100.0),
+        3,
+    );
+    if let Some(fraction) = ui.slider(brush, "vignette", control(3), look.vignette) {
+        look.vignette = fraction;
+    }
+
+    label(
+        ui,
+        brush,
+        &format!("{}: {:.1}x", tr("Soft shadows"), look.shadow_softness),
+        4,
+    );
+    let softness = look.shadow_softness / MAX_SHADOW_SOFTNESS;
+    if let Some(fraction) = ui.slider(brush, "shadows", control(4), softness) {
+        look.shadow_softness = fraction * MAX_SHADOW_SOFTNESS;
+    }
+
+    let mut choice = None;
+    if ui.button(brush, tr("Save as theme"), control(rows - 2)) {
+        choice = Some(Choice::Save);
+    }
+    if ui.button(brush, tr("Back"), control(rows - 1)) {
+        choice = Some(Choice::Back);
+    }
+    choice
+}
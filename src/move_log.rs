@@ -0,0 +1,74 @@
+//! The moves made so far, listed in a panel down the left of the window.
+
+use cards_core::game::MoveRecord;
+
+use crate::{
+    locale::tr,
+    text::TextBrush,
+    ui::{Rect, Ui},
+};
+
+/// The height of a line of the log, in logical pixels.
+const TEXT_SIZE: f32 = 16.0;
+
+/// Space between the edge of the window, the panel and its text, in logical pixels.
+const MARGIN: f32 = 8.0;
+
+/// How many characters of each move fit across the panel; longer moves are cut short.
+const COLUMNS: usize = 32;
+
+/// The height of the export button, in logical pixels.
+const BUTTON_HEIGHT: f32 = 24.0;
+
+/// Shows `moves`, numbered, in a panel above `bottom` physical pixels down the window. The
+/// latest moves are shown unless the log is scrolled back by `scroll` lines, which the mouse
+/// wheel changes. Returns whether the export button was clicked.
+pub fn draw(
+    ui: &mut Ui,
+    brush: &mut TextBrush,
+    moves: &[MoveRecord],
+    scroll: &mut usize,
+    bottom: f32,
+    scale: f32,
+) -> bool {
+    let size = TEXT_SIZE * scale;
+    let margin = MARGIN * scale;
+    let button_height = BUTTON_HEIGHT * scale;
+
+    let width = COLUMNS as f32 * TextBrush::measure("M", size)[0];
+    let panel = Rect::new(
+        margin,
+        margin,
+        width + 2.0 * margin,
+        (bottom - 2.0 * margin).max(0.0),
+    );
+    ui.panel(brush, panel);
+    let inner = panel.inset(margin);
+
+    let rows = ((inner.height - button_height - margin) / size).max(0.0) as usize;
+    let hidden = moves.len().saturating_sub(rows);
+    let lines = ui.scroll(panel).round() as isize;
+    *scroll = scroll.saturating_add_signed(lines).min(hidden);
+
+    let first = hidden - *scroll;
+    let text = moves
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .map(|(index, mv)| {
+            let line = format!("{:>3}. {}", index + 1, mv.notation);
+            line.chars().take(COLUMNS).collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    ui.label(brush, &text, [inner.x, inner.y], size);
+
+    let button = Rect::new(
+        inner.x,
+        inner.y + inner.height - button_height,
+        inner.width,
+        button_height,
+    );
+    ui.button(brush, tr("Export"), button)
+}
@@ -0,0 +1,644 @@
+//! Games hosted by a `cards-server`, played over a WebSocket: from a background thread (native)
+//! or the browser's own WebSocket (web). A dropped connection is made again, with the seat taken
+//! back and the game caught up on, without the player having to do anything.
+//!
+//! Moves from the server are held for a short interpolation delay before they're made here, and
+//! then made at the pace they were made on the server, so moves that arrive late or bunched
+//! together still deal and collect tricks smoothly rather than jumping.
+//!
+//! On the web, two players can also play without a server, with one browser hosting the game for
+//! the other over a direct connection; see `peer`.
+
+use std::{collections::VecDeque, time::Duration};
+
+use cards_core::{
+    game::MoveRecord,
+    protocol::{
+        self, ClientMessage, Lobby, MoveDelta, RoomSummary, ServerMessage, PROTOCOL_VERSION,
+    },
+};
+use instant::Instant;
+use log::{info, warn};
+
+#[cfg(target_arch = "wasm32")]
+mod peer;
+
+/// How long to wait before connecting again after the connection drops, doubling each time it
+/// fails again up to `MAX_RETRY_DELAY`.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(8);
+
+/// How long moves from the server are held before they're made here, unless
+/// `interpolation_delay` says otherwise: enough to even out a connection across the internet
+/// without the other players seeming slow.
+pub const DEFAULT_INTERPOLATION_DELAY: Duration = Duration::from_millis(100);
+
+/// How long to hold moves from the server before making them here, from `CARDS_NET_DELAY`
+/// (native) or the `net-delay` query parameter (web), in milliseconds.
+pub fn interpolation_delay() -> Duration {
+    #[cfg(not(target_arch = "wasm32"))]
+    let delay = std::env::var("CARDS_NET_DELAY").ok();
+    #[cfg(target_arch = "wasm32")]
+    let delay = crate::util::query_param("net-delay");
+
+    delay
+        .and_then(|delay| delay.parse().ok())
+        .map_or(DEFAULT_INTERPOLATION_DELAY, Duration::from_millis)
+}
+
+/// The server to play on, from `CARDS_SERVER` (native) or the `server` query parameter (web),
+/// such as `ws://localhost:9000`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn server_address() -> Option<String> {
+    std::env::var("CARDS_SERVER").ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn server_address() -> Option<String> {
+    crate::util::query_param("server")
+}
+
+/// The room to join on the server straight away, rather than picking one in the lobby, from
+/// `CARDS_ROOM` (native) or the `room` query parameter (web).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn room_name() -> Option<String> {
+    std::env::var("CARDS_ROOM").ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn room_name() -> Option<String> {
+    crate::util::query_param("room")
+}
+
+/// The game with other players to play from the start, if any: on the server at
+/// `server_address`, or on the web, hosted here for another browser with the `host` query
+/// parameter, or hosted by another browser with the link it gave out, which has a `join` query
+/// parameter.
+pub fn startup_multiplayer() -> Option<Multiplayer> {
+    if let Some(address) = server_address() {
+        return Some(Multiplayer::connect(address, room_name()));
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        if crate::util::query_param("host").is_some() {
+            return Some(Multiplayer::new(
+                Connection::Host(Box::new(peer::LocalHost::start())),
+                None,
+            ));
+        }
+        if let Some(offer) = crate::util::query_param("join") {
+            info!("joining a game hosted by another browser");
+            return Some(Multiplayer::new(
+                Connection::Peer(peer::Channel::join(offer)),
+                None,
+            ));
+        }
+    }
+
+    None
+}
+
+/// What's happened since the server was last heard from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetEvent {
+    /// The open rooms, as asked for with `ClientMessage::ListRooms`.
+    Rooms(Vec<RoomSummary>),
+    /// The room the player is waiting in for the game to start, which has changed.
+    Lobby(Lobby),
+    /// The game has started with the player in it, or the player has a seat, on joining or on
+    /// getting it back after the connection dropped, in a game dealt from `seed` in which `moves`
+    /// have been made.
+    Joined {
+        game: String,
+        seed: u64,
+        seat: usize,
+        moves: Vec<MoveRecord>,
+    },
+    /// Moves made since the last event, following on from those, as they fall due to be made
+    /// here.
+    Moves(Vec<MoveRecord>),
+    /// The connection dropped, and is being made again.
+    Reconnecting,
+    /// The connection to another browser dropped, which can't be made again.
+    Disconnected,
+    /// The player in `seat` said something to the room.
+    Said { seat: usize, text: String },
+    /// The server turned down what was asked of it, for this reason.
+    Rejected(String),
+}
+
+/// A connection to a server, and the seat taken there, which is kept through dropped connections.
+pub struct Multiplayer {
+    /// The server to connect to again when the connection drops, if it's to a server.
+    address: Option<String>,
+    connection: Connection,
+    /// What to take the seat back with if the connection drops, once there's a seat.
+    session: Option<u64>,
+    seat: Option<usize>,
+    /// Whether the seat is being taken back after the connection dropped, and not yet given.
+    resuming: bool,
+    /// How many moves have been heard about, and when the last of them was made.
+    received: usize,
+    last_move: Duration,
+    /// Moves heard about but not yet made here, each with when it's due to be made.
+    pending: VecDeque<(Instant, MoveRecord)>,
+    /// When the game started by the clock here, as near as can be told from how soon moves have
+    /// arrived after being made, which is what the moves are timed from.
+    started: Option<Instant>,
+    /// How long moves are held after they're due by `started`, to smooth over late ones.
+    delay: Duration,
+    /// Whether the player has asked for a move that hasn't been heard about yet, which is made
+    /// as soon as it is rather than held back.
+    playing: bool,
+    /// When to connect again, while the connection is down.
+    retry_at: Option<Instant>,
+    retry_delay: Duration,
+}
+
+impl Multiplayer {
+    /// Connects to the server at `address`, and takes a seat in `room` if one is given, or asks
+    /// for the open rooms if not.
+    pub fn connect(address: String, room: Option<String>) -> Self {
+        info!("connecting to {address}");
+        let connection = Connection::Server(Socket::open(&address));
+        let mut multiplayer = Self::new(connection, Some(address));
+        if let Some(room) = room {
+            multiplayer.send(ClientMessage::Join { room });
+        }
+        multiplayer
+    }
+
+    fn new(connection: Connection, address: Option<String>) -> Self {
+        let mut multiplayer = Self {
+            address,
+            connection,
+            session: None,
+            seat: None,
+            resuming: false,
+            received: 0,
+            last_move: Duration::ZERO,
+            pending: VecDeque::new(),
+            started: None,
+            delay: interpolation_delay(),
+            playing: false,
+            retry_at: None,
+            retry_delay: RETRY_DELAY,
+        };
+        multiplayer.greet();
+        multiplayer
+    }
+
+    /// Asks something of the server, other than making a move, which goes through `play`.
+    pub fn send(&mut self, message: ClientMessage) {
+        if message == ClientMessage::Leave {
+            self.session = None;
+            self.seat = None;
+            self.received = 0;
+            self.pending.clear();
+        }
+        self.connection.send(protocol::encode(&message));
+    }
+
+    /// Gives the player's seat up, if they have one, and closes the connection once that's been
+    /// sent.
+    pub fn close(mut self) {
+        if self.session.is_some() {
+            self.send(ClientMessage::Leave);
+        }
+        self.connection.close();
+    }
+
+    /// Says hello, then takes the seat back if there is one, or asks for the open rooms if not.
+    fn greet(&mut self) {
+        self.send(ClientMessage::Hello {
+            version: PROTOCOL_VERSION,
+        });
+        self.resuming = self.session.is_some();
+        self.send(match self.session {
+            Some(session) => ClientMessage::Resume { session },
+            None => ClientMessage::ListRooms,
+        });
+    }
+
+    /// The seat the player has taken, if they've taken one.
+    pub fn seat(&self) -> Option<usize> {
+        self.seat
+    }
+
+    /// Asks for the move at `index` in the game's `legal_moves` to be made. It's only made once
+    /// the server says so.
+    pub fn play(&mut self, index: usize) {
+        if self.session.is_some() && !self.resuming && self.retry_at.is_none() {
+            self.send(ClientMessage::Play { index });
+            self.playing = true;
+        }
+    }
+
+    /// Takes in what the server has sent, and connects again if it's time to.
+    pub fn poll(&mut self) -> Vec<NetEvent> {
+        let mut events = Vec::new();
+
+        if let (Some(retry_at), Some(address)) = (self.retry_at, &self.address) {
+            if Instant::now() < retry_at {
+                self.release_moves(&mut events);
+                return events;
+            }
+            info!("connecting to {address} again");
+            self.retry_at = None;
+            self.connection = Connection::Server(Socket::open(address));
+            self.greet();
+        }
+
+        let (frames, closed) = self.connection.receive();
+        for frame in frames {
+            match protocol::decode(&frame) {
+                Some(message) => events.extend(self.handle(message)),
+                None => warn!("ignoring a message from the server that couldn't be read"),
+            }
+        }
+
+        if closed {
+            match &self.address {
+                Some(address) => {
+                    warn!(
+                        "lost the connection to {address}; trying again in {:?}",
+                        self.retry_delay
+                    );
+                    self.retry_at = Some(Instant::now() + self.retry_delay);
+                    self.retry_delay = (self.retry_delay * 2).min(MAX_RETRY_DELAY);
+                    events.push(NetEvent::Reconnecting);
+                }
+                None => events.push(NetEvent::Disconnected),
+            }
+        }
+
+        self.release_moves(&mut events);
+        events
+    }
+
+    /// Passes on the moves that have fallen due.
+    fn release_moves(&mut self, events: &mut Vec<NetEvent>) {
+        let now = Instant::now();
+        let mut due = Vec::new();
+        while self.pending.front().is_some_and(|(at, _)| *at <= now) {
+            due.extend(self.pending.pop_front().map(|(_, mv)| mv));
+        }
+        if !due.is_empty() {
+            events.push(NetEvent::Moves(due));
+        }
+    }
+
+    fn handle(&mut self, message: ServerMessage) -> Option<NetEvent> {
+        match message {
+            ServerMessage::Welcome { .. } => {
+                self.retry_delay = RETRY_DELAY;
+                None
+            }
+            ServerMessage::Rooms(rooms) => Some(NetEvent::Rooms(rooms)),
+            ServerMessage::Lobby(lobby) => {
+                self.session = Some(lobby.session);
+                self.seat = Some(lobby.seat);
+                self.resuming = false;
+                self.received = 0;
+                self.last_move = Duration::ZERO;
+                self.pending.clear();
+                Some(NetEvent::Lobby(lobby))
+            }
+            ServerMessage::Joined {
+                room,
+                seat,
+                session,
+                game,
+                seed,
+                moves,
+            } => {
+                info!("sitting in seat {seat} in room {room:?}");
+                let moves = MoveDelta::decode(&moves, Duration::ZERO);
+                self.session = Some(session);
+                self.seat = Some(seat);
+                self.resuming = false;
+                self.received = moves.len();
+                self.last_move = moves.last().map_or(Duration::ZERO, |mv| mv.time);
+                // The game is caught up on straight away, and the moves after it timed from
+                // when the last of its moves was made, which can't have been after now.
+                self.pending.clear();
+                self.started = Instant::now().checked_sub(self.last_move);
+                self.playing = false;
+                Some(NetEvent::Joined {
+                    game,
+                    seed,
+                    seat,
+                    moves,
+                })
+            }
+            ServerMessage::Moves { first, moves } => {
+                if first != self.received {
+                    // Some moves went missing, so ask for the whole game again.
+                    warn!("expected move {} but got move {first}", self.received);
+                    if let Some(session) = self.session {
+                        self.send(ClientMessage::Resume { session });
+                    }
+                    return None;
+                }
+
+                let moves = MoveDelta::decode(&moves, self.last_move);
+                self.received += moves.len();
+                self.last_move = moves.last().map_or(self.last_move, |mv| mv.time);
+
+                let now = Instant::now();
+                for mv in moves {
+                    // A move arriving sooner after it was made than any before it shows the game
+                    // started later than thought.
+                    let started = now.checked_sub(mv.time).unwrap_or(now);
+                    let started = self.started.map_or(started, |before| before.min(started));
+                    self.started = Some(started);
+
+                    let due = match std::mem::take(&mut self.playing) {
+                        true => now,
+                        false => started + mv.time + self.delay,
+                    };
+                    self.pending.push_back((due, mv));
+                }
+                None
+            }
+            ServerMessage::Said { seat, text } => Some(NetEvent::Said { seat, text }),
+            ServerMessage::Rejected(reason) => {
+                warn!("the server said: {reason}");
+                self.playing = false;
+                if self.resuming {
+                    // The seat was given up while the connection was down, so find another.
+                    self.session = None;
+                    self.seat = None;
+                    self.resuming = false;
+                    self.send(ClientMessage::ListRooms);
+                }
+                Some(NetEvent::Rejected(reason))
+            }
+            ServerMessage::SeatTaken if self.address.is_some() => {
+                // The server hasn't noticed the old connection drop yet, so connect again in a
+                // while and ask once more.
+                warn!("the seat is still held by the old connection");
+                self.retry_at = Some(Instant::now() + self.retry_delay);
+                self.retry_delay = (self.retry_delay * 2).min(MAX_RETRY_DELAY);
+                Some(NetEvent::Reconnecting)
+            }
+            ServerMessage::SeatTaken => self.handle(ServerMessage::Rejected(
+                "that seat is still taken".to_owned(),
+            )),
+        }
+    }
+}
+
+/// What carries messages between the player and whoever's hosting the game.
+enum Connection {
+    Server(Socket),
+    /// A direct connection to another browser hosting the game.
+    #[cfg(target_arch = "wasm32")]
+    Peer(peer::Channel),
+    /// The game hosted here, for the player here and another browser.
+    #[cfg(target_arch = "wasm32")]
+    Host(Box<peer::LocalHost>),
+}
+
+impl Connection {
+    fn send(&mut self, frame: Vec<u8>) {
+        match self {
+            Self::Server(socket) => socket.send(frame),
+            #[cfg(target_arch = "wasm32")]
+            Self::Peer(channel) => channel.send(frame),
+            #[cfg(target_arch = "wasm32")]
+            Self::Host(host) => host.send(frame),
+        }
+    }
+
+    /// Closes the connection once everything sent on it has gone. On the web the browser sends
+    /// it on its own, as the connection is dropped.
+    fn close(self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Self::Server(socket) = self;
+            socket.close();
+        }
+    }
+
+    /// The frames received since the last call, and whether the connection has closed.
+    fn receive(&mut self) -> (Vec<Vec<u8>>, bool) {
+        match self {
+            Self::Server(socket) => socket.receive(),
+            #[cfg(target_arch = "wasm32")]
+            Self::Peer(channel) => channel.receive(),
+            #[cfg(target_arch = "wasm32")]
+            Self::Host(host) => host.receive(),
+        }
+    }
+}
+
+/// A WebSocket connection to the server, carrying encoded messages as binary frames, read and
+/// written on a background thread.
+#[cfg(not(target_arch = "wasm32"))]
+struct Socket {
+    outgoing: std::sync::mpsc::Sender<Vec<u8>>,
+    incoming: std::sync::mpsc::Receiver<Vec<u8>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Socket {
+    /// How long the thread waits for a frame from the server before sending any of its own.
+    const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+    /// The longest `close` waits for what's been sent to go.
+    const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+    fn open(address: &str) -> Self {
+        let (outgoing, to_send) = std::sync::mpsc::channel::<Vec<u8>>();
+        let (received, incoming) = std::sync::mpsc::channel();
+        let address = address.to_owned();
+
+        // The thread ends, dropping `received`, when the connection does.
+        std::thread::spawn(move || {
+            let mut socket = match tungstenite::connect(address.as_str()) {
+                Ok((socket, _)) => socket,
+                Err(e) => {
+                    warn!("couldn't connect to {address}: {e}");
+                    return;
+                }
+            };
+            if let tungstenite::stream::MaybeTlsStream::Plain(stream) = socket.get_ref() {
+                let _ = stream.set_read_timeout(Some(Self::POLL_INTERVAL));
+            }
+
+            loop {
+                match socket.read() {
+                    Ok(tungstenite::Message::Binary(frame)) => {
+                        if received.send(frame).is_err() {
+                            return;
+                        }
+                    }
+                    Ok(tungstenite::Message::Close(_)) => return,
+                    Ok(_) => {}
+                    Err(tungstenite::Error::Io(e))
+                        if matches!(
+                            e.kind(),
+                            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                        ) => {}
+                    Err(_) => return,
+                }
+
+                loop {
+                    match to_send.try_recv() {
+                        Ok(frame) => {
+                            if socket.send(tungstenite::Message::Binary(frame)).is_err() {
+                                return;
+                            }
+                        }
+                        Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                        // Everything's been sent, and the socket's closing.
+                        Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                            let _ = socket.close(None);
+                            let _ = socket.flush();
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+
+        Self { outgoing, incoming }
+    }
+
+    fn send(&self, frame: Vec<u8>) {
+        // A send to a closed connection is noticed by `receive`.
+        let _ = self.outgoing.send(frame);
+    }
+
+    /// Has the thread send what's left and close the connection, waiting up to `CLOSE_TIMEOUT`
+    /// for it to finish, which it has once it drops its end of `incoming`.
+    fn close(self) {
+        let Self { outgoing, incoming } = self;
+        drop(outgoing);
+        let deadline = Instant::now() + Self::CLOSE_TIMEOUT;
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            if incoming.recv_timeout(left).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// The frames received since the last call, and whether the connection has closed.
+    fn receive(&mut self) -> (Vec<Vec<u8>>, bool) {
+        let mut frames = Vec::new();
+        loop {
+            match self.incoming.try_recv() {
+                Ok(frame) => frames.push(frame),
+                Err(std::sync::mpsc::TryRecvError::Empty) => return (frames, false),
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => return (frames, true),
+            }
+        }
+    }
+}
+
+/// What the browser's WebSocket callbacks have to pass on.
+#[cfg(target_arch = "wasm32")]
+#[derive(Default)]
+struct Inbox {
+    frames: Vec<Vec<u8>>,
+    closed: bool,
+    /// Frames sent before the connection opened, to go once it does.
+    unsent: Vec<Vec<u8>>,
+}
+
+/// A WebSocket connection to the server, carrying encoded messages as binary frames, through the
+/// browser.
+#[cfg(target_arch = "wasm32")]
+struct Socket {
+    socket: Option<web_sys::WebSocket>,
+    inbox: std::rc::Rc<std::cell::RefCell<Inbox>>,
+    /// The callbacks, kept alive for as long as the socket.
+    _callbacks: Vec<wasm_bindgen::closure::Closure<dyn FnMut(wasm_bindgen::JsValue)>>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Socket {
+    fn open(address: &str) -> Self {
+        use std::{cell::RefCell, rc::Rc};
+        use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+        let inbox = Rc::new(RefCell::new(Inbox::default()));
+        let socket = match web_sys::WebSocket::new(address) {
+            Ok(socket) => socket,
+            Err(e) => {
+                warn!("couldn't connect to {address}: {e:?}");
+                inbox.borrow_mut().closed = true;
+                return Self {
+                    socket: None,
+                    inbox,
+                    _callbacks: Vec::new(),
+                };
+            }
+        };
+        socket.set_binary_type(web_sys::BinaryType::Arraybuffer);
+
+        let on_message = {
+            let inbox = inbox.clone();
+            Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                let data = event.unchecked_into::<web_sys::MessageEvent>().data();
+                inbox
+                    .borrow_mut()
+                    .frames
+                    .push(js_sys::Uint8Array::new(&data).to_vec());
+            })
+        };
+        let on_open = {
+            let inbox = inbox.clone();
+            let socket = socket.clone();
+            Closure::<dyn FnMut(JsValue)>::new(move |_| {
+                for frame in std::mem::take(&mut inbox.borrow_mut().unsent) {
+                    let _ = socket.send_with_u8_array(&frame);
+                }
+            })
+        };
+        let on_close = {
+            let inbox = inbox.clone();
+            Closure::<dyn FnMut(JsValue)>::new(move |_| inbox.borrow_mut().closed = true)
+        };
+
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        Self {
+            socket: Some(socket),
+            inbox,
+            _callbacks: vec![on_message, on_open, on_close],
+        }
+    }
+
+    fn send(&self, frame: Vec<u8>) {
+        match &self.socket {
+            Some(socket) if socket.ready_state() == web_sys::WebSocket::OPEN => {
+                let _ = socket.send_with_u8_array(&frame);
+            }
+            Some(_) => self.inbox.borrow_mut().unsent.push(frame),
+            None => {}
+        }
+    }
+
+    /// The frames received since the last call, and whether the connection has closed.
+    fn receive(&mut self) -> (Vec<Vec<u8>>, bool) {
+        let mut inbox = self.inbox.borrow_mut();
+        (std::mem::take(&mut inbox.frames), inbox.closed)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for Socket {
+    fn drop(&mut self) {
+        if let Some(socket) = &self.socket {
+            socket.set_onmessage(None);
+            socket.set_onopen(None);
+            socket.set_onclose(None);
+            let _ = socket.close();
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! Games played directly between two browsers over a WebRTC data channel, without a server. One
+//! browser hosts the game, running the same rooms a `cards-server` does, and the other joins it.
+//!
+//! Before the channel can open, each browser has to tell the other how to reach it, which is left
+//! to the players: the host is given a link to send the other player, who opens it and is given a
+//! code to send back, which the host pastes in.
+
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::mpsc::{self, Receiver},
+};
+
+use cards_core::{
+    host::{self, ClientId, Event, Host},
+    protocol::{self, ClientMessage, ServerMessage},
+};
+use instant::Instant;
+use log::{info, warn};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    RtcConfiguration, RtcDataChannel, RtcDataChannelEvent, RtcDataChannelState, RtcDataChannelType,
+    RtcIceGatheringState, RtcIceServer, RtcPeerConnection, RtcSdpType, RtcSessionDescriptionInit,
+};
+
+/// The server each browser asks for the address it can be reached at from outside its own
+/// network, unless the `stun` query parameter gives another.
+const DEFAULT_STUN_SERVER: &str = "stun:stun.l.google.com:19302";
+
+/// The player on the hosting browser, and the one joining it, as the host knows them.
+const LOCAL_PLAYER: ClientId = 0;
+const OTHER_PLAYER: ClientId = 1;
+
+/// What the data channel's callbacks have to pass on.
+#[derive(Default)]
+struct Link {
+    /// The data channel, once there is one; the joining browser is only given it once connected.
+    channel: Option<RtcDataChannel>,
+    frames: Vec<Vec<u8>>,
+    closed: bool,
+    /// Frames sent before the channel opened, to go once it does.
+    unsent: Vec<Vec<u8>>,
+    /// The callbacks, kept alive for as long as the connection.
+    callbacks: Vec<Closure<dyn FnMut(JsValue)>>,
+}
+
+/// A data channel to the other browser, carrying encoded messages as binary frames.
+pub struct Channel {
+    connection: Option<RtcPeerConnection>,
+    link: Rc<RefCell<Link>>,
+}
+
+impl Channel {
+    /// Opens a channel for another browser to join, giving the player a link to send them.
+    pub fn host() -> Self {
+        let channel = Self::open();
+        if let Some(connection) = &channel.connection {
+            attach(&channel.link, connection.create_data_channel("cards"));
+            channel.signal(offer(connection.clone()));
+        }
+        channel
+    }
+
+    /// Joins the browser whose offer is `offer`, from the link it gave out, giving the player a
+    /// code to send back to it.
+    pub fn join(offer: String) -> Self {
+        let channel = Self::open();
+        if let Some(connection) = &channel.connection {
+            let on_channel = {
+                let link = channel.link.clone();
+                Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                    attach(
+                        &link,
+                        event.unchecked_into::<RtcDataChannelEvent>().channel(),
+                    );
+                })
+            };
+            connection.set_ondatachannel(Some(on_channel.as_ref().unchecked_ref()));
+            channel.link.borrow_mut().callbacks.push(on_channel);
+            channel.signal(answer(connection.clone(), offer));
+        }
+        channel
+    }
+
+    fn open() -> Self {
+        let link = Rc::new(RefCell::new(Link::default()));
+        let stun = crate::util::query_param("stun").unwrap_or(DEFAULT_STUN_SERVER.to_owned());
+        let mut server = RtcIceServer::new();
+        server.urls(&JsValue::from_str(&stun));
+        let mut configuration = RtcConfiguration::new();
+        configuration.ice_servers(&js_sys::Array::of1(&server));
+
+        let connection = match RtcPeerConnection::new_with_configuration(&configuration) {
+            Ok(connection) => Some(connection),
+            Err(e) => {
+                warn!("couldn't make a connection to another browser: {e:?}");
+                link.borrow_mut().closed = true;
+                None
+            }
+        };
+        Self { connection, link }
+    }
+
+    /// Runs the signaling in the background, closing the channel if it fails.
+    fn signal(&self, signaling: impl std::future::Future<Output = Result<(), JsValue>> + 'static) {
+        let link = self.link.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = signaling.await {
+                warn!("couldn't connect to the other browser: {e:?}");
+                link.borrow_mut().closed = true;
+            }
+        });
+    }
+
+    pub fn send(&self, frame: Vec<u8>) {
+        let link = &mut *self.link.borrow_mut();
+        match &link.channel {
+            Some(channel) if channel.ready_state() == RtcDataChannelState::Open => {
+                let _ = channel.send_with_u8_array(&frame);
+            }
+            _ if link.closed => {}
+            _ => link.unsent.push(frame),
+        }
+    }
+
+    /// The frames received since the last call, and whether the channel has closed.
+    pub fn receive(&mut self) -> (Vec<Vec<u8>>, bool) {
+        let mut link = self.link.borrow_mut();
+        (std::mem::take(&mut link.frames), link.closed)
+    }
+}
+
+impl Drop for Channel {
+    fn drop(&mut self) {
+        let mut link = self.link.borrow_mut();
+        if let Some(channel) = link.channel.take() {
+            channel.set_onmessage(None);
+            channel.set_onopen(None);
+            channel.set_onclose(None);
+            channel.close();
+        }
+        if let Some(connection) = &self.connection {
+            connection.set_ondatachannel(None);
+            connection.close();
+        }
+        link.callbacks.clear();
+    }
+}
+
+/// Has `link` pass on what comes over `channel`.
+fn attach(link: &Rc<RefCell<Link>>, channel: RtcDataChannel) {
+    channel.set_binary_type(RtcDataChannelType::Arraybuffer);
+
+    let on_message = {
+        let link = link.clone();
+        Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+            let data = event.unchecked_into::<web_sys::MessageEvent>().data();
+            link.borrow_mut()
+                .frames
+                .push(js_sys::Uint8Array::new(&data).to_vec());
+        })
+    };
+    let on_open = {
+        let link = link.clone();
+        Closure::<dyn FnMut(JsValue)>::new(move |_| {
+            info!("connected to the other browser");
+            let link = &mut *link.borrow_mut();
+            if let Some(channel) = &link.channel {
+                for frame in std::mem::take(&mut link.unsent) {
+                    let _ = channel.send_with_u8_array(&frame);
+                }
+            }
+        })
+    };
+    let on_close = {
+        let link = link.clone();
+        Closure::<dyn FnMut(JsValue)>::new(move |_| link.borrow_mut().closed = true)
+    };
+
+    channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    channel.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+    channel.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+    let mut link = link.borrow_mut();
+    link.channel = Some(channel);
+    link.callbacks.extend([on_message, on_open, on_close]);
+}
+
+fn window() -> Result<web_sys::Window, JsValue> {
+    web_sys::window().ok_or_else(|| "there's no window".into())
+}
+
+/// The hosting browser's side of signaling: makes an offer, gives the player a link to it, and
+/// takes the other browser's answer.
+async fn offer(connection: RtcPeerConnection) -> Result<(), JsValue> {
+    let window = window()?;
+    let offer = JsFuture::from(connection.create_offer()).await?;
+    let code = describe(&connection, RtcSdpType::Offer, offer).await?;
+
+    let location = window.location();
+    let link = format!(
+        "{}{}?join={}",
+        location.origin()?,
+        location.pathname()?,
+        String::from(js_sys::encode_uri_component(&code))
+    );
+    window.prompt_with_message_and_default(
+        "Send this link to the other player, then press OK:",
+        &link,
+    )?;
+    let answer = window
+        .prompt_with_message("Paste the code the other player sent back:")?
+        .ok_or("no code was given")?;
+
+    let sdp = window.atob(answer.trim())?;
+    let mut description = RtcSessionDescriptionInit::new(RtcSdpType::Answer);
+    description.sdp(&sdp);
+    JsFuture::from(connection.set_remote_description(&description)).await?;
+    Ok(())
+}
+
+/// The joining browser's side of signaling: takes the host's offer, and gives the player a code
+/// to send back with an answer to it.
+async fn answer(connection: RtcPeerConnection, offer: String) -> Result<(), JsValue> {
+    let window = window()?;
+    let sdp = window.atob(offer.trim())?;
+    let mut description = RtcSessionDescriptionInit::new(RtcSdpType::Offer);
+    description.sdp(&sdp);
+    JsFuture::from(connection.set_remote_description(&description)).await?;
+
+    let answer = JsFuture::from(connection.create_answer()).await?;
+    let code = describe(&connection, RtcSdpType::Answer, answer).await?;
+    window.prompt_with_message_and_default(
+        "Send this code back to the player hosting the game, then press OK:",
+        &code,
+    )?;
+    Ok(())
+}
+
+/// Takes `description`, an offer or answer `connection` made, as its own, and waits until it
+/// knows every address it can be reached at. Returns the description with those addresses, as a
+/// code for the other browser.
+async fn describe(
+    connection: &RtcPeerConnection,
+    kind: RtcSdpType,
+    description: JsValue,
+) -> Result<String, JsValue> {
+    let sdp = js_sys::Reflect::get(&description, &"sdp".into())?
+        .as_string()
+        .unwrap_or_default();
+    let mut own = RtcSessionDescriptionInit::new(kind);
+    own.sdp(&sdp);
+    JsFuture::from(connection.set_local_description(&own)).await?;
+
+    if connection.ice_gathering_state() != RtcIceGatheringState::Complete {
+        let resolve = Rc::new(RefCell::new(None::<js_sys::Function>));
+        let gathered = js_sys::Promise::new(&mut |done, _| *resolve.borrow_mut() = Some(done));
+        let on_change = {
+            let connection = connection.clone();
+            Closure::<dyn FnMut(JsValue)>::new(move |_| {
+                if connection.ice_gathering_state() == RtcIceGatheringState::Complete {
+                    if let Some(done) = resolve.borrow_mut().take() {
+                        let _ = done.call0(&JsValue::NULL);
+                    }
+                }
+            })
+        };
+        connection.set_onicegatheringstatechange(Some(on_change.as_ref().unchecked_ref()));
+        JsFuture::from(gathered).await?;
+        connection.set_onicegatheringstatechange(None);
+    }
+
+    let sdp = connection
+        .local_description()
+        .map_or(sdp, |description| description.sdp());
+    window()?.btoa(&sdp)
+}
+
+/// A game hosted in this browser, for the player here and one other over a data channel.
+pub struct LocalHost {
+    host: Host,
+    last_update: Instant,
+    /// What the host has sent the player here, and what it's sent the other player once they've
+    /// said hello.
+    local: Receiver<ServerMessage>,
+    other: Option<Receiver<ServerMessage>>,
+    channel: Channel,
+    /// Answers to the player here saying hello, which don't go through the host.
+    greetings: Vec<Vec<u8>>,
+}
+
+impl LocalHost {
+    /// Starts hosting, giving the player a link to send the other player.
+    pub fn start() -> Self {
+        let mut host = Host::new(crate::util::random_seed(), crate::util::unix_time());
+        let (outbox, local) = mpsc::channel();
+        host.handle(Event::Connected(LOCAL_PLAYER, outbox));
+        info!("hosting a game for another browser");
+        Self {
+            host,
+            last_update: Instant::now(),
+            local,
+            other: None,
+            channel: Channel::host(),
+            greetings: Vec::new(),
+        }
+    }
+
+    /// Takes a frame from the player here.
+    pub fn send(&mut self, frame: Vec<u8>) {
+        let Some(message) = protocol::decode::<ClientMessage>(&frame) else {
+            return;
+        };
+        match message {
+            ClientMessage::Hello { .. } => {
+                let answer = host::greet(&message).unwrap_or_else(ServerMessage::Rejected);
+                self.greetings.push(protocol::encode(&answer));
+            }
+            message => self.host.handle(Event::Message(LOCAL_PLAYER, message)),
+        }
+    }
+
+    /// Brings the game up to date, passing on what the other player has said and sending them
+    /// what the host has to say. Returns the frames for the player here, and whether the
+    /// connection to the host has closed, which it never does.
+    pub fn receive(&mut self) -> (Vec<Vec<u8>>, bool) {
+        let now = Instant::now();
+        self.host.update(now - self.last_update);
+        self.last_update = now;
+
+        let (frames, closed) = self.channel.receive();
+        for frame in frames {
+            let Some(message) = protocol::decode::<ClientMessage>(&frame) else {
+                let rejected = ServerMessage::Rejected("couldn't read that".to_owned());
+                self.channel.send(protocol::encode(&rejected));
+                continue;
+            };
+            if self.other.is_some() {
+                self.host.handle(Event::Message(OTHER_PLAYER, message));
+                continue;
+            }
+            match host::greet(&message) {
+                Ok(welcome) => {
+                    self.channel.send(protocol::encode(&welcome));
+                    let (outbox, other) = mpsc::channel();
+                    self.host.handle(Event::Connected(OTHER_PLAYER, outbox));
+                    self.other = Some(other);
+                }
+                Err(reason) => {
+                    self.channel
+                        .send(protocol::encode(&ServerMessage::Rejected(reason)));
+                }
+            }
+        }
+
+        if closed && self.other.take().is_some() {
+            warn!("lost the connection to the other browser");
+            self.host.handle(Event::Disconnected(OTHER_PLAYER));
+        }
+        if let Some(other) = &self.other {
+            for message in other.try_iter() {
+                self.channel.send(protocol::encode(&message));
+            }
+        }
+
+        let mut frames = std::mem::take(&mut self.greetings);
+        frames.extend(
+            self.local
+                .try_iter()
+                .map(|message| protocol::encode(&message)),
+        );
+        (frames, false)
+    }
+}
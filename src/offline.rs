@@ -0,0 +1,33 @@
+//! Keeping the web build usable offline. The service worker in `sw.js` does the caching, using the
+//! asset list in `assets.json`; this side only asks it to.
+
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::JsValue;
+
+use crate::errors::*;
+
+fn js_error(e: JsValue) -> Error {
+    Error::from(format!("{e:?}"))
+}
+
+/// Asks the service worker to download every asset, plus `extra_urls`, so the game keeps working
+/// without a network.
+pub fn precache(extra_urls: &[String]) -> Result<()> {
+    let controller = web_sys::window()
+        .ok_or("no window to precache from")?
+        .navigator()
+        .service_worker()
+        .controller()
+        .ok_or("no service worker is controlling the page")?;
+
+    let urls = extra_urls
+        .iter()
+        .map(|url| JsValue::from_str(url))
+        .collect::<Array>();
+
+    let message = Object::new();
+    Reflect::set(&message, &"type".into(), &"precache".into()).map_err(js_error)?;
+    Reflect::set(&message, &"urls".into(), &urls).map_err(js_error)?;
+
+    controller.post_message(&message).map_err(js_error)
+}
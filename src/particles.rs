@@ -0,0 +1,360 @@
+use std::{f32::consts::TAU, mem::size_of, time::Duration};
+
+use bytemuck::{cast_slice, Pod, Zeroable};
+use cgmath::{Point2, Vector2};
+use wgpu::{
+    BindGroup, BindGroupLayout, BlendState, BufferAddress, BufferDescriptor, BufferUsages,
+    ColorTargetState, ColorWrites, CompareFunction, DepthStencilState, Device, FragmentState,
+    MultisampleState, PipelineLayoutDescriptor, Queue, RenderPass, RenderPipeline,
+    RenderPipelineDescriptor, TextureFormat, VertexAttribute, VertexBufferLayout, VertexState,
+    VertexStepMode,
+};
+
+use crate::diagnostics::DrawStats;
+
+/// Particles the instance buffer starts with room for; it grows as needed.
+const INITIAL_CAPACITY: usize = 256;
+
+/// How many particles each kind of burst throws out.
+const CONFETTI_COUNT: usize = 48;
+const SPARKLE_COUNT: usize = 10;
+
+/// The colours confetti is cut from, premultiplied and opaque.
+const CONFETTI_COLORS: [[f32; 4]; 6] = [
+    [0.95, 0.25, 0.3, 1.0],
+    [1.0, 0.75, 0.15, 1.0],
+    [0.3, 0.8, 0.4, 1.0],
+    [0.25, 0.55, 1.0, 1.0],
+    [0.75, 0.35, 0.95, 1.0],
+    [1.0, 1.0, 1.0, 1.0],
+];
+
+/// The light a sparkle gives off, added to what's behind it.
+const SPARKLE_COLOR: [f32; 4] = [1.0, 0.85, 0.45, 0.0];
+
+/// How long a particle takes to fade out at the end of its life, as a fraction of it.
+const FADE: f32 = 0.25;
+
+/// The shapes the particle shader draws, by the `shape` of an instance.
+const SHAPE_CONFETTI: u32 = 0;
+const SHAPE_SPARKLE: u32 = 1;
+
+/// A kind of burst of particles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emitter {
+    /// Scraps of coloured paper thrown up and fluttering down, for a game won.
+    Confetti,
+    /// A twinkle of light, for a card landing where it's done with.
+    Sparkle,
+}
+
+/// A burst waiting to go off.
+struct Burst {
+    emitter: Emitter,
+    at: Point2<f32>,
+    delay: Duration,
+}
+
+struct Particle {
+    position: Point2<f32>,
+    velocity: Vector2<f32>,
+    /// How far the particle is turned, and how fast it turns, in radians and radians per second.
+    angle: f32,
+    spin: f32,
+    /// How fast a scrap of confetti tumbles over, in radians per second, which narrows it as it
+    /// turns edge on.
+    tumble: f32,
+    size: Vector2<f32>,
+    color: [f32; 4],
+    shape: u32,
+    /// Pull downwards, in world units per second per second, and the fraction of its speed the
+    /// air takes away each second.
+    gravity: f32,
+    drag: f32,
+    age: f32,
+    life: f32,
+}
+
+/// One particle, as it's fed to the particle shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct Instance {
+    /// The centre, in world units.
+    position: [f32; 2],
+    size: [f32; 2],
+    /// Premultiplied by its alpha, which is how much the particle covers what's behind it.
+    color: [f32; 4],
+    angle: f32,
+    shape: u32,
+}
+
+impl Instance {
+    const ATTRIBUTES: [VertexAttribute; 5] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x2,
+        2 => Float32x4,
+        3 => Float32,
+        4 => Uint32,
+    ];
+
+    const BUFFER_LAYOUT: VertexBufferLayout<'static> = VertexBufferLayout {
+        array_stride: size_of::<Instance>() as BufferAddress,
+        step_mode: VertexStepMode::Instance,
+        attributes: &Self::ATTRIBUTES,
+    };
+}
+
+/// Small effects drawn over the cards, such as confetti and sparkles. Bursts are set off at places
+/// on the table, moved on by `update`, uploaded by `prepare`, and drawn by `draw` in a single
+/// instanced draw.
+pub struct Particles {
+    pipeline: RenderPipeline,
+    instance_buffer: wgpu::Buffer,
+    capacity: usize,
+    bursts: Vec<Burst>,
+    particles: Vec<Particle>,
+    prepared: u32,
+    /// A xorshift state, for scattering the particles of a burst.
+    noise: u32,
+}
+
+impl Particles {
+    pub fn new(
+        device: &Device,
+        camera_layout: &BindGroupLayout,
+        format: TextureFormat,
+        depth_format: TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        Self {
+            pipeline: create_pipeline(device, camera_layout, format, depth_format, sample_count),
+            instance_buffer: create_instance_buffer(device, INITIAL_CAPACITY),
+            capacity: INITIAL_CAPACITY,
+            bursts: Vec::new(),
+            particles: Vec::new(),
+            prepared: 0,
+            noise: 0x9e37_79b9,
+        }
+    }
+
+    /// Rebuilds the pipeline for a new MSAA sample count.
+    pub fn set_sample_count(
+        &mut self,
+        device: &Device,
+        camera_layout: &BindGroupLayout,
+        format: TextureFormat,
+        depth_format: TextureFormat,
+        sample_count: u32,
+    ) {
+        self.pipeline = create_pipeline(device, camera_layout, format, depth_format, sample_count);
+    }
+
+    /// Sets off a burst from `at`, in world units, after `delay`.
+    pub fn emit(&mut self, emitter: Emitter, at: Point2<f32>, delay: Duration) {
+        self.bursts.push(Burst { emitter, at, delay });
+    }
+
+    /// Sets off the bursts that are due and moves every particle on by `dt`, dropping those that
+    /// have faded out.
+    pub fn update(&mut self, dt: Duration) {
+        let mut due = Vec::new();
+        self.bursts
+            .retain_mut(|burst| match burst.delay.checked_sub(dt) {
+                Some(delay) if !delay.is_zero() => {
+                    burst.delay = delay;
+                    true
+                }
+                _ => {
+                    due.push((burst.emitter, burst.at));
+                    false
+                }
+            });
+        for (emitter, at) in due {
+            self.burst(emitter, at);
+        }
+
+        let dt = dt.as_secs_f32();
+        for particle in &mut self.particles {
+            particle.velocity.y -= particle.gravity * dt;
+            particle.velocity *= (1.0 - particle.drag * dt).max(0.0);
+            particle.position += particle.velocity * dt;
+            particle.angle += particle.spin * dt;
+            particle.age += dt;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.life);
+    }
+
+    /// Throws out the particles of a burst from `emitter` at `at`.
+    fn burst(&mut self, emitter: Emitter, at: Point2<f32>) {
+        match emitter {
+            Emitter::Confetti => {
+                for _ in 0..CONFETTI_COUNT {
+                    // Mostly up, fanning out to either side.
+                    let direction = TAU / 4.0 + self.between(-1.0, 1.0);
+                    let speed = self.between(150.0, 350.0);
+                    let color = CONFETTI_COLORS[self.next() as usize % CONFETTI_COLORS.len()];
+                    let particle = Particle {
+                        position: at,
+                        velocity: Vector2::new(direction.cos(), direction.sin()) * speed,
+                        angle: self.between(0.0, TAU),
+                        spin: self.between(-6.0, 6.0),
+                        tumble: self.between(4.0, 12.0),
+                        size: Vector2::new(5.0, 3.0),
+                        color,
+                        shape: SHAPE_CONFETTI,
+                        // Light enough to drift down slowly once the throw's worn off.
+                        gravity: 240.0,
+                        drag: 2.5,
+                        age: 0.0,
+                        life: self.between(4.0, 6.0),
+                    };
+                    self.particles.push(particle);
+                }
+            }
+            Emitter::Sparkle => {
+                for _ in 0..SPARKLE_COUNT {
+                    let direction = self.between(0.0, TAU);
+                    let speed = self.between(30.0, 90.0);
+                    let size = self.between(8.0, 14.0);
+                    let particle = Particle {
+                        position: at,
+                        velocity: Vector2::new(direction.cos(), direction.sin()) * speed,
+                        angle: self.between(0.0, TAU),
+                        spin: self.between(-2.0, 2.0),
+                        tumble: 0.0,
+                        size: Vector2::new(size, size),
+                        color: SPARKLE_COLOR,
+                        shape: SHAPE_SPARKLE,
+                        gravity: 0.0,
+                        drag: 4.0,
+                        age: 0.0,
+                        life: self.between(0.4, 0.7),
+                    };
+                    self.particles.push(particle);
+                }
+            }
+        }
+    }
+
+    fn next(&mut self) -> u32 {
+        self.noise ^= self.noise << 13;
+        self.noise ^= self.noise >> 17;
+        self.noise ^= self.noise << 5;
+        self.noise
+    }
+
+    /// A number from `min` to `max`.
+    fn between(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * (self.next() as f32 / u32::MAX as f32)
+    }
+
+    /// Uploads the particles as they are now, to be drawn by `draw`.
+    pub fn prepare(&mut self, device: &Device, queue: &Queue) {
+        if self.particles.len() > self.capacity {
+            self.capacity = self.particles.len().next_power_of_two();
+            self.instance_buffer = create_instance_buffer(device, self.capacity);
+        }
+
+        let instances = self
+            .particles
+            .iter()
+            .map(|particle| {
+                let remaining = 1.0 - particle.age / particle.life;
+                let fade = (remaining / FADE).min(1.0);
+                let width = particle.size.x * (particle.tumble * particle.age).cos();
+                Instance {
+                    position: particle.position.into(),
+                    size: [width, particle.size.y],
+                    color: particle.color.map(|channel| channel * fade),
+                    angle: particle.angle,
+                    shape: particle.shape,
+                }
+            })
+            .collect::<Vec<_>>();
+        if !instances.is_empty() {
+            queue.write_buffer(&self.instance_buffer, 0, cast_slice(&instances));
+        }
+        self.prepared = instances.len() as u32;
+    }
+
+    /// Draws the particles uploaded by the last `prepare`, in the world the camera shows.
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut RenderPass<'a>,
+        camera: &'a BindGroup,
+    ) -> DrawStats {
+        if self.prepared == 0 {
+            return DrawStats::default();
+        }
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, camera, &[]);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.prepared);
+
+        DrawStats {
+            draw_calls: 1,
+            instances: self.prepared,
+        }
+    }
+}
+
+fn create_instance_buffer(device: &Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&BufferDescriptor {
+        label: Some("Particle Instance Buffer"),
+        size: (capacity * size_of::<Instance>()) as BufferAddress,
+        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+fn create_pipeline(
+    device: &Device,
+    camera_layout: &BindGroupLayout,
+    format: TextureFormat,
+    depth_format: TextureFormat,
+    sample_count: u32,
+) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::include_wgsl!("particles.wgsl"));
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+        label: Some("Particle Pipeline Layout"),
+        bind_group_layouts: &[camera_layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("Particle Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[Instance::BUFFER_LAYOUT],
+        },
+        fragment: Some(FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(ColorTargetState {
+                format,
+                // Premultiplied, so confetti covers what's behind it while sparkles, with no
+                // alpha, only add their light to it.
+                blend: Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                write_mask: ColorWrites::ALL,
+            })],
+        }),
+        primitive: Default::default(),
+        // Particles are drawn over the cards, so they neither test nor write depth.
+        depth_stencil: Some(DepthStencilState {
+            format: depth_format,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::Always,
+            stencil: Default::default(),
+            bias: Default::default(),
+        }),
+        multisample: MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        multiview: None,
+    })
+}
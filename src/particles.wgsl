@@ -0,0 +1,66 @@
+// Confetti and sparkles, drawn over the cards. Each instance is one particle, drawn as a quad on
+// the table turned to its angle.
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct ParticleInput {
+    @location(0) position: vec2<f32>,
+    @location(1) size: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) angle: f32,
+    @location(4) shape: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // Position across the quad, from -1 to 1 each way.
+    @location(0) local_position: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) @interpolate(flat) shape: u32,
+}
+
+const SHAPE_SPARKLE: u32 = 1u;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, particle: ParticleInput) -> VertexOutput {
+    // Two triangles covering the quad.
+    var corners = array<vec2<f32>, 6>(
+        vec2(-1.0, -1.0),
+        vec2(1.0, -1.0),
+        vec2(1.0, 1.0),
+        vec2(-1.0, -1.0),
+        vec2(1.0, 1.0),
+        vec2(-1.0, 1.0),
+    );
+    let corner = corners[index];
+
+    let c = cos(particle.angle);
+    let s = sin(particle.angle);
+    let offset = corner * particle.size / 2.0;
+    let turned = vec2(offset.x * c - offset.y * s, offset.x * s + offset.y * c);
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4(particle.position + turned, 0.0, 1.0);
+    out.local_position = corner;
+    out.color = particle.color;
+    out.shape = particle.shape;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if in.shape != SHAPE_SPARKLE {
+        return in.color;
+    }
+
+    // A soft glow with a thin cross of light through it, brightest in the middle.
+    let p = abs(in.local_position);
+    let glow = pow(max(1.0 - length(p), 0.0), 2.0);
+    let rays = max(1.0 - p.x * 8.0, 0.0) * (1.0 - p.y) + max(1.0 - p.y * 8.0, 0.0) * (1.0 - p.x);
+    return in.color * min(glow + rays, 1.0);
+}
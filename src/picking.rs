@@ -0,0 +1,54 @@
+use cgmath::Point2;
+use winit::{
+    dpi::PhysicalPosition,
+    event::{Touch, TouchPhase},
+};
+
+use crate::card::Card;
+
+/// The kind of pointer a pick request came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerKind {
+    Mouse,
+    Touch,
+}
+
+impl PointerKind {
+    /// Extra room around each card that still counts as a hit, in screen pixels.
+    ///
+    /// Fingers are far less precise than a mouse, and the thin strips of card left showing in
+    /// overlapping cascades are otherwise almost impossible to tap.
+    pub fn hit_slop(&self) -> f32 {
+        match self {
+            PointerKind::Mouse => 0.0,
+            PointerKind::Touch => 12.0,
+        }
+    }
+}
+
+/// Finds the card under `point` (in world coordinates), returning its index in `cards`.
+///
+/// Cards later in the slice are drawn on top of earlier ones. A card that actually contains the
+/// point always wins, topmost first. Failing that, the card whose edge is nearest within `slop`
+/// world units is chosen, again preferring the topmost on ties.
+pub fn pick(cards: &[Card], point: Point2<f32>, slop: f32) -> Option<usize> {
+    cards
+        .iter()
+        .enumerate()
+        .map(|(index, card)| (index, card.distance_outside(point)))
+        .filter(|&(_, distance)| distance <= slop)
+        .rev()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index)
+}
+
+/// Returns the screen position of a touch that should select a card, if any.
+///
+/// Only the initial contact counts; moves and releases are left to whatever drags or gestures
+/// are layered on top.
+pub fn touch_pick_position(touch: &Touch) -> Option<PhysicalPosition<f64>> {
+    match touch.phase {
+        TouchPhase::Started => Some(touch.location),
+        _ => None,
+    }
+}
@@ -0,0 +1,116 @@
+//! The puzzles to pick from: a panel with a row for each, saying which game it is, what it takes
+//! to solve it and how the player has done, with a button to play it.
+
+use cards_core::puzzle::{Goal, Progress, Puzzle};
+
+use crate::{
+    locale::{tr, tr_with},
+    scene::title_case,
+    text::TextBrush,
+    ui::{Rect, Ui, BUTTON_SIZE, SPACING},
+};
+
+/// The headings of the columns, and how wide each is in logical pixels.
+const COLUMNS: [(&str, f32); 4] = [
+    ("Puzzle", 160.0),
+    ("Game", 112.0),
+    ("Goal", 200.0),
+    ("Solved", 96.0),
+];
+
+/// What was picked on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    /// Deal the puzzle with this index in the list.
+    Play(usize),
+    Back,
+}
+
+/// Shows `puzzles`, with the player's `progress` at them, in a panel in the middle of a
+/// `window_width` by `window_height` physical pixel window. Returns what was picked, if anything.
+pub fn draw(
+    ui: &mut Ui,
+    brush: &mut TextBrush,
+    puzzles: &[Puzzle],
+    progress: &Progress,
+    [window_width, window_height]: [f32; 2],
+    scale: f32,
+) -> Option<Choice> {
+    let [button_width, row_height] = BUTTON_SIZE.map(|length| length * scale);
+    let spacing = SPACING * scale;
+    let text_size = row_height * 0.6;
+    let label_offset = (row_height - text_size) / 2.0;
+
+    let mut rows = vec![COLUMNS.map(|(heading, _)| tr(heading).to_owned())];
+    rows.extend(puzzles.iter().map(|puzzle| cells(puzzle, progress)));
+
+    let columns_width = COLUMNS.iter().map(|(_, width)| width * scale).sum::<f32>();
+    let width = columns_width + button_width + 3.0 * spacing;
+    let height = (rows.len() + 2) as f32 * (row_height + spacing) + spacing;
+    let panel = Rect::new(
+        (window_width - width) / 2.0,
+        ((window_height - height) / 2.0).max(0.0),
+        width,
+        height,
+    );
+    ui.panel(brush, panel);
+    let inner = panel.inset(spacing);
+
+    let row = |index: usize| inner.y + index as f32 * (row_height + spacing);
+    ui.label(
+        brush,
+        tr("Puzzles"),
+        [inner.x, row(0) + label_offset],
+        text_size,
+    );
+
+    let mut picked = None;
+    for (index, cells) in rows.iter().enumerate() {
+        let mut x = inner.x;
+        for (cell, (_, width)) in cells.iter().zip(COLUMNS) {
+            ui.label(brush, cell, [x, row(index + 1) + label_offset], text_size);
+            x += width * scale;
+        }
+
+        // The first row is the headings.
+        if index > 0 {
+            let play = Rect::new(x + spacing, row(index + 1), button_width, row_height);
+            if ui.button(brush, tr("Play"), play) {
+                picked = Some(Choice::Play(index - 1));
+            }
+        }
+    }
+
+    let back = Rect::new(
+        inner.x + inner.width - button_width,
+        row(rows.len() + 1),
+        button_width,
+        row_height,
+    );
+    if ui.button(brush, tr("Back"), back) {
+        picked = Some(Choice::Back);
+    }
+    picked
+}
+
+/// A row of the table, for `puzzle`.
+fn cells(puzzle: &Puzzle, progress: &Progress) -> [String; 4] {
+    let goals = puzzle
+        .goals
+        .iter()
+        .map(|goal| match goal {
+            Goal::Win => tr("Win").to_owned(),
+            Goal::Moves(most) => tr_with("In {0} moves", &[most]),
+            Goal::LastTricks(count) => tr_with("Last {0} tricks", &[count]),
+            Goal::Declarer => tr("As declarer").to_owned(),
+        })
+        .collect::<Vec<_>>();
+    [
+        puzzle.name.clone(),
+        title_case(&puzzle.game),
+        goals.join(", "),
+        progress
+            .best(&puzzle.id)
+            .map_or("-".to_owned(), |best| tr_with("{0} moves", &[&best])),
+    ]
+}
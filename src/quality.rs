@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use instant::Instant;
+use log::info;
+use wgpu::{AdapterInfo, DeviceType, TextureFormatFeatureFlags};
+
+/// Frames rendered before timing starts, so shader compilation and first uploads don't count.
+const WARMUP_FRAMES: u32 = 10;
+
+/// Frames timed when deciding whether a preset is sustainable.
+const MEASURED_FRAMES: u32 = 60;
+
+/// Average frame time above which the benchmark steps down a preset. This is a little slower
+/// than 60Hz, so a vsync-locked device that keeps up never trips it.
+const FRAME_TIME_BUDGET: Duration = Duration::from_millis(22);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualityPreset {
+    Low,
+    Medium,
+    High,
+}
+
+impl QualityPreset {
+    /// A first guess based on the kind of adapter, before any frames have been timed.
+    pub fn for_adapter(info: &AdapterInfo) -> Self {
+        if cfg!(target_arch = "wasm32") {
+            return QualityPreset::Medium;
+        }
+
+        match info.device_type {
+            DeviceType::DiscreteGpu => QualityPreset::High,
+            DeviceType::IntegratedGpu | DeviceType::VirtualGpu => QualityPreset::Medium,
+            DeviceType::Cpu | DeviceType::Other => QualityPreset::Low,
+        }
+    }
+
+    /// A preset forced by the `CARDS_QUALITY` environment variable (`low`, `medium` or `high`),
+    /// which also turns off benchmarking.
+    pub fn from_env() -> Option<Self> {
+        if cfg!(target_arch = "wasm32") {
+            return None;
+        }
+
+        match std::env::var("CARDS_QUALITY").ok()?.to_lowercase().as_str() {
+            "low" => Some(QualityPreset::Low),
+            "medium" => Some(QualityPreset::Medium),
+            "high" => Some(QualityPreset::High),
+            other => {
+                info!("ignoring unknown CARDS_QUALITY value {other:?}");
+                None
+            }
+        }
+    }
+
+    pub fn lower(&self) -> Option<Self> {
+        match self {
+            QualityPreset::Low => None,
+            QualityPreset::Medium => Some(QualityPreset::Low),
+            QualityPreset::High => Some(QualityPreset::Medium),
+        }
+    }
+
+    /// The MSAA sample count for this preset, clamped to what the surface format supports.
+    pub fn msaa_samples(&self, format_flags: TextureFormatFeatureFlags) -> u32 {
+        let wanted = match self {
+            QualityPreset::Low => 1,
+            QualityPreset::Medium => 4,
+            QualityPreset::High => 8,
+        };
+
+        [8, 4, 2]
+            .into_iter()
+            .filter(|&count| count <= wanted)
+            .find(|&count| format_flags.sample_count_supported(count))
+            .unwrap_or(1)
+    }
+
+    /// The anisotropic filtering level for this preset.
+    pub fn anisotropy(&self) -> u8 {
+        match self {
+            QualityPreset::Low => 1,
+            QualityPreset::Medium => 4,
+            QualityPreset::High => 16,
+        }
+    }
+}
+
+/// Times the first frames rendered at a preset to check the device can keep up with it.
+pub struct QualityBenchmark {
+    frames: u32,
+    started: Option<Instant>,
+}
+
+impl QualityBenchmark {
+    pub fn new() -> Self {
+        Self {
+            frames: 0,
+            started: None,
+        }
+    }
+
+    /// Records that a frame was presented. Once enough frames have been timed, returns their
+    /// average frame time and whether it fits within the budget.
+    pub fn record_frame(&mut self) -> Option<(Duration, bool)> {
+        self.frames += 1;
+
+        if self.frames == WARMUP_FRAMES {
+            self.started = Some(Instant::now());
+        }
+
+        if self.frames != WARMUP_FRAMES + MEASURED_FRAMES {
+            return None;
+        }
+
+        let average = self.started?.elapsed() / MEASURED_FRAMES;
+        Some((average, average <= FRAME_TIME_BUDGET))
+    }
+}
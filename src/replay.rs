@@ -0,0 +1,214 @@
+//! Game records played back move by move, at the pace they were played or faster, with controls
+//! along the bottom of the window to pause, step and scrub through them.
+
+use std::time::Duration;
+
+use cards_core::{game::MoveRecord, save::SavedGame};
+
+use crate::{
+    locale::tr,
+    text::TextBrush,
+    ui::{Rect, Ui, SPACING},
+};
+
+/// The playback speeds the speed button steps through, as multiples of the pace the game was
+/// played at.
+const SPEEDS: [f32; 5] = [0.5, 1.0, 2.0, 4.0, 8.0];
+const DEFAULT_SPEED: usize = 1;
+
+/// The size of a control button, in logical pixels.
+const CONTROL_SIZE: [f32; 2] = [72.0, 28.0];
+
+/// The height of the scrubbing bar, in logical pixels.
+const BAR_HEIGHT: f32 = 12.0;
+
+/// What the replay controls were asked to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    TogglePlay,
+    /// Moves on to the next speed in `SPEEDS`, going back to the slowest after the fastest.
+    Faster,
+    StepBack,
+    StepForward,
+    /// Jumps to just after this many moves.
+    Seek(usize),
+    /// Stops playing back, leaving the game where the replay got to.
+    Exit,
+}
+
+pub struct Replay {
+    record: SavedGame,
+    /// How many of the record's moves have been made.
+    position: usize,
+    /// How far into the game playback has got. Each move is made as this passes its time.
+    clock: Duration,
+    playing: bool,
+    /// The index of the playback speed in `SPEEDS`.
+    speed: usize,
+}
+
+impl Replay {
+    /// Starts playing `record` back from the deal.
+    pub fn new(record: SavedGame) -> Self {
+        Self {
+            record,
+            position: 0,
+            clock: Duration::ZERO,
+            playing: true,
+            speed: DEFAULT_SPEED,
+        }
+    }
+
+    pub fn record(&self) -> &SavedGame {
+        &self.record
+    }
+
+    /// The moves made so far in playback.
+    pub fn moves(&self) -> &[MoveRecord] {
+        &self.record.moves[..self.position]
+    }
+
+    /// How far into the game playback has got.
+    pub fn clock(&self) -> Duration {
+        self.clock
+    }
+
+    /// Lets `dt` pass, making any moves that fall due at the playback speed. Returns whether any
+    /// did. Playback pauses at the end of the record.
+    pub fn advance(&mut self, dt: Duration) -> bool {
+        if !self.playing {
+            return false;
+        }
+
+        self.clock += dt.mul_f32(SPEEDS[self.speed]);
+        let start = self.position;
+        while self
+            .record
+            .moves
+            .get(self.position)
+            .is_some_and(|mv| mv.time <= self.clock)
+        {
+            self.position += 1;
+        }
+
+        if self.position == self.record.moves.len() {
+            self.playing = false;
+        }
+        self.position != start
+    }
+
+    /// Carries out `control`, other than `Exit`, returning whether it changed which moves have
+    /// been made.
+    pub fn control(&mut self, control: Control) -> bool {
+        let start = self.position;
+        match control {
+            Control::TogglePlay => {
+                // Playing on from the end starts again from the deal.
+                if !self.playing && self.position == self.record.moves.len() {
+                    self.seek(0);
+                }
+                self.playing = !self.playing;
+            }
+            Control::Faster => self.speed = (self.speed + 1) % SPEEDS.len(),
+            Control::StepBack => {
+                self.playing = false;
+                self.seek(self.position.saturating_sub(1));
+            }
+            Control::StepForward => {
+                self.playing = false;
+                self.seek(self.position + 1);
+            }
+            Control::Seek(position) => self.seek(position),
+            Control::Exit => {}
+        }
+        self.position != start
+    }
+
+    /// Jumps to just after `position` moves, with the clock at the time the last of them was made.
+    fn seek(&mut self, position: usize) {
+        self.position = position.min(self.record.moves.len());
+        self.clock = match self.position {
+            0 => Duration::ZERO,
+            position => self.record.moves[position - 1].time,
+        };
+    }
+
+    /// Shows the controls in a panel centred across a `window_width` physical pixels wide window,
+    /// with its bottom `bottom` pixels down: a scrubbing bar with the move count and time, and
+    /// buttons beneath it. Returns what was asked for, if anything.
+    pub fn draw(
+        &self,
+        ui: &mut Ui,
+        brush: &mut TextBrush,
+        window_width: f32,
+        bottom: f32,
+        scale: f32,
+    ) -> Option<Control> {
+        let [button_width, button_height] = CONTROL_SIZE.map(|length| length * scale);
+        let bar_height = BAR_HEIGHT * scale;
+        let spacing = SPACING * scale;
+
+        let speed = format!("{}x", SPEEDS[self.speed]);
+        let buttons = [
+            ("Back", Control::StepBack),
+            (
+                if self.playing { "Pause" } else { "Play" },
+                Control::TogglePlay,
+            ),
+            ("Step", Control::StepForward),
+            (speed.as_str(), Control::Faster),
+            ("Exit", Control::Exit),
+        ];
+
+        let width = buttons.len() as f32 * (button_width + spacing) + spacing;
+        let height = bar_height + button_height + 3.0 * spacing;
+        let panel = Rect::new((window_width - width) / 2.0, bottom - height, width, height);
+        ui.panel(brush, panel);
+        let inner = panel.inset(spacing);
+
+        let total = self.record.moves.len();
+        let seconds = self.clock.as_secs();
+        let label = format!(
+            "{}/{total} {}:{:02}",
+            self.position,
+            seconds / 60,
+            seconds % 60
+        );
+        let label_size = bar_height * 1.2;
+        let label_width = TextBrush::measure(&label, label_size)[0];
+        ui.label(
+            brush,
+            &label,
+            [inner.x, inner.y - bar_height * 0.1],
+            label_size,
+        );
+
+        let bar = Rect::new(
+            inner.x + label_width + spacing,
+            inner.y,
+            (inner.width - label_width - spacing).max(0.0),
+            bar_height,
+        );
+        let fraction = match total {
+            0 => 1.0,
+            total => self.position as f32 / total as f32,
+        };
+        let mut control = ui
+            .slider(brush, "replay", bar, fraction)
+            .map(|fraction| Control::Seek((fraction * total as f32).round() as usize));
+
+        for (index, (label, action)) in buttons.into_iter().enumerate() {
+            let rect = Rect::new(
+                inner.x + index as f32 * (button_width + spacing),
+                inner.y + bar_height + spacing,
+                button_width,
+                button_height,
+            );
+            if ui.button_with_id(brush, label, tr(label), rect) {
+                control = Some(action);
+            }
+        }
+
+        control
+    }
+}
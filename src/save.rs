@@ -0,0 +1,219 @@
+//! Where the game in progress, the player's statistics and the puzzles they've solved are kept
+//! between runs: files next to the app (native) or the browser's local storage (web).
+
+use cards_core::{puzzle::Progress, save::SavedGame, stats::Stats};
+
+use crate::errors::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+const SAVE_PATH: &str = "cards-save.txt";
+
+#[cfg(not(target_arch = "wasm32"))]
+const STATS_PATH: &str = "cards-stats.txt";
+
+#[cfg(not(target_arch = "wasm32"))]
+const PUZZLES_PATH: &str = "cards-puzzles.txt";
+
+#[cfg(target_arch = "wasm32")]
+const SAVE_PATH: &str = "cards-save";
+
+#[cfg(target_arch = "wasm32")]
+const STATS_PATH: &str = "cards-stats";
+
+#[cfg(target_arch = "wasm32")]
+const PUZZLES_PATH: &str = "cards-puzzles";
+
+/// The saved game, if there is one and it can be read.
+pub fn load() -> Option<SavedGame> {
+    let text = read(SAVE_PATH)?;
+    let saved = SavedGame::from_text(&text);
+    if saved.is_none() {
+        log::warn!("ignoring a saved game that couldn't be read, or is from another version");
+    }
+    saved
+}
+
+/// Saves `game` over any saved before it.
+pub fn store(game: &SavedGame) -> Result<()> {
+    write(SAVE_PATH, &game.to_text()).chain_err(|| "couldn't save the game")
+}
+
+/// The player's statistics, or none yet if they haven't finished a game or they can't be read.
+pub fn load_stats() -> Stats {
+    let Some(text) = read(STATS_PATH) else {
+        return Stats::default();
+    };
+    Stats::from_text(&text).unwrap_or_else(|| {
+        log::warn!("ignoring statistics that couldn't be read");
+        Stats::default()
+    })
+}
+
+pub fn store_stats(stats: &Stats) -> Result<()> {
+    write(STATS_PATH, &stats.to_text()).chain_err(|| "couldn't save the statistics")
+}
+
+/// The puzzles the player has solved, or none yet if they haven't or it can't be read.
+pub fn load_progress() -> Progress {
+    let Some(text) = read(PUZZLES_PATH) else {
+        return Progress::default();
+    };
+    Progress::from_text(&text).unwrap_or_else(|| {
+        log::warn!("ignoring puzzle progress that couldn't be read");
+        Progress::default()
+    })
+}
+
+pub fn store_progress(progress: &Progress) -> Result<()> {
+    write(PUZZLES_PATH, &progress.to_text()).chain_err(|| "couldn't save the puzzles solved")
+}
+
+/// The text kept at `path`, which on the web is its key in local storage.
+#[cfg(not(target_arch = "wasm32"))]
+fn read(path: &str) -> Option<String> {
+    std::fs::read_to_string(path).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(path: &str) -> Option<String> {
+    local_storage().ok()?.get_item(path).ok()?
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(path: &str, text: &str) -> Result<()> {
+    std::fs::write(path, text).chain_err(|| format!("couldn't write {path}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(path: &str, text: &str) -> Result<()> {
+    local_storage()?
+        .set_item(path, text)
+        .map_err(|_| "local storage refused the write".into())
+}
+
+/// Forgets the saved game, once it's finished.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn clear() -> Result<()> {
+    match std::fs::remove_file(SAVE_PATH) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).chain_err(|| "couldn't delete the saved game")
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn clear() -> Result<()> {
+    local_storage()?
+        .remove_item(SAVE_PATH)
+        .map_err(|_| "couldn't delete the saved game".into())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| "local storage isn't available".into())
+}
+
+/// Writes out a record of `game` for reading or playing back, returning where it went: a new file
+/// in the working directory (native) or the browser console (web).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export(game: &SavedGame) -> Result<String> {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let path = format!("cards-record-{timestamp}.txt");
+    std::fs::write(&path, game.to_text()).chain_err(|| "couldn't export the game record")?;
+    Ok(path)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn export(game: &SavedGame) -> Result<String> {
+    log::info!("game record:\n{}", game.to_text());
+    Ok("the console".to_owned())
+}
+
+/// A record to play back instead of dealing a game, from the file given with `--replay <file>`,
+/// or the saved game for a bare `--replay` (native), or the saved game for the `replay` query
+/// parameter (web).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn replay_requested() -> Option<SavedGame> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--replay");
+    args.next()?;
+
+    let path = args
+        .next()
+        .filter(|arg| !arg.starts_with("--"))
+        .unwrap_or_else(|| SAVE_PATH.to_owned());
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => {
+            log::warn!("ignoring the replay {path}: {e}");
+            return None;
+        }
+    };
+    let record = SavedGame::from_text(&text);
+    if record.is_none() {
+        log::warn!("ignoring the replay {path}, which isn't a game record");
+    }
+    record
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn replay_requested() -> Option<SavedGame> {
+    crate::util::query_param("replay").and_then(|_| load())
+}
+
+/// Whether to carry on the saved game straight away, from the `--resume` argument (native) or the
+/// `resume` query parameter (web).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn resume_requested() -> bool {
+    std::env::args().any(|arg| arg == "--resume")
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn resume_requested() -> bool {
+    crate::util::query_param("resume").is_some()
+}
+
+/// A game carried over from another device, from the code given with `--resume <code>` (native)
+/// or as the `resume` query parameter's value (web), to carry on in place of any saved here.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn handed_off() -> Option<SavedGame> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--resume");
+    args.next()?;
+    from_code(&args.next().filter(|arg| !arg.starts_with("--"))?)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn handed_off() -> Option<SavedGame> {
+    from_code(&crate::util::query_param("resume").filter(|code| !code.is_empty())?)
+}
+
+fn from_code(code: &str) -> Option<SavedGame> {
+    let game = SavedGame::from_code(code);
+    if game.is_none() {
+        log::warn!("ignoring the resume code {code}, which isn't a game");
+    }
+    game
+}
+
+/// What carries `game` on on another device: the command line that does (native), or a link to
+/// this page that does (web).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn resume_link(game: &SavedGame) -> String {
+    format!("cards --resume {}", game.to_code())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn resume_link(game: &SavedGame) -> String {
+    let page = web_sys::window()
+        .and_then(|window| {
+            let location = window.location();
+            Some(location.origin().ok()? + &location.pathname().ok()?)
+        })
+        .unwrap_or_default();
+    format!("{page}?resume={}", game.to_code())
+}
@@ -0,0 +1,245 @@
+//! The screens the app moves between, kept as a stack so that going back returns to the screen
+//! before: the menu it starts on, picking a game or a puzzle to play, the game itself, the game
+//! paused, carrying it on on another device, the settings, key bindings and theme tuning, and asking
+//! whether to keep the game on quitting.
+
+use crate::{
+    locale::tr,
+    text::TextBrush,
+    ui::{Rect, Ui, BUTTON_SIZE, SPACING},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scene {
+    Menu,
+    GameSelect,
+    /// The puzzles, which have their own screen.
+    Puzzles,
+    InGame,
+    Pause,
+    /// The code and link for carrying the game on on another device, from the pause screen.
+    HandOff,
+    Settings,
+    /// The key bindings, from the settings.
+    Keys,
+    /// Tuning how the theme looks, from the settings.
+    Look,
+    /// Asking whether to keep the game being played, on closing the window in the middle of it.
+    Quit,
+}
+
+/// What was picked on one of the screens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    /// On to picking a game.
+    Play,
+    /// Carry on the saved game.
+    Continue,
+    /// On to picking a puzzle.
+    Puzzles,
+    Stats,
+    Settings,
+    /// Deal the game with this index in the list of games.
+    Pick(usize),
+    /// Switch between random deals and the day's deal.
+    Daily,
+    Back,
+    /// Deal the game being played again from a new seed.
+    Restart,
+    /// Show how to carry the game on on another device.
+    HandOff,
+    /// Leave the game for the menu.
+    Menu,
+    /// Close the window, keeping the game being played to carry on with next time.
+    SaveAndQuit,
+    /// Close the window, forgetting the game being played.
+    QuitWithoutSaving,
+}
+
+/// What the screens need to know to show their buttons.
+pub struct MenuOptions<'a> {
+    /// The games that can be picked, by name.
+    pub games: &'a [&'a str],
+    /// Whether there's a game to carry on with.
+    pub can_continue: bool,
+    /// Whether there are puzzles for any of the games.
+    pub puzzles: bool,
+    /// Whether games are dealt from the day's seed.
+    pub daily: bool,
+    /// Whether the game is played on a server, so can't be dealt again or left for the menu.
+    pub online: bool,
+    /// Whether the game could be carried on elsewhere, as one that's saved can.
+    pub hand_off: bool,
+}
+
+pub struct Scenes {
+    stack: Vec<Scene>,
+}
+
+impl Scenes {
+    /// Starts on the menu, or straight in a game with the menu to go back to.
+    pub fn new(playing: bool) -> Self {
+        let mut scenes = Self {
+            stack: vec![Scene::Menu],
+        };
+        if playing {
+            scenes.start_game();
+        }
+        scenes
+    }
+
+    pub fn current(&self) -> Scene {
+        *self.stack.last().expect("the menu is never left")
+    }
+
+    pub fn playing(&self) -> bool {
+        self.current() == Scene::InGame
+    }
+
+    /// Whether there's a game behind the current screen, or on it.
+    pub fn in_game(&self) -> bool {
+        self.stack.contains(&Scene::InGame)
+    }
+
+    pub fn push(&mut self, scene: Scene) {
+        self.stack.push(scene);
+    }
+
+    /// Goes back to the screen before, returning whether there was one: from the menu there's
+    /// nowhere to go back to.
+    pub fn back(&mut self) -> bool {
+        match self.stack.len() {
+            1 => false,
+            _ => {
+                self.stack.pop();
+                true
+            }
+        }
+    }
+
+    /// Leaves everything for the menu.
+    pub fn leave_for_menu(&mut self) {
+        self.stack.truncate(1);
+    }
+
+    /// Goes in to a game just dealt, with the menu behind it.
+    pub fn start_game(&mut self) {
+        self.stack.truncate(1);
+        self.stack.push(Scene::InGame);
+    }
+
+    /// Shows the current screen's buttons in a column in the middle of a `window_width` by
+    /// `window_height` physical pixel window. Returns what was picked, if anything. A game, the
+    /// puzzles, the hand-off and the settings have their own widgets, so show nothing here.
+    pub fn draw(
+        &self,
+        ui: &mut Ui,
+        brush: &mut TextBrush,
+        options: &MenuOptions,
+        [window_width, window_height]: [f32; 2],
+        scale: f32,
+    ) -> Option<Choice> {
+        let (title, buttons) = match self.current() {
+            Scene::Menu => {
+                let mut buttons = vec![(tr("Play").to_owned(), Choice::Play)];
+                if options.can_continue {
+                    buttons.push((tr("Continue").to_owned(), Choice::Continue));
+                }
+                if options.puzzles {
+                    buttons.push((tr("Puzzles").to_owned(), Choice::Puzzles));
+                }
+                buttons.push((tr("Statistics").to_owned(), Choice::Stats));
+                buttons.push((tr("Settings").to_owned(), Choice::Settings));
+                (tr("Cards"), buttons)
+            }
+            Scene::GameSelect => {
+                let mut buttons = options
+                    .games
+                    .iter()
+                    .enumerate()
+                    .map(|(index, name)| (title_case(name), Choice::Pick(index)))
+                    .collect::<Vec<_>>();
+                let deal = match options.daily {
+                    true => "Deal: daily",
+                    false => "Deal: random",
+                };
+                buttons.push((tr(deal).to_owned(), Choice::Daily));
+                buttons.push((tr("Back").to_owned(), Choice::Back));
+                (tr("Pick a game"), buttons)
+            }
+            Scene::Pause => {
+                let mut buttons = vec![(tr("Resume").to_owned(), Choice::Back)];
+                if !options.online {
+                    buttons.push((tr("Restart").to_owned(), Choice::Restart));
+                }
+                if options.hand_off {
+                    buttons.push((tr("Continue elsewhere").to_owned(), Choice::HandOff));
+                }
+                buttons.push((tr("Settings").to_owned(), Choice::Settings));
+                if !options.online {
+                    buttons.push((tr("Menu").to_owned(), Choice::Menu));
+                }
+                (tr("Paused"), buttons)
+            }
+            Scene::Quit => {
+                let buttons = vec![
+                    (tr("Save and quit").to_owned(), Choice::SaveAndQuit),
+                    (tr("Don't save").to_owned(), Choice::QuitWithoutSaving),
+                    (tr("Cancel").to_owned(), Choice::Back),
+                ];
+                (tr("Save this game?"), buttons)
+            }
+            Scene::Puzzles
+            | Scene::InGame
+            | Scene::HandOff
+            | Scene::Settings
+            | Scene::Keys
+            | Scene::Look => return None,
+        };
+
+        let [button_width, row_height] = BUTTON_SIZE.map(|length| length * scale);
+        let spacing = SPACING * scale;
+        let text_size = row_height * 0.6;
+
+        let width = button_width + 2.0 * spacing;
+        let height = (buttons.len() + 1) as f32 * (row_height + spacing) + spacing;
+        let panel = Rect::new(
+            (window_width - width) / 2.0,
+            ((window_height - height) / 2.0).max(0.0),
+            width,
+            height,
+        );
+        ui.panel(brush, panel);
+        let inner = panel.inset(spacing);
+
+        let row = |index: usize| inner.y + index as f32 * (row_height + spacing);
+        let title_width = TextBrush::measure(title, text_size)[0];
+        ui.label(
+            brush,
+            title,
+            [
+                inner.x + (inner.width - title_width) / 2.0,
+                row(0) + (row_height - text_size) / 2.0,
+            ],
+            text_size,
+        );
+
+        let mut picked = None;
+        for (index, (label, choice)) in buttons.iter().enumerate() {
+            let rect = Rect::new(inner.x, row(index + 1), button_width, row_height);
+            if ui.button(brush, label, rect) {
+                picked = Some(*choice);
+            }
+        }
+        picked
+    }
+}
+
+/// `game` with a capital first letter, as it's shown.
+pub fn title_case(game: &str) -> String {
+    let mut chars = game.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,182 @@
+//! The settings screen: buttons for the settings with a few values to pick from, and sliders for
+//! the ones that run over a range.
+
+use crate::{
+    config::Settings,
+    locale::{self, tr, tr_with},
+    text::TextBrush,
+    ui::{Rect, Ui, SPACING},
+};
+
+/// The width of a label and of the control beside it, and the height of a row, in logical
+/// pixels.
+const LABEL_WIDTH: f32 = 176.0;
+const CONTROL_SIZE: [f32; 2] = [192.0, 32.0];
+
+/// The slowest and fastest the cards can be set to move, as multiples of their usual speed.
+const MIN_SPEED: f32 = 0.5;
+const MAX_SPEED: f32 = 2.0;
+
+/// A change made on the screen that has to be carried out, beyond the sliders' changes to the
+/// settings themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Vsync,
+    /// Move on to the next theme.
+    Theme,
+    /// Tune how the theme looks.
+    Look,
+    /// Move on to the next language.
+    Language,
+    FourColour,
+    SuitGlyphs,
+    LargeCards,
+    ReducedMotion,
+    PlayableGlow,
+    AutoCamera,
+    /// Show the key bindings.
+    Keys,
+    Back,
+}
+
+/// Shows the settings in a panel in the middle of a `window_width` by `window_height` physical
+/// pixel window, `theme` being the name of the theme in use and `reduced_motion` whether less
+/// motion is being shown, as set or as the system asks. The sliders change `settings` as
+/// they're dragged; anything else picked is returned.
+pub fn draw(
+    ui: &mut Ui,
+    brush: &mut TextBrush,
+    settings: &mut Settings,
+    theme: &str,
+    reduced_motion: bool,
+    [window_width, window_height]: [f32; 2],
+    scale: f32,
+) -> Option<Change> {
+    let label_width = LABEL_WIDTH * scale;
+    let [control_width, row_height] = CONTROL_SIZE.map(|length| length * scale);
+    let spacing = SPACING * scale;
+    let text_size = row_height * 0.6;
+    let label_offset = (row_height - text_size) / 2.0;
+
+    let rows = 16;
+    let width = label_width + control_width + 3.0 * spacing;
+    let height = rows as f32 * (row_height + spacing) + spacing;
+    let panel = Rect::new(
+        (window_width - width) / 2.0,
+        ((window_height - height) / 2.0).max(0.0),
+        width,
+        height,
+    );
+    ui.panel(brush, panel);
+    let inner = panel.inset(spacing);
+
+    let row = |index: usize| inner.y + index as f32 * (row_height + spacing);
+    let control = |index: usize| {
+        Rect::new(
+            inner.x + label_width + spacing,
+            row(index),
+            control_width,
+            row_height,
+        )
+    };
+    let label = |ui: &mut Ui, brush: &mut TextBrush, text: &str, index: usize| {
+        ui.label(brush, text, [inner.x, row(index) + label_offset], text_size);
+    };
+
+    label(ui, brush, tr("Settings"), 0);
+    let mut change = None;
+
+    label(ui, brush, tr("Vsync"), 1);
+    let vsync = match settings.vsync {
+        true => "On",
+        false => "Off",
+    };
+    if ui.button(brush, tr(vsync), control(1)) {
+        change = Some(Change::Vsync);
+    }
+
+    // The theme's button shares its row with one for tuning it.
+    label(ui, brush, tr("Theme"), 2);
+    let mut theme_button = control(2);
+    theme_button.width = (control_width - spacing) * 2.0 / 3.0;
+    let tune_button = Rect::new(
+        theme_button.x + theme_button.width + spacing,
+        theme_button.y,
+        control_width - theme_button.width - spacing,
+        row_height,
+    );
+    if ui.button(brush, theme, theme_button) {
+        change = Some(Change::Theme);
+    }
+    if ui.button(brush, tr("Tune"), tune_button) {
+        change = Some(Change::Look);
+    }
+
+    label(ui, brush, tr("Language"), 3);
+    if ui.button(brush, locale::language().name(), control(3)) {
+        change = Some(Change::Language);
+    }
+
+    let toggles = [
+        (
+            "Four colours",
+            settings.four_colour_suits,
+            Change::FourColour,
+        ),
+        ("Suit marks", settings.suit_glyphs, Change::SuitGlyphs),
+        ("Large cards", settings.large_cards, Change::LargeCards),
+        ("Reduce motion", reduced_motion, Change::ReducedMotion),
+        (
+            "Playable glow",
+            settings.playable_glow,
+            Change::PlayableGlow,
+        ),
+        ("Auto camera", settings.auto_camera, Change::AutoCamera),
+    ];
+    for (index, (name, on, toggle)) in toggles.into_iter().enumerate() {
+        let index = index + 4;
+        label(ui, brush, tr(name), index);
+        let text = match on {
+            true => "On",
+            false => "Off",
+        };
+        if ui.button_with_id(brush, name, tr(text), control(index)) {
+            change = Some(toggle);
+        }
+    }
+
+    let speed = settings.animation_speed;
+    let text = tr_with("Speed: {0}x", &[&format!("{speed:.1}")]);
+    label(ui, brush, &text, 10);
+    let fraction = (speed - MIN_SPEED) / (MAX_SPEED - MIN_SPEED);
+    if let Some(fraction) = ui.slider(brush, "speed", control(10), fraction) {
+        settings.animation_speed = MIN_SPEED + fraction * (MAX_SPEED - MIN_SPEED);
+    }
+
+    let audio = &mut settings.audio;
+    let volumes = [
+        ("Volume", &mut audio.master),
+        ("Effects", &mut audio.effects),
+        ("Music", &mut audio.music),
+    ];
+    for (index, (name, volume)) in volumes.into_iter().enumerate() {
+        let index = index + 11;
+        label(
+            ui,
+            brush,
+            &format!("{}: {:.0}%", tr(name), *volume * 100.0),
+            index,
+        );
+        if let Some(fraction) = ui.slider(brush, name, control(index), *volume) {
+            *volume = fraction;
+        }
+    }
+
+    if ui.button(brush, tr("Keys"), control(rows - 2)) {
+        change = Some(Change::Keys);
+    }
+    if ui.button(brush, tr("Back"), control(rows - 1)) {
+        change = Some(Change::Back);
+    }
+    change
+}
@@ -5,9 +5,19 @@ struct InstanceInput {
     @location(8) model_matrix_3: vec4<f32>,
     @location(9) rank: u32,
     @location(10) suit: u32,
-    @location(11) facedown: u32,
+    @location(11) flags: u32,
+    @location(12) highlight: f32,
+    @location(13) layer: u32,
+    // A colour to multiply by, with how far to grey out in place of alpha.
+    @location(15) modulate: vec4<f32>,
 }
 
+// The bits of an instance's `flags`. Mirrors `card::FLAG_*`.
+const FLAG_FACEDOWN = 1u;
+const FLAG_SELECTED = 2u;
+const FLAG_TRUMP = 4u;
+const FLAG_FOIL = 8u;
+
 struct CameraUniform {
     view_proj: mat4x4<f32>,
 };
@@ -15,6 +25,21 @@ struct CameraUniform {
 @group(1) @binding(0)
 var<uniform> camera: CameraUniform;
 
+// What changes from frame to frame. Mirrors `state::FrameUniform`.
+struct FrameUniform {
+    // Seconds since the app started.
+    time: f32,
+    // How much things on the table move by themselves, 0 when less motion is asked for.
+    motion: f32,
+    // How much softer card shadows are than usual, from the theme's look.
+    shadow_softness: f32,
+    // The colour playable cards glow, from the theme's look.
+    highlight: vec4<f32>,
+};
+
+@group(1) @binding(1)
+var<uniform> frame: FrameUniform;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
@@ -26,6 +51,14 @@ struct VertexOutput {
     @location(1) rank: u32,
     @location(2) suit: u32,
     @location(3) facedown: u32,
+    @location(4) highlight: f32,
+    @location(5) selected: u32,
+    @location(6) trump: u32,
+    @location(7) layer: u32,
+    @location(8) foil: u32,
+    // Where the card is on screen, from -1 to 1 across and up, which foil shimmers with.
+    @location(9) screen_position: vec2<f32>,
+    @location(10) modulate: vec4<f32>,
 };
 
 @vertex
@@ -45,26 +78,273 @@ fn vs_main(
     out.clip_position = camera.view_proj * instance_matrix * vec4<f32>(model.position, 1.0);
     out.rank = instance.rank;
     out.suit = instance.suit;
-    out.facedown = instance.facedown;
+    out.facedown = u32((instance.flags & FLAG_FACEDOWN) != 0u);
+    out.highlight = instance.highlight;
+    out.selected = u32((instance.flags & FLAG_SELECTED) != 0u);
+    out.trump = u32((instance.flags & FLAG_TRUMP) != 0u);
+    out.layer = instance.layer;
+    out.foil = u32((instance.flags & FLAG_FOIL) != 0u);
+    out.modulate = instance.modulate;
+    out.screen_position = out.clip_position.xy / out.clip_position.w;
     return out;
 }
 
 @group(0) @binding(0)
-var t_diffuse: texture_2d<f32>;
+var t_diffuse: texture_2d_array<f32>;
 
 @group(0) @binding(1)
 var s_diffuse: sampler;
 
+// Where each card sits on the sheet, in texels. Mirrors `texture::AtlasUniform`.
+struct Atlas {
+    texture_size: vec2<f32>,
+    cell_size: vec2<f32>,
+    margin: vec2<f32>,
+    spacing: vec2<f32>,
+    back: vec2<f32>,
+};
+
+@group(0) @binding(2)
+var<uniform> atlas: Atlas;
+
+// How card edges are drawn, in texels, and how the suits are told apart. Mirrors
+// `card::CardStyle`.
+struct CardStyle {
+    border_color: vec4<f32>,
+    corner_radius: f32,
+    border_width: f32,
+    four_colour: u32,
+    suit_glyphs: u32,
+    large_ranks: u32,
+    back_frames: u32,
+    back_frame_time: f32,
+};
+
+@group(0) @binding(3)
+var<uniform> style: CardStyle;
+
+// Card dimensions in texels.
+const CARD_SIZE = vec2<f32>(34.0, 48.0);
+const OUTLINE_WIDTH = 1.5;
+const OUTLINE_COLOR = vec3<f32>(1.0, 0.8, 0.2);
+// The trump underline sits just above the bottom edge, clear of the rounded corners.
+const UNDERLINE_INSET = 3.0;
+const UNDERLINE_WIDTH = 1.5;
+const UNDERLINE_COLOR = vec3<f32>(0.2, 0.6, 1.0);
+
+// The rows of the card sheet holding each suit's faces. Mirrors `card::texture_index`.
+const HEARTS = 0u;
+const DIAMONDS = 1u;
+const SPADES = 2u;
+const CLUBS = 3u;
+
+// The colours four-colour suits are drawn in, in place of red for diamonds and black for clubs.
+const DIAMONDS_BLUE = vec3<f32>(0.1, 0.35, 0.9);
+const CLUBS_GREEN = vec3<f32>(0.05, 0.55, 0.2);
+const SUIT_RED = vec3<f32>(0.8, 0.1, 0.1);
+const SUIT_BLACK = vec3<f32>(0.1, 0.1, 0.1);
+// How far red has to stand out from the other channels, and how dark a grey has to be, for a
+// pixel to count as part of a pip rather than the card or the artwork.
+const RED_THRESHOLD = 0.3;
+const BLACK_THRESHOLD = 0.35;
+// Pips are only recoloured this far inside the card's edge, which is often drawn black too.
+const RECOLOUR_INSET = 2.0;
+
+// Where the suit mark sits, in texels from the card's top-left corner, and the radius of the mark
+// itself and of the white badge behind it.
+const GLYPH_CENTRE = vec2<f32>(27.0, 7.0);
+const GLYPH_RADIUS = 4.5;
+const BADGE_RADIUS = 5.5;
+
+// Where the large-print rank starts, in texels from the card's top-left corner, how big each of
+// its pixels is, and how far its badge reaches past it.
+const RANK_ORIGIN = vec2<f32>(2.0, 1.5);
+const RANK_PIXEL = 1.5;
+const RANK_BADGE_MARGIN = 1.0;
+const RANK_GLYPH_SIZE = 5.0;
+
+// How much each channel counts towards how bright a colour looks, for greying it out.
+const LUMINANCE = vec3<f32>(0.299, 0.587, 0.114);
+
+// Foil: bands of rainbow colour running diagonally across the card, which slide along as the
+// card moves across the screen, as if the light caught it at a different angle.
+const FOIL_BANDS = 1.5;
+const FOIL_SLIDE = vec2<f32>(0.8, 0.5);
+const FOIL_STRENGTH = 0.35;
+
+// `color` with the foil's shimmer over it, at `tex_coords` on a card at `screen_position`.
+fn foil(color: vec3<f32>, tex_coords: vec2<f32>, screen_position: vec2<f32>) -> vec3<f32> {
+    let along = dot(tex_coords, vec2(0.6, 0.8)) * FOIL_BANDS + dot(screen_position, FOIL_SLIDE);
+    let rainbow = 0.5 + 0.5 * cos(6.2831853 * (along + vec3(0.0, 0.333, 0.667)));
+    // A brighter streak where the light catches it most.
+    let sheen = 0.4 + 0.6 * pow(0.5 + 0.5 * sin(6.2831853 * along * 0.5), 6.0);
+    return min(color + rainbow * FOIL_STRENGTH * sheen, vec3(1.0));
+}
+
+// Signed distance from `p` to a rounded rectangle centred on the origin, negative inside.
+fn rounded_rect_sdf(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + radius;
+    return length(max(q, vec2(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+fn circle_sdf(p: vec2<f32>, centre: vec2<f32>, radius: f32) -> f32 {
+    return length(p - centre) - radius;
+}
+
+// Roughly the distance to a diamond with its points `radius` from `centre`, negative inside.
+fn diamond_sdf(p: vec2<f32>, centre: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p - centre);
+    return (q.x + q.y - radius) * 0.7071;
+}
+
+fn box_sdf(p: vec2<f32>, centre: vec2<f32>, half_size: vec2<f32>) -> f32 {
+    let q = abs(p - centre) - half_size;
+    return length(max(q, vec2(0.0))) + min(max(q.x, q.y), 0.0);
+}
+
+// A heart pointing down, about a unit across, centred on the origin with y pointing up.
+fn heart_sdf(p: vec2<f32>) -> f32 {
+    let lobes = min(circle_sdf(p, vec2(-0.38, 0.3), 0.42), circle_sdf(p, vec2(0.38, 0.3), 0.42));
+    return min(lobes, diamond_sdf(p, vec2(0.0, -0.1), 0.8));
+}
+
+// Roughly the distance to `suit`'s mark, at `p` in units of its radius with y pointing up.
+fn suit_sdf(p: vec2<f32>, suit: u32) -> f32 {
+    let stem = box_sdf(p, vec2(0.0, -0.65), vec2(0.1, 0.3));
+    if suit == HEARTS {
+        return heart_sdf(p);
+    }
+    if suit == DIAMONDS {
+        return diamond_sdf(p, vec2(0.0), 1.0);
+    }
+    if suit == SPADES {
+        return min(heart_sdf(vec2(p.x, 0.25 - p.y)), stem);
+    }
+    let leaves = min(
+        circle_sdf(p, vec2(0.0, 0.42), 0.36),
+        min(circle_sdf(p, vec2(-0.42, -0.12), 0.36), circle_sdf(p, vec2(0.42, -0.12), 0.36)),
+    );
+    return min(leaves, stem);
+}
+
+// The large-print rank, ace to king, as five rows of five pixels each, one bit per pixel from the
+// top-left.
+fn rank_glyph(rank: u32) -> u32 {
+    var glyphs = array<u32, 13>(
+        0x0a53944u, 0x0e1390eu, 0x0e4310eu, 0x084394au, 0x0e4384eu, 0x0e5384eu, 0x042210eu,
+        0x0e5394eu, 0x0e4394eu, 0x1dad6bdu, 0x0452108u, 0x0c32944u, 0x0a5194au,
+    );
+    return glyphs[min(rank, 12u)];
+}
+
+// Whether the pixel of `rank`'s large-print glyph at `position`, in texels from the card's
+// top-left corner, is lit.
+fn rank_pixel(position: vec2<f32>, rank: u32) -> bool {
+    let pixel = floor((position - RANK_ORIGIN) / RANK_PIXEL);
+    if any(pixel < vec2(0.0)) || any(pixel >= vec2(RANK_GLYPH_SIZE)) {
+        return false;
+    }
+    let bit = u32(pixel.y * RANK_GLYPH_SIZE + pixel.x);
+    return ((rank_glyph(rank) >> bit) & 1u) == 1u;
+}
+
+// The colour `suit`'s pips are drawn in.
+fn suit_colour(suit: u32) -> vec3<f32> {
+    let four_colour = bool(style.four_colour);
+    if suit == HEARTS {
+        return SUIT_RED;
+    }
+    if suit == DIAMONDS {
+        return select(SUIT_RED, DIAMONDS_BLUE, four_colour);
+    }
+    if suit == SPADES {
+        return SUIT_BLACK;
+    }
+    return select(SUIT_BLACK, CLUBS_GREEN, four_colour);
+}
+
+// Redraws the red pips of diamonds in blue and the black pips of clubs in green, keeping each
+// pixel's shading.
+fn recolour(colour: vec3<f32>, suit: u32) -> vec3<f32> {
+    let brightest = max(colour.r, max(colour.g, colour.b));
+    if suit == DIAMONDS && colour.r - max(colour.g, colour.b) > RED_THRESHOLD {
+        return DIAMONDS_BLUE * (colour.r / SUIT_RED.r);
+    }
+    if suit == CLUBS && brightest < BLACK_THRESHOLD
+        && brightest - min(colour.r, min(colour.g, colour.b)) < 0.1 {
+        return mix(CLUBS_GREEN, vec3(0.0), brightest / BLACK_THRESHOLD * 0.5);
+    }
+    return colour;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let cards_per_row = 13.0;
-    let cards_per_col = 5.0;
-    let tex_size = vec2(cards_per_row, cards_per_col);
+    let faceup_cell = vec2(f32(in.rank), f32(in.suit));
+    // The back's animation stays on its first frame when less motion is asked for.
+    let back_frame = u32(frame.time * frame.motion / style.back_frame_time) % max(style.back_frames, 1u);
+    let back_cell = atlas.back + vec2(f32(back_frame), 0.0);
+    let cell = select(faceup_cell, back_cell, bool(in.facedown));
+
+    let cell_origin = atlas.margin + cell * (atlas.cell_size + atlas.spacing);
+    let coords = (cell_origin + in.tex_coords * atlas.cell_size) / atlas.texture_size;
+
+    let edge_distance = -rounded_rect_sdf((in.tex_coords - 0.5) * CARD_SIZE, CARD_SIZE / 2.0, style.corner_radius);
+
+    var color = textureSample(t_diffuse, s_diffuse, coords, i32(in.layer));
+    let face_up = !bool(in.facedown);
+    if face_up && bool(style.four_colour) && edge_distance > RECOLOUR_INSET {
+        color = vec4(recolour(color.rgb, in.suit), color.a);
+    }
+
+    // The suit mark, on a white badge so that it stands out from the artwork.
+    let glyph_offset = (in.tex_coords * CARD_SIZE - GLYPH_CENTRE) * vec2(1.0, -1.0);
+    let badge_distance = length(glyph_offset) - BADGE_RADIUS;
+    let glyph_distance = suit_sdf(glyph_offset / GLYPH_RADIUS, in.suit) * GLYPH_RADIUS;
+    let badge_coverage = clamp(0.5 - badge_distance / fwidth(badge_distance), 0.0, 1.0);
+    let glyph_coverage = clamp(0.5 - glyph_distance / fwidth(glyph_distance), 0.0, 1.0);
+    if face_up && bool(style.suit_glyphs) {
+        let badged = mix(color.rgb, vec3(1.0), badge_coverage);
+        color = vec4(mix(badged, suit_colour(in.suit), glyph_coverage), color.a);
+    }
+
+    // The rank in large print, over the small one printed in the corner.
+    let rank_half_size = vec2(RANK_GLYPH_SIZE * RANK_PIXEL / 2.0 + RANK_BADGE_MARGIN);
+    let rank_centre = RANK_ORIGIN + RANK_GLYPH_SIZE * RANK_PIXEL / 2.0;
+    let rank_badge = rounded_rect_sdf(in.tex_coords * CARD_SIZE - rank_centre, rank_half_size, 1.0);
+    let rank_badge_coverage = clamp(0.5 - rank_badge / fwidth(rank_badge), 0.0, 1.0);
+    if face_up && bool(style.large_ranks) {
+        let badged = mix(color.rgb, vec3(1.0), rank_badge_coverage);
+        let lit = rank_pixel(in.tex_coords * CARD_SIZE, in.rank);
+        color = vec4(select(badged, suit_colour(in.suit), lit), color.a);
+    }
+
+    if face_up && bool(in.foil) {
+        color = vec4(foil(color.rgb, in.tex_coords, in.screen_position), color.a);
+    }
+
+    let grey = vec3(dot(color.rgb, LUMINANCE));
+    let modulated = mix(color.rgb, grey, in.modulate.a) * in.modulate.rgb;
+    color = vec4(modulated, color.a);
+
+    let brightness = 1.0 + 0.25 * in.highlight;
+    let shaded = vec4(min(color.rgb * brightness, vec3(1.0)), color.a);
+    // Fade the edge out over about a pixel on screen, whatever the zoom.
+    let coverage = clamp(edge_distance / fwidth(edge_distance) + 0.5, 0.0, 1.0);
+
+    let outline = bool(in.selected) && edge_distance >= 0.0 && edge_distance < OUTLINE_WIDTH;
+
+    let card_position = in.tex_coords * CARD_SIZE;
+    let above_bottom = CARD_SIZE.y - card_position.y - UNDERLINE_INSET;
+    let underline = bool(in.trump) && !bool(in.facedown)
+        && above_bottom >= 0.0 && above_bottom < UNDERLINE_WIDTH
+        && abs(card_position.x - CARD_SIZE.x / 2.0) < CARD_SIZE.x / 2.0 - UNDERLINE_INSET;
 
-    let facedown_tex_tl = vec2(0.0, 4.0);
-    let faceup_tex_tl = vec2(f32(in.rank), f32(in.suit));
+    let underlined = select(shaded, vec4(UNDERLINE_COLOR, 1.0), underline);
 
-    let coords = (select(faceup_tex_tl, facedown_tex_tl, bool(in.facedown)) + in.tex_coords) / tex_size;
+    let border = edge_distance < style.border_width;
+    let border_color = vec4(mix(underlined.rgb, style.border_color.rgb, style.border_color.a), underlined.a);
+    let bordered = select(underlined, border_color, border);
 
-    return textureSample(t_diffuse, s_diffuse, coords);
+    let outlined = select(bordered, vec4(OUTLINE_COLOR, 1.0), outline);
+    return vec4(outlined.rgb, outlined.a * coverage);
 }
@@ -0,0 +1,121 @@
+// Soft drop shadows drawn under each card, offset away from a light above the top-left of the
+// table. Cards lifted off the table cast larger, blurrier shadows further away. Cards the player
+// can play also glow around their edges, pulsing gently.
+
+struct InstanceInput {
+    @location(5) model_matrix_0: vec4<f32>,
+    @location(6) model_matrix_1: vec4<f32>,
+    @location(7) model_matrix_2: vec4<f32>,
+    @location(8) model_matrix_3: vec4<f32>,
+    @location(11) flags: u32,
+    @location(14) elevation: f32,
+}
+
+// The bit of an instance's `flags` for a card the player can play. Mirrors `card::FLAG_PLAYABLE`.
+const FLAG_PLAYABLE = 16u;
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+// What changes from frame to frame. Mirrors `state::FrameUniform`.
+struct FrameUniform {
+    // Seconds since the app started.
+    time: f32,
+    // How much things on the table move by themselves, 0 when less motion is asked for.
+    motion: f32,
+    // How much softer card shadows are than usual, from the theme's look.
+    shadow_softness: f32,
+    // The colour playable cards glow, from the theme's look.
+    highlight: vec4<f32>,
+};
+
+@group(1) @binding(1)
+var<uniform> frame: FrameUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // Position in the card's own frame, in texels from its centre.
+    @location(0) local_position: vec2<f32>,
+    // How far the shadow is from the card, in the card's own frame.
+    @location(1) shadow_offset: vec2<f32>,
+    @location(2) softness: f32,
+    @location(3) playable: u32,
+};
+
+// Card dimensions in texels.
+const CARD_SIZE = vec2<f32>(34.0, 48.0);
+const CORNER_RADIUS = 3.0;
+// How far the quad extends past the card, to leave room for the blur, the offset and the glow.
+const SHADOW_MARGIN = 12.0;
+const SHADOW_OFFSET = vec2<f32>(1.0, -1.5);
+const SHADOW_OPACITY = 0.35;
+// How far the glow reaches past the card's edge, and how long a pulse takes.
+const GLOW_WIDTH = 4.0;
+const GLOW_PERIOD = 1.6;
+
+// Signed distance from `p` to a rounded rectangle centred on the origin, negative inside.
+fn rounded_rect_sdf(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + radius;
+    return length(max(q, vec2(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    let instance_matrix = mat4x4<f32>(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+
+    let local_position = model.position.xy * (CARD_SIZE + 2.0 * SHADOW_MARGIN) / CARD_SIZE;
+    let world_position = instance_matrix * vec4(local_position, 0.0, 1.0);
+
+    // The offset is in world space, so the light stays put as cards rotate. It's turned into the
+    // card's frame, whose axes are the matrix's first two columns, to find the shadow's edge.
+    let offset = SHADOW_OFFSET * (1.0 + 3.0 * instance.elevation);
+    let x_axis = instance.model_matrix_0.xy;
+    let y_axis = instance.model_matrix_1.xy;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * world_position;
+    out.local_position = local_position;
+    out.shadow_offset = vec2(
+        dot(offset, x_axis) / dot(x_axis, x_axis),
+        dot(offset, y_axis) / dot(y_axis, y_axis),
+    );
+    out.softness = (1.5 + 4.0 * instance.elevation) * frame.shadow_softness;
+    out.playable = u32((instance.flags & FLAG_PLAYABLE) != 0u);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let shadow_distance = rounded_rect_sdf(
+        in.local_position - in.shadow_offset,
+        CARD_SIZE / 2.0,
+        CORNER_RADIUS,
+    );
+    let shadow = SHADOW_OPACITY * (1.0 - smoothstep(-in.softness, in.softness, shadow_distance));
+
+    // The glow fades out from the card's edge, and pulses between half and full strength.
+    let edge_distance = rounded_rect_sdf(in.local_position, CARD_SIZE / 2.0, CORNER_RADIUS);
+    let pulse = 1.0 - 0.25 * frame.motion * (1.0 - cos(6.2831853 * frame.time / GLOW_PERIOD));
+    let fade = 1.0 - smoothstep(0.0, GLOW_WIDTH, edge_distance);
+    let glow = select(0.0, fade * pulse, bool(in.playable));
+
+    // The glow over the shadow.
+    let alpha = glow + shadow * (1.0 - glow);
+    return vec4(frame.highlight.rgb * glow / max(alpha, 0.001), alpha);
+}
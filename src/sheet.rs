@@ -0,0 +1,144 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+use image::{DynamicImage, GenericImageView};
+use log::error;
+use wgpu::{Device, ErrorFilter, Queue};
+
+use crate::{
+    card,
+    errors::*,
+    texture::{self, Atlas, Texture, TextureOptions},
+};
+
+const BUILT_IN_SHEET: &[u8] = include_bytes!("cards.png");
+const BUILT_IN_LABEL: &str = "cards.png";
+
+/// A card sheet on the GPU, along with where each card sits on it.
+pub struct CardSheet {
+    pub texture: Texture,
+    pub atlas: Atlas,
+}
+
+/// A card sheet decoded on the CPU and ready to upload.
+pub struct DecodedSheet {
+    image: DynamicImage,
+    label: String,
+    is_built_in: bool,
+}
+
+impl DecodedSheet {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn is_built_in(&self) -> bool {
+        self.is_built_in
+    }
+
+    pub fn image_mut(&mut self) -> &mut DynamicImage {
+        &mut self.image
+    }
+
+    /// A copy of the sheet with its image passed through `f`, labelled with `variant`.
+    pub fn map_image(&self, variant: &str, f: impl FnOnce(&DynamicImage) -> DynamicImage) -> Self {
+        Self {
+            image: f(&self.image),
+            label: format!("{} ({variant})", self.label),
+            is_built_in: self.is_built_in,
+        }
+    }
+}
+
+pub fn decode_built_in() -> Result<DecodedSheet> {
+    let image = image::load_from_memory(BUILT_IN_SHEET)
+        .chain_err(|| "couldn't decode the built-in card sheet")?;
+
+    Ok(DecodedSheet {
+        image,
+        label: BUILT_IN_LABEL.to_owned(),
+        is_built_in: true,
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn decode_file(path: &Path) -> Result<DecodedSheet> {
+    Ok(DecodedSheet {
+        image: texture::load_image(path)?,
+        label: path.to_string_lossy().into_owned(),
+        is_built_in: false,
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn fetch(url: &str) -> Result<DecodedSheet> {
+    Ok(DecodedSheet {
+        image: texture::fetch_image(url).await?,
+        label: url.to_owned(),
+        is_built_in: false,
+    })
+}
+
+/// The URL in the page's `deck` query parameter, if it has one.
+#[cfg(target_arch = "wasm32")]
+pub fn deck_url() -> Option<String> {
+    crate::util::query_param("deck")
+}
+
+/// Decodes the card sheet named by `CARDS_DECK` (native) or the `deck` query parameter (web) if
+/// there is one, or the built-in sheet otherwise.
+///
+/// Decoding is the slowest part of startup that doesn't need the GPU, so this is meant to run in
+/// the background while the device and pipelines are set up.
+pub async fn decode() -> Result<DecodedSheet> {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = std::env::var_os("CARDS_DECK") {
+        let path = Path::new(&path);
+        match decode_file(path) {
+            Ok(sheet) => return Ok(sheet),
+            Err(e) => error!(
+                "couldn't load deck {}, using the built-in deck instead: {e:?}",
+                path.display()
+            ),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    if let Some(url) = deck_url() {
+        match fetch(&url).await {
+            Ok(sheet) => return Ok(sheet),
+            Err(e) => error!("couldn't load deck {url}, using the built-in deck instead: {e:?}"),
+        }
+    }
+
+    decode_built_in()
+}
+
+/// Checks sheets against the deck layout and uploads them as the layers of one texture, catching
+/// any GPU validation errors so a bad file can't take the renderer down with it.
+///
+/// Every layer shares the first sheet's atlas.
+pub async fn upload(
+    device: &Device,
+    queue: &Queue,
+    layers: &[DecodedSheet],
+    options: &TextureOptions,
+) -> Result<CardSheet> {
+    let first = layers.first().ok_or("no card sheets to upload")?;
+    let (width, height) = first.image.dimensions();
+    let atlas = card::sheet_atlas(width, height);
+    card::validate_sheet(&atlas)?;
+
+    let images = layers.iter().map(|sheet| &sheet.image).collect::<Vec<_>>();
+
+    device.push_error_scope(ErrorFilter::Validation);
+    let texture = Texture::from_layers(device, queue, &images, Some(&first.label), options);
+    if let Some(e) = device.pop_error_scope().await {
+        bail!("GPU rejected deck image: {}", e);
+    }
+
+    Ok(CardSheet {
+        texture: texture?,
+        atlas,
+    })
+}
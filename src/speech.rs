@@ -0,0 +1,162 @@
+//! What's happening in a game, put into words for screen readers.
+//!
+//! On the web, announcements go into a live region: an element kept off screen whose text
+//! screen readers read out whenever it changes. The canvas is labelled with the card that has
+//! the focus, so it's read out when the canvas is. Native builds have nowhere to send either,
+//! so they only log them.
+
+use cards_core::card::{ACE, JACK, KING, QUEEN, TEN};
+
+use crate::{
+    card::{Rank, Suit},
+    locale::{tr, tr_with},
+};
+
+/// Keeps a live region in the page off screen without hiding it from screen readers, as
+/// `display: none` would.
+#[cfg(target_arch = "wasm32")]
+const HIDDEN_STYLE: &str = "position: absolute; left: -10000px; width: 1px; height: 1px; \
+                            overflow: hidden;";
+
+/// Announcements for screen readers, gathered up over a frame and made together.
+pub struct Speech {
+    queued: Vec<String>,
+    /// The text last announced, which is changed a little when it comes up again so that it's
+    /// still read out.
+    last: String,
+    #[cfg(target_arch = "wasm32")]
+    region: Option<web_sys::Element>,
+}
+
+impl Speech {
+    pub fn new() -> Self {
+        Self {
+            queued: Vec::new(),
+            last: String::new(),
+            #[cfg(target_arch = "wasm32")]
+            region: live_region(),
+        }
+    }
+
+    /// Queues `text` to be announced at the end of the frame, after anything queued before it.
+    pub fn say(&mut self, text: impl Into<String>) {
+        self.queued.push(text.into());
+    }
+
+    /// Describes what has the focus, as the canvas's label, and announces it.
+    pub fn describe_focus(&mut self, text: &str) {
+        #[cfg(target_arch = "wasm32")]
+        if let Some(canvas) = canvas() {
+            let _ = canvas.set_attribute("aria-label", text);
+        }
+        self.say(text);
+    }
+
+    /// Announces what's been said since the last frame, if anything.
+    pub fn flush(&mut self) {
+        if self.queued.is_empty() {
+            return;
+        }
+        let mut text = std::mem::take(&mut self.queued).join(". ");
+        if text == self.last {
+            text.push('\u{a0}');
+        }
+        self.last = text.clone();
+
+        #[cfg(target_arch = "wasm32")]
+        if let Some(region) = &self.region {
+            region.set_text_content(Some(&text));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        log::debug!("announcing {text:?}");
+    }
+}
+
+/// Adds a live region to the page, off screen, for announcements to be read out from.
+#[cfg(target_arch = "wasm32")]
+fn live_region() -> Option<web_sys::Element> {
+    let document = web_sys::window()?.document()?;
+    let region = document.create_element("div").ok()?;
+    for (name, value) in [
+        ("role", "status"),
+        ("aria-live", "polite"),
+        ("style", HIDDEN_STYLE),
+    ] {
+        region.set_attribute(name, value).ok()?;
+    }
+    document.body()?.append_child(&region).ok()?;
+
+    // Screen readers treat the canvas as one control, the table, rather than skipping it as an
+    // image.
+    if let Some(canvas) = canvas() {
+        let _ = canvas.set_attribute("role", "application");
+        let _ = canvas.set_attribute("aria-roledescription", "card table");
+    }
+    Some(region)
+}
+
+/// The canvas the game is drawn on.
+#[cfg(target_arch = "wasm32")]
+fn canvas() -> Option<web_sys::Element> {
+    web_sys::window()?
+        .document()?
+        .query_selector("canvas")
+        .ok()
+        .flatten()
+}
+
+/// A card's name in full in the current language, such as `Queen of Hearts`.
+pub fn card_name(suit: Suit, rank: Rank) -> String {
+    const RANKS: [&str; 13] = [
+        "Ace", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine", "Ten", "Jack",
+        "Queen", "King",
+    ];
+    let rank = RANKS.get(rank as usize).copied().unwrap_or("?");
+    let suit = format!("{suit:?}");
+    tr_with("{0} of {1}", &[&tr(rank), &tr(&suit)])
+}
+
+/// A move as the game writes it out, such as `West: QS` or `7H+2 to column 3`, with the cards
+/// named in full for reading out: `West: Queen of Spades`, `Seven of Hearts and 2 more to
+/// column 3`.
+pub fn spoken(notation: &str) -> String {
+    notation
+        .split(' ')
+        .map(|word| {
+            let (card, more) = word.split_once('+').unwrap_or((word, ""));
+            match (parse_card(card), more) {
+                (Some((suit, rank)), "") => card_name(suit, rank),
+                (Some((suit, rank)), more) => {
+                    tr_with("{0} and {1} more", &[&card_name(suit, rank), &more])
+                }
+                (None, _) => word.to_owned(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Reads a card back from its move notation, such as `QS`.
+fn parse_card(word: &str) -> Option<(Suit, Rank)> {
+    let mut chars = word.chars();
+    let (Some(rank), Some(suit), None) = (chars.next(), chars.next(), chars.next()) else {
+        return None;
+    };
+    let rank = match rank {
+        'A' => ACE,
+        'T' => TEN,
+        'J' => JACK,
+        'Q' => QUEEN,
+        'K' => KING,
+        '2'..='9' => rank as u8 - b'1',
+        _ => return None,
+    };
+    let suit = match suit {
+        'C' => Suit::Clubs,
+        'S' => Suit::Spades,
+        'H' => Suit::Hearts,
+        'D' => Suit::Diamonds,
+        _ => return None,
+    };
+    Some((suit, rank))
+}
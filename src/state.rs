@@ -1,32 +1,244 @@
+use std::{path::PathBuf, time::Duration};
+
 use bytemuck::cast_slice;
+use cards_core::{
+    game::{
+        Command, Engine, Framing, Game, Hint, Label, LabelStyle, Location, MoveRecord, Pile, Sweep,
+        Zone, ZoneKind,
+    },
+    puzzle::{Progress, Puzzle},
+    qr::QrCode,
+    rng::daily_seed,
+    save::SavedGame,
+    stats::Stats,
+    status::{GameStatus, Outcome},
+    trumps::TrumpMode,
+};
 use cgmath::EuclideanSpace;
-use log::info;
-use strum::IntoEnumIterator;
+use instant::Instant;
+use log::{error, info, warn};
 use wgpu::{
     include_wgsl,
     util::{BufferInitDescriptor, DeviceExt},
-    Adapter, Backends, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
-    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, BlendState,
-    BufferBindingType, BufferUsages, ColorTargetState, ColorWrites, CommandEncoderDescriptor,
-    Device, DeviceDescriptor, Face, Features, FragmentState, FrontFace, IndexFormat,
-    InstanceDescriptor, Limits, LoadOp, MultisampleState, Operations, PipelineLayout,
-    PipelineLayoutDescriptor, PolygonMode, PowerPreference, PrimitiveState, PrimitiveTopology,
-    Queue, RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline,
+    Adapter, AdapterInfo, Backends, BindGroup, BindGroupDescriptor, BindGroupEntry,
+    BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, BlendState,
+    BufferBindingType, BufferDescriptor, BufferUsages, ColorTargetState, ColorWrites,
+    CommandEncoderDescriptor, CompareFunction, DepthBiasState, DepthStencilState, Device,
+    DeviceDescriptor, Extent3d, Face, Features, FilterMode, FragmentState, FrontFace, IndexFormat,
+    InstanceDescriptor, Limits, LoadOp, Maintain, MultisampleState, Operations, PipelineLayout,
+    PipelineLayoutDescriptor, PolygonMode, PowerPreference, PresentMode, PrimitiveState,
+    PrimitiveTopology, Queue, RenderPass, RenderPassColorAttachment,
+    RenderPassDepthStencilAttachment, RenderPassDescriptor, RenderPipeline,
     RenderPipelineDescriptor, RequestAdapterOptionsBase, SamplerBindingType, ShaderModule,
-    ShaderStages, Surface, SurfaceCapabilities, SurfaceConfiguration, SurfaceError, TextureFormat,
-    TextureSampleType, TextureUsages, TextureViewDescriptor, TextureViewDimension,
+    ShaderStages, StencilState, Surface, SurfaceCapabilities, SurfaceConfiguration, SurfaceError,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureFormatFeatureFlags,
+    TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension,
     VertexBufferLayout, VertexState,
 };
-use winit::{dpi::PhysicalSize, event::WindowEvent, window::Window};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{
+        ElementState, KeyboardInput, ModifiersState, MouseButton, Touch, TouchPhase,
+        VirtualKeyCode, WindowEvent,
+    },
+    window::Window,
+};
+
+#[cfg(any(feature = "hearts", feature = "doppelkopf"))]
+use cards_core::ai::{
+    ismcts::{HiddenInformation, IsmctsPlayer},
+    AiPlayer, RandomPlayer, RuleBasedPlayer,
+};
+#[cfg(feature = "blackjack")]
+use cards_core::blackjack::{Blackjack, BlackjackRules};
+#[cfg(feature = "doppelkopf")]
+use cards_core::doppelkopf::{self, Doppelkopf};
+#[cfg(feature = "gallery")]
+use cards_core::gallery::Gallery;
+// Every game but blackjack is dealt with `setup`.
+#[cfg(any(
+    feature = "klondike",
+    feature = "hearts",
+    feature = "doppelkopf",
+    feature = "memory",
+    feature = "gallery"
+))]
+use cards_core::game::GameState;
+#[cfg(feature = "hearts")]
+use cards_core::hearts::{self, Hearts};
+#[cfg(feature = "klondike")]
+use cards_core::klondike::Klondike;
+#[cfg(feature = "memory")]
+use cards_core::memory::Memory;
+#[cfg(feature = "multiplayer")]
+use cards_core::protocol::ClientMessage;
 
+#[cfg(feature = "gamepad")]
+use crate::gamepad::{Gamepads, PadInput};
 use crate::{
-    camera::{Camera, CameraController, CameraUniform},
+    animation::{self, Cascade, DealTiming, Motion, Riffle, Timeline, Toss, Tween},
+    audio::{Audio, Sound},
+    background::{self, Background},
+    badges::Badges,
+    bounds::Bounds,
+    camera::{Camera, CameraController, CameraUniform, Effect, Projection},
     card::{self, Card, Suit},
+    config::{Config, SavedTheme, Settings},
+    diagnostics::{self, BugReport, DrawStats, FrameTimer},
+    drag::{Drag, DropZone},
+    end_screen::{self, EndScreen, Session},
     errors::*,
-    include_texture,
-    texture::{self, Texture},
+    focus::{self, Direction},
+    handoff_screen, hud,
+    keymap::{Action, Binding, Keymap},
+    keys_screen,
+    layout::{self, Orientation},
+    locale::{self, tr, tr_with, Language},
+    look_screen, move_log,
+    particles::{Emitter, Particles},
+    picking::{self, PointerKind},
+    puzzle_screen,
+    quality::{QualityBenchmark, QualityPreset},
+    replay::{self, Replay},
+    save,
+    scene::{self, MenuOptions, Scene, Scenes},
+    settings_screen,
+    speech::{self, Speech},
+    stats_screen,
+    text::TextBrush,
+    texture::TextureOptions,
+    theme::{self, Look, Themes},
+    ui::{self, Rect, Ui},
+    util::{prefers_reduced_motion, random_seed, unix_time, BackgroundTask},
+};
+#[cfg(feature = "multiplayer")]
+use crate::{
+    chat::{Chat, ChatInput},
+    lobby::Lobby,
+    net::{self, Multiplayer, NetEvent},
 };
 
+/// Space left around the cards when framing them, in world units.
+const FRAME_PADDING: f32 = 16.0;
+
+/// How long the camera takes to move onto the part of the table a new phase of a game is played
+/// on.
+const FRAMING_DURATION: Duration = Duration::from_millis(600);
+
+/// How much bigger than usual the cards are drawn with the large cards setting on.
+const LARGE_CARD_SCALE: f32 = 1.3;
+
+const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+/// The most moves read out at once, past which they're taken to have been caught up on rather
+/// than played.
+const MAX_SPOKEN_MOVES: usize = 4;
+
+/// The most cards heard at once when a table's laid out, so a whole deck dealt doesn't drown
+/// everything else out.
+const MAX_MOTION_SOUNDS: usize = 12;
+
+/// How many bursts of confetti are thrown over a game won, and the wait between them.
+const CONFETTI_BURSTS: usize = 5;
+const CONFETTI_INTERVAL: Duration = Duration::from_millis(150);
+
+/// How far the table shakes as a big trick is taken, in physical pixels, and for how long.
+const TRICK_SHAKE: f32 = 6.0;
+const TRICK_SHAKE_DURATION: Duration = Duration::from_millis(350);
+
+/// How much closer the view goes onto a big trick as it's gathered up, how long it takes to get
+/// there and back, and how long it lingers.
+const TRICK_FOCUS_ZOOM: f32 = 1.5;
+const TRICK_FOCUS_DURATION: Duration = Duration::from_millis(300);
+const TRICK_FOCUS_HOLD: Duration = Duration::from_millis(500);
+
+/// How far the view zooms in as a game ends, as a fraction of it, and for how long.
+const GAME_OVER_PUNCH: f32 = 0.06;
+const GAME_OVER_PUNCH_DURATION: Duration = Duration::from_millis(500);
+
+/// How far from a zone the cards can go on, in card heights, tossed cards can settle and still
+/// land on it.
+const TOSS_REACH: f32 = 1.5;
+
+/// How long a notice stays on screen, the last part of which it spends fading out.
+const NOTICE_DURATION: Duration = Duration::from_millis(2000);
+const NOTICE_FADE: Duration = Duration::from_millis(500);
+
+/// The height of a line of notice text, in logical pixels.
+const NOTICE_TEXT_SIZE: f32 = 20.0;
+
+/// The size of the buttons along the bottom of the window, and the gap around them, in logical
+/// pixels.
+const TOOLBAR_BUTTON_SIZE: [f32; 2] = [96.0, 28.0];
+const TOOLBAR_SPACING: f32 = 6.0;
+
+/// The height of a line of debug overlay text, in logical pixels.
+const DEBUG_TEXT_SIZE: f32 = 14.0;
+
+/// The height of the text of labels on the table, such as players' names, in logical pixels,
+/// and its colour.
+const LABEL_TEXT_SIZE: f32 = 16.0;
+const LABEL_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.85];
+
+/// How long a hint is shown for, how long each of its pulses takes, and the tint over empty
+/// piles it points at.
+const HINT_DURATION: Duration = Duration::from_millis(2400);
+const HINT_PULSE: Duration = Duration::from_millis(800);
+const HINT_COLOR: [f32; 4] = [1.0, 0.9, 0.4, 0.35];
+
+/// The longest gap between two clicks on the same card that makes them a double-click.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+/// The tint over a zone that cards are dragged over, where dropping them would make a move and
+/// where it wouldn't.
+const DROP_ALLOWED_COLOR: [f32; 4] = [0.2, 0.8, 0.3, 0.3];
+const DROP_REFUSED_COLOR: [f32; 4] = [0.9, 0.2, 0.2, 0.3];
+
+/// How thick the ring around the place on the table with the focus is, in logical pixels.
+const FOCUS_THICKNESS: f32 = 3.0;
+
+/// The colour of the backing behind badges, and how far it reaches past their text, in logical
+/// pixels.
+const BADGE_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 0.8];
+const BADGE_PADDING: f32 = 4.0;
+
+/// How greyed out and darkened the cards a game dims are, and the colour of cards it tints.
+const DIMMED_DESATURATION: f32 = 0.8;
+const DIMMED_TINT: [f32; 3] = [0.75; 3];
+const TINT_COLOR: [f32; 3] = [1.0, 0.9, 0.6];
+
+/// How long the frame uniform's time runs before starting again from 0, so it keeps its
+/// precision. Every animation the shaders run repeats a whole number of times in it.
+const FRAME_TIME_WRAP: f32 = 3600.0;
+
+/// What the timeline tells the table once it's played up to a point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cue {
+    /// A new deal is out, so the game can get going.
+    Dealt,
+}
+
+/// The layers a frame is built from. They share one render pass and are drawn in this order, so
+/// each appears over the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenderLayer {
+    /// The clear colour and table image.
+    Background,
+    /// Cards and their shadows.
+    Cards,
+    /// Confetti, sparkles and the like, over the cards.
+    Particles,
+    /// Text over the whole window, including any letterbox bars.
+    Text,
+}
+
+const RENDER_LAYERS: [RenderLayer; 4] = [
+    RenderLayer::Background,
+    RenderLayer::Cards,
+    RenderLayer::Particles,
+    RenderLayer::Text,
+];
+
 fn create_instance() -> wgpu::Instance {
     wgpu::Instance::new(InstanceDescriptor {
         backends: Backends::all(),
@@ -45,7 +257,39 @@ async fn create_adapter(instance: &wgpu::Instance, surface: &Surface) -> Result<
         .chain_err(|| "couldn't create adapter")
 }
 
+/// The directory to record a wgpu API trace into, taken from `CARDS_WGPU_TRACE`.
+///
+/// Traces let rendering bugs on hardware we don't have be replayed with wgpu's `player`. They
+/// need the `trace` feature; without it the variable is ignored.
+#[cfg(not(target_arch = "wasm32"))]
+fn trace_path() -> Option<PathBuf> {
+    let path = PathBuf::from(std::env::var_os("CARDS_WGPU_TRACE")?);
+
+    if !cfg!(feature = "trace") {
+        warn!("CARDS_WGPU_TRACE is set, but tracing needs the `trace` feature");
+        return None;
+    }
+
+    match std::fs::create_dir_all(&path) {
+        Ok(()) => {
+            info!("recording wgpu trace into {}", path.display());
+            Some(path)
+        }
+        Err(e) => {
+            error!("couldn't create trace directory {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn trace_path() -> Option<PathBuf> {
+    None
+}
+
 async fn create_logical_device_and_queue(adapter: &Adapter) -> Result<(Device, Queue)> {
+    let trace_path = trace_path();
+
     adapter
         .request_device(
             &DeviceDescriptor {
@@ -57,12 +301,24 @@ async fn create_logical_device_and_queue(adapter: &Adapter) -> Result<(Device, Q
                 },
                 label: None,
             },
-            None,
+            trace_path.as_deref(),
         )
         .await
         .chain_err(|| "couldn't create logical device and queue")
 }
 
+/// How to present frames: waiting for the display if `vsync` is on, and otherwise as soon as
+/// they're drawn if any of `modes` allows it.
+fn present_mode(vsync: bool, modes: &[PresentMode]) -> PresentMode {
+    let immediate = [PresentMode::Mailbox, PresentMode::Immediate]
+        .into_iter()
+        .find(|mode| modes.contains(mode));
+    match (vsync, immediate) {
+        (false, Some(mode)) => mode,
+        _ => PresentMode::Fifo,
+    }
+}
+
 fn get_surface_format(surface_caps: &SurfaceCapabilities) -> TextureFormat {
     surface_caps
         .formats
@@ -84,7 +340,7 @@ fn create_pipeline_layout(
     })
 }
 
-fn create_vertex_state(shader: &ShaderModule) -> VertexState {
+fn create_vertex_state(shader: &ShaderModule) -> VertexState<'_> {
     const VERTEX_BUFFERS: [VertexBufferLayout; 2] =
         [card::Vertex::BUFFER_LAYOUT, card::Instance::BUFFER_LAYOUT];
 
@@ -118,16 +374,17 @@ fn create_primitive_state() -> PrimitiveState {
     }
 }
 
-fn create_render_pipeline(
+/// Builds a pipeline that draws card instances with the vertex and fragment entry points of
+/// `shader`, using the card vertex and instance buffers and both bind groups.
+fn create_instanced_pipeline(
     device: &Device,
     config: &SurfaceConfiguration,
-    texture_bind_group_layout: &BindGroupLayout,
-    camera_bind_group_layout: &BindGroupLayout,
+    sample_count: u32,
+    layout: &PipelineLayout,
+    shader: &ShaderModule,
+    label: &str,
+    depth_write_enabled: bool,
 ) -> RenderPipeline {
-    let shader = device.create_shader_module(include_wgsl!("shader.wgsl"));
-    let render_pipeline_layout =
-        create_pipeline_layout(device, texture_bind_group_layout, camera_bind_group_layout);
-
     let color_target_states = &[Some(ColorTargetState {
         format: config.format,
         blend: Some(BlendState::ALPHA_BLENDING),
@@ -135,14 +392,21 @@ fn create_render_pipeline(
     })];
 
     device.create_render_pipeline(&RenderPipelineDescriptor {
-        label: Some("Render Pipeline"),
-        layout: Some(&render_pipeline_layout),
-        vertex: create_vertex_state(&shader),
-        fragment: Some(create_fragment_state(&shader, color_target_states)),
+        label: Some(label),
+        layout: Some(layout),
+        vertex: create_vertex_state(shader),
+        fragment: Some(create_fragment_state(shader, color_target_states)),
         primitive: create_primitive_state(),
-        depth_stencil: None,
+        depth_stencil: Some(DepthStencilState {
+            format: DEPTH_FORMAT,
+            depth_write_enabled,
+            // Cards at the same depth are drawn in order, later ones on top.
+            depth_compare: CompareFunction::LessEqual,
+            stencil: StencilState::default(),
+            bias: DepthBiasState::default(),
+        }),
         multisample: MultisampleState {
-            count: 1,
+            count: sample_count,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
@@ -150,6 +414,104 @@ fn create_render_pipeline(
     })
 }
 
+fn create_render_pipeline(
+    device: &Device,
+    config: &SurfaceConfiguration,
+    sample_count: u32,
+    texture_bind_group_layout: &BindGroupLayout,
+    camera_bind_group_layout: &BindGroupLayout,
+) -> RenderPipeline {
+    let shader = device.create_shader_module(include_wgsl!("shader.wgsl"));
+    let layout =
+        create_pipeline_layout(device, texture_bind_group_layout, camera_bind_group_layout);
+
+    create_instanced_pipeline(
+        device,
+        config,
+        sample_count,
+        &layout,
+        &shader,
+        "Render Pipeline",
+        true,
+    )
+}
+
+/// The pipeline for card drop shadows. Shadows are translucent and sit under their card, so they
+/// don't write depth.
+fn create_shadow_pipeline(
+    device: &Device,
+    config: &SurfaceConfiguration,
+    sample_count: u32,
+    texture_bind_group_layout: &BindGroupLayout,
+    camera_bind_group_layout: &BindGroupLayout,
+) -> RenderPipeline {
+    let shader = device.create_shader_module(include_wgsl!("shadow.wgsl"));
+    let layout =
+        create_pipeline_layout(device, texture_bind_group_layout, camera_bind_group_layout);
+
+    create_instanced_pipeline(
+        device,
+        config,
+        sample_count,
+        &layout,
+        &shader,
+        "Shadow Pipeline",
+        false,
+    )
+}
+
+fn create_depth_view(
+    device: &Device,
+    config: &SurfaceConfiguration,
+    sample_count: u32,
+) -> TextureView {
+    let texture = device.create_texture(&TextureDescriptor {
+        label: Some("Depth Buffer"),
+        size: Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+
+    texture.create_view(&TextureViewDescriptor::default())
+}
+
+/// Creates the multisampled colour target that is resolved into the surface texture each frame,
+/// or `None` if multisampling is off.
+fn create_msaa_view(
+    device: &Device,
+    config: &SurfaceConfiguration,
+    sample_count: u32,
+) -> Option<TextureView> {
+    if sample_count == 1 {
+        return None;
+    }
+
+    let texture = device.create_texture(&TextureDescriptor {
+        label: Some("MSAA Framebuffer"),
+        size: Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: TextureDimension::D2,
+        format: config.format,
+        usage: TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+
+    Some(texture.create_view(&TextureViewDescriptor::default()))
+}
+
 fn create_texture_bind_group_layout(device: &Device) -> BindGroupLayout {
     device.create_bind_group_layout(&BindGroupLayoutDescriptor {
         label: Some("texture_bind_group_layout"),
@@ -160,7 +522,7 @@ fn create_texture_bind_group_layout(device: &Device) -> BindGroupLayout {
                 visibility: ShaderStages::FRAGMENT,
                 ty: BindingType::Texture {
                     multisampled: false,
-                    view_dimension: TextureViewDimension::D2,
+                    view_dimension: TextureViewDimension::D2Array,
                     sample_type: TextureSampleType::Float { filterable: true },
                 },
             },
@@ -170,69 +532,440 @@ fn create_texture_bind_group_layout(device: &Device) -> BindGroupLayout {
                 visibility: ShaderStages::FRAGMENT,
                 ty: BindingType::Sampler(SamplerBindingType::Filtering),
             },
+            BindGroupLayoutEntry {
+                binding: 2,
+                count: None,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+            },
+            BindGroupLayoutEntry {
+                binding: 3,
+                count: None,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+            },
         ],
     })
 }
 
-fn create_texture_bind_group(
-    device: &Device,
-    texture: &Texture,
-    layout: &BindGroupLayout,
-) -> BindGroup {
-    device.create_bind_group(&BindGroupDescriptor {
-        label: Some("diffuse_bind_group"),
-        layout,
-        entries: &[
-            BindGroupEntry {
-                binding: 0,
-                resource: BindingResource::TextureView(&texture.view),
-            },
-            BindGroupEntry {
-                binding: 1,
-                resource: BindingResource::Sampler(&texture.sampler),
+/// The theme to start with, from `CARDS_THEME`.
+#[cfg(not(target_arch = "wasm32"))]
+fn initial_theme() -> Option<String> {
+    std::env::var("CARDS_THEME").ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn initial_theme() -> Option<String> {
+    None
+}
+
+/// The name of the game to play, from `CARDS_GAME` (native) or the `game` query parameter (web).
+#[cfg(not(target_arch = "wasm32"))]
+fn game_name() -> Option<String> {
+    std::env::var("CARDS_GAME").ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn game_name() -> Option<String> {
+    crate::util::query_param("game")
+}
+
+/// What to deal the game from, from `CARDS_SEED` (native) or the `seed` query parameter (web).
+#[cfg(not(target_arch = "wasm32"))]
+fn seed_name() -> Option<String> {
+    std::env::var("CARDS_SEED").ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn seed_name() -> Option<String> {
+    crate::util::query_param("seed")
+}
+
+/// The seed to deal the first game from: the one asked for, the day's for `daily`, or a random
+/// one.
+fn startup_seed() -> u64 {
+    match seed_name().as_deref() {
+        None => random_seed(),
+        Some("daily") => daily_seed(unix_time()),
+        Some(other) => other.parse().unwrap_or_else(|_| {
+            warn!("ignoring seed {other:?}, expected a number or daily");
+            random_seed()
+        }),
+    }
+}
+
+/// How the computer plays, from `CARDS_AI` (native) or the `ai` query parameter (web).
+#[cfg(all(
+    any(feature = "hearts", feature = "doppelkopf"),
+    not(target_arch = "wasm32")
+))]
+fn ai_name() -> Option<String> {
+    std::env::var("CARDS_AI").ok()
+}
+
+#[cfg(all(
+    any(feature = "hearts", feature = "doppelkopf"),
+    target_arch = "wasm32"
+))]
+fn ai_name() -> Option<String> {
+    crate::util::query_param("ai")
+}
+
+/// The ways the computer can play the other seats.
+#[cfg(any(feature = "hearts", feature = "doppelkopf"))]
+#[derive(Debug, Clone, Copy)]
+enum ComputerPlayer {
+    Rules,
+    Random,
+    /// Searching the given number of iterations before each move.
+    Search(usize),
+}
+
+#[cfg(any(feature = "hearts", feature = "doppelkopf"))]
+impl ComputerPlayer {
+    /// Parses `rules`, `random`, or `ismcts`, optionally followed by `:<iterations>`.
+    fn from_name(name: Option<&str>) -> Self {
+        match name {
+            None | Some("rules") => Self::Rules,
+            Some("random") => Self::Random,
+            Some("ismcts") => Self::Search(IsmctsPlayer::DEFAULT_ITERATIONS),
+            Some(other) => match other.strip_prefix("ismcts:").map(str::parse) {
+                Some(Ok(iterations)) if iterations > 0 => Self::Search(iterations),
+                _ => {
+                    warn!("ignoring unknown computer player {other:?}");
+                    Self::Rules
+                }
             },
-        ],
+        }
+    }
+}
+
+/// Has the computer play every seat but `player`'s.
+#[cfg(any(feature = "hearts", feature = "doppelkopf"))]
+fn fill_seats<G: HiddenInformation + 'static>(
+    engine: Engine<G>,
+    player: usize,
+    computer: ComputerPlayer,
+) -> Engine<G>
+where
+    RuleBasedPlayer: AiPlayer<G>,
+{
+    let seed = random_seed();
+    engine.with_computer_players(player, |seat| -> Box<dyn AiPlayer<G>> {
+        let seed = seed.wrapping_add(seat as u64);
+        match computer {
+            ComputerPlayer::Rules => Box::new(RuleBasedPlayer),
+            ComputerPlayer::Random => Box::new(RandomPlayer::new(seed)),
+            ComputerPlayer::Search(iterations) => Box::new(IsmctsPlayer::new(iterations, seed)),
+        }
     })
 }
 
-fn create_camera(size: PhysicalSize<u32>) -> Camera {
+/// Blackjack's table rules, from `CARDS_DECKS` for the number of decks in the shoe and
+/// `CARDS_HIT_SOFT_17` to have the dealer draw on a soft 17.
+#[cfg(all(feature = "blackjack", not(target_arch = "wasm32")))]
+fn blackjack_rules() -> BlackjackRules {
+    rules_from(
+        std::env::var("CARDS_DECKS").ok(),
+        std::env::var("CARDS_HIT_SOFT_17").ok(),
+    )
+}
+
+/// Blackjack's table rules, from the `decks` and `hit-soft-17` query parameters.
+#[cfg(all(feature = "blackjack", target_arch = "wasm32"))]
+fn blackjack_rules() -> BlackjackRules {
+    rules_from(
+        crate::util::query_param("decks"),
+        crate::util::query_param("hit-soft-17"),
+    )
+}
+
+#[cfg(feature = "blackjack")]
+fn rules_from(decks: Option<String>, hit_soft_17: Option<String>) -> BlackjackRules {
+    let mut rules = BlackjackRules::default();
+
+    if let Some(decks) = decks {
+        match decks.parse() {
+            Ok(decks) if (1..=8).contains(&decks) => rules.decks = decks,
+            _ => warn!("ignoring deck count {decks:?}, expected 1 to 8"),
+        }
+    }
+    if let Some(hit_soft_17) = hit_soft_17 {
+        match hit_soft_17.as_str() {
+            "1" | "true" => rules.hit_soft_17 = true,
+            "0" | "false" => rules.hit_soft_17 = false,
+            _ => warn!("ignoring unknown soft 17 rule {hit_soft_17:?}"),
+        }
+    }
+
+    rules
+}
+
+/// The games that can be picked, by name: those built in, out of every game there is.
+const GAMES: &[&str] = &[
+    #[cfg(feature = "klondike")]
+    "klondike",
+    #[cfg(feature = "doppelkopf")]
+    "doppelkopf",
+    #[cfg(feature = "hearts")]
+    "hearts",
+    #[cfg(feature = "blackjack")]
+    "blackjack",
+    #[cfg(feature = "memory")]
+    "memory",
+    #[cfg(feature = "gallery")]
+    "gallery",
+];
+
+/// Whether `event` is a key, a button or a finger being pressed, after which browsers let the
+/// page make sounds.
+fn is_press(event: &WindowEvent) -> bool {
+    match event {
+        WindowEvent::KeyboardInput { input, .. } => input.state == ElementState::Pressed,
+        WindowEvent::MouseInput { state, .. } => *state == ElementState::Pressed,
+        // Browsers wait for the finger to lift.
+        WindowEvent::Touch(touch) => touch.phase == TouchPhase::Ended,
+        _ => false,
+    }
+}
+
+/// The name of the game picked at startup, Klondike unless another is asked for.
+fn startup_game() -> String {
+    match game_name() {
+        Some(name) if GAMES.contains(&name.as_str()) => name,
+        Some(other) => {
+            warn!("ignoring unknown game {other:?}");
+            GAMES[0].to_owned()
+        }
+        None => GAMES[0].to_owned(),
+    }
+}
+
+/// Deals the game called `name`, one of `GAMES`, from `seed`, or the first of them if it isn't
+/// one.
+fn create_game(name: &str, seed: u64, orientation: Orientation) -> Box<dyn Game> {
+    #[cfg(any(feature = "hearts", feature = "doppelkopf"))]
+    let computer = ComputerPlayer::from_name(ai_name().as_deref());
+
+    match name {
+        #[cfg(feature = "gallery")]
+        "gallery" => Box::new(Engine::new(Gallery::setup(seed), orientation)),
+        #[cfg(feature = "doppelkopf")]
+        "doppelkopf" => Box::new(fill_seats(
+            Engine::new(Doppelkopf::setup(seed), orientation),
+            doppelkopf::PLAYER,
+            computer,
+        )),
+        #[cfg(feature = "hearts")]
+        "hearts" => Box::new(fill_seats(
+            Engine::new(Hearts::setup(seed), orientation),
+            hearts::PLAYER,
+            computer,
+        )),
+        #[cfg(feature = "memory")]
+        "memory" => Box::new(Engine::new(Memory::setup(seed), orientation)),
+        #[cfg(feature = "blackjack")]
+        "blackjack" => Box::new(Engine::new(
+            Blackjack::new(blackjack_rules(), seed),
+            orientation,
+        )),
+        #[cfg(feature = "klondike")]
+        _ => Box::new(Engine::new(Klondike::setup(seed), orientation)),
+        #[cfg(not(feature = "klondike"))]
+        _ => create_game(GAMES[0], seed, orientation),
+    }
+}
+
+/// Deals the game called `name` from `seed` to be played on a server from `seat`, or `None` if
+/// it can't be played over the network.
+#[cfg(feature = "multiplayer")]
+fn create_network_game(
+    name: &str,
+    seed: u64,
+    seat: usize,
+    orientation: Orientation,
+) -> Option<Box<dyn Game>> {
+    match name {
+        #[cfg(feature = "doppelkopf")]
+        "doppelkopf" => Some(Box::new(
+            Engine::new(Doppelkopf::setup(seed), orientation).played_elsewhere(seat),
+        )),
+        #[cfg(feature = "hearts")]
+        "hearts" => Some(Box::new(
+            Engine::new(Hearts::setup(seed), orientation).played_elsewhere(seat),
+        )),
+        _ => None,
+    }
+}
+
+/// The aspect ratio to letterbox the table to, from `CARDS_ASPECT` given as `width:height`.
+#[cfg(not(target_arch = "wasm32"))]
+fn letterbox_aspect_ratio() -> Option<f32> {
+    let value = std::env::var("CARDS_ASPECT").ok()?;
+    let parsed = value
+        .split_once(':')
+        .and_then(|(width, height)| Some((width.parse::<f32>().ok()?, height.parse::<f32>().ok()?)))
+        .filter(|&(width, height)| width > 0.0 && height > 0.0);
+
+    match parsed {
+        Some((width, height)) => Some(width / height),
+        None => {
+            warn!("ignoring CARDS_ASPECT value {value:?}, expected width:height");
+            None
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn letterbox_aspect_ratio() -> Option<f32> {
+    None
+}
+
+fn create_camera(size: PhysicalSize<u32>, scale_factor: f64) -> Camera {
     Camera {
         eye: cgmath::Point2::origin(),
         viewport_size: size,
+        scale_factor,
         zoom: 2.0,
+        aspect_ratio: letterbox_aspect_ratio(),
+        projection: Projection::Orthographic,
         znear: 0.1,
         zfar: 100.0,
+        ease: None,
+        focus: None,
+        effects: Vec::new(),
+        effect_intensity: 1.0,
+    }
+}
+
+/// How much bigger than usual the settings have the cards drawn.
+fn card_scale(settings: &Settings) -> f32 {
+    match settings.large_cards {
+        true => LARGE_CARD_SCALE,
+        false => 1.0,
+    }
+}
+
+/// Where a new deal goes out from: the middle of the cards being dealt.
+fn deck_position(cards: &[Card]) -> cgmath::Vector3<i32> {
+    let centre = Bounds::from_points(
+        cards
+            .iter()
+            .map(|card| cgmath::Point2::new(card.position.x as f32, card.position.y as f32)),
+    )
+    .map_or(cgmath::Point2::new(0.0, 0.0), |bounds| bounds.center());
+    cgmath::Vector3::new(centre.x as i32, centre.y as i32, 0)
+}
+
+/// Points the camera so that every card is in view.
+fn frame_cards(camera: &mut Camera, cards: &[Card]) {
+    if let Some(bounds) = cards.iter().map(Card::bounds).reduce(|a, b| a.union(b)) {
+        camera.fit_to_bounds(bounds, FRAME_PADDING);
     }
 }
 
+/// Creates a buffer with room for `capacity` card instances, and always at least one.
+fn create_instance_buffer(device: &Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&BufferDescriptor {
+        label: Some("Instance Buffer"),
+        size: (capacity.max(1) * std::mem::size_of::<card::Instance>()) as u64,
+        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// The layout of the camera and frame uniforms, which the shaders drawing on the table share.
 fn create_camera_bind_group_layout(device: &Device) -> BindGroupLayout {
     device.create_bind_group_layout(&BindGroupLayoutDescriptor {
         label: Some("camera_bind_group_layout"),
-        entries: &[BindGroupLayoutEntry {
-            binding: 0,
-            visibility: ShaderStages::VERTEX,
-            ty: BindingType::Buffer {
-                ty: BufferBindingType::Uniform.to_owned(),
-                has_dynamic_offset: false,
-                min_binding_size: None,
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform.to_owned(),
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
             },
-            count: None,
-        }],
+        ],
     })
 }
 
 fn create_camera_bind_group(
     device: &Device,
-    buffer: &wgpu::Buffer,
+    camera_buffer: &wgpu::Buffer,
+    frame_buffer: &wgpu::Buffer,
     layout: &BindGroupLayout,
 ) -> BindGroup {
     device.create_bind_group(&BindGroupDescriptor {
         label: Some("camera_bind_group"),
         layout,
-        entries: &[BindGroupEntry {
-            binding: 0,
-            resource: buffer.as_entire_binding(),
-        }],
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: frame_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+/// What changes from frame to frame for the shaders, besides the camera. This is the layout of
+/// their `frame` uniform.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct FrameUniform {
+    /// Seconds since the app started, starting again every `FRAME_TIME_WRAP`.
+    time: f32,
+    /// How much things on the table move by themselves, 0 when less motion is asked for.
+    motion: f32,
+    /// How much softer card shadows are than usual, from the theme's look.
+    shadow_softness: f32,
+    _padding: f32,
+    /// The colour playable cards glow, from the theme's look.
+    highlight: [f32; 4],
+}
+
+/// The colour playable cards glow with `look`, as the shaders take it.
+fn highlight(look: &Look) -> [f32; 4] {
+    let [r, g, b] = look.highlight;
+    [r, g, b, 1.0]
+}
+
+fn create_frame_buffer(device: &Device) -> wgpu::Buffer {
+    device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("Frame Buffer"),
+        contents: cast_slice(&[FrameUniform {
+            time: 0.0,
+            motion: 1.0,
+            shadow_softness: Look::DEFAULT.shadow_softness,
+            _padding: 0.0,
+            highlight: highlight(&Look::DEFAULT),
+        }]),
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
     })
 }
 
@@ -252,23 +985,165 @@ pub struct State {
     pub size: PhysicalSize<u32>,
     window: Window,
     render_pipeline: RenderPipeline,
+    shadow_pipeline: RenderPipeline,
+    background: Background,
+    particles: Particles,
+    badges: Badges,
+    text: TextBrush,
+    notice: Option<(String, Instant)>,
+    ui: Ui,
+    game_status: Option<GameStatus>,
+    /// Text the game shows on the table, such as the players' names by their seats.
+    labels: Vec<Label>,
+    /// The labels of the game's action buttons.
+    actions: Vec<String>,
+    show_debug_overlay: bool,
+    show_move_log: bool,
+    /// How many lines the move log is scrolled back from the latest move.
+    move_log_scroll: usize,
+    frame_timer: FrameTimer,
+    /// What went into the last frame, for the debug overlay.
+    draw_stats: DrawStats,
+    texture_bind_group_layout: BindGroupLayout,
+    camera_bind_group_layout: BindGroupLayout,
+    adapter_info: AdapterInfo,
+    format_flags: TextureFormatFeatureFlags,
+    quality: QualityPreset,
+    quality_benchmark: Option<QualityBenchmark>,
+    sample_count: u32,
+    msaa_view: Option<TextureView>,
+    depth_view: TextureView,
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     num_indices: u32,
-    diffuse_bind_group: BindGroup,
-    _diffuse_texture: texture::Texture,
+    themes: Themes,
+    /// The texture layer of the theme in use.
+    theme: u32,
+    /// The name of the theme in use, which is a saved theme's when it's drawn from the layer of
+    /// the built-in theme it was saved from.
+    theme_name: String,
+    /// How much bigger than usual the cards are drawn, which the table is laid out to fit.
+    card_scale: f32,
+    /// Whether the system asked for less motion on screen when the app started, which holds
+    /// unless the settings say otherwise.
+    prefers_reduced_motion: bool,
+    texture_options: TextureOptions,
     camera: Camera,
     camera_uniform: CameraUniform,
     camera_buffer: wgpu::Buffer,
+    frame_buffer: wgpu::Buffer,
+    /// When the app started, which the frame uniform's time counts from.
+    started: Instant,
     camera_bind_group: BindGroup,
     camera_controller: CameraController,
+    game: Box<dyn Game>,
+    /// The name the game was picked by, and the seed it was dealt from, for saving it.
+    game_name: String,
+    seed: u64,
+    /// How many of the game's moves had been made when it was last saved.
+    saved_moves: usize,
+    /// What's read out by screen readers, and how many of the game's moves have been.
+    speech: Speech,
+    spoken_moves: usize,
+    /// A game saved on an earlier run, offered to carry on until a move is made in this one.
+    saved_game: Option<SavedGame>,
+    /// The QR code and link for carrying the game on on another device, while they're shown.
+    handoff: Option<(QrCode, String)>,
+    /// Whether the game being played is saved on the way out, which it is unless the player
+    /// chose not to keep it.
+    save_on_exit: bool,
+    game_over: bool,
+    /// The screens the player has come through to get to the one they're on.
+    scenes: Scenes,
+    /// Whether games picked from the menu are dealt from the day's seed.
+    daily: bool,
+    /// How the game went, shown once it's over until it's closed.
+    end_screen: Option<EndScreen>,
+    /// How the games finished since the window opened have gone.
+    session: Session,
+    /// How the player has done at each game over every run.
+    stats: Stats,
+    show_stats: bool,
+    /// The puzzles for the games there are, and the ones the player has solved.
+    puzzles: Vec<Puzzle>,
+    progress: Progress,
+    /// The index in `puzzles` of the puzzle being played, if the game is one.
+    puzzle: Option<usize>,
+    /// Whether to set the cards cascading once they've stopped moving, for a game just won.
+    celebrate: bool,
+    /// The cards cascading off the table.
+    cascade: Option<Cascade>,
+    /// The record being played back, if the game is a replay rather than being played.
+    replay: Option<Replay>,
+    /// The server the game is being played on, if it's played over the network.
+    #[cfg(feature = "multiplayer")]
+    multiplayer: Option<Multiplayer>,
+    /// Finding a game on the server, until one starts.
+    #[cfg(feature = "multiplayer")]
+    lobby: Option<Lobby>,
+    /// What's been said to the room on the server.
+    #[cfg(feature = "multiplayer")]
+    chat: Chat,
+    /// The seat the game is played from, which a server can pick.
+    seat: usize,
     cards: Vec<Card>,
+    /// Where each of `cards` is in the game's table.
+    locations: Vec<Location>,
+    piles: Vec<Pile>,
+    /// What's still to play of the cards getting to where the game last put them.
+    timeline: Timeline<Cue>,
+    /// Whether the next table is a new deal, to be shuffled and dealt out.
+    dealing: bool,
+    /// Whether the game is held still while a new deal goes out, its clock stopped and the
+    /// computer waiting to play.
+    awaiting_deal: bool,
+    /// How the cards on their way to the next table are swept together, if they are.
+    sweep: Option<Sweep>,
+    /// The part of the table the game last asked to have framed.
+    framing: Framing,
+    /// The piles the game lets cards be dragged onto.
+    zones: Vec<Zone>,
+    /// The cards picked up with the pointer, while it's still pressed.
+    drag: Option<Drag>,
+    /// Cards tossed across the table, with the drag they were let go of from, to tell where they
+    /// land.
+    toss: Option<(Toss, Drag)>,
+    /// The finger that picked cards up, which is the only one that moves them until it lifts.
+    touch: Option<u64>,
+    /// The hint being shown, and when it was asked for.
+    hint: Option<(Hint, Instant)>,
+    /// When and where on the table the last click landed, to tell double-clicks apart.
+    last_click: Option<(Instant, Location)>,
+    orientation: Orientation,
     instance_buffer: wgpu::Buffer,
+    cursor_position: PhysicalPosition<f64>,
+    modifiers: ModifiersState,
+    pressed_card: Option<usize>,
+    hovered_card: Option<usize>,
+    trump_mode: Option<TrumpMode>,
+    last_update: Instant,
+    /// The ways the surface can present frames.
+    present_modes: Vec<PresentMode>,
+    /// The player's settings.
+    user_config: Config,
+    audio: Audio,
+    /// What the keys do.
+    keymap: Keymap,
+    /// The action waiting for a key to be pressed to bind to it.
+    rebinding: Option<Action>,
+    exit_requested: bool,
+    #[cfg(feature = "gamepad")]
+    gamepads: Gamepads,
+    /// The place on the table with the focus, while it's being moved without the mouse.
+    focus: Option<Location>,
 }
 
 impl State {
-    pub async fn new(window: Window) -> Result<Self> {
+    pub async fn new(window: Window, user_config: Config) -> Result<Self> {
+        let started = Instant::now();
         let size = window.inner_size();
+        let decoded_themes = BackgroundTask::spawn(theme::decode());
+        let table_image = BackgroundTask::spawn(background::load_table_image());
 
         let instance = create_instance();
         let surface =
@@ -283,71 +1158,146 @@ impl State {
             format: surface_format,
             width: size.width,
             height: size.height,
-            present_mode: surface_caps.present_modes[0],
+            present_mode: present_mode(user_config.settings.vsync, &surface_caps.present_modes),
             alpha_mode: surface_caps.alpha_modes[0],
             view_formats: vec![],
         };
         surface.configure(&device, &config);
 
-        let diffuse_texture = include_texture!(&device, &queue, "cards.png")?;
+        let format_flags = adapter.get_texture_format_features(config.format).flags;
+        let (quality, quality_benchmark) = match QualityPreset::from_env() {
+            Some(quality) => (quality, None),
+            None => (
+                QualityPreset::for_adapter(&adapter.get_info()),
+                Some(QualityBenchmark::new()),
+            ),
+        };
+        let sample_count = quality.msaa_samples(format_flags);
+        let msaa_view = create_msaa_view(&device, &config, sample_count);
+        let depth_view = create_depth_view(&device, &config, sample_count);
+        info!("starting with {quality:?} quality ({sample_count}x MSAA)");
+
         let texture_bind_group_layout = create_texture_bind_group_layout(&device);
-        let diffuse_bind_group =
-            create_texture_bind_group(&device, &diffuse_texture, &texture_bind_group_layout);
 
-        let camera = create_camera(size);
+        let camera = create_camera(size, window.scale_factor());
         let mut camera_uniform = CameraUniform::new();
         camera_uniform.update_view_proj(&camera);
         let camera_buffer = create_camera_buffer(&device, camera_uniform);
         let camera_bind_group_layout = create_camera_bind_group_layout(&device);
-        let camera_bind_group =
-            create_camera_bind_group(&device, &camera_buffer, &camera_bind_group_layout);
+        let frame_buffer = create_frame_buffer(&device);
+        let camera_bind_group = create_camera_bind_group(
+            &device,
+            &camera_buffer,
+            &frame_buffer,
+            &camera_bind_group_layout,
+        );
 
-        let camera_controller = CameraController::new(2.0);
+        let camera_controller = CameraController::new(120.0, 0.5..=8.0);
 
         let render_pipeline = create_render_pipeline(
             &device,
             &config,
+            sample_count,
+            &texture_bind_group_layout,
+            &camera_bind_group_layout,
+        );
+        let shadow_pipeline = create_shadow_pipeline(
+            &device,
+            &config,
+            sample_count,
             &texture_bind_group_layout,
             &camera_bind_group_layout,
         );
 
+        let mut background =
+            Background::new(&device, &queue, config.format, DEPTH_FORMAT, sample_count)?;
+        if let Some(image) = table_image.join().await {
+            background.set_image(&device, &queue, Some(&image))?;
+        }
+        background.set_look(&queue, &user_config.settings.look);
+
+        let particles = Particles::new(
+            &device,
+            &camera_bind_group_layout,
+            config.format,
+            DEPTH_FORMAT,
+            sample_count,
+        );
+        let badges = Badges::new(
+            &device,
+            &queue,
+            &camera_bind_group_layout,
+            config.format,
+            DEPTH_FORMAT,
+            sample_count,
+        )?;
+        let text = TextBrush::new(&device, &queue, config.format, DEPTH_FORMAT, sample_count)?;
+
+        let texture_options = TextureOptions {
+            anisotropy: quality.anisotropy(),
+            ..Default::default()
+        };
+        let themes = Themes::upload(
+            &device,
+            &queue,
+            &texture_bind_group_layout,
+            decoded_themes.join().await?,
+            &texture_options,
+        )
+        .await?;
+        // The look is the one in the settings, which may have been tuned since the theme was
+        // picked, rather than the theme's own.
+        let (theme_name, theme) = initial_theme()
+            .or_else(|| user_config.settings.theme.clone())
+            .and_then(
+                |name| match themes.find(&name, &user_config.settings.themes) {
+                    Some((layer, _)) => Some((name, layer)),
+                    None => {
+                        warn!("ignoring unknown theme {name:?}");
+                        None
+                    }
+                },
+            )
+            .unwrap_or_else(|| (themes.name(0).to_owned(), 0));
+        themes.use_style(&queue, theme, &user_config.settings);
+        locale::set_language(
+            user_config
+                .settings
+                .language
+                .unwrap_or_else(Language::system),
+        );
+        let card_scale = card_scale(&user_config.settings);
+
         let vertex_buffer = card::create_vertex_buffer(&device);
         let index_buffer = card::create_index_buffer(&device);
 
         let num_indices = card::INDICES.len() as u32;
 
-        let cards = Suit::iter()
-            .flat_map(|suit| {
-                (0..13u8).map(move |rank| {
-                    let position = cgmath::Vector3::new(
-                        (1.2 * card::WIDTH as f32 * (rank as f32 - 6.0)) as i32,
-                        (1.2 * card::HEIGHT as f32 * (suit.doppelkopf_suit_strength() as f32 - 2.5))
-                            as i32,
-                        0,
-                    );
-
-                    Card {
-                        position,
-                        facedown: (rank + suit.doppelkopf_suit_strength()) % 3 == 0,
-                        rank,
-                        suit,
-                    }
-                })
-            })
-            .collect::<Vec<_>>();
+        let orientation = layout::orientation_for_viewport(size, Orientation::Landscape);
+        let game_asked_for = game_name().is_some();
+        let game_name = startup_game();
+        let seed = startup_seed();
+        info!("dealing {game_name} from seed {seed}");
+        #[cfg(feature = "multiplayer")]
+        let multiplayer = net::startup_multiplayer();
+        #[cfg(feature = "multiplayer")]
+        let lobby = multiplayer.as_ref().map(|_| Lobby::new(&game_name));
+        #[cfg(feature = "multiplayer")]
+        let online = multiplayer.is_some();
+        #[cfg(not(feature = "multiplayer"))]
+        let online = false;
+        // On a server, the table shows a deal of its own until a game there starts.
+        let game = create_game(&game_name, seed, orientation);
+        let saved_game = save::handed_off().or_else(save::load);
+        let replay = save::replay_requested();
+        let resume = save::resume_requested();
+        let playing = game_asked_for || online || replay.is_some() || resume;
 
-        let instance_data = cards
-            .iter()
-            .map(Card::to_instance)
-            .collect::<Result<Vec<_>>>()?;
+        let instance_buffer = create_instance_buffer(&device, 0);
 
-        let instance_buffer = device.create_buffer_init(&BufferInitDescriptor {
-            label: Some("Instance Buffer"),
-            contents: cast_slice(&instance_data),
-            usage: BufferUsages::VERTEX,
-        });
+        info!("initialised renderer in {:?}", started.elapsed());
 
-        Ok(Self {
+        let mut state = Self {
             window,
             surface,
             device,
@@ -355,19 +1305,123 @@ impl State {
             config,
             size,
             render_pipeline,
+            shadow_pipeline,
+            background,
+            particles,
+            badges,
+            text,
+            notice: None,
+            ui: Ui::new(),
+            game_status: None,
+            labels: Vec::new(),
+            actions: Vec::new(),
+            show_debug_overlay: false,
+            show_move_log: false,
+            move_log_scroll: 0,
+            frame_timer: FrameTimer::new(),
+            draw_stats: DrawStats::default(),
+            texture_bind_group_layout,
+            camera_bind_group_layout,
+            adapter_info: adapter.get_info(),
+            format_flags,
+            quality,
+            quality_benchmark,
+            sample_count,
+            msaa_view,
+            depth_view,
             vertex_buffer,
             index_buffer,
             num_indices,
-            diffuse_bind_group,
-            _diffuse_texture: diffuse_texture,
+            themes,
+            theme,
+            theme_name,
+            card_scale,
+            prefers_reduced_motion: prefers_reduced_motion(),
+            texture_options,
             camera,
             camera_uniform,
             camera_buffer,
+            frame_buffer,
+            started: Instant::now(),
             camera_bind_group,
             camera_controller,
-            cards,
+            game,
+            game_name,
+            seed,
+            saved_moves: 0,
+            save_on_exit: true,
+            speech: Speech::new(),
+            spoken_moves: 0,
+            saved_game,
+            handoff: None,
+            game_over: false,
+            scenes: Scenes::new(playing),
+            daily: seed_name().as_deref() == Some("daily"),
+            end_screen: None,
+            session: Session::default(),
+            stats: save::load_stats(),
+            show_stats: false,
+            puzzles: Puzzle::bundled()
+                .into_iter()
+                .filter(|puzzle| GAMES.contains(&puzzle.game.as_str()))
+                .collect(),
+            progress: save::load_progress(),
+            puzzle: None,
+            celebrate: false,
+            cascade: None,
+            replay: None,
+            #[cfg(feature = "multiplayer")]
+            multiplayer,
+            #[cfg(feature = "multiplayer")]
+            lobby,
+            #[cfg(feature = "multiplayer")]
+            chat: Chat::new(),
+            seat: 0,
+            cards: Vec::new(),
+            locations: Vec::new(),
+            piles: Vec::new(),
+            timeline: Timeline::new(Instant::now()),
+            dealing: false,
+            awaiting_deal: false,
+            sweep: None,
+            framing: Framing::Table,
+            zones: Vec::new(),
+            drag: None,
+            toss: None,
+            touch: None,
+            hint: None,
+            last_click: None,
+            orientation,
             instance_buffer,
-        })
+            cursor_position: PhysicalPosition::new(0.0, 0.0),
+            modifiers: ModifiersState::empty(),
+            pressed_card: None,
+            hovered_card: None,
+            trump_mode: None,
+            last_update: Instant::now(),
+            present_modes: surface_caps.present_modes,
+            keymap: Keymap::new(&user_config.settings.keys),
+            audio: Audio::new(&user_config.settings.audio),
+            user_config,
+            rebinding: None,
+            exit_requested: false,
+            #[cfg(feature = "gamepad")]
+            gamepads: Gamepads::new(),
+            focus: None,
+        };
+        state.use_reduced_motion();
+        // The table is dealt straight away if there's a game to play, and otherwise once one's
+        // picked from the menu.
+        if state.scenes.playing() {
+            state.apply_game_commands();
+        }
+        if let Some(record) = replay {
+            state.start_replay(record);
+        } else if resume {
+            state.resume();
+        }
+
+        Ok(state)
     }
 
     pub fn window(&self) -> &Window {
@@ -380,7 +1434,12 @@ impl State {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
+            self.msaa_view = create_msaa_view(&self.device, &self.config, self.sample_count);
+            self.depth_view = create_depth_view(&self.device, &self.config, self.sample_count);
             self.camera.viewport_size = new_size;
+            self.camera.scale_factor = self.window.scale_factor();
+            self.update_orientation();
+            self.frame_table(false);
         }
 
         info!(
@@ -389,18 +1448,2402 @@ impl State {
         );
     }
 
-    pub fn input(&mut self, event: &WindowEvent) -> bool {
-        self.camera_controller.process_events(event)
+    fn update_orientation(&mut self) {
+        let orientation = layout::orientation_for_viewport(self.size, self.orientation);
+        if orientation == self.orientation {
+            return;
+        }
+
+        info!("switching to {orientation:?} layout");
+        self.orientation = orientation;
+        self.game.set_orientation(orientation);
+        self.apply_game_commands();
     }
 
-    pub fn update(&mut self) {
-        self.camera_controller.update_camera(&mut self.camera);
+    /// Carries out whatever the game has asked for since it was last asked.
+    fn apply_game_commands(&mut self) {
+        for command in self.game.take_commands() {
+            match command {
+                Command::Table(piles) => self.set_table(piles),
+                Command::Labels(labels) => self.labels = labels,
+                Command::Sweep(sweep) => self.sweep = Some(sweep),
+                Command::Zones(zones) => self.zones = zones,
+                Command::Status(status) => self.set_game_status(status),
+                Command::Actions(labels) => self.actions = labels,
+                Command::GameOver(outcome) => self.finish_game(outcome),
+                Command::Framing(framing) => {
+                    self.framing = framing;
+                    if self.user_config.settings.auto_camera {
+                        self.frame_table(true);
+                    }
+                }
+            }
+        }
+
+        #[cfg(feature = "multiplayer")]
+        if let Some(multiplayer) = &mut self.multiplayer {
+            for index in self.game.take_requested_moves() {
+                multiplayer.play(index);
+            }
+        }
+
+        if self.saves_game() && self.game.moves().len() != self.saved_moves {
+            self.saved_game = None;
+            self.save_game();
+        }
+
+        self.speak_moves();
+    }
+
+    /// Reads out the moves made since the last were. A game that's jumped ahead by more than a
+    /// few, resumed or caught up with a server, isn't read out from the start.
+    fn speak_moves(&mut self) {
+        let moves = self.game.moves();
+        let new = moves.get(self.spoken_moves..).unwrap_or_default();
+        if new.len() <= MAX_SPOKEN_MOVES {
+            for mv in new.iter().filter(|mv| !mv.notation.is_empty()) {
+                self.speech.say(speech::spoken(&mv.notation));
+            }
+        }
+        self.spoken_moves = moves.len();
+    }
+
+    /// Marks the game over, and unless it's a replay, counts it towards the session and shows how
+    /// it went, setting the cards cascading if it was won.
+    fn finish_game(&mut self, outcome: Option<Outcome>) {
+        info!("game over: {outcome:?}");
+        self.speech.say(tr(match outcome {
+            Some(Outcome::Won) => "You won",
+            Some(Outcome::Lost) => "You lost",
+            Some(Outcome::Drawn) => "Drawn",
+            None => "Game over",
+        }));
+        let newly = !self.game_over;
+        self.game_over = true;
+        if self.replay.is_some() {
+            self.notify(tr("Game over").to_owned());
+            return;
+        }
+
+        let elapsed = self.game.elapsed();
+        if newly {
+            let punch = Effect::Punch {
+                strength: GAME_OVER_PUNCH,
+            };
+            self.camera.add_effect(punch, GAME_OVER_PUNCH_DURATION);
+            self.session.record(outcome, elapsed);
+            self.stats.record(&self.game_name, outcome, elapsed);
+            if let Err(e) = save::store_stats(&self.stats) {
+                error!("{e:?}");
+            }
+            self.finish_puzzle(outcome);
+        }
+        self.end_screen = Some(EndScreen::new(
+            outcome,
+            elapsed,
+            self.game.moves().len(),
+            self.session,
+            self.stats.game(&self.game_name),
+        ));
+        self.celebrate = outcome == Some(Outcome::Won);
+    }
+
+    /// Tells the player whether the game just over solved the puzzle it was, if it was one, and
+    /// keeps track of it if so.
+    fn finish_puzzle(&mut self, outcome: Option<Outcome>) {
+        let Some(puzzle) = self.puzzle.and_then(|index| self.puzzles.get(index)) else {
+            return;
+        };
+        if !puzzle.is_solved(outcome, self.game.as_ref()) {
+            self.notify(tr("Puzzle not solved").to_owned());
+            return;
+        }
+
+        info!("solved the puzzle {}", puzzle.id);
+        self.progress.record(&puzzle.id, self.game.moves().len());
+        if let Err(e) = save::store_progress(&self.progress) {
+            error!("{e:?}");
+        }
+        self.notify(tr("Puzzle solved!").to_owned());
+    }
+
+    /// Deals a new game of the same kind as the last, or the same deal again for a puzzle.
+    fn deal_again(&mut self) {
+        match self.puzzle {
+            Some(index) => self.start_puzzle(index),
+            None => self.deal(self.game_name.clone()),
+        }
+    }
+
+    /// Deals a game of `name`, one of `GAMES`, from the day's seed if that's picked or a new one
+    /// otherwise, and goes in to play it.
+    fn deal(&mut self, name: String) {
+        let seed = match self.daily {
+            true => daily_seed(unix_time()),
+            false => random_seed(),
+        };
+        self.deal_from(name, seed);
+    }
+
+    /// Deals the puzzle with `index` in `puzzles` and goes in to play it.
+    fn start_puzzle(&mut self, index: usize) {
+        let Some(puzzle) = self.puzzles.get(index) else {
+            return;
+        };
+        self.deal_from(puzzle.game.clone(), puzzle.seed);
+        self.puzzle = Some(index);
+    }
+
+    /// Deals a game of `name`, one of `GAMES`, from `seed`, and goes in to play it.
+    fn deal_from(&mut self, name: String, seed: u64) {
+        self.seed = seed;
+        info!("dealing {name} from seed {seed}");
+        self.game = create_game(&name, seed, self.orientation);
+        self.game_name = name;
+        self.puzzle = None;
+        self.saved_moves = 0;
+        self.game_over = false;
+        self.end_screen = None;
+        self.replay = None;
+        self.scenes.start_game();
+        self.dealing = true;
+        self.apply_game_commands();
+    }
+
+    /// Leaves the game for the menu, keeping it to carry on with if it's the player's own and
+    /// isn't over.
+    fn quit_to_menu(&mut self) {
+        if self.saves_game() && !self.game_over && !self.game.moves().is_empty() {
+            self.saved_game = Some(self.game_record());
+        }
+        self.scenes.leave_for_menu();
+    }
+
+    /// Puts the end screen away, bringing back any cards that cascaded off the table.
+    fn close_end_screen(&mut self) {
+        self.end_screen = None;
+        if self.cascade.is_some() || self.celebrate {
+            let piles = std::mem::take(&mut self.piles);
+            self.set_table(piles);
+        }
+    }
+
+    /// Whether the game is the player's own, to be saved as it goes. A replay or a game on a
+    /// server isn't, so it isn't saved over theirs.
+    fn saves_game(&self) -> bool {
+        self.replay.is_none() && !self.online()
+    }
+
+    /// Whether the game is being played over the network.
+    fn online(&self) -> bool {
+        #[cfg(feature = "multiplayer")]
+        return self.multiplayer.is_some();
+        #[cfg(not(feature = "multiplayer"))]
+        return false;
+    }
+
+    /// Saves the game in progress, or forgets the saved game once it's over.
+    fn save_game(&mut self) {
+        self.saved_moves = self.game.moves().len();
+
+        let result = if self.game_over {
+            save::clear()
+        } else {
+            save::store(&self.game_record())
+        };
+        if let Err(e) = result {
+            error!("{e:?}");
+        }
+    }
+
+    /// The game so far, as it's saved or exported.
+    fn game_record(&self) -> SavedGame {
+        SavedGame {
+            game: self.game_name.clone(),
+            seed: self.seed,
+            elapsed: self.game.elapsed(),
+            moves: self.game.moves().to_vec(),
+        }
+    }
+
+    /// Shows the QR code and link that carry the game on from here on another device.
+    fn hand_off(&mut self) {
+        let link = save::resume_link(&self.game_record());
+        let Some(code) = QrCode::encode(link.as_bytes()) else {
+            self.notify(tr("Too many moves to carry over").to_owned());
+            return;
+        };
+        info!("carry the game on elsewhere with {link}");
+        self.handoff = Some((code, link));
+        self.scenes.push(Scene::HandOff);
+    }
+
+    /// Writes out the record of the game so far, which can be loaded again to play it back.
+    fn export_record(&mut self) {
+        match save::export(&self.game_record()) {
+            Ok(place) => {
+                info!("exported the game record to {place}");
+                self.notify(tr("Game record exported").to_owned());
+            }
+            Err(e) => error!("{e:?}"),
+        }
+    }
+
+    /// Takes back the player's last move, with the cards sliding back to where they were. In a
+    /// replay, steps back a move instead.
+    fn undo(&mut self) {
+        if self.replay.is_some() {
+            self.control_replay(replay::Control::StepBack);
+        } else if self.game.undo() {
+            self.game_over = false;
+            self.apply_game_commands();
+        }
+    }
+
+    fn redo(&mut self) {
+        if self.replay.is_some() {
+            self.control_replay(replay::Control::StepForward);
+        } else if self.game.redo() {
+            self.apply_game_commands();
+        }
+    }
+
+    /// Swaps the game for the one saved on an earlier run, if there is one.
+    fn resume(&mut self) {
+        let Some(saved) = self.saved_game.take() else {
+            return;
+        };
+
+        let mut game = create_game(&saved.game, saved.seed, self.orientation);
+        if !game.restore(&saved.moves, saved.elapsed) {
+            warn!("the saved game didn't play back in full; carrying on from where it stopped");
+        }
+        info!("resumed {} after {:?}", saved.game, saved.elapsed);
+
+        self.saved_moves = game.moves().len();
+        self.replay = None;
+        self.game = game;
+        self.game_name = saved.game;
+        self.seed = saved.seed;
+        self.puzzle = None;
+        self.game_over = false;
+        self.scenes.start_game();
+        self.apply_game_commands();
+    }
+
+    /// Swaps the game for a playback of `record`, starting from the deal.
+    fn start_replay(&mut self, record: SavedGame) {
+        info!(
+            "replaying {} from seed {} ({} moves)",
+            record.game,
+            record.seed,
+            record.moves.len()
+        );
+        self.game = create_game(&record.game, record.seed, self.orientation);
+        self.game_name = record.game.clone();
+        self.seed = record.seed;
+        self.puzzle = None;
+        self.game_over = false;
+        // The replay is watched here, rather than played on a server.
+        #[cfg(feature = "multiplayer")]
+        {
+            self.multiplayer = None;
+            self.lobby = None;
+        }
+        self.replay = Some(Replay::new(record));
+        self.scenes.start_game();
+        self.apply_game_commands();
+    }
+
+    fn control_replay(&mut self, control: replay::Control) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+
+        if control == replay::Control::Exit {
+            // The game carries on from where the replay got to, and is saved from its next move.
+            self.replay = None;
+            self.saved_moves = self.game.moves().len();
+            self.notify(tr("Replay stopped").to_owned());
+        } else if replay.control(control) {
+            self.sync_replay();
+        }
+    }
+
+    /// Brings the game up to the moves the replay has made.
+    fn sync_replay(&mut self) {
+        let Some(replay) = &self.replay else {
+            return;
+        };
+
+        let moves = replay.moves().to_vec();
+        let clock = replay.clock();
+        let (game, seed) = (replay.record().game.clone(), replay.record().seed);
+        let orientation = self.orientation;
+        if !self.catch_up(&moves, clock, || create_game(&game, seed, orientation)) {
+            warn!("the replay doesn't match its game; stopping at the last move that does");
+        }
+    }
+
+    /// Brings the game up to `moves`, `elapsed` into it: by making the new ones if it's only
+    /// behind, or by dealing it again with `deal` and making them all if it's gone another way.
+    /// Either way the cards slide from where they were to where they end up. Returns whether
+    /// every move could be made.
+    fn catch_up(
+        &mut self,
+        moves: &[MoveRecord],
+        elapsed: Duration,
+        deal: impl FnOnce() -> Box<dyn Game>,
+    ) -> bool {
+        let made = self.game.moves();
+        let behind = made.len() <= moves.len()
+            && made
+                .iter()
+                .zip(moves)
+                .all(|(made, mv)| made.index == mv.index);
+        if !behind {
+            self.game = deal();
+        }
+
+        let made = self.game.moves().len();
+        let complete = self.game.restore(&moves[made..], elapsed);
+        self.game_over = false;
+        self.apply_game_commands();
+        complete
+    }
+
+    /// Takes in what the server has sent, if the game is being played on one.
+    #[cfg(feature = "multiplayer")]
+    fn poll_multiplayer(&mut self) {
+        let Some(multiplayer) = &mut self.multiplayer else {
+            return;
+        };
+
+        if self.lobby.as_mut().is_some_and(|lobby| lobby.wants_rooms()) {
+            multiplayer.send(ClientMessage::ListRooms);
+        }
+
+        for event in multiplayer.poll() {
+            match event {
+                NetEvent::Rooms(rooms) => {
+                    // A seat lost for good while the connection was down lands back here.
+                    self.lobby
+                        .get_or_insert_with(|| Lobby::new(&self.game_name))
+                        .set_rooms(rooms);
+                }
+                NetEvent::Lobby(room) => {
+                    self.lobby
+                        .get_or_insert_with(|| Lobby::new(&self.game_name))
+                        .set_room(Some(room));
+                }
+                NetEvent::Joined {
+                    game,
+                    seed,
+                    seat,
+                    moves,
+                } => {
+                    self.lobby = None;
+                    self.join_network_game(game, seed, seat, moves);
+                }
+                NetEvent::Moves(moves) => {
+                    let elapsed = moves.last().map_or(self.game.elapsed(), |mv| mv.time);
+                    if !self.game.restore(&moves, elapsed) {
+                        warn!("a move from the server couldn't be made here");
+                    }
+                    self.apply_game_commands();
+                }
+                NetEvent::Said { seat, text } => {
+                    let own = self.multiplayer.as_ref().and_then(Multiplayer::seat);
+                    let who = match own == Some(seat) {
+                        true => tr("You").to_owned(),
+                        false => tr_with("Seat {0}", &[&(seat + 1)]),
+                    };
+                    self.chat.receive(who, text);
+                }
+                NetEvent::Reconnecting => {
+                    self.notify(tr("Connection lost; reconnecting").to_owned());
+                }
+                NetEvent::Disconnected => {
+                    self.notify(tr("The other player's connection was lost").to_owned());
+                    self.multiplayer = None;
+                    self.lobby = None;
+                    return;
+                }
+                NetEvent::Rejected(reason) => self.notify(reason),
+            }
+        }
+    }
+
+    /// Opens or closes the chat, letting the input method compose text for it while it's open.
+    #[cfg(feature = "multiplayer")]
+    fn toggle_chat(&mut self) {
+        let open = self.chat.toggle();
+        self.window.set_ime_allowed(open);
+    }
+
+    /// Gives the player's seat up to the computer, and goes back to the lobby to find another
+    /// game. The game on the table stays there until the next one starts.
+    #[cfg(feature = "multiplayer")]
+    fn leave_network_game(&mut self) {
+        if let Some(multiplayer) = &mut self.multiplayer {
+            multiplayer.send(ClientMessage::Leave);
+            self.lobby = Some(Lobby::new(&self.game_name));
+        }
+    }
+
+    /// Sits the player at `seat` in the server's game of `game` dealt from `seed`, catching up
+    /// on `moves`. After a dropped connection this is the game already on the table, which only
+    /// has to catch up on what was missed.
+    #[cfg(feature = "multiplayer")]
+    fn join_network_game(&mut self, game: String, seed: u64, seat: usize, moves: Vec<MoveRecord>) {
+        let orientation = self.orientation;
+        let Some(dealt) = create_network_game(&game, seed, seat, orientation) else {
+            warn!("the server is playing {game}, which can't be played here");
+            self.multiplayer = None;
+            return;
+        };
+
+        let rejoined = game == self.game_name && seed == self.seed && seat == self.seat;
+        if !rejoined {
+            info!("playing {game} from seed {seed} in seat {seat}");
+            self.game = dealt;
+            self.game_name = game;
+            self.seed = seed;
+            self.seat = seat;
+            self.puzzle = None;
+        }
+
+        let elapsed = moves.last().map_or(Duration::ZERO, |mv| mv.time);
+        let name = self.game_name.clone();
+        let complete = self.catch_up(&moves, elapsed, || {
+            create_network_game(&name, seed, seat, orientation).expect("the game was just dealt")
+        });
+        if !complete {
+            warn!("the server's game couldn't be played here in full");
+        }
+        if rejoined {
+            self.notify(tr("Reconnected").to_owned());
+        }
+    }
+
+    /// Replaces the cards on the table with the game's piles, sliding any that moved from where
+    /// they were and turning over any that were flipped, or dealing them all out if it's a new
+    /// deal.
+    fn set_table(&mut self, piles: Vec<Pile>) {
+        let first_table = self.cards.is_empty();
+        let dealing = std::mem::take(&mut self.dealing);
+
+        let old_cards = std::mem::take(&mut self.cards);
+        let old_locations = std::mem::take(&mut self.locations);
+        for (pile_index, pile) in piles.iter().enumerate() {
+            for (index, view) in pile.cards.iter().enumerate() {
+                self.cards.push(Card {
+                    position: layout::card_position(pile, index, self.card_scale),
+                    rotation: cgmath::Rad(0.0),
+                    scale: cgmath::Vector2::new(self.card_scale, self.card_scale),
+                    facedown: view.facedown,
+                    highlighted: false,
+                    glow: 0.0,
+                    selected: view.selected,
+                    trump: self
+                        .trump_mode
+                        .is_some_and(|mode| mode.is_trump(view.suit, view.rank)),
+                    foil: view.foil,
+                    playable: view.playable && self.user_config.settings.playable_glow,
+                    badge: view.badge,
+                    tint: match (view.dimmed, view.tinted) {
+                        (true, _) => DIMMED_TINT,
+                        (false, true) => TINT_COLOR,
+                        (false, false) => [1.0; 3],
+                    },
+                    desaturation: if view.dimmed {
+                        DIMMED_DESATURATION
+                    } else {
+                        0.0
+                    },
+                    layer: self.theme,
+                    // Selected cards are lifted off the table.
+                    elevation: if view.selected { 1.0 } else { 0.0 },
+                    rank: view.rank,
+                    suit: view.suit,
+                });
+                self.locations.push(Location::card(pile_index, index));
+            }
+        }
+        self.piles = piles;
+
+        let sweep = self.sweep.take().map(|sweep| {
+            let via = layout::sweep_position(&sweep, self.card_scale);
+            if sweep.big {
+                let shake = Effect::Shake {
+                    strength: TRICK_SHAKE,
+                };
+                self.camera.add_effect(shake, TRICK_SHAKE_DURATION);
+                // A big trick is looked at up close as it's gathered, unless less motion is
+                // asked for.
+                if !self.reduced_motion() {
+                    let zoom = self.camera.resting_zoom() * TRICK_FOCUS_ZOOM;
+                    let zoom = self.camera_controller.clamp_zoom(zoom);
+                    let target = cgmath::Point2::new(via.x as f32, via.y as f32);
+                    self.camera
+                        .focus_on(target, zoom, TRICK_FOCUS_DURATION, TRICK_FOCUS_HOLD);
+                }
+            }
+            (sweep.from.clone(), via)
+        });
+        let now = Instant::now();
+        let speed = self.user_config.settings.animation_speed;
+        let deck = deck_position(&self.cards);
+        // Whatever was still to come of the last table is cut short.
+        let mut timeline = Timeline::new(now);
+        let motions = match dealing {
+            true => {
+                // The deck is shuffled before it's dealt, to the sound of the riffle, while the
+                // camera drifts over to it.
+                let riffle = Riffle::new(animation::dealt(deck, &self.cards), deck, now);
+                let delay = match self.reduced_motion() {
+                    true => Duration::ZERO,
+                    false => riffle.falling().saturating_duration_since(now),
+                };
+                self.audio.play(Sound::Shuffle, delay, self.pan(deck));
+                let camera = Tween::Camera {
+                    start: now,
+                    target: cgmath::Point2::new(deck.x as f32, deck.y as f32),
+                    duration: Riffle::DURATION,
+                };
+                timeline.then([Tween::Riffle(riffle), camera]);
+
+                animation::deal(
+                    deck,
+                    &self.cards,
+                    &self.locations,
+                    DealTiming::new(self.cards.len(), speed),
+                    timeline.cursor(),
+                )
+            }
+            false => animation::motions(
+                &old_cards,
+                &old_locations,
+                &self.timeline.in_flight(now),
+                &self.cards,
+                &self.locations,
+                sweep,
+                speed,
+                now,
+            ),
+        };
+        self.play_motion_sounds(&motions, dealing, now);
+        if !self.reduced_motion() && !dealing {
+            self.sparkle_foundations(&old_cards, &old_locations, &motions, now);
+        }
+
+        // The game gets going once a new deal is out.
+        timeline.then([Tween::Motions(motions)]);
+        if dealing {
+            timeline.cue(Cue::Dealt);
+        }
+        // With less motion asked for, cards jump straight to where they're going.
+        if self.reduced_motion() {
+            timeline = Timeline::new(now);
+        }
+        let cut_short = std::mem::replace(&mut self.timeline, timeline).into_cues();
+        for cue in cut_short {
+            self.cue(cue);
+        }
+        self.awaiting_deal = dealing && !self.timeline.is_empty();
+        self.advance_timeline();
+
+        self.pressed_card = None;
+        self.hovered_card = None;
+        self.drag = None;
+        self.toss = None;
+        self.hint = None;
+        self.focus = self.focus.filter(|&at| focus::is_target(&self.piles, at));
+        self.celebrate = false;
+        self.cascade = None;
+
+        let needed = self.cards.len() * std::mem::size_of::<card::Instance>();
+        if needed as u64 > self.instance_buffer.size() {
+            self.instance_buffer = create_instance_buffer(&self.device, self.cards.len());
+        }
+        self.badges.reserve(&self.device, self.cards.len());
+
+        if first_table {
+            frame_cards(&mut self.camera, &self.cards);
+        }
+
+        if let Err(e) = self.write_instances() {
+            error!("{e:?}");
+        }
+    }
+
+    /// Plays the sounds of the cards in `motions` being dealt, put down or turned over, as they
+    /// happen, or straight away if the cards jump there. Each is heard from the side of the screen
+    /// the card ends up on.
+    fn play_motion_sounds(&mut self, motions: &[Motion], dealing: bool, now: Instant) {
+        let reduced_motion = self.reduced_motion();
+        let sounds = motions.iter().map(|motion| match motion.moves() {
+            true if dealing => (Sound::Deal, motion.start(), motion.card),
+            true => (Sound::Place, motion.end(), motion.card),
+            false => (Sound::Flip, motion.start(), motion.card),
+        });
+        for (sound, at, card) in sounds.take(MAX_MOTION_SOUNDS) {
+            let delay = match reduced_motion {
+                true => Duration::ZERO,
+                false => at.saturating_duration_since(now),
+            };
+            self.audio
+                .play(sound, delay, self.pan(self.cards[card].position));
+        }
+    }
+
+    /// Sets off a sparkle where each card in `motions` lands on a foundation from somewhere else,
+    /// `old_cards` at `old_locations` being the table before.
+    fn sparkle_foundations(
+        &mut self,
+        old_cards: &[Card],
+        old_locations: &[Location],
+        motions: &[Motion],
+        now: Instant,
+    ) {
+        let foundations = self
+            .zones
+            .iter()
+            .filter(|zone| zone.kind == ZoneKind::Foundation)
+            .map(|zone| zone.pile)
+            .collect::<Vec<_>>();
+        let was_up = |card: &Card| {
+            old_cards.iter().zip(old_locations).any(|(old, location)| {
+                old.suit == card.suit
+                    && old.rank == card.rank
+                    && foundations.contains(&location.pile)
+            })
+        };
+
+        for motion in motions.iter().filter(|motion| motion.moves()) {
+            let card = &self.cards[motion.card];
+            if foundations.contains(&self.locations[motion.card].pile) && !was_up(card) {
+                let at = cgmath::Point2::new(card.position.x as f32, card.position.y as f32);
+                let delay = motion.end().saturating_duration_since(now);
+                self.particles.emit(Emitter::Sparkle, at, delay);
+            }
+        }
+    }
+
+    /// How far to pan a sound from `position` on the table, by where the camera shows it across
+    /// the window: -1 at the left edge, 1 at the right.
+    fn pan(&self, position: cgmath::Vector3<i32>) -> f32 {
+        let screen = self
+            .camera
+            .world_to_screen(cgmath::Point2::new(position.x as f32, position.y as f32));
+        screen.x / self.size.width.max(1) as f32 * 2.0 - 1.0
+    }
+
+    /// Plays the timeline on to how the cards should look by now, acting on any cues that have
+    /// come due.
+    fn advance_timeline(&mut self) {
+        let cues = self
+            .timeline
+            .advance(Instant::now(), &mut self.cards, &mut self.camera);
+        for cue in cues {
+            self.cue(cue);
+        }
+    }
+
+    /// Plays every animation of the cards to its end at once.
+    fn fast_forward(&mut self) {
+        let cues = self.timeline.finish(&mut self.cards, &mut self.camera);
+        for cue in cues {
+            self.cue(cue);
+        }
+        if let Err(e) = self.write_instances() {
+            error!("{e:?}");
+        }
+    }
+
+    /// Acts on a cue from the timeline.
+    fn cue(&mut self, cue: Cue) {
+        match cue {
+            Cue::Dealt => self.awaiting_deal = false,
+        }
+    }
+
+    /// Switches the cards and the look to the named theme's, built in or saved.
+    pub fn set_theme(&mut self, name: &str) -> Result<()> {
+        let settings = &mut self.user_config.settings;
+        let (layer, look) = self
+            .themes
+            .find(name, &settings.themes)
+            .ok_or_else(|| Error::from(format!("no theme called {name:?}")))?;
+        settings.look = look;
+        self.background.set_look(&self.queue, &look);
+        self.theme = layer;
+        self.theme_name = name.to_owned();
+        for card in &mut self.cards {
+            card.layer = self.theme;
+        }
+        self.use_card_style();
+
+        info!("switched to the {name} theme");
+        self.notify(tr_with("Theme: {0}", &[&name]));
+        self.write_instances()
+    }
+
+    /// Has the camera jump rather than ease, and leaves out its effects, if less motion is asked
+    /// for.
+    fn use_reduced_motion(&mut self) {
+        let reduced_motion = self.reduced_motion();
+        self.camera_controller.set_reduced_motion(reduced_motion);
+        self.camera.effect_intensity = if reduced_motion { 0.0 } else { 1.0 };
+    }
+
+    /// Whether cards should jump into place and effects be left out, as the settings say or, if
+    /// they don't, as the system asked.
+    fn reduced_motion(&self) -> bool {
+        self.user_config
+            .settings
+            .reduced_motion
+            .unwrap_or(self.prefers_reduced_motion)
+    }
+
+    /// Draws the cards in the style of the theme, with the suits and ranks marked as the
+    /// settings say.
+    fn use_card_style(&self) {
+        self.themes
+            .use_style(&self.queue, self.theme, &self.user_config.settings);
+    }
+
+    /// Lays the table out again for cards drawn at `scale`, and frames it afresh, since it's
+    /// grown or shrunk.
+    fn set_card_scale(&mut self, scale: f32) {
+        self.card_scale = scale;
+        self.set_table(self.piles.clone());
+        self.frame_table(false);
+    }
+
+    /// Points the camera at what the game's phase asks to have framed, or at every card if the
+    /// camera's left to the player, easing there if `ease` and more motion is fine.
+    fn frame_table(&mut self, ease: bool) {
+        let phase = match &self.framing {
+            Framing::Piles(piles) if self.user_config.settings.auto_camera => piles
+                .iter()
+                .filter_map(|&pile| self.piles.get(pile))
+                .map(|pile| layout::pile_extent(pile, self.card_scale))
+                .reduce(|a, b| a.union(b)),
+            _ => None,
+        };
+        let Some(bounds) = phase.or_else(|| {
+            self.cards
+                .iter()
+                .map(Card::bounds)
+                .reduce(|a, b| a.union(b))
+        }) else {
+            return;
+        };
+
+        if !ease || self.reduced_motion() {
+            self.camera.fit_to_bounds(bounds, FRAME_PADDING);
+            return;
+        }
+        let zoom = self.camera.zoom_to_fit(bounds, FRAME_PADDING);
+        let zoom = self.camera_controller.clamp_zoom(zoom);
+        self.camera.zoom_to(bounds.center(), zoom, FRAMING_DURATION);
+    }
+
+    /// Changes how card textures are sampled.
+    pub fn set_texture_options(&mut self, options: TextureOptions) {
+        self.texture_options = options;
+        self.themes
+            .set_texture_options(&self.device, &self.texture_bind_group_layout, &options);
+
+        info!("sampling card textures with {options:?}");
+    }
+
+    /// Shows the scores and turn of the game being played in the HUD, or hides it for `None`.
+    pub fn set_game_status(&mut self, status: Option<GameStatus>) {
+        let turn = |status: &Option<GameStatus>| {
+            status
+                .as_ref()
+                .filter(|status| status.players.len() > 1)
+                .and_then(|status| status.turn)
+        };
+        if turn(&status) == Some(self.seat) && turn(&self.game_status) != Some(self.seat) {
+            self.speech.say(tr("Your turn"));
+        }
+        self.game_status = status;
+    }
+
+    /// Moves on to the next theme, the built-in ones and then the saved ones, wrapping around
+    /// after the last.
+    fn next_theme(&mut self) {
+        let names = self
+            .themes
+            .names(&self.user_config.settings.themes)
+            .collect::<Vec<_>>();
+        let index = names.iter().position(|name| *name == self.theme_name);
+        let next = names[index.map_or(0, |index| (index + 1) % names.len())].to_owned();
+        if let Err(e) = self.set_theme(&next) {
+            error!("{e:?}");
+        }
+    }
+
+    /// Saves the look as a new theme, with the cards of the theme in use, and switches to it.
+    fn save_theme(&mut self) {
+        let settings = &mut self.user_config.settings;
+        let name = (1..)
+            .map(|number| format!("custom-{number}"))
+            .find(|name| self.themes.layer(name).is_none() && !settings.themes.contains_key(name))
+            .expect("there's always a free name");
+        let theme = SavedTheme {
+            cards: self.themes.name(self.theme).to_owned(),
+            look: settings.look,
+        };
+        settings.themes.insert(name.clone(), theme);
+        settings.theme = Some(name.clone());
+        self.theme_name = name;
+        if let Err(e) = self.user_config.store() {
+            error!("{e:?}");
+        }
+
+        info!("saved the {} theme", self.theme_name);
+        self.notify(tr_with("Saved theme {0}", &[&self.theme_name]));
+    }
+
+    /// Switches card textures between crisp and smoothed magnification.
+    fn toggle_filter(&mut self) {
+        let filter = match self.texture_options.filter {
+            FilterMode::Nearest => FilterMode::Linear,
+            FilterMode::Linear => FilterMode::Nearest,
+        };
+        self.set_texture_options(TextureOptions {
+            filter,
+            ..self.texture_options
+        });
+        self.notify(format!("Card filtering: {filter:?}"));
+    }
+
+    /// Moves on to underlining the trumps of the next game mode.
+    fn next_trump_mode(&mut self) {
+        let next = match self.trump_mode {
+            None => Some(TrumpMode::Doppelkopf),
+            Some(TrumpMode::Doppelkopf) => Some(TrumpMode::Skat(Suit::Clubs)),
+            Some(TrumpMode::Skat(_)) => Some(TrumpMode::Schafkopf),
+            Some(TrumpMode::Schafkopf) => None,
+        };
+        self.set_trump_mode(next);
+    }
+
+    /// Underlines the trumps of a game mode, or clears the underlines for `None`.
+    fn set_trump_mode(&mut self, trump_mode: Option<TrumpMode>) {
+        self.trump_mode = trump_mode;
+        for card in &mut self.cards {
+            card.trump = trump_mode.is_some_and(|mode| mode.is_trump(card.suit, card.rank));
+        }
+
+        info!("showing trumps for {trump_mode:?}");
+        self.notify(match trump_mode {
+            Some(mode) => format!("Trumps: {mode:?}"),
+            None => tr("Trumps: off").to_owned(),
+        });
+
+        if let Err(e) = self.write_instances() {
+            error!("{e:?}");
+        }
+    }
+
+    /// Switches to a different quality preset, rebuilding whatever depends on it.
+    fn set_quality(&mut self, quality: QualityPreset) {
+        self.quality = quality;
+        self.sample_count = quality.msaa_samples(self.format_flags);
+        self.msaa_view = create_msaa_view(&self.device, &self.config, self.sample_count);
+        self.depth_view = create_depth_view(&self.device, &self.config, self.sample_count);
+        self.render_pipeline = create_render_pipeline(
+            &self.device,
+            &self.config,
+            self.sample_count,
+            &self.texture_bind_group_layout,
+            &self.camera_bind_group_layout,
+        );
+        self.shadow_pipeline = create_shadow_pipeline(
+            &self.device,
+            &self.config,
+            self.sample_count,
+            &self.texture_bind_group_layout,
+            &self.camera_bind_group_layout,
+        );
+        self.background.set_sample_count(
+            &self.device,
+            self.config.format,
+            DEPTH_FORMAT,
+            self.sample_count,
+        );
+        self.particles.set_sample_count(
+            &self.device,
+            &self.camera_bind_group_layout,
+            self.config.format,
+            DEPTH_FORMAT,
+            self.sample_count,
+        );
+        self.badges.set_sample_count(
+            &self.device,
+            &self.camera_bind_group_layout,
+            self.config.format,
+            DEPTH_FORMAT,
+            self.sample_count,
+        );
+        self.text.set_sample_count(
+            &self.device,
+            self.config.format,
+            DEPTH_FORMAT,
+            self.sample_count,
+        );
+
+        self.set_texture_options(TextureOptions {
+            anisotropy: quality.anisotropy(),
+            ..self.texture_options
+        });
+
+        info!(
+            "switched to {quality:?} quality ({}x MSAA)",
+            self.sample_count
+        );
+        self.notify(format!("Quality: {quality:?}"));
+    }
+
+    /// Briefly shows a line of text at the top of the window, replacing any notice already there.
+    fn notify(&mut self, message: String) {
+        self.notice = Some((message, Instant::now()));
+    }
+
+    /// Queues the game's labels, each centred on its place on the table wherever the camera has
+    /// put it, with badges on a backing of their own.
+    fn queue_labels(&mut self) {
+        let scale = self.window.scale_factor() as f32;
+        let size = LABEL_TEXT_SIZE * scale;
+        let padding = BADGE_PADDING * scale;
+        for label in &self.labels {
+            let position = layout::label_position(label, self.card_scale);
+            let centre = self.camera.world_to_screen(position);
+            let text = tr(&label.text);
+            let [width, height] = TextBrush::measure(text, size);
+            let position = [centre.x - width / 2.0, centre.y - height / 2.0];
+            if label.style == LabelStyle::Badge {
+                self.text.fill(
+                    [position[0] - padding, position[1] - padding],
+                    [width + 2.0 * padding, height + 2.0 * padding],
+                    BADGE_COLOR,
+                );
+            }
+            self.text.queue(text, position, size, LABEL_COLOR);
+        }
+    }
+
+    /// Tints the zone the cards being dragged are over, by whether dropping them there would make
+    /// a move.
+    fn queue_drop_zone(&mut self) {
+        let Some(drag) = self.drag.as_ref().filter(|drag| drag.moving) else {
+            return;
+        };
+
+        let zones = DropZone::lay_out(&self.zones, &self.piles, self.card_scale);
+        let point = self.camera.screen_to_world(drag.pointer);
+        let Some(zone) = drag.target(&zones, point) else {
+            return;
+        };
+
+        let color = match drag.allowed.contains(&zone.zone.pile) {
+            true => DROP_ALLOWED_COLOR,
+            false => DROP_REFUSED_COLOR,
+        };
+        let (position, size) = self.camera.bounds_to_screen(zone.bounds);
+        self.text.fill(position, size, color);
+    }
+
+    /// Tints the empty piles the hint points at, pulsing with the cards it points at.
+    fn queue_hint_piles(&mut self) {
+        let (_, piles) = self.hint_targets();
+        let [red, green, blue, alpha] = HINT_COLOR;
+        let color = [red, green, blue, alpha * self.hint_glow()];
+        for pile in piles {
+            let Some(pile) = self.piles.get(pile) else {
+                continue;
+            };
+            let bounds = layout::pile_bounds(pile, self.card_scale);
+            let (position, size) = self.camera.bounds_to_screen(bounds);
+            self.text.fill(position, size, color);
+        }
+    }
+
+    /// Rings the card or empty pile with the focus.
+    fn queue_focus(&mut self) {
+        let Some(at) = self.focus.filter(|_| self.scenes.playing()) else {
+            return;
+        };
+        let bounds = match self.locations.iter().position(|&location| location == at) {
+            Some(card) => self.cards[card].bounds(),
+            None => match self.piles.get(at.pile) {
+                Some(pile) => layout::pile_bounds(pile, self.card_scale),
+                None => return,
+            },
+        };
+
+        let (position, size) = self.camera.bounds_to_screen(bounds);
+        let thickness = FOCUS_THICKNESS * self.window.scale_factor() as f32;
+        self.text
+            .outline(position, size, thickness, ui::FOCUS_COLOR);
+    }
+
+    /// Queues the current notice, centred at the top of the window and fading out at the end of
+    /// its time on screen.
+    fn queue_notice(&mut self) {
+        let Some((message, shown)) = &self.notice else {
+            return;
+        };
+
+        let remaining = NOTICE_DURATION.saturating_sub(shown.elapsed());
+        if remaining.is_zero() {
+            self.notice = None;
+            return;
+        }
+
+        let alpha = (remaining.as_secs_f32() / NOTICE_FADE.as_secs_f32()).min(1.0);
+        let size = NOTICE_TEXT_SIZE * self.window.scale_factor() as f32;
+        let [width, _] = TextBrush::measure(message, size);
+        let position = [(self.size.width as f32 - width) / 2.0, size];
+
+        self.text
+            .queue(message, position, size, [1.0, 1.0, 1.0, alpha]);
+    }
+
+    /// Declares this frame's widgets: a toolbar along the bottom of the window with buttons for
+    /// the settings that otherwise need function keys, the game's actions in the middle of the
+    /// bottom edge, and the HUD if a game is being played.
+    fn build_ui(&mut self) {
+        if !self.scenes.playing() {
+            self.build_menu_ui();
+            return;
+        }
+
+        let scale = self.window.scale_factor() as f32;
+        let [button_width, button_height] = TOOLBAR_BUTTON_SIZE.map(|length| length * scale);
+        let spacing = TOOLBAR_SPACING * scale;
+
+        let mut buttons = vec![
+            "Menu", "Theme", "Trumps", "Filter", "Undo", "Redo", "Hint", "Moves", "Stats",
+        ];
+        if self.saved_game.is_some() && self.saves_game() {
+            buttons.push("Continue");
+        }
+        #[cfg(feature = "multiplayer")]
+        let chat = match self.chat.unread() {
+            0 => tr("Chat").to_owned(),
+            unread => tr_with("Chat ({0})", &[&unread]),
+        };
+        #[cfg(feature = "multiplayer")]
+        if self.multiplayer.is_some() {
+            buttons.push(&chat);
+            if self.lobby.is_none() {
+                buttons.push("Leave");
+            }
+        }
+        let toolbar = Rect::new(
+            0.0,
+            self.size.height as f32 - button_height - 2.0 * spacing,
+            buttons.len() as f32 * (button_width + spacing) + spacing,
+            button_height + 2.0 * spacing,
+        );
+        self.ui.panel(&mut self.text, toolbar);
+
+        let first = toolbar.inset(spacing);
+        let mut clicked = None;
+        for (index, label) in buttons.into_iter().enumerate() {
+            let rect = Rect::new(
+                first.x + index as f32 * (button_width + spacing),
+                first.y,
+                button_width,
+                button_height,
+            );
+            if self
+                .ui
+                .button_with_id(&mut self.text, label, tr(label), rect)
+            {
+                clicked = Some(label);
+            }
+        }
+
+        let mut action = None;
+        let mut replay_control = None;
+        if let Some(replay) = &self.replay {
+            replay_control = replay.draw(
+                &mut self.ui,
+                &mut self.text,
+                self.size.width as f32,
+                toolbar.y,
+                scale,
+            );
+        } else if !self.actions.is_empty() {
+            let width = self.actions.len() as f32 * (button_width + spacing) - spacing;
+            let first = Rect::new(
+                (self.size.width as f32 - width) / 2.0,
+                toolbar.y + spacing,
+                button_width,
+                button_height,
+            );
+            for (index, label) in self.actions.iter().enumerate() {
+                let rect = Rect {
+                    x: first.x + index as f32 * (button_width + spacing),
+                    ..first
+                };
+                if self
+                    .ui
+                    .button_with_id(&mut self.text, label, tr(label), rect)
+                {
+                    action = Some(index);
+                }
+            }
+        }
+
+        let mut choice = None;
+        let online = self.online();
+        if let (true, Some(end_screen)) = (self.game_over, &self.end_screen) {
+            choice = end_screen.draw(
+                &mut self.ui,
+                &mut self.text,
+                online,
+                self.size.width as f32,
+                toolbar.y,
+                scale,
+            );
+        }
+
+        let mut close_stats = false;
+        if self.show_stats {
+            close_stats = stats_screen::draw(
+                &mut self.ui,
+                &mut self.text,
+                &self.stats,
+                self.size.width as f32,
+                toolbar.y,
+                scale,
+            );
+        }
+
+        #[cfg(feature = "multiplayer")]
+        let mut request = None;
+        #[cfg(feature = "multiplayer")]
+        if let Some(lobby) = &mut self.lobby {
+            request = lobby.draw(
+                &mut self.ui,
+                &mut self.text,
+                self.size.width as f32,
+                toolbar.y,
+                scale,
+            );
+        }
+
+        #[cfg(feature = "multiplayer")]
+        if self.chat.is_open() {
+            let [x, y] = self.chat.draw(
+                &mut self.ui,
+                &mut self.text,
+                self.size.width as f32,
+                toolbar.y,
+                scale,
+            );
+            self.window.set_ime_position(PhysicalPosition::new(x, y));
+        }
+
+        let mut export = false;
+        if self.show_move_log {
+            export = move_log::draw(
+                &mut self.ui,
+                &mut self.text,
+                self.game.moves(),
+                &mut self.move_log_scroll,
+                toolbar.y,
+                scale,
+            );
+        }
+
+        if let Some(status) = &self.game_status {
+            hud::draw(
+                &mut self.ui,
+                &mut self.text,
+                status,
+                self.size.width as f32,
+                scale,
+            );
+        }
+
+        if self.show_debug_overlay {
+            let text = diagnostics::overlay_text(
+                &self.adapter_info,
+                self.frame_timer.average(),
+                self.draw_stats,
+            );
+            let size = DEBUG_TEXT_SIZE * scale;
+            let [width, height] = TextBrush::measure(&text, size);
+            let panel = Rect::new(0.0, 0.0, width + 2.0 * spacing, height + 2.0 * spacing);
+            self.ui.panel(&mut self.text, panel);
+            self.ui
+                .label(&mut self.text, &text, [spacing, spacing], size);
+        }
+
+        match clicked {
+            Some("Menu") => self.scenes.push(Scene::Pause),
+            Some("Theme") => self.next_theme(),
+            Some("Trumps") => self.next_trump_mode(),
+            Some("Filter") => self.toggle_filter(),
+            Some("Moves") => self.show_move_log = !self.show_move_log,
+            Some("Stats") => self.show_stats = !self.show_stats,
+            Some("Undo") => self.undo(),
+            Some("Redo") => self.redo(),
+            Some("Hint") => self.show_hint(),
+            Some("Continue") => self.resume(),
+            #[cfg(feature = "multiplayer")]
+            Some("Leave") => self.leave_network_game(),
+            #[cfg(feature = "multiplayer")]
+            Some(label) if label == chat => self.toggle_chat(),
+            _ => {}
+        }
+
+        if let Some(index) = action {
+            self.game.act(index);
+            self.apply_game_commands();
+        }
+        if let Some(control) = replay_control {
+            self.control_replay(control);
+        }
+        if export {
+            self.export_record();
+        }
+        if close_stats {
+            self.show_stats = false;
+        }
+        match choice {
+            Some(end_screen::Choice::DealAgain) => self.deal_again(),
+            Some(end_screen::Choice::Lobby) => {
+                self.end_screen = None;
+                #[cfg(feature = "multiplayer")]
+                self.leave_network_game();
+            }
+            Some(end_screen::Choice::Close) => self.close_end_screen(),
+            None => {}
+        }
+        #[cfg(feature = "multiplayer")]
+        if let (Some(request), Some(multiplayer)) = (request, &mut self.multiplayer) {
+            multiplayer.send(request);
+        }
+
+        self.ui.end_frame();
+    }
+
+    /// Builds the widgets for the screen the player's on outside a game: its buttons, or the
+    /// statistics over them while they're open.
+    fn build_menu_ui(&mut self) {
+        let scale = self.window.scale_factor() as f32;
+        let window = [self.size.width as f32, self.size.height as f32];
+
+        let mut choice = None;
+        let mut change = None;
+        let mut keys_choice = None;
+        let mut puzzle_choice = None;
+        let mut look_choice = None;
+        if self.scenes.current() == Scene::Keys {
+            keys_choice = keys_screen::draw(
+                &mut self.ui,
+                &mut self.text,
+                &self.keymap,
+                self.rebinding,
+                window,
+                scale,
+            );
+        } else if self.scenes.current() == Scene::Settings {
+            let reduced_motion = self.reduced_motion();
+            change = settings_screen::draw(
+                &mut self.ui,
+                &mut self.text,
+                &mut self.user_config.settings,
+                &self.theme_name,
+                reduced_motion,
+                window,
+                scale,
+            );
+        } else if self.scenes.current() == Scene::Look {
+            let settings = &mut self.user_config.settings;
+            let look = settings.look;
+            look_choice = look_screen::draw(
+                &mut self.ui,
+                &mut self.text,
+                &mut settings.look,
+                window,
+                scale,
+            );
+            if settings.look != look {
+                self.background.set_look(&self.queue, &settings.look);
+            }
+        } else if self.scenes.current() == Scene::HandOff {
+            if let Some((code, link)) = &self.handoff {
+                if handoff_screen::draw(&mut self.ui, &mut self.text, code, link, window, scale) {
+                    self.scenes.back();
+                }
+            }
+        } else if self.scenes.current() == Scene::Puzzles {
+            puzzle_choice = puzzle_screen::draw(
+                &mut self.ui,
+                &mut self.text,
+                &self.puzzles,
+                &self.progress,
+                window,
+                scale,
+            );
+        } else if self.show_stats {
+            if stats_screen::draw(
+                &mut self.ui,
+                &mut self.text,
+                &self.stats,
+                window[0],
+                window[1],
+                scale,
+            ) {
+                self.show_stats = false;
+            }
+        } else {
+            let options = MenuOptions {
+                games: GAMES,
+                can_continue: self.saved_game.is_some(),
+                puzzles: !self.puzzles.is_empty(),
+                daily: self.daily,
+                online: self.online(),
+                hand_off: self.saves_game(),
+            };
+            choice = self
+                .scenes
+                .draw(&mut self.ui, &mut self.text, &options, window, scale);
+        }
+
+        match choice {
+            Some(scene::Choice::Play) => self.scenes.push(Scene::GameSelect),
+            Some(scene::Choice::Continue) => self.resume(),
+            Some(scene::Choice::Puzzles) => self.scenes.push(Scene::Puzzles),
+            Some(scene::Choice::Stats) => self.show_stats = true,
+            Some(scene::Choice::Settings) => self.scenes.push(Scene::Settings),
+            Some(scene::Choice::Pick(index)) => self.deal(GAMES[index].to_owned()),
+            Some(scene::Choice::Daily) => self.daily = !self.daily,
+            Some(scene::Choice::Back) => {
+                self.scenes.back();
+            }
+            Some(scene::Choice::Restart) => self.deal_again(),
+            Some(scene::Choice::HandOff) => self.hand_off(),
+            Some(scene::Choice::Menu) => self.quit_to_menu(),
+            Some(scene::Choice::SaveAndQuit) => {
+                self.save_game();
+                self.exit_requested = true;
+            }
+            Some(scene::Choice::QuitWithoutSaving) => {
+                self.save_on_exit = false;
+                if let Err(e) = save::clear() {
+                    error!("{e:?}");
+                }
+                self.exit_requested = true;
+            }
+            None => {}
+        }
+        match change {
+            Some(settings_screen::Change::Vsync) => {
+                let settings = &mut self.user_config.settings;
+                settings.vsync = !settings.vsync;
+                self.apply_settings();
+            }
+            Some(settings_screen::Change::Language) => {
+                self.user_config.settings.language = Some(locale::language().next());
+                self.apply_settings();
+            }
+            Some(settings_screen::Change::Theme) => {
+                self.next_theme();
+                self.user_config.settings.theme = Some(self.theme_name.clone());
+            }
+            Some(settings_screen::Change::Look) => self.scenes.push(Scene::Look),
+            Some(settings_screen::Change::FourColour) => {
+                let settings = &mut self.user_config.settings;
+                settings.four_colour_suits = !settings.four_colour_suits;
+                self.use_card_style();
+            }
+            Some(settings_screen::Change::SuitGlyphs) => {
+                let settings = &mut self.user_config.settings;
+                settings.suit_glyphs = !settings.suit_glyphs;
+                self.use_card_style();
+            }
+            Some(settings_screen::Change::ReducedMotion) => {
+                let reduced_motion = self.reduced_motion();
+                self.user_config.settings.reduced_motion = Some(!reduced_motion);
+                self.apply_settings();
+            }
+            Some(settings_screen::Change::PlayableGlow) => {
+                let settings = &mut self.user_config.settings;
+                settings.playable_glow = !settings.playable_glow;
+                self.apply_settings();
+            }
+            Some(settings_screen::Change::AutoCamera) => {
+                let settings = &mut self.user_config.settings;
+                settings.auto_camera = !settings.auto_camera;
+                self.apply_settings();
+                if self.user_config.settings.auto_camera {
+                    self.frame_table(true);
+                }
+            }
+            Some(settings_screen::Change::LargeCards) => {
+                let settings = &mut self.user_config.settings;
+                settings.large_cards = !settings.large_cards;
+                self.apply_settings();
+            }
+            Some(settings_screen::Change::Keys) => self.scenes.push(Scene::Keys),
+            Some(settings_screen::Change::Back) => self.leave_settings(),
+            None => {}
+        }
+        match look_choice {
+            Some(look_screen::Choice::Save) => self.save_theme(),
+            Some(look_screen::Choice::Back) => {
+                self.scenes.back();
+            }
+            None => {}
+        }
+        match puzzle_choice {
+            Some(puzzle_screen::Choice::Play(index)) => self.start_puzzle(index),
+            Some(puzzle_screen::Choice::Back) => {
+                self.scenes.back();
+            }
+            None => {}
+        }
+        match keys_choice {
+            Some(keys_screen::Choice::Rebind(action)) => self.rebinding = Some(action),
+            Some(keys_screen::Choice::Reset) => {
+                self.keymap = Keymap::new(&Default::default());
+                self.save_keys();
+            }
+            Some(keys_screen::Choice::Back) => {
+                self.rebinding = None;
+                self.scenes.back();
+            }
+            None => {}
+        }
+
+        self.ui.end_frame();
+    }
+
+    /// Goes back from the settings screen, writing back what was changed there.
+    fn leave_settings(&mut self) {
+        if let Err(e) = self.user_config.store() {
+            error!("{e:?}");
+        }
+        self.scenes.back();
+    }
+
+    /// Brings the language, the theme, the suit marks, the size of the cards, the camera's motion,
+    /// the keys and how frames are presented in line with the settings, after they've changed.
+    /// The rest are looked up as they're needed.
+    fn apply_settings(&mut self) {
+        let settings = &self.user_config.settings;
+        locale::set_language(settings.language.unwrap_or_else(Language::system));
+        self.keymap = Keymap::new(&settings.keys);
+        let mode = present_mode(settings.vsync, &self.present_modes);
+        if mode != self.config.present_mode {
+            info!("presenting frames with {mode:?}");
+            self.config.present_mode = mode;
+            self.surface.configure(&self.device, &self.config);
+        }
+
+        let theme = settings.theme.clone();
+        if let Some(theme) = theme.filter(|theme| *theme != self.theme_name) {
+            if let Err(e) = self.set_theme(&theme) {
+                error!("{e:?}");
+            }
+        }
+        self.background
+            .set_look(&self.queue, &self.user_config.settings.look);
+        self.use_card_style();
+        self.use_reduced_motion();
+
+        let scale = card_scale(&self.user_config.settings);
+        if scale != self.card_scale {
+            self.set_card_scale(scale);
+        }
+        self.mark_playable();
+        if self.reduced_motion() {
+            self.fast_forward();
+        }
+    }
+
+    /// Makes the cards the game says the player can play glow, if that's wanted, or stops them.
+    fn mark_playable(&mut self) {
+        let glow = self.user_config.settings.playable_glow;
+        for (card, location) in self.cards.iter_mut().zip(&self.locations) {
+            let view = self
+                .piles
+                .get(location.pile)
+                .and_then(|pile| pile.cards.get(location.index?));
+            card.playable = glow && view.is_some_and(|view| view.playable);
+        }
+        if let Err(e) = self.write_instances() {
+            error!("{e:?}");
+        }
+    }
+
+    /// Feeds a presented frame to the quality benchmark, stepping the preset down if the device
+    /// can't keep up with it.
+    fn benchmark_frame(&mut self) {
+        let Some(benchmark) = &mut self.quality_benchmark else {
+            return;
+        };
+        let Some((average, sustainable)) = benchmark.record_frame() else {
+            return;
+        };
+
+        info!("{:?} quality averaged {average:?} per frame", self.quality);
+
+        match self.quality.lower() {
+            Some(lower) if !sustainable => {
+                self.set_quality(lower);
+                self.quality_benchmark = Some(QualityBenchmark::new());
+            }
+            _ => self.quality_benchmark = None,
+        }
+    }
+
+    /// Runs the cleanup that has to happen before the process goes away, whichever way the app is
+    /// closed: the game, unless the player chose not to keep it, the settings, statistics and
+    /// puzzle progress are written out, the server is told the player's going, the sound stops
+    /// and the log is flushed. Whether to keep a game in the middle of being played has already
+    /// been asked, by `request_exit`.
+    pub fn shutdown(&mut self) {
+        info!("shutting down");
+
+        // Let in-flight frames and uploads finish so the device, and any API trace being
+        // recorded, are torn down cleanly.
+        self.device.poll(Maintain::Wait);
+
+        // Keep the time played up to date, which isn't saved move by move.
+        if self.save_on_exit && self.saves_game() && !self.game.moves().is_empty() {
+            self.save_game();
+        }
+        let stored = [
+            self.user_config.store(),
+            save::store_stats(&self.stats),
+            save::store_progress(&self.progress),
+        ];
+        for e in stored.into_iter().filter_map(Result::err) {
+            error!("{e:?}");
+        }
+
+        // The seat goes to the computer now, rather than being held for a player who isn't
+        // coming back.
+        #[cfg(feature = "multiplayer")]
+        if let Some(multiplayer) = self.multiplayer.take() {
+            multiplayer.close();
+        }
+
+        self.audio.stop();
+        log::logger().flush();
+    }
+
+    /// Gathers a bug report, shows what it contains, and saves it (native) or opens a prefilled
+    /// issue (web).
+    fn report_problem(&self) {
+        let report = BugReport::gather(&self.adapter_info, self.quality);
+        info!("bug report contents:\n{}", report.to_text());
+
+        #[cfg(not(target_arch = "wasm32"))]
+        match report.save() {
+            Ok(path) => info!(
+                "saved bug report to {}; file it at {}",
+                path.display(),
+                report.issue_url()
+            ),
+            Err(e) => error!("{e:?}"),
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        if let Err(e) = report.open_issue() {
+            error!("{e:?}");
+        }
+    }
+
+    /// Asks the service worker to cache everything the web build needs, including any custom deck,
+    /// so it works offline. Native builds have nothing to download.
+    fn download_for_offline(&self) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let urls = crate::sheet::deck_url()
+                .into_iter()
+                .chain(crate::background::table_url())
+                .collect::<Vec<_>>();
+            match crate::offline::precache(&urls) {
+                Ok(()) => info!("downloading assets for offline play"),
+                Err(e) => error!("{e:?}"),
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        info!("native builds already work offline");
+    }
+
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        if is_press(event) {
+            self.audio.resume();
+        }
+
+        // Widgets are over the table, so they get the first look at the mouse.
+        if self.ui.input(event) {
+            return true;
+        }
+
+        // The chat takes the keyboard while it's open.
+        #[cfg(feature = "multiplayer")]
+        match self.chat.input(event) {
+            ChatInput::Ignored => {}
+            ChatInput::Taken => return true,
+            ChatInput::Say(text) => {
+                if let Some(multiplayer) = &mut self.multiplayer {
+                    multiplayer.send(ClientMessage::Say(text));
+                }
+                return true;
+            }
+            ChatInput::Close => {
+                self.toggle_chat();
+                return true;
+            }
+        }
+
+        if let WindowEvent::ModifiersChanged(modifiers) = event {
+            self.modifiers = *modifiers;
+            return false;
+        }
+
+        if let WindowEvent::KeyboardInput {
+            input:
+                KeyboardInput {
+                    state,
+                    virtual_keycode: Some(key),
+                    ..
+                },
+            ..
+        } = event
+        {
+            let pressed = *state == ElementState::Pressed;
+            if let Some(action) = self.rebinding.filter(|_| pressed) {
+                return self.rebind(action, *key);
+            }
+            // A key standing for several actions does the first that does anything.
+            return self
+                .keymap
+                .actions(*key, self.modifiers)
+                .into_iter()
+                .any(|action| self.act(action, pressed));
+        }
+
+        // Outside a game only the menus take input.
+        if !self.scenes.playing() {
+            return false;
+        }
+
+        match event {
+            WindowEvent::Touch(touch) => {
+                // Two fingers pan and pinch, putting back any cards the first was dragging.
+                if self.camera_controller.process_events(event) {
+                    self.touch = None;
+                    self.cancel_drag();
+                    return true;
+                }
+                self.touch_cards(touch)
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = *position;
+                self.drag_to(*position) || self.camera_controller.process_events(event)
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.pick(self.cursor_position, PointerKind::Mouse);
+                true
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+                ..
+            } => self.drop_cards(),
+            _ => self.camera_controller.process_events(event),
+        }
+    }
+
+    /// Carries out `action`, its key having been pressed or released. Returns whether the key
+    /// did anything.
+    fn act(&mut self, action: Action, pressed: bool) -> bool {
+        if !pressed {
+            return action.is_held() && self.camera_controller.act(action, pressed);
+        }
+
+        match action {
+            Action::Back => self.back(),
+            Action::DebugOverlay => self.show_debug_overlay = !self.show_debug_overlay,
+            Action::ReportProblem => self.report_problem(),
+            Action::DownloadOffline => self.download_for_offline(),
+            Action::NextTheme => self.next_theme(),
+            Action::ToggleFilter => self.toggle_filter(),
+            Action::FocusNext => self.cycle_focus(1),
+            Action::FocusPrevious => self.cycle_focus(-1),
+            // Until something has the focus, the keys that move it are free to do other things.
+            Action::FocusUp | Action::FocusLeft | Action::FocusDown | Action::FocusRight
+                if self.in_menu() || self.focus.is_some() =>
+            {
+                if let Some(direction) = action.focus_direction() {
+                    self.step_focus(direction);
+                }
+            }
+            Action::Select if self.in_menu() || self.focus.is_some() => self.select_focus(),
+            // The rest are for the game, so wait until there is one.
+            _ if !self.scenes.playing() => return false,
+            Action::NextTrumps => self.next_trump_mode(),
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::Hint => self.show_hint(),
+            Action::SkipAnimation => self.fast_forward(),
+            Action::PlayPause if self.replay.is_some() => {
+                self.control_replay(replay::Control::TogglePlay)
+            }
+            #[cfg(feature = "multiplayer")]
+            Action::Chat if self.multiplayer.is_some() => self.toggle_chat(),
+            Action::PlayPause | Action::Chat => return false,
+            _ => return self.camera_controller.act(action, pressed),
+        }
+        true
+    }
+
+    /// Whether the keys and controllers are moving between buttons rather than over the table.
+    fn in_menu(&self) -> bool {
+        !self.scenes.playing() || self.show_stats || self.end_screen.is_some()
+    }
+
+    /// Moves the focus `step` places on, wrapping around: between the buttons on a menu, or
+    /// between the places on the table in a game.
+    fn cycle_focus(&mut self, step: isize) {
+        match self.in_menu() {
+            true => self.ui.move_focus(step),
+            false => {
+                self.focus = focus::cycle(&self.piles, self.focus, step);
+                self.describe_focus();
+            }
+        }
+    }
+
+    /// Moves the focus `direction`: up and left to the button before on a menu, and down and right
+    /// to the one after, or to the nearest place that way on the table in a game.
+    fn step_focus(&mut self, direction: Direction) {
+        if !self.in_menu() {
+            self.focus = focus::step(&self.piles, self.focus, direction, self.card_scale);
+            self.describe_focus();
+            return;
+        }
+        match direction {
+            Direction::Up | Direction::Left => self.ui.move_focus(-1),
+            Direction::Down | Direction::Right => self.ui.move_focus(1),
+        }
+    }
+
+    /// Tells screen readers which card or pile has the focus.
+    fn describe_focus(&mut self) {
+        let Some(at) = self.focus else {
+            return;
+        };
+        let Some(pile) = self.piles.get(at.pile) else {
+            return;
+        };
+        let description = match at.index.and_then(|index| pile.cards.get(index)) {
+            Some(card) if card.facedown => tr("Face-down card").to_owned(),
+            Some(card) if card.selected => tr_with(
+                "{0}, picked up",
+                &[&speech::card_name(card.suit, card.rank)],
+            ),
+            Some(card) => speech::card_name(card.suit, card.rank),
+            None => tr("Empty pile").to_owned(),
+        };
+        self.speech
+            .describe_focus(&tr_with("{0}, pile {1}", &[&description, &(at.pile + 1)]));
+    }
+
+    /// Presses the button with the focus on a menu, or clicks the place with it on the table.
+    fn select_focus(&mut self) {
+        if self.in_menu() {
+            self.ui.activate();
+            return;
+        }
+        // The cards of a replay are only moved by the replay.
+        if self.replay.is_none() {
+            self.game.click(self.focus);
+            self.apply_game_commands();
+        }
+    }
+
+    /// Goes back a screen, or in a game pauses it. On the menu, closes the window.
+    fn back(&mut self) {
+        match self.scenes.current() {
+            _ if self.show_stats => self.show_stats = false,
+            Scene::Settings => self.leave_settings(),
+            Scene::InGame => self.scenes.push(Scene::Pause),
+            _ => {
+                if !self.scenes.back() {
+                    self.request_exit();
+                }
+            }
+        }
+    }
+
+    /// Closes the window, first asking whether to keep the game if one's in the middle of being
+    /// played. Asking again while that's being asked closes it straight away.
+    pub fn request_exit(&mut self) {
+        let unfinished = self.saves_game() && !self.game_over && !self.game.moves().is_empty();
+        if unfinished && self.scenes.in_game() && self.scenes.current() != Scene::Quit {
+            self.scenes.push(Scene::Quit);
+        } else {
+            self.exit_requested = true;
+        }
+    }
+
+    /// Whether the player has asked to close the window.
+    pub fn exit_requested(&self) -> bool {
+        self.exit_requested
+    }
+
+    /// Binds `key`, with the modifiers held, to `action` in place of its keys, and saves it. A
+    /// modifier key on its own is waited past, and Escape cancels. Returns whether the key was
+    /// taken.
+    fn rebind(&mut self, action: Action, key: VirtualKeyCode) -> bool {
+        if key == VirtualKeyCode::Escape {
+            self.rebinding = None;
+            return true;
+        }
+        if !Binding::can_bind(key) {
+            return false;
+        }
+
+        let binding = Binding::new(key, self.modifiers);
+        info!("binding {} to {}", binding.name(), action.name());
+        self.keymap.rebind(action, binding);
+        self.rebinding = None;
+        self.save_keys();
+        true
+    }
+
+    /// Keeps the key bindings in the settings, and writes them back.
+    fn save_keys(&mut self) {
+        self.user_config.settings.keys = self.keymap.to_settings();
+        if let Err(e) = self.user_config.store() {
+            error!("{e:?}");
+        }
+    }
+
+    fn pick(&mut self, position: PhysicalPosition<f64>, pointer: PointerKind) {
+        // The cards of a replay are only moved by the replay.
+        if self.replay.is_some() {
+            return;
+        }
+
+        // Pointing at the table puts the focus away until the keys move it again.
+        self.focus = None;
+
+        let point = self.camera.screen_to_world(position);
+        let slop = pointer.hit_slop() / self.camera.zoom;
+        self.pressed_card = picking::pick(&self.cards, point, slop);
+
+        info!("picked card {:?} with {pointer:?}", self.pressed_card);
+
+        // A click that misses every card can still land on an empty pile.
+        let location = match self.pressed_card {
+            Some(card_id) => Some(self.locations[card_id]),
+            None => self
+                .piles
+                .iter()
+                .position(|pile| layout::pile_bounds(pile, self.card_scale).contains(point))
+                .map(Location::pile),
+        };
+
+        // A second click on a card sends it on wherever the game thinks best, if anywhere.
+        let now = Instant::now();
+        let double_click = self
+            .last_click
+            .is_some_and(|(time, last)| location == Some(last) && now - time < DOUBLE_CLICK_TIME);
+        self.last_click = location.map(|location| (now, location));
+        let card = location.filter(|location| location.index.is_some());
+        if let Some(card) = card.filter(|_| double_click) {
+            if self.game.auto_move(card) {
+                self.last_click = None;
+                self.apply_game_commands();
+                return;
+            }
+        }
+
+        self.game.click(location);
+        self.apply_game_commands();
+        self.start_drag(location, position);
+    }
+
+    /// Lets the cards the game has just picked up at `location` be dragged, along with any on top
+    /// of them, if it has zones to drop them on.
+    fn start_drag(&mut self, location: Option<Location>, position: PhysicalPosition<f64>) {
+        self.drag = None;
+        let Some(from) = location.filter(|location| location.index.is_some()) else {
+            return;
+        };
+        let picked_up = self
+            .locations
+            .iter()
+            .position(|&location| location == from)
+            .is_some_and(|card| self.cards[card].selected);
+        if !picked_up || self.zones.is_empty() {
+            return;
+        }
+
+        let point = self.camera.screen_to_world(position);
+        let cards = self
+            .locations
+            .iter()
+            .enumerate()
+            .filter(|(_, location)| location.pile == from.pile && location.index >= from.index)
+            .map(|(card, _)| {
+                let position = self.cards[card].position;
+                let offset =
+                    cgmath::Vector2::new(position.x as f32 - point.x, position.y as f32 - point.y);
+                (card, offset)
+            })
+            .collect();
+        let allowed = self
+            .zones
+            .iter()
+            .map(|zone| zone.pile)
+            .filter(|&pile| self.game.can_drop(from, pile))
+            .collect();
+        self.drag = Some(Drag::new(from, cards, allowed, position, point));
+    }
+
+    /// Carries the cards being dragged to under the pointer at `position`, returning whether
+    /// they're following it.
+    fn drag_to(&mut self, position: PhysicalPosition<f64>) -> bool {
+        let Some(drag) = &mut self.drag else {
+            return false;
+        };
+        if !drag.follow(position) {
+            return false;
+        }
+
+        let point = self.camera.screen_to_world(position);
+        drag.track(point, Instant::now());
+        self.timeline.release(|card| drag.carries(card));
+        self.carry_dragged_cards(Duration::ZERO);
+        true
+    }
+
+    /// Carries the cards being dragged on for `dt` after the pointer, trailing it on a spring and
+    /// tipping as they swing sideways, or right under it if less motion is asked for.
+    fn carry_dragged_cards(&mut self, dt: Duration) {
+        let reduced_motion = self.reduced_motion();
+        let Some(drag) = self.drag.as_mut().filter(|drag| drag.moving) else {
+            return;
+        };
+
+        let target = self.camera.screen_to_world(drag.pointer);
+        let tilt = match reduced_motion {
+            true => {
+                drag.hold_at(target);
+                0.0
+            }
+            false => drag.pull(target, dt),
+        };
+        let held = drag.held();
+        for &(card, offset) in &drag.cards {
+            let card = &mut self.cards[card];
+            card.position =
+                cgmath::Vector3::new((held.x + offset.x) as i32, (held.y + offset.y) as i32, 0);
+            card.rotation = cgmath::Rad(tilt);
+            card.elevation = 1.0;
+        }
+
+        if let Err(e) = self.write_instances() {
+            error!("{e:?}");
+        }
+    }
+
+    /// Picks cards up with a finger on the table, and drags and drops them as it moves and lifts.
+    /// Fingers that come down while another is on the cards are ignored.
+    fn touch_cards(&mut self, touch: &Touch) -> bool {
+        if let Some(position) = picking::touch_pick_position(touch) {
+            if self.touch.is_some() {
+                return false;
+            }
+            self.touch = Some(touch.id);
+            self.pick(position, PointerKind::Touch);
+            return true;
+        }
+        if self.touch != Some(touch.id) {
+            return false;
+        }
+
+        match touch.phase {
+            TouchPhase::Moved => self.drag_to(touch.location),
+            TouchPhase::Cancelled => {
+                self.touch = None;
+                self.cancel_drag()
+            }
+            _ => {
+                self.touch = None;
+                self.drop_cards()
+            }
+        }
+    }
+
+    /// Sends the cards being dragged back where they came from, still picked up. Returns whether
+    /// cards were being dragged.
+    fn cancel_drag(&mut self) -> bool {
+        if !self.drag.take().is_some_and(|drag| drag.moving) {
+            return false;
+        }
+        let piles = std::mem::take(&mut self.piles);
+        self.set_table(piles);
+        true
+    }
+
+    /// Drops the cards being dragged on the zone under the pointer, making the move that drop
+    /// makes, if any. Cards dropped anywhere else go back where they came from, and cards let go
+    /// of quickly enough are tossed, to land where they settle. Returns whether cards were being
+    /// dragged.
+    fn drop_cards(&mut self) -> bool {
+        let Some(drag) = self.drag.take().filter(|drag| drag.moving) else {
+            return false;
+        };
+
+        let point = self.camera.screen_to_world(drag.pointer);
+        let velocity = drag.release_velocity(Instant::now());
+        if Toss::is_toss(velocity) && !self.reduced_motion() {
+            if let Some(view) = self.view_bounds() {
+                let toss = Toss::new(drag.cards.clone(), drag.held(), velocity, view);
+                self.toss = Some((toss, drag));
+                return true;
+            }
+        }
+        self.land_cards(&drag, point, false);
+        true
+    }
+
+    /// Puts the cards of `drag` down at `point`, making the move a drop on the zone there makes,
+    /// if any, or if they were `tossed`, a drop on the nearest zone they can go on within reach.
+    /// Cards put down anywhere else go back where they came from.
+    fn land_cards(&mut self, drag: &Drag, point: cgmath::Point2<f32>, tossed: bool) {
+        let zones = DropZone::lay_out(&self.zones, &self.piles, self.card_scale);
+        let reach = TOSS_REACH * card::HEIGHT as f32 * self.card_scale;
+        let target = drag
+            .target(&zones, point)
+            .or_else(|| {
+                tossed
+                    .then(|| drag.nearest_allowed(&zones, point, reach))
+                    .flatten()
+            })
+            .map(|zone| Location::pile(zone.zone.pile));
+
+        // The cards set off back to their pile from where they were dropped, and on from there to
+        // wherever the move takes them.
+        let piles = std::mem::take(&mut self.piles);
+        self.set_table(piles);
+        self.game.click(target);
+        self.apply_game_commands();
+    }
+
+    /// The part of the table in view.
+    fn view_bounds(&self) -> Option<Bounds> {
+        let corners = [
+            PhysicalPosition::new(0.0, 0.0),
+            PhysicalPosition::new(self.size.width as f64, self.size.height as f64),
+        ];
+        Bounds::from_points(corners.map(|corner| self.camera.screen_to_world(corner)))
+    }
+
+    pub fn update(&mut self) {
+        let now = Instant::now();
+        let dt = now - self.last_update;
+        self.last_update = now;
+        self.audio.set_volumes(&self.user_config.settings.audio);
+
+        match &mut self.replay {
+            Some(replay) => {
+                if replay.advance(dt) {
+                    self.sync_replay();
+                }
+            }
+            None => {
+                #[cfg(feature = "multiplayer")]
+                self.poll_multiplayer();
+                // The game stands still while it's paused, or left for the menu.
+                if self.scenes.playing() && !self.awaiting_deal {
+                    self.game.update(dt);
+                    self.apply_game_commands();
+                }
+            }
+        }
+
+        if !self.timeline.is_empty() {
+            self.advance_timeline();
+            if let Err(e) = self.write_instances() {
+                error!("{e:?}");
+            }
+        } else if std::mem::take(&mut self.celebrate) && !self.reduced_motion() {
+            if let Some(view) = self.view_bounds() {
+                self.cascade = Some(Cascade::new(&self.cards, view));
+                // Confetti is thrown from along the top of the view, to flutter down over it.
+                for index in 0..CONFETTI_BURSTS {
+                    let across = (index as f32 + 0.5) / CONFETTI_BURSTS as f32;
+                    let at = cgmath::Point2::new(
+                        view.min.x + across * (view.max.x - view.min.x),
+                        view.max.y,
+                    );
+                    self.particles
+                        .emit(Emitter::Confetti, at, CONFETTI_INTERVAL * index as u32);
+                }
+            }
+        }
+
+        if let Some(cascade) = &mut self.cascade {
+            if !cascade.advance(dt, &mut self.cards) {
+                self.cascade = None;
+            }
+            if let Err(e) = self.write_instances() {
+                error!("{e:?}");
+            }
+        }
+
+        self.carry_dragged_cards(dt);
+        if let Some((toss, _)) = &mut self.toss {
+            if !toss.advance(dt, &mut self.cards) {
+                if let Some((toss, drag)) = self.toss.take() {
+                    self.land_cards(&drag, toss.position(), true);
+                }
+            }
+            if let Err(e) = self.write_instances() {
+                error!("{e:?}");
+            }
+        }
+        self.particles.update(dt);
+
+        #[cfg(feature = "gamepad")]
+        self.poll_gamepads();
+
+        self.camera_controller.update_camera(&mut self.camera, dt);
+        self.camera.update(dt);
         self.camera_uniform.update_view_proj(&self.camera);
         self.queue
             .write_buffer(&self.camera_buffer, 0, cast_slice(&[self.camera_uniform]));
+        let frame = FrameUniform {
+            time: (now - self.started).as_secs_f32() % FRAME_TIME_WRAP,
+            motion: if self.reduced_motion() { 0.0 } else { 1.0 },
+            shadow_softness: self.user_config.settings.look.shadow_softness,
+            _padding: 0.0,
+            highlight: highlight(&self.user_config.settings.look),
+        };
+        self.queue
+            .write_buffer(&self.frame_buffer, 0, cast_slice(&[frame]));
+
+        if self.user_config.reload() {
+            self.apply_settings();
+        }
+
+        self.speech.flush();
+        self.pulse_hint();
+        self.update_hover();
+    }
+
+    /// Asks the game for a move to suggest, and points it out.
+    fn show_hint(&mut self) {
+        if self.replay.is_some() {
+            return;
+        }
+        match self.game.hint() {
+            Some(hint) => self.hint = Some((hint, Instant::now())),
+            None => self.notify(tr("No hint").to_owned()),
+        }
+    }
+
+    /// Where the current hint points: the cards it points at, by index on the table, and the
+    /// empty piles.
+    fn hint_targets(&self) -> (Vec<usize>, Vec<usize>) {
+        let Some((hint, _)) = self.hint else {
+            return (Vec::new(), Vec::new());
+        };
+
+        let mut cards = Vec::new();
+        let mut piles = Vec::new();
+        for location in [Some(hint.from), hint.to].into_iter().flatten() {
+            // A card brings those on top of it along, and a pile is pointed at by its top card.
+            let mut pointed = self
+                .locations
+                .iter()
+                .enumerate()
+                .filter(|(_, at)| at.pile == location.pile)
+                .filter(|(_, at)| at.index >= location.index)
+                .map(|(card, _)| card);
+            let pointed = match location.index {
+                Some(_) => pointed.collect::<Vec<_>>(),
+                None => pointed.next_back().into_iter().collect(),
+            };
+            if pointed.is_empty() {
+                piles.push(location.pile);
+            }
+            cards.extend(pointed);
+        }
+        (cards, piles)
+    }
+
+    /// How brightly the current hint glows by now, pulsing from nothing to full and back, or
+    /// glowing steadily with less motion asked for.
+    fn hint_glow(&self) -> f32 {
+        let Some((_, shown)) = self.hint else {
+            return 0.0;
+        };
+        if self.reduced_motion() {
+            return 1.0;
+        }
+        let pulses = shown.elapsed().as_secs_f32() / HINT_PULSE.as_secs_f32();
+        0.5 - 0.5 * (std::f32::consts::TAU * pulses).cos()
+    }
+
+    /// Brightens the cards the hint points at as far as it glows by now, putting them back once
+    /// it's been shown for long enough.
+    fn pulse_hint(&mut self) {
+        let Some((_, shown)) = self.hint else {
+            return;
+        };
+
+        let (cards, _) = self.hint_targets();
+        let glow = match shown.elapsed() < HINT_DURATION {
+            true => self.hint_glow(),
+            false => {
+                self.hint = None;
+                0.0
+            }
+        };
+        for card in cards {
+            self.cards[card].glow = glow;
+        }
+
+        if let Err(e) = self.write_instances() {
+            error!("{e:?}");
+        }
+    }
+
+    /// Re-picks the card under the mouse, since either the cursor or the camera may have moved.
+    fn update_hover(&mut self) {
+        let point = self.camera.screen_to_world(self.cursor_position);
+        let hovered = picking::pick(&self.cards, point, PointerKind::Mouse.hit_slop());
+
+        if hovered == self.hovered_card {
+            return;
+        }
+
+        if let Some(previous) = self.hovered_card {
+            self.cards[previous].highlighted = false;
+        }
+        if let Some(current) = hovered {
+            self.cards[current].highlighted = true;
+        }
+        self.hovered_card = hovered;
+
+        if let Err(e) = self.write_instances() {
+            error!("{e:?}");
+        }
+    }
+
+    /// The cards in the order they're drawn: those being dragged over the rest.
+    fn draw_order(&self) -> impl Iterator<Item = &Card> + Clone {
+        let carried = |index: &usize| self.drag.as_ref().is_some_and(|drag| drag.carries(*index));
+        (0..self.cards.len())
+            .filter(move |index| !carried(index))
+            .chain((0..self.cards.len()).filter(carried))
+            .map(|index| &self.cards[index])
+    }
+
+    fn write_instances(&self) -> Result<()> {
+        let instance_data = self
+            .draw_order()
+            .map(Card::to_instance)
+            .collect::<Result<Vec<_>>>()?;
+
+        self.queue
+            .write_buffer(&self.instance_buffer, 0, cast_slice(&instance_data));
+        self.badges.write(&self.queue, self.draw_order());
+
+        Ok(())
+    }
+
+    /// Records the draws for one layer of the frame. Each layer sets up its own pipeline, bind
+    /// groups and buffers, so layers don't depend on what was drawn before them.
+    fn draw_layer<'a>(&'a self, layer: RenderLayer, render_pass: &mut RenderPass<'a>) -> DrawStats {
+        match layer {
+            RenderLayer::Background => self.background.draw(render_pass),
+            RenderLayer::Cards => {
+                render_pass.set_bind_group(0, &self.themes.bind_group, &[]);
+                render_pass.set_bind_group(1, &self.camera_bind_group, &[]);
+
+                render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+                render_pass.set_index_buffer(self.index_buffer.slice(..), IndexFormat::Uint16);
+
+                // Each card's shadow is drawn just before it, so it falls on the cards beneath,
+                // and its badge just after it, so the cards above cover the badge too.
+                let mut stats = DrawStats {
+                    draw_calls: 2 * self.cards.len() as u32,
+                    instances: 2 * self.cards.len() as u32,
+                };
+                for (instance, card) in (0..).zip(self.draw_order()) {
+                    render_pass.set_pipeline(&self.shadow_pipeline);
+                    render_pass.draw_indexed(0..self.num_indices, 0, instance..instance + 1);
+                    render_pass.set_pipeline(&self.render_pipeline);
+                    render_pass.draw_indexed(0..self.num_indices, 0, instance..instance + 1);
+                    if card.badge.is_some() {
+                        stats += self.badges.draw(render_pass, instance);
+                        render_pass.set_bind_group(0, &self.themes.bind_group, &[]);
+                        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                    }
+                }
+                stats
+            }
+            RenderLayer::Particles => self.particles.draw(render_pass, &self.camera_bind_group),
+            RenderLayer::Text => {
+                render_pass.set_viewport(
+                    0.0,
+                    0.0,
+                    self.config.width as f32,
+                    self.config.height as f32,
+                    0.0,
+                    1.0,
+                );
+                self.text.draw(render_pass)
+            }
+        }
     }
 
     pub fn render(&mut self) -> core::result::Result<(), SurfaceError> {
+        self.frame_timer.tick();
+        // Labels go first, so the UI is drawn over them.
+        self.queue_labels();
+        self.queue_drop_zone();
+        self.queue_hint_piles();
+        self.queue_focus();
+        self.build_ui();
+        self.queue_notice();
+        self.text.prepare(
+            &self.device,
+            &self.queue,
+            [self.config.width as f32, self.config.height as f32],
+        );
+        self.particles.prepare(&self.device, &self.queue);
+
         let output = self.surface.get_current_texture()?;
         let view = output
             .texture
@@ -411,40 +3854,86 @@ impl State {
                 label: Some("Render Encoder"),
             });
 
+        let mut draw_stats = DrawStats::default();
         {
             let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
                 label: Some("Render Pass"),
                 color_attachments: &[Some(RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
+                    view: self.msaa_view.as_ref().unwrap_or(&view),
+                    resolve_target: self.msaa_view.as_ref().map(|_| &view),
                     ops: Operations {
-                        load: LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
+                        load: LoadOp::Clear(self.background.clear_color),
                         store: true,
                     },
                 })],
-                depth_stencil_attachment: None,
+                depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(Operations {
+                        load: LoadOp::Clear(1.0),
+                        store: false,
+                    }),
+                    stencil_ops: None,
+                }),
             });
 
-            render_pass.set_pipeline(&self.render_pipeline);
-
-            render_pass.set_bind_group(0, &self.diffuse_bind_group, &[]);
-            render_pass.set_bind_group(1, &self.camera_bind_group, &[]);
+            let viewport = self.camera.viewport();
+            render_pass.set_viewport(
+                viewport.x,
+                viewport.y,
+                viewport.width,
+                viewport.height,
+                0.0,
+                1.0,
+            );
 
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
-            render_pass.set_index_buffer(self.index_buffer.slice(..), IndexFormat::Uint16);
-
-            render_pass.draw_indexed(0..self.num_indices, 0, 0..self.cards.len() as _);
+            for layer in RENDER_LAYERS {
+                draw_stats += self.draw_layer(layer, &mut render_pass);
+            }
         }
+        self.draw_stats = draw_stats;
 
         self.queue.submit(std::iter::once(encoder.finish()));
         output.present();
+        // Sound is only got going once there's something on screen, so as not to hold it up.
+        self.audio.start();
+
+        self.benchmark_frame();
 
         Ok(())
     }
 }
+
+#[cfg(feature = "gamepad")]
+impl State {
+    /// Acts on whatever's been done with a controller since the last frame: on the table, moving
+    /// the cursor and clicking where it is, and elsewhere, moving between buttons and pressing
+    /// them.
+    fn poll_gamepads(&mut self) {
+        self.camera_controller.set_stick(self.gamepads.pan());
+
+        let in_menu = self.in_menu();
+        for input in self.gamepads.poll() {
+            match input {
+                PadInput::Move(direction) => self.step_focus(direction),
+                PadInput::Select => self.select_focus(),
+                PadInput::Pause | PadInput::Cancel if in_menu => self.back(),
+                _ if in_menu => {}
+                // The cards of a replay are only moved by the replay.
+                PadInput::Cancel if self.replay.is_some() => {}
+                PadInput::Cancel => {
+                    self.game.click(None);
+                    self.apply_game_commands();
+                }
+                PadInput::Undo => self.undo(),
+                PadInput::Hint => self.show_hint(),
+                PadInput::ZoomIn => {
+                    self.camera_controller.act(Action::ZoomIn, true);
+                }
+                PadInput::ZoomOut => {
+                    self.camera_controller.act(Action::ZoomOut, true);
+                }
+                PadInput::Pause => self.back(),
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! The player's statistics over every run: a panel with a row for each game they've finished one
+//! of and a row for all of them together.
+
+use cards_core::stats::{GameStats, Stats};
+
+use crate::{
+    end_screen::clock,
+    locale::tr,
+    scene::title_case,
+    text::TextBrush,
+    ui::{Rect, Ui, BUTTON_SIZE, SPACING},
+};
+
+/// The headings of the columns, and how wide each is in logical pixels.
+const COLUMNS: [(&str, f32); 7] = [
+    ("Game", 112.0),
+    ("Played", 72.0),
+    ("Won", 72.0),
+    ("Rate", 72.0),
+    ("Best", 72.0),
+    ("Streak", 72.0),
+    ("Longest", 72.0),
+];
+
+/// Shows `stats` in a panel centred across a `window_width` physical pixels wide window, with its
+/// bottom `bottom` pixels down. Returns whether it was closed.
+pub fn draw(
+    ui: &mut Ui,
+    brush: &mut TextBrush,
+    stats: &Stats,
+    window_width: f32,
+    bottom: f32,
+    scale: f32,
+) -> bool {
+    let [button_width, row_height] = BUTTON_SIZE.map(|length| length * scale);
+    let spacing = SPACING * scale;
+    let text_size = row_height * 0.6;
+    let label_offset = (row_height - text_size) / 2.0;
+
+    let mut rows = vec![COLUMNS.map(|(heading, _)| tr(heading).to_owned())];
+    rows.extend(
+        stats
+            .games()
+            .map(|(name, game)| cells(title_case(name), game)),
+    );
+    rows.push(cells(tr("All games").to_owned(), &stats.total()));
+
+    let width = COLUMNS.iter().map(|(_, width)| width * scale).sum::<f32>() + 2.0 * spacing;
+    let height = (rows.len() + 2) as f32 * (row_height + spacing) + spacing;
+    let panel = Rect::new(
+        (window_width - width) / 2.0,
+        ((bottom - height) / 2.0).max(0.0),
+        width,
+        height,
+    );
+    ui.panel(brush, panel);
+    let inner = panel.inset(spacing);
+
+    let row = |index: usize| inner.y + index as f32 * (row_height + spacing);
+    ui.label(
+        brush,
+        tr("Statistics"),
+        [inner.x, row(0) + label_offset],
+        text_size,
+    );
+    for (index, cells) in rows.iter().enumerate() {
+        let mut x = inner.x;
+        for (cell, (_, width)) in cells.iter().zip(COLUMNS) {
+            ui.label(brush, cell, [x, row(index + 1) + label_offset], text_size);
+            x += width * scale;
+        }
+    }
+
+    let close = Rect::new(
+        inner.x + inner.width - button_width,
+        row(rows.len() + 1),
+        button_width,
+        row_height,
+    );
+    ui.button(brush, tr("Close"), close)
+}
+
+/// A row of the table, for the game called `name`.
+fn cells(name: String, stats: &GameStats) -> [String; 7] {
+    [
+        name,
+        stats.played.to_string(),
+        stats.won.to_string(),
+        format!("{:.0}%", stats.win_rate() * 100.0),
+        stats.best.map_or("-".to_owned(), clock),
+        stats.streak.to_string(),
+        stats.longest_streak.to_string(),
+    ]
+}
@@ -0,0 +1,372 @@
+use std::{borrow::Cow, mem::size_of};
+
+use bytemuck::{cast_slice, Pod, Zeroable};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, BlendState, BufferAddress,
+    BufferBindingType, BufferDescriptor, BufferUsages, ColorTargetState, ColorWrites,
+    CompareFunction, DepthStencilState, Device, FilterMode, FragmentState, MultisampleState,
+    PipelineLayoutDescriptor, Queue, RenderPass, RenderPipeline, RenderPipelineDescriptor,
+    SamplerBindingType, ShaderStages, TextureFormat, TextureSampleType, TextureViewDimension,
+    VertexAttribute, VertexBufferLayout, VertexState, VertexStepMode,
+};
+
+use crate::{
+    diagnostics::DrawStats,
+    errors::*,
+    texture::{Texture, TextureOptions},
+};
+
+/// A monospace font rendered from DejaVu Sans Mono: printable ASCII in 16 columns of 8x16 cells,
+/// starting from the space. The last cell is a box, drawn for characters the font doesn't have.
+const FONT: &[u8] = include_bytes!("font.png");
+
+const FIRST_CHAR: u32 = ' ' as u32;
+const MISSING_CHAR: u32 = 127;
+
+/// Letters the font doesn't have, spelled out with ones it does, as German is written without
+/// umlauts.
+const SPELLINGS: [(char, &str); 7] = [
+    ('ä', "ae"),
+    ('ö', "oe"),
+    ('ü', "ue"),
+    ('Ä', "Ae"),
+    ('Ö', "Oe"),
+    ('Ü', "Ue"),
+    ('ß', "ss"),
+];
+
+/// The cell given to filled rectangles, which the shader draws solid instead of sampling the font.
+const SOLID_CELL: u32 = u32::MAX;
+
+/// The width of a glyph cell as a fraction of its height.
+const CELL_ASPECT: f32 = 0.5;
+
+/// Glyphs the instance buffer starts with room for; it grows as needed.
+const INITIAL_CAPACITY: usize = 256;
+
+/// One character on screen, as it's fed to the text shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct Glyph {
+    /// Top-left corner, in physical pixels from the top-left of the window.
+    position: [f32; 2],
+    size: [f32; 2],
+    color: [f32; 4],
+    /// The character's cell in the font atlas, or `SOLID_CELL`.
+    cell: u32,
+}
+
+impl Glyph {
+    const ATTRIBUTES: [VertexAttribute; 4] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4, 3 => Uint32];
+
+    const BUFFER_LAYOUT: VertexBufferLayout<'static> = VertexBufferLayout {
+        array_stride: size_of::<Glyph>() as BufferAddress,
+        step_mode: VertexStepMode::Instance,
+        attributes: &Self::ATTRIBUTES,
+    };
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct ScreenUniform {
+    size: [f32; 2],
+    _padding: [f32; 2],
+}
+
+/// Draws strings, and the flat rectangles behind them, over everything else in the frame. Text is
+/// queued during the frame, uploaded by `prepare`, and drawn by `draw` in the order it was
+/// queued, after which the queue starts again empty.
+pub struct TextBrush {
+    pipeline: RenderPipeline,
+    layout: BindGroupLayout,
+    bind_group: BindGroup,
+    #[allow(dead_code)]
+    font: Texture,
+    screen_buffer: wgpu::Buffer,
+    instance_buffer: wgpu::Buffer,
+    capacity: usize,
+    queued: Vec<Glyph>,
+    prepared: u32,
+}
+
+impl TextBrush {
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        format: TextureFormat,
+        depth_format: TextureFormat,
+        sample_count: u32,
+    ) -> Result<Self> {
+        let image =
+            image::load_from_memory(FONT).chain_err(|| "couldn't decode the built-in font")?;
+        let options = TextureOptions {
+            filter: FilterMode::Linear,
+            ..Default::default()
+        };
+        let font = Texture::from_layers(device, queue, &[&image], Some("font"), &options)?;
+
+        let screen_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Text Screen Buffer"),
+            contents: cast_slice(&[ScreenUniform {
+                size: [1.0, 1.0],
+                _padding: [0.0; 2],
+            }]),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        let layout = create_bind_group_layout(device);
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("text_bind_group"),
+            layout: &layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&font.view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(&font.sampler),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: screen_buffer.as_entire_binding(),
+                },
+            ],
+        });
+        let pipeline = create_pipeline(device, &layout, format, depth_format, sample_count);
+
+        Ok(Self {
+            pipeline,
+            layout,
+            bind_group,
+            font,
+            screen_buffer,
+            instance_buffer: create_instance_buffer(device, INITIAL_CAPACITY),
+            capacity: INITIAL_CAPACITY,
+            queued: Vec::new(),
+            prepared: 0,
+        })
+    }
+
+    /// Rebuilds the pipeline for a new MSAA sample count.
+    pub fn set_sample_count(
+        &mut self,
+        device: &Device,
+        format: TextureFormat,
+        depth_format: TextureFormat,
+        sample_count: u32,
+    ) {
+        self.pipeline = create_pipeline(device, &self.layout, format, depth_format, sample_count);
+    }
+
+    /// Queues a string to be drawn this frame with its top-left corner at `position`, in physical
+    /// pixels. `size` is the height of a line, and `\n` starts a new one.
+    pub fn queue(&mut self, text: &str, position: [f32; 2], size: f32, color: [f32; 4]) {
+        let advance = size * CELL_ASPECT;
+        let text = spelled_out(text);
+
+        for (row, line) in text.lines().enumerate() {
+            let y = position[1] + row as f32 * size;
+
+            for (column, c) in line.chars().enumerate() {
+                if c == ' ' {
+                    continue;
+                }
+
+                let code = c as u32;
+                let cell = if (FIRST_CHAR..MISSING_CHAR).contains(&code) {
+                    code - FIRST_CHAR
+                } else {
+                    MISSING_CHAR - FIRST_CHAR
+                };
+
+                self.queued.push(Glyph {
+                    position: [position[0] + column as f32 * advance, y],
+                    size: [advance, size],
+                    color,
+                    cell,
+                });
+            }
+        }
+    }
+
+    /// Queues a rectangle filled with `color`, such as a backdrop for text queued after it.
+    pub fn fill(&mut self, position: [f32; 2], size: [f32; 2], color: [f32; 4]) {
+        self.queued.push(Glyph {
+            position,
+            size,
+            color,
+            cell: SOLID_CELL,
+        });
+    }
+
+    /// Queues the edges of a rectangle, `thickness` wide on the inside, in `color`.
+    pub fn outline(&mut self, position: [f32; 2], size: [f32; 2], thickness: f32, color: [f32; 4]) {
+        let [x, y] = position;
+        let [width, height] = size;
+        self.fill([x, y], [width, thickness], color);
+        self.fill([x, y + height - thickness], [width, thickness], color);
+        self.fill([x, y], [thickness, height], color);
+        self.fill([x + width - thickness, y], [thickness, height], color);
+    }
+
+    /// The size a string would take up on screen if queued at `size`, in physical pixels.
+    pub fn measure(text: &str, size: f32) -> [f32; 2] {
+        let text = spelled_out(text);
+        let columns = text.lines().map(|line| line.chars().count()).max();
+        let rows = text.lines().count();
+
+        [
+            columns.unwrap_or(0) as f32 * size * CELL_ASPECT,
+            rows as f32 * size,
+        ]
+    }
+
+    /// Uploads the text queued this frame, for a window of `screen_size` physical pixels, and
+    /// clears the queue.
+    pub fn prepare(&mut self, device: &Device, queue: &Queue, screen_size: [f32; 2]) {
+        if self.queued.len() > self.capacity {
+            self.capacity = self.queued.len().next_power_of_two();
+            self.instance_buffer = create_instance_buffer(device, self.capacity);
+        }
+
+        queue.write_buffer(
+            &self.screen_buffer,
+            0,
+            cast_slice(&[ScreenUniform {
+                size: screen_size,
+                _padding: [0.0; 2],
+            }]),
+        );
+        queue.write_buffer(&self.instance_buffer, 0, cast_slice(&self.queued));
+
+        self.prepared = self.queued.len() as u32;
+        self.queued.clear();
+    }
+
+    /// Draws the text uploaded by the last `prepare`.
+    pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>) -> DrawStats {
+        if self.prepared == 0 {
+            return DrawStats::default();
+        }
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.prepared);
+
+        DrawStats {
+            draw_calls: 1,
+            instances: self.prepared,
+        }
+    }
+}
+
+fn create_instance_buffer(device: &Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&BufferDescriptor {
+        label: Some("Text Instance Buffer"),
+        size: (capacity * size_of::<Glyph>()) as BufferAddress,
+        usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+fn create_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("text_bind_group_layout"),
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                count: None,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: TextureViewDimension::D2Array,
+                    sample_type: TextureSampleType::Float { filterable: true },
+                },
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                count: None,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(SamplerBindingType::Filtering),
+            },
+            BindGroupLayoutEntry {
+                binding: 2,
+                count: None,
+                visibility: ShaderStages::VERTEX,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+            },
+        ],
+    })
+}
+
+fn create_pipeline(
+    device: &Device,
+    layout: &BindGroupLayout,
+    format: TextureFormat,
+    depth_format: TextureFormat,
+    sample_count: u32,
+) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::include_wgsl!("text.wgsl"));
+    let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+        label: Some("Text Pipeline Layout"),
+        bind_group_layouts: &[layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("Text Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[Glyph::BUFFER_LAYOUT],
+        },
+        fragment: Some(FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(ColorTargetState {
+                format,
+                blend: Some(BlendState::ALPHA_BLENDING),
+                write_mask: ColorWrites::ALL,
+            })],
+        }),
+        primitive: Default::default(),
+        // Text is drawn over everything, so it neither tests nor writes depth.
+        depth_stencil: Some(DepthStencilState {
+            format: depth_format,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::Always,
+            stencil: Default::default(),
+            bias: Default::default(),
+        }),
+        multisample: MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        multiview: None,
+    })
+}
+
+/// `text` with the letters the font doesn't have spelled out with ones it does, where it can be.
+fn spelled_out(text: &str) -> Cow<'_, str> {
+    if text.is_ascii() {
+        return Cow::Borrowed(text);
+    }
+    let mut spelled = String::with_capacity(text.len());
+    for c in text.chars() {
+        match SPELLINGS.iter().find(|(letter, _)| *letter == c) {
+            Some((_, spelling)) => spelled.push_str(spelling),
+            None => spelled.push(c),
+        }
+    }
+    Cow::Owned(spelled)
+}
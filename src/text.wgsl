@@ -0,0 +1,70 @@
+// Screen-space text: each instance is one glyph, drawn as a quad sampled from the font atlas.
+
+struct Screen {
+    size: vec2<f32>,
+}
+
+@group(0) @binding(2)
+var<uniform> screen: Screen;
+
+struct GlyphInput {
+    @location(0) position: vec2<f32>,
+    @location(1) size: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) cell: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) @interpolate(flat) solid: u32,
+}
+
+// The cell of filled rectangles, which aren't sampled from the font.
+const SOLID_CELL: u32 = 0xffffffffu;
+
+// The font atlas is 16 cells across and 6 down.
+const COLUMNS: u32 = 16u;
+const CELL_SIZE: vec2<f32> = vec2<f32>(0.0625, 0.16666667);
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, glyph: GlyphInput) -> VertexOutput {
+    // Two triangles covering the unit square.
+    var corners = array<vec2<f32>, 6>(
+        vec2(0.0, 0.0),
+        vec2(0.0, 1.0),
+        vec2(1.0, 1.0),
+        vec2(0.0, 0.0),
+        vec2(1.0, 1.0),
+        vec2(1.0, 0.0),
+    );
+    let corner = corners[index];
+
+    // Pixels from the top-left, into clip space where y points up.
+    let pixel = glyph.position + corner * glyph.size;
+    let clip = pixel / screen.size * vec2(2.0, -2.0) + vec2(-1.0, 1.0);
+
+    let cell = vec2<f32>(f32(glyph.cell % COLUMNS), f32(glyph.cell / COLUMNS));
+
+    var out: VertexOutput;
+    out.clip_position = vec4(clip, 0.0, 1.0);
+    out.tex_coords = (cell + corner) * CELL_SIZE;
+    out.color = glyph.color;
+    out.solid = u32(glyph.cell == SOLID_CELL);
+    return out;
+}
+
+@group(0) @binding(0)
+var t_font: texture_2d_array<f32>;
+
+@group(0) @binding(1)
+var s_font: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Sampled either way, since textureSample has to be in uniform control flow.
+    let glyph = textureSample(t_font, s_font, in.tex_coords, 0).a;
+    let coverage = select(glyph, 1.0, in.solid != 0u);
+    return vec4(in.color.rgb, in.color.a * coverage);
+}
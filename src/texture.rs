@@ -1,83 +1,283 @@
-use image::GenericImageView;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+use std::num::NonZeroU8;
+
+use bytemuck::{Pod, Zeroable};
+use image::{imageops::FilterType, DynamicImage, GenericImageView};
+use log::warn;
 
 use crate::errors::*;
 
+/// Reads and decodes an image file.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_image(path: &Path) -> Result<DynamicImage> {
+    let bytes = std::fs::read(path).chain_err(|| format!("couldn't read {}", path.display()))?;
+    image::load_from_memory(&bytes).chain_err(|| format!("couldn't decode {}", path.display()))
+}
+
+/// Fetches and decodes an image over HTTP.
+#[cfg(target_arch = "wasm32")]
+pub async fn fetch_image(url: &str) -> Result<DynamicImage> {
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+
+    let js_error = |e: JsValue| Error::from(format!("couldn't fetch {url}: {e:?}"));
+
+    let window = web_sys::window().ok_or("no window to fetch from")?;
+    let response: web_sys::Response = JsFuture::from(window.fetch_with_str(url))
+        .await
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(js_error)?;
+    if !response.ok() {
+        bail!("couldn't fetch {}: HTTP {}", url, response.status());
+    }
+
+    let buffer = JsFuture::from(response.array_buffer().map_err(js_error)?)
+        .await
+        .map_err(js_error)?;
+    let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+
+    image::load_from_memory(&bytes).chain_err(|| format!("couldn't decode {url}"))
+}
+
+/// How a texture is sampled. These only affect the sampler, so they can change without touching
+/// the texture itself or any bind group layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureOptions {
+    /// How cards are filtered when drawn larger than the sheet: `Nearest` keeps pixel-art decks
+    /// crisp, `Linear` smooths them. Smaller cards are always filtered trilinearly from the
+    /// texture's mipmaps, so they don't shimmer.
+    pub filter: wgpu::FilterMode,
+    pub address_mode: wgpu::AddressMode,
+    /// The most samples taken for anisotropic filtering, where 1 turns it off. Rounded up to a
+    /// power of two, at most 16.
+    pub anisotropy: u8,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            filter: wgpu::FilterMode::Nearest,
+            address_mode: wgpu::AddressMode::ClampToEdge,
+            anisotropy: 1,
+        }
+    }
+}
+
+fn create_sampler(device: &wgpu::Device, options: &TextureOptions) -> wgpu::Sampler {
+    device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: options.address_mode,
+        address_mode_v: options.address_mode,
+        address_mode_w: options.address_mode,
+        mag_filter: options.filter,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Linear,
+        anisotropy_clamp: NonZeroU8::new(options.anisotropy.clamp(1, 16).next_power_of_two())
+            .filter(|clamp| clamp.get() > 1),
+        ..Default::default()
+    })
+}
+
+const TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// The number of levels in a full mip chain for a texture of the given size, down to 1x1.
+fn mip_level_count(width: u32, height: u32) -> u32 {
+    u32::BITS - width.max(height).max(1).leading_zeros()
+}
+
+/// Fills in every mip level below the first by drawing each level, halved, into the next. Each
+/// layer of an array texture gets its own chain.
+fn generate_mipmaps(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    mip_level_count: u32,
+    layer_count: u32,
+) {
+    let shader = device.create_shader_module(wgpu::include_wgsl!("mipmap.wgsl"));
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Mipmap Pipeline"),
+        layout: None,
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(TEXTURE_FORMAT.into())],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    });
+    let layout = pipeline.get_bind_group_layout(0);
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("Mipmap Sampler"),
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    });
+
+    let level_view = |layer, level| {
+        texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_mip_level: level,
+            mip_level_count: std::num::NonZeroU32::new(1),
+            base_array_layer: layer,
+            array_layer_count: std::num::NonZeroU32::new(1),
+            ..Default::default()
+        })
+    };
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Mipmap Encoder"),
+    });
+
+    for layer in 0..layer_count {
+        for level in 1..mip_level_count {
+            let source = level_view(layer, level - 1);
+            let target = level_view(layer, level);
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                ],
+            });
+
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mipmap Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+    }
+
+    queue.submit(Some(encoder.finish()));
+}
+
 pub struct Texture {
+    #[allow(dead_code)]
     pub texture: wgpu::Texture,
     pub view: wgpu::TextureView,
     pub sampler: wgpu::Sampler,
 }
 
-#[macro_export]
-macro_rules! include_texture {
-    ($device:expr, $queue:expr, $file:expr $(,)?) => {{
-        let bytes = include_bytes!($file);
-        Texture::from_bytes($device, $queue, bytes, $file)
-    }};
-}
-
 impl Texture {
-    pub fn from_bytes(
-        device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        bytes: &[u8],
-        label: &str,
-    ) -> Result<Self> {
-        let img = image::load_from_memory(bytes).chain_err(|| "couldn't load image from memory")?;
-        Self::from_image(device, queue, &img, Some(label))
-    }
-
-    pub fn from_image(
+    /// Packs images into the layers of a texture array, in order. Images that differ in size from
+    /// the first are resized to match it.
+    pub fn from_layers(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        img: &image::DynamicImage,
+        layers: &[&DynamicImage],
         label: Option<&str>,
+        options: &TextureOptions,
     ) -> Result<Self> {
-        let rgba = img.to_rgba8();
-        let dimensions = img.dimensions();
+        let first = layers.first().ok_or("no images to make a texture from")?;
+        let dimensions = first.dimensions();
+        let limits = device.limits();
+        let max = limits.max_texture_dimension_2d;
+        if dimensions.0 > max || dimensions.1 > max {
+            bail!(ErrorKind::TextureTooLarge(dimensions.0, dimensions.1, max));
+        }
+        if layers.len() > limits.max_texture_array_layers as usize {
+            bail!(
+                "{} layers is more than the {} a texture array can hold",
+                layers.len(),
+                limits.max_texture_array_layers
+            );
+        }
 
-        let size = wgpu::Extent3d {
+        let layer_size = wgpu::Extent3d {
             width: dimensions.0,
             height: dimensions.1,
             depth_or_array_layers: 1,
         };
+        let mip_level_count = mip_level_count(dimensions.0, dimensions.1);
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label,
-            size,
-            mip_level_count: 1,
+            size: wgpu::Extent3d {
+                depth_or_array_layers: layers.len() as u32,
+                ..layer_size
+            },
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            format: TEXTURE_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::RENDER_ATTACHMENT,
             view_formats: &[],
         });
 
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                aspect: wgpu::TextureAspect::All,
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-            },
-            &rgba,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: std::num::NonZeroU32::new(4 * dimensions.0),
-                rows_per_image: std::num::NonZeroU32::new(dimensions.1),
-            },
-            size,
+        for (layer, img) in layers.iter().enumerate() {
+            let rgba = if img.dimensions() == dimensions {
+                img.to_rgba8()
+            } else {
+                warn!(
+                    "resizing layer {layer} from {:?} to {dimensions:?} to fit the texture array",
+                    img.dimensions()
+                );
+                img.resize_exact(dimensions.0, dimensions.1, FilterType::Triangle)
+                    .to_rgba8()
+            };
+
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    aspect: wgpu::TextureAspect::All,
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer as u32,
+                    },
+                },
+                &rgba,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(4 * dimensions.0),
+                    rows_per_image: std::num::NonZeroU32::new(dimensions.1),
+                },
+                layer_size,
+            );
+        }
+
+        generate_mipmaps(
+            device,
+            queue,
+            &texture,
+            mip_level_count,
+            layers.len() as u32,
         );
 
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
             ..Default::default()
         });
+        let sampler = create_sampler(device, options);
 
         Ok(Self {
             texture,
@@ -85,4 +285,74 @@ impl Texture {
             sampler,
         })
     }
+
+    /// Replaces the sampler. Bind groups using the old one need rebuilding to pick it up.
+    pub fn set_options(&mut self, device: &wgpu::Device, options: &TextureOptions) {
+        self.sampler = create_sampler(device, options);
+    }
+}
+
+/// Describes where images sit within a texture atlas: a grid of equally sized cells, optionally
+/// inset from the texture's top-left corner and separated by gaps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Atlas {
+    /// Size of the whole texture, in texels.
+    pub texture_size: [u32; 2],
+    pub cell_size: [u32; 2],
+    /// Offset of the first cell from the texture's top-left corner.
+    pub margin: [u32; 2],
+    /// Gap between neighbouring cells.
+    pub spacing: [u32; 2],
+    /// The `[column, row]` of the cell holding the card back.
+    pub back: [u32; 2],
+}
+
+impl Atlas {
+    /// An atlas of `columns` by `rows` cells that exactly fill the texture.
+    pub fn grid(texture_size: [u32; 2], columns: u32, rows: u32, back: [u32; 2]) -> Self {
+        Self {
+            texture_size,
+            cell_size: [texture_size[0] / columns, texture_size[1] / rows],
+            margin: [0, 0],
+            spacing: [0, 0],
+            back,
+        }
+    }
+
+    /// How many whole cells fit across and down the texture.
+    pub fn cell_counts(&self) -> [u32; 2] {
+        [0, 1].map(|axis| {
+            let stride = self.cell_size[axis] + self.spacing[axis];
+            let available = self.texture_size[axis].saturating_sub(self.margin[axis]);
+
+            (available + self.spacing[axis])
+                .checked_div(stride)
+                .unwrap_or(0)
+        })
+    }
+
+    pub fn to_uniform(self) -> AtlasUniform {
+        let float = |pair: [u32; 2]| pair.map(|value| value as f32);
+
+        AtlasUniform {
+            texture_size: float(self.texture_size),
+            cell_size: float(self.cell_size),
+            margin: float(self.margin),
+            spacing: float(self.spacing),
+            back: float(self.back),
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+pub struct AtlasUniform {
+    texture_size: [f32; 2],
+    cell_size: [f32; 2],
+    margin: [f32; 2],
+    spacing: [f32; 2],
+    back: [f32; 2],
+    // Uniform buffers must be a multiple of 16 bytes.
+    _padding: [f32; 2],
 }
@@ -0,0 +1,382 @@
+use std::collections::BTreeMap;
+
+use bytemuck::cast_slice;
+use image::{DynamicImage, GenericImageView, Rgba};
+use log::error;
+use serde::{Deserialize, Serialize};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindingResource, BufferUsages,
+    Device, Queue,
+};
+
+use crate::{
+    card::{self, CardStyle},
+    config::{SavedTheme, Settings},
+    errors::*,
+    sheet::{self, DecodedSheet},
+    texture::{Atlas, Texture, TextureOptions},
+};
+
+struct ThemeInfo {
+    name: &'static str,
+    style: CardStyle,
+    look: Look,
+}
+
+/// The themes, in the order of their layers in the card texture. The first is the default.
+///
+/// The high-contrast theme keeps a still card back, and glows a brighter yellow around the cards
+/// that can be played.
+const THEMES: [ThemeInfo; 2] = [
+    ThemeInfo {
+        name: "classic",
+        style: CardStyle::rounded(card::CORNER_RADIUS)
+            .with_back_animation(BACK_FRAMES, BACK_FRAME_TIME),
+        look: Look::DEFAULT,
+    },
+    ThemeInfo {
+        name: "high-contrast",
+        style: CardStyle::rounded(card::CORNER_RADIUS).with_border(1.0, [0.0, 0.0, 0.0, 1.0]),
+        look: Look {
+            highlight: [1.0, 1.0, 0.0],
+            ..Look::DEFAULT
+        },
+    },
+];
+
+/// How a theme looks beyond its cards, which can be tuned from the settings and saved as a theme
+/// of its own, in the settings' `themes` table.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Look {
+    /// How far the table's colour is turned round the colour wheel, in degrees.
+    pub table_hue: f32,
+    /// The linear colour of the glow around the cards that can be played.
+    pub highlight: [f32; 3],
+    /// How far the table darkens towards the corners of the window, from 0 for not at all to 1
+    /// for black.
+    pub vignette: f32,
+    /// How soft the edges of the cards' shadows are, as a multiple of the usual.
+    pub shadow_softness: f32,
+}
+
+impl Look {
+    pub const DEFAULT: Self = Self {
+        table_hue: 0.0,
+        highlight: [1.0, 0.85, 0.3],
+        vignette: 0.0,
+        shadow_softness: 1.0,
+    };
+}
+
+impl Default for Look {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Where `color` is round the colour wheel, in degrees from red, or 0 for a grey.
+pub fn hue([r, g, b]: [f32; 3]) -> f32 {
+    let max = r.max(g).max(b);
+    let chroma = max - r.min(g).min(b);
+    if chroma <= 0.0 {
+        return 0.0;
+    }
+    let sixths = if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    sixths * 60.0
+}
+
+/// `color` moved to `degrees` round the colour wheel, keeping how bright and how strong it is.
+pub fn with_hue(color @ [r, g, b]: [f32; 3], degrees: f32) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let chroma = max - r.min(g).min(b);
+    if chroma <= 0.0 {
+        return color;
+    }
+
+    let sixths = degrees.rem_euclid(360.0) / 60.0;
+    let middle = chroma * (1.0 - (sixths % 2.0 - 1.0).abs());
+    let [r, g, b] = match sixths as u32 {
+        0 => [chroma, middle, 0.0],
+        1 => [middle, chroma, 0.0],
+        2 => [0.0, chroma, middle],
+        3 => [0.0, middle, chroma],
+        4 => [middle, 0.0, chroma],
+        _ => [chroma, 0.0, middle],
+    };
+    let lowest = max - chroma;
+    [r + lowest, g + lowest, b + lowest]
+}
+
+/// How far apart a pixel's channels can be for the high-contrast theme to treat it as grey.
+const GREY_TOLERANCE: u8 = 48;
+
+/// The brightest a coloured pixel is left in the high-contrast theme, so pips stand out against
+/// white card faces.
+const HIGH_CONTRAST_PEAK: u32 = 200;
+
+/// How many frames the classic card back's animation has, and how long each is shown, in seconds.
+const BACK_FRAMES: u32 = 16;
+const BACK_FRAME_TIME: f32 = 0.1;
+
+/// How many frames a glint takes to cross the card back. The rest show the plain back, so the
+/// glint comes round every so often rather than all the time.
+const GLINT_FRAMES: u32 = 6;
+
+/// How wide the glint is, as a fraction of the way from corner to corner, and how far it
+/// brightens the back towards white.
+const GLINT_WIDTH: f32 = 0.12;
+const GLINT_STRENGTH: f32 = 0.6;
+
+/// Every card theme, each drawn from its own layer of one texture array.
+pub struct Themes {
+    pub bind_group: BindGroup,
+    texture: Texture,
+    atlas_buffer: wgpu::Buffer,
+    style_buffer: wgpu::Buffer,
+}
+
+/// The sheet for every theme, in layer order.
+///
+/// The classic theme is the deck sheet itself with its back animated, and the high-contrast theme
+/// is derived from it, so a custom deck gets both too.
+fn theme_sheets(mut deck: DecodedSheet) -> Vec<DecodedSheet> {
+    let high_contrast = deck.map_image("high contrast", high_contrast);
+    add_back_glint(deck.image_mut());
+    vec![deck, high_contrast]
+}
+
+/// Decodes the deck and derives the sheets for every theme from it.
+pub async fn decode() -> Result<Vec<DecodedSheet>> {
+    Ok(theme_sheets(sheet::decode().await?))
+}
+
+/// Draws the frames of the card back's animation into the cells to the right of the back, which
+/// the deck layout leaves empty: a glint of light sweeping across the back from corner to corner.
+fn add_back_glint(image: &mut DynamicImage) {
+    let (width, height) = image.dimensions();
+    let atlas = card::sheet_atlas(width, height);
+    let [cell_width, cell_height] = atlas.cell_size;
+    let [back_column, back_row] = atlas.back;
+    let origin = |column: u32| {
+        (
+            atlas.margin[0] + column * (cell_width + atlas.spacing[0]),
+            atlas.margin[1] + back_row * (cell_height + atlas.spacing[1]),
+        )
+    };
+    let fits = |(x, y): (u32, u32)| x + cell_width <= width && y + cell_height <= height;
+
+    let (back_x, back_y) = origin(back_column);
+    if !fits((back_x, back_y)) {
+        return;
+    }
+
+    let mut sheet = image.to_rgba8();
+    for frame in 1..BACK_FRAMES {
+        let (frame_x, frame_y) = origin(back_column + frame);
+        if !fits((frame_x, frame_y)) {
+            break;
+        }
+
+        // Where the glint's centre is, from 0 at the top-left corner to 1 at the bottom-right. It
+        // starts and ends just off the card.
+        let sweep = frame as f32 / GLINT_FRAMES as f32 * (1.0 + 2.0 * GLINT_WIDTH) - GLINT_WIDTH;
+        for y in 0..cell_height {
+            for x in 0..cell_width {
+                let along = (x as f32 / cell_width as f32 + y as f32 / cell_height as f32) / 2.0;
+                let glint = GLINT_STRENGTH * (1.0 - (along - sweep).abs() / GLINT_WIDTH).max(0.0);
+                let brighten = |channel: u8| channel + ((255 - channel) as f32 * glint) as u8;
+
+                let Rgba([r, g, b, a]) = *sheet.get_pixel(back_x + x, back_y + y);
+                let glinting = Rgba([brighten(r), brighten(g), brighten(b), a]);
+                sheet.put_pixel(frame_x + x, frame_y + y, glinting);
+            }
+        }
+    }
+
+    *image = DynamicImage::ImageRgba8(sheet);
+}
+
+/// Snaps greys to black or white and deepens colours to full saturation.
+fn high_contrast(image: &DynamicImage) -> DynamicImage {
+    let mut image = image.to_rgba8();
+
+    for Rgba([r, g, b, _]) in image.pixels_mut() {
+        let max = (*r).max(*g).max(*b);
+        let min = (*r).min(*g).min(*b);
+
+        if max - min < GREY_TOLERANCE {
+            let luminance = (*r as u32 * 299 + *g as u32 * 587 + *b as u32 * 114) / 1000;
+            let value = if luminance > 127 { 255 } else { 0 };
+            (*r, *g, *b) = (value, value, value);
+        } else {
+            let stretch = |channel: u8| {
+                ((channel - min) as u32 * HIGH_CONTRAST_PEAK / (max - min) as u32) as u8
+            };
+            (*r, *g, *b) = (stretch(*r), stretch(*g), stretch(*b));
+        }
+    }
+
+    DynamicImage::ImageRgba8(image)
+}
+
+impl Themes {
+    /// Uploads the theme sheets, falling back to themes made from the built-in sheet if a custom
+    /// deck can't be used.
+    pub async fn upload(
+        device: &Device,
+        queue: &Queue,
+        layout: &BindGroupLayout,
+        sheets: Vec<DecodedSheet>,
+        options: &TextureOptions,
+    ) -> Result<Self> {
+        let deck = &sheets[0];
+        let sheet = match sheet::upload(device, queue, &sheets, options).await {
+            Ok(sheet) => sheet,
+            Err(e) if !deck.is_built_in() => {
+                error!(
+                    "couldn't load deck {}, using the built-in deck instead: {e:?}",
+                    deck.label()
+                );
+                let built_in = theme_sheets(sheet::decode_built_in()?);
+                sheet::upload(device, queue, &built_in, options)
+                    .await
+                    .chain_err(|| "built-in card sheet is invalid")?
+            }
+            Err(e) => return Err(e).chain_err(|| "built-in card sheet is invalid"),
+        };
+
+        let atlas_buffer = create_atlas_buffer(device, &sheet.atlas);
+        let style_buffer = create_style_buffer(device, THEMES[0].style);
+        let bind_group =
+            create_texture_bind_group(device, &sheet.texture, &atlas_buffer, &style_buffer, layout);
+
+        Ok(Self {
+            bind_group,
+            texture: sheet.texture,
+            atlas_buffer,
+            style_buffer,
+        })
+    }
+
+    /// Changes how every theme is sampled, keeping the same bind group layout.
+    pub fn set_texture_options(
+        &mut self,
+        device: &Device,
+        layout: &BindGroupLayout,
+        options: &TextureOptions,
+    ) {
+        self.texture.set_options(device, options);
+        self.bind_group = create_texture_bind_group(
+            device,
+            &self.texture,
+            &self.atlas_buffer,
+            &self.style_buffer,
+            layout,
+        );
+    }
+
+    /// Draws card edges in the style of the theme on the given layer, with the suits and ranks
+    /// marked as `settings` ask. Large cards always have their suits marked, beside their large
+    /// ranks.
+    pub fn use_style(&self, queue: &Queue, layer: u32, settings: &Settings) {
+        let style = THEMES[layer as usize % THEMES.len()]
+            .style
+            .with_suit_marks(
+                settings.four_colour_suits,
+                settings.suit_glyphs || settings.large_cards,
+            )
+            .with_large_ranks(settings.large_cards);
+        self.set_card_style(queue, style);
+    }
+
+    pub fn set_card_style(&self, queue: &Queue, style: CardStyle) {
+        queue.write_buffer(&self.style_buffer, 0, cast_slice(&[style]));
+    }
+
+    /// The texture layer holding the named theme.
+    pub fn layer(&self, name: &str) -> Option<u32> {
+        THEMES
+            .iter()
+            .position(|theme| theme.name == name)
+            .map(|layer| layer as u32)
+    }
+
+    pub fn name(&self, layer: u32) -> &'static str {
+        THEMES[layer as usize % THEMES.len()].name
+    }
+
+    /// The names of the built-in themes, in layer order, then of the `saved` themes.
+    pub fn names<'a>(
+        &self,
+        saved: &'a BTreeMap<String, SavedTheme>,
+    ) -> impl Iterator<Item = &'a str> {
+        THEMES
+            .iter()
+            .map(|theme| theme.name)
+            .chain(saved.keys().map(String::as_str))
+    }
+
+    /// The texture layer and the look of the named theme, built in or one of the `saved` themes.
+    pub fn find(&self, name: &str, saved: &BTreeMap<String, SavedTheme>) -> Option<(u32, Look)> {
+        if let Some(layer) = self.layer(name) {
+            return Some((layer, THEMES[layer as usize].look));
+        }
+        let saved = saved.get(name)?;
+        Some((self.layer(&saved.cards)?, saved.look))
+    }
+}
+
+fn create_atlas_buffer(device: &Device, atlas: &Atlas) -> wgpu::Buffer {
+    device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("Atlas Buffer"),
+        contents: cast_slice(&[atlas.to_uniform()]),
+        usage: BufferUsages::UNIFORM,
+    })
+}
+
+fn create_style_buffer(device: &Device, style: CardStyle) -> wgpu::Buffer {
+    device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("Card Style Buffer"),
+        contents: cast_slice(&[style]),
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    })
+}
+
+fn create_texture_bind_group(
+    device: &Device,
+    texture: &Texture,
+    atlas_buffer: &wgpu::Buffer,
+    style_buffer: &wgpu::Buffer,
+    layout: &BindGroupLayout,
+) -> BindGroup {
+    device.create_bind_group(&BindGroupDescriptor {
+        label: Some("diffuse_bind_group"),
+        layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(&texture.view),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(&texture.sampler),
+            },
+            BindGroupEntry {
+                binding: 2,
+                resource: atlas_buffer.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 3,
+                resource: style_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
@@ -0,0 +1,340 @@
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent};
+
+use crate::text::TextBrush;
+
+const PANEL_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const BUTTON_COLOR: [f32; 4] = [0.2, 0.2, 0.2, 0.9];
+const BUTTON_HOVER_COLOR: [f32; 4] = [0.3, 0.3, 0.3, 0.9];
+const BUTTON_PRESSED_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 0.9];
+const SLIDER_FILL_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 0.9];
+const TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// The colour of the ring around whatever has the keyboard's focus, on the widgets and on the
+/// table.
+pub const FOCUS_COLOR: [f32; 4] = [0.3, 0.7, 1.0, 0.9];
+
+/// How thick the ring around the focused button is, as a fraction of the button's height.
+const FOCUS_THICKNESS: f32 = 0.08;
+
+/// The size of a button on the menus and screens, and the height of a row, in logical pixels.
+pub const BUTTON_SIZE: [f32; 2] = [192.0, 32.0];
+
+/// Space between the rows of the menus and screens and around them, in logical pixels.
+pub const SPACING: f32 = 6.0;
+
+/// How far a touchpad scrolls to count as one line of a mouse wheel.
+const PIXELS_PER_LINE: f32 = 40.0;
+
+/// A rectangle on screen, in physical pixels from the top-left of the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn contains(&self, [x, y]: [f32; 2]) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// The rectangle shrunk by `amount` on every side.
+    pub fn inset(&self, amount: f32) -> Self {
+        Self::new(
+            self.x + amount,
+            self.y + amount,
+            (self.width - 2.0 * amount).max(0.0),
+            (self.height - 2.0 * amount).max(0.0),
+        )
+    }
+}
+
+/// Immediate-mode widgets drawn over the table.
+///
+/// Widgets are declared afresh every frame, between the input events for that frame and
+/// `end_frame`. Calling a widget queues what it looks like into a `TextBrush` and, for buttons,
+/// reports whether it was clicked. Mouse events are offered to `input` before anything else, so
+/// the table underneath a widget doesn't react to clicks on it. A finger on a widget stands in
+/// for the mouse until it's lifted.
+pub struct Ui {
+    cursor: Option<[f32; 2]>,
+    /// Whether the left button went down or up since the last frame.
+    pressed: bool,
+    released: bool,
+    /// The button being held down, which is only clicked if the mouse is released over it.
+    active: Option<String>,
+    /// Where the widgets were last frame, which is what input is tested against.
+    hit_areas: Vec<Rect>,
+    next_hit_areas: Vec<Rect>,
+    /// Whether the press being held started on a widget.
+    captured: bool,
+    /// Lines scrolled over a widget since the last frame, positive for up.
+    wheel: f32,
+    /// The finger pressing a widget, if one is.
+    touch: Option<u64>,
+    /// The button picked out without the mouse, by its place among this frame's buttons.
+    focus: Option<usize>,
+    /// Whether the focused button was pressed since the last frame.
+    activated: bool,
+    /// How many buttons there were last frame, and how many so far this frame.
+    buttons: usize,
+    next_buttons: usize,
+}
+
+impl Ui {
+    pub fn new() -> Self {
+        Self {
+            cursor: None,
+            pressed: false,
+            released: false,
+            active: None,
+            hit_areas: Vec::new(),
+            next_hit_areas: Vec::new(),
+            captured: false,
+            wheel: 0.0,
+            touch: None,
+            focus: None,
+            activated: false,
+            buttons: 0,
+            next_buttons: 0,
+        }
+    }
+
+    /// Tracks the mouse, returning true if the event was meant for a widget and shouldn't go on to
+    /// the table.
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = Some([position.x as f32, position.y as f32]);
+                false
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor = None;
+                false
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } if self.is_over_widget() => {
+                self.pressed = true;
+                self.captured = true;
+                // The mouse takes over from the keys.
+                self.focus = None;
+                true
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+                ..
+            } if self.captured => {
+                self.released = true;
+                self.captured = false;
+                true
+            }
+            WindowEvent::MouseWheel { delta, .. } if self.is_over_widget() => {
+                self.wheel += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_LINE,
+                };
+                true
+            }
+            WindowEvent::Touch(touch) => {
+                let location = [touch.location.x as f32, touch.location.y as f32];
+                match touch.phase {
+                    TouchPhase::Started if self.touch.is_none() && self.covers(location) => {
+                        self.cursor = Some(location);
+                        self.touch = Some(touch.id);
+                        self.pressed = true;
+                        self.captured = true;
+                    }
+                    _ if self.touch != Some(touch.id) => return false,
+                    TouchPhase::Started | TouchPhase::Moved => self.cursor = Some(location),
+                    // The finger is forgotten at the end of the frame, once its button has seen
+                    // it lift.
+                    TouchPhase::Ended => {
+                        self.cursor = Some(location);
+                        self.released = true;
+                        self.captured = false;
+                    }
+                    TouchPhase::Cancelled => {
+                        self.active = None;
+                        self.captured = false;
+                        self.touch = None;
+                        self.cursor = None;
+                    }
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Moves the focus `step` buttons on, wrapping around, or onto the first button if none has
+    /// it yet.
+    pub fn move_focus(&mut self, step: isize) {
+        if self.buttons == 0 {
+            return;
+        }
+        self.focus = Some(match self.focus {
+            Some(focus) => (focus as isize + step).rem_euclid(self.buttons as isize) as usize,
+            None => 0,
+        });
+    }
+
+    /// Clicks the focused button on the next frame, returning whether there is one.
+    pub fn activate(&mut self) -> bool {
+        self.activated = self.focus.is_some();
+        self.activated
+    }
+
+    /// Counts a button among this frame's, returning whether it has the focus and whether it was
+    /// activated with it.
+    fn next_button(&mut self) -> (bool, bool) {
+        let focused = self.focus == Some(self.next_buttons);
+        self.next_buttons += 1;
+        (focused, focused && self.activated)
+    }
+
+    fn is_over_widget(&self) -> bool {
+        self.cursor.is_some_and(|cursor| self.covers(cursor))
+    }
+
+    /// Whether a widget was at `point` last frame.
+    fn covers(&self, point: [f32; 2]) -> bool {
+        self.hit_areas.iter().any(|area| area.contains(point))
+    }
+
+    /// Finishes the frame's widgets, ready for the next frame's input.
+    pub fn end_frame(&mut self) {
+        if self.released {
+            self.active = None;
+            // A lifted finger doesn't hover over anything.
+            if self.touch.take().is_some() {
+                self.cursor = None;
+            }
+        }
+        self.pressed = false;
+        self.released = false;
+        self.wheel = 0.0;
+        self.activated = false;
+        self.buttons = std::mem::take(&mut self.next_buttons);
+        self.focus = self.focus.filter(|&focus| focus < self.buttons);
+        std::mem::swap(&mut self.hit_areas, &mut self.next_hit_areas);
+        self.next_hit_areas.clear();
+    }
+
+    /// A translucent backdrop that also stops clicks reaching the table.
+    pub fn panel(&mut self, brush: &mut TextBrush, rect: Rect) {
+        brush.fill([rect.x, rect.y], [rect.width, rect.height], PANEL_COLOR);
+        self.next_hit_areas.push(rect);
+    }
+
+    /// How many lines the mouse wheel was scrolled over `rect` since the last frame, positive for
+    /// up.
+    pub fn scroll(&self, rect: Rect) -> f32 {
+        if self.cursor.is_some_and(|cursor| rect.contains(cursor)) {
+            self.wheel
+        } else {
+            0.0
+        }
+    }
+
+    /// A line of text with its top-left corner at `position`. `size` is the height of a line.
+    pub fn label(&mut self, brush: &mut TextBrush, text: &str, position: [f32; 2], size: f32) {
+        brush.queue(text, position, size, TEXT_COLOR);
+    }
+
+    /// A button showing `text`, which also identifies it while it's held down. Returns true on the
+    /// frame the button is clicked: pressed and then released without leaving it.
+    pub fn button(&mut self, brush: &mut TextBrush, text: &str, rect: Rect) -> bool {
+        self.button_with_id(brush, text, text, rect)
+    }
+
+    /// A button showing `text`, identified by `id` while it's held down, for buttons that might
+    /// show the same text as others.
+    pub fn button_with_id(
+        &mut self,
+        brush: &mut TextBrush,
+        id: &str,
+        text: &str,
+        rect: Rect,
+    ) -> bool {
+        let hovered = self.cursor.is_some_and(|cursor| rect.contains(cursor));
+        if hovered && self.pressed {
+            self.active = Some(id.to_owned());
+        }
+
+        let held = self.active.as_deref() == Some(id);
+        let (focused, activated) = self.next_button();
+        let clicked = (held && hovered && self.released) || activated;
+        let hovered = hovered || focused;
+
+        let color = match (held, hovered) {
+            (true, true) => BUTTON_PRESSED_COLOR,
+            (_, true) => BUTTON_HOVER_COLOR,
+            _ => BUTTON_COLOR,
+        };
+        brush.fill([rect.x, rect.y], [rect.width, rect.height], color);
+        if focused {
+            let thickness = (rect.height * FOCUS_THICKNESS).max(1.0);
+            brush.outline(
+                [rect.x, rect.y],
+                [rect.width, rect.height],
+                thickness,
+                FOCUS_COLOR,
+            );
+        }
+
+        let size = rect.height * 0.6;
+        let [width, height] = TextBrush::measure(text, size);
+        let position = [
+            rect.x + (rect.width - width) / 2.0,
+            rect.y + (rect.height - height) / 2.0,
+        ];
+        self.label(brush, text, position, size);
+
+        self.next_hit_areas.push(rect);
+        clicked
+    }
+
+    /// A bar filled `fraction` of the way along, identified by `id` while it's held down. Returns
+    /// how far along the bar the mouse is, from 0 to 1, on every frame it's pressed on the bar or
+    /// dragged from there.
+    pub fn slider(
+        &mut self,
+        brush: &mut TextBrush,
+        id: &str,
+        rect: Rect,
+        fraction: f32,
+    ) -> Option<f32> {
+        let hovered = self.cursor.is_some_and(|cursor| rect.contains(cursor));
+        if hovered && self.pressed {
+            self.active = Some(id.to_owned());
+        }
+
+        let color = if hovered {
+            BUTTON_HOVER_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+        brush.fill([rect.x, rect.y], [rect.width, rect.height], color);
+        let filled = rect.width * fraction.clamp(0.0, 1.0);
+        brush.fill([rect.x, rect.y], [filled, rect.height], SLIDER_FILL_COLOR);
+
+        self.next_hit_areas.push(rect);
+
+        let [x, _] = self.cursor.filter(|_| self.active.as_deref() == Some(id))?;
+        Some(((x - rect.x) / rect.width).clamp(0.0, 1.0))
+    }
+}
@@ -53,6 +53,100 @@ macro_rules! attributes {
     }};
 }
 
+/// Work started in the background on native targets, where it runs to completion on its own
+/// thread. The web has no threads to spare, so there it only runs once it's joined.
+pub struct BackgroundTask<T> {
+    #[cfg(not(target_arch = "wasm32"))]
+    handle: std::thread::JoinHandle<T>,
+    #[cfg(target_arch = "wasm32")]
+    task: std::pin::Pin<Box<dyn std::future::Future<Output = T>>>,
+}
+
+impl<T: Send + 'static> BackgroundTask<T> {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(task: impl std::future::Future<Output = T> + Send + 'static) -> Self {
+        Self {
+            handle: std::thread::spawn(move || pollster::block_on(task)),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn spawn(task: impl std::future::Future<Output = T> + 'static) -> Self {
+        Self {
+            task: Box::pin(task),
+        }
+    }
+
+    /// Waits for the work to finish, passing on any panic it raised.
+    pub async fn join(self) -> T {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self
+            .handle
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+
+        #[cfg(target_arch = "wasm32")]
+        return self.task.await;
+    }
+}
+
+/// The value of a query parameter in the page's URL.
+#[cfg(target_arch = "wasm32")]
+pub fn query_param(name: &str) -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    web_sys::UrlSearchParams::new_with_str(&search)
+        .ok()?
+        .get(name)
+}
+
+/// A seed for dealing a new game, different every time.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn random_seed() -> u64 {
+    (js_sys::Math::random() * u64::MAX as f64) as u64
+}
+
+/// Whether the system asks for as little motion on screen as can be managed, which on the web is
+/// the browser's `prefers-reduced-motion`. Native builds have no way of asking, so take it that
+/// it doesn't.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn prefers_reduced_motion() -> bool {
+    false
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn prefers_reduced_motion() -> bool {
+    web_sys::window()
+        .and_then(|window| {
+            window
+                .match_media("(prefers-reduced-motion: reduce)")
+                .ok()
+                .flatten()
+        })
+        .is_some_and(|query| query.matches())
+}
+
+/// Seconds since the Unix epoch.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn unix_time() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
 pub fn create_buffer<A: bytemuck::Pod>(
     device: &Device,
     name: &str,
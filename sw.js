@@ -0,0 +1,60 @@
+// Caches the web build so it keeps working offline after its first load.
+//
+// Responses come from the cache when they can, so slow networks don't hold up startup, and are
+// refreshed in the background for next time.
+
+const CACHE = "cards-v1";
+
+async function precache(extraUrls = []) {
+    const response = await fetch("assets.json", { cache: "no-cache" });
+    const assets = await response.json();
+    const cache = await caches.open(CACHE);
+    await cache.addAll([...assets, ...extraUrls]);
+}
+
+self.addEventListener("install", (event) => {
+    event.waitUntil(precache().then(() => self.skipWaiting()));
+});
+
+self.addEventListener("activate", (event) => {
+    event.waitUntil(
+        caches.keys()
+            .then((keys) => Promise.all(keys.filter((key) => key !== CACHE).map((key) => caches.delete(key))))
+            .then(() => self.clients.claim())
+    );
+});
+
+self.addEventListener("fetch", (event) => {
+    if (event.request.method !== "GET") {
+        return;
+    }
+
+    event.respondWith(caches.open(CACHE).then(async (cache) => {
+        const cached = await cache.match(event.request);
+        const refreshed = fetch(event.request)
+            .then((response) => {
+                if (response.ok) {
+                    cache.put(event.request, response.clone());
+                }
+                return response;
+            })
+            .catch((error) => {
+                if (!cached) {
+                    throw error;
+                }
+            });
+
+        if (cached) {
+            event.waitUntil(refreshed);
+            return cached;
+        }
+        return refreshed;
+    }));
+});
+
+// Sent by the app to download everything it needs, including any custom deck, ahead of time.
+self.addEventListener("message", (event) => {
+    if (event.data?.type === "precache") {
+        event.waitUntil(precache(event.data.urls ?? []));
+    }
+});